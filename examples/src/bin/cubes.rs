@@ -115,7 +115,7 @@ impl<Context: GobsContext> App<Context> {
                     .build();
 
                 ctx.new_material(&name)
-                    .from_base("normal.array")
+                    .from_base("normal.bindless")
                     .with_textures(&[diffuse_texture, normal_texture])
                     .with_prop(MaterialDataPropData::EmissionColor(
                         Color::new(0., 0.01, 0.01, 1.).into(),
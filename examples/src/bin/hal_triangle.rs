@@ -51,11 +51,9 @@ impl<Context: GobsContext> GobsGame for App<Context> {
 
         let frame_id = hal.frame_id(self.frame_number);
 
-        self.cmd.wait();
+        self.cmd.wait(hal)?;
 
-        if hal.acquire(frame_id).is_err() {
-            return Err(RenderError::Outdated);
-        }
+        hal.acquire(frame_id)?;
 
         self.cmd.reset();
 
@@ -93,11 +91,9 @@ impl<Context: GobsContext> GobsGame for App<Context> {
         self.cmd.end_label();
         self.cmd.end();
 
-        self.cmd.submit_graphics(hal, frame_id);
+        self.cmd.submit_graphics(hal, frame_id)?;
 
-        let Ok(_) = hal.present() else {
-            return Err(RenderError::Outdated);
-        };
+        hal.present()?;
 
         Ok(())
     }
@@ -144,7 +140,7 @@ impl<Context: GobsContext> App<Context> {
         hal.upload_buffer(staging, &vertices, 0);
         hal.upload_buffer(staging, bytemuck::cast_slice(indices), vertices_size as u64);
 
-        cmd.run_immediate_mut("Upload buffer", &mut |cmd| {
+        if let Err(err) = cmd.run_immediate_mut("Upload buffer", &mut |cmd| {
             cmd.copy_buffer_to_buffer(hal, staging, vertex_buffer, vertices_size, 0, 0);
             cmd.copy_buffer_to_buffer(
                 hal,
@@ -154,7 +150,9 @@ impl<Context: GobsContext> App<Context> {
                 vertices_size as u64,
                 0,
             );
-        });
+        }) {
+            tracing::error!(target: logger::APP, "Cannot upload buffers: {}", err);
+        }
 
         hal.destroy_buffer(staging);
 
@@ -16,6 +16,7 @@ use gobs::{
         graph::{node::Node, scenegraph::SceneGraph},
         scene::Scene,
     },
+    ui::MemoryPanel,
 };
 
 pub struct Ui<C> {
@@ -23,6 +24,7 @@ pub struct Ui<C> {
     pub show_light: bool,
     pub show_models: bool,
     pub show_resources: bool,
+    pub show_memory: bool,
     pub show_batch: bool,
     pub show_texture: bool,
     pub ui_hovered: bool,
@@ -46,6 +48,7 @@ where
             show_light: true,
             show_models: true,
             show_resources: false,
+            show_memory: false,
             show_batch: false,
             show_texture: false,
             ui_hovered: false,
@@ -84,6 +87,8 @@ where
 
             self.show_resources(ui, resource_manager);
 
+            self.show_memory(ui, renderer);
+
             self.draw_general(ui, renderer, scene, delta);
 
             self.show_texture(&mut renderer.gfx, ui, resource_manager);
@@ -169,6 +174,24 @@ where
         self.show_resources = show_resources;
     }
 
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    fn show_memory(&mut self, ui: &mut egui::Ui, renderer: &Renderer) {
+        if ui.button("Show memory").clicked() {
+            self.show_memory = true;
+        }
+
+        let mut show_memory = self.show_memory;
+
+        egui::Window::new("Memory")
+            .open(&mut show_memory)
+            .show(ui, |ui| {
+                let report = renderer.gfx.hal().memory_report();
+                ui.add(MemoryPanel::new(&report));
+            });
+
+        self.show_memory = show_memory;
+    }
+
     fn show_resource<R: ResourceType + 'static>(
         &mut self,
         body: &mut TableBody,
@@ -280,12 +303,17 @@ where
 
     fn draw_node(&mut self, ui: &mut egui::Ui, graph: &SceneGraph, node_key: NodeId) {
         let node = graph.get(node_key).unwrap();
-        let node_name = match &node.base.value {
+        let node_type = match &node.base.value {
             NodeValue::None => "None",
             NodeValue::Model(_model) => "Model",
             NodeValue::Camera(_camera) => "Camera",
             NodeValue::Light(_light) => "Light",
         };
+        let node_name = if node.base.name.is_empty() {
+            node_type.to_string()
+        } else {
+            format!("{} ({})", node.base.name, node_type)
+        };
         let has_children = !node.base.children.is_empty();
 
         if has_children {
@@ -295,7 +323,7 @@ where
                 true,
             )
             .show_header(ui, |ui| {
-                ui.selectable_value(&mut self.selected_node, node.base.id, node_name.to_string());
+                ui.selectable_value(&mut self.selected_node, node.base.id, node_name);
             })
             .body(|ui| {
                 for child in &graph.get(node_key).unwrap().base.children {
@@ -303,7 +331,7 @@ where
                 }
             });
         } else {
-            ui.selectable_value(&mut self.selected_node, node.base.id, node_name.to_string());
+            ui.selectable_value(&mut self.selected_node, node.base.id, node_name);
         }
     }
 
@@ -485,7 +513,7 @@ where
         let (mesh, _) = &self.selected_mesh;
 
         if let Some(mesh) = mesh {
-            let mesh_props = &resource_manager.get(mesh).properties;
+            let mesh_props = &resource_manager.get(mesh).unwrap().properties;
             egui::CollapsingHeader::new("Mesh")
                 .default_open(true)
                 .show(ui, |ui| {
@@ -519,7 +547,8 @@ where
                             let img = ColorImage::from_rgba_unmultiplied(size, data);
                             let texture = ui.load_texture("texture-view", img, Default::default());
                             self.texture_view = Some(texture);
-                        });
+                        })
+                        .unwrap();
                     };
                     let texture = self.texture_view.as_ref().unwrap();
                     ui.image((texture.id(), texture.size_vec2()));
@@ -533,9 +562,10 @@ where
     fn draw_material(&mut self, ui: &mut egui::Ui, resource_manager: &ResourceManager) {
         let (_, material) = &self.selected_mesh;
         if let Some(material) = material {
-            let mat_instance_props = &resource_manager.get(material).properties;
+            let mat_instance_props = &resource_manager.get(material).unwrap().properties;
             let mat_props = &resource_manager
                 .get(&mat_instance_props.material)
+                .unwrap()
                 .properties;
 
             egui::CollapsingHeader::new("Material")
@@ -551,7 +581,7 @@ where
                         ui.label("Textures:");
                     }
                     for texture in &mat_instance_props.textures {
-                        let texture_props = &resource_manager.get(texture).properties;
+                        let texture_props = &resource_manager.get(texture).unwrap().properties;
 
                         ui.label(format!("  Name: {}", texture_props.name(),));
                         ui.label(format!("  Id: {:?}", texture.id));
@@ -617,6 +647,10 @@ where
                 .default_open(true)
                 .show(ui, |ui| {
                     ui.label(format!("Type: {}", node_name));
+                    ui.horizontal(|ui| {
+                        ui.label("Name: ");
+                        ui.text_edit_singleline(&mut node.base.name);
+                    });
 
                     self.draw_local_properties(ui, node);
                     self.draw_global_properties(ui, node);
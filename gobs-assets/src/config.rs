@@ -2,12 +2,15 @@ use gobs_core::{ConfigDefault, ConfigWriter as _, GobsConfig, ImageFormat};
 
 pub enum GltfConfig {
     TextureFormat,
+    /// Format of textures holding linear data (normal, metallic-roughness, occlusion)
+    DataTextureFormat,
 }
 
 impl AsRef<str> for GltfConfig {
     fn as_ref(&self) -> &str {
         match self {
             GltfConfig::TextureFormat => "config.gltf.texture.format",
+            GltfConfig::DataTextureFormat => "config.gltf.texture.data_format",
         }
     }
 }
@@ -15,5 +18,6 @@ impl AsRef<str> for GltfConfig {
 impl ConfigDefault for GltfConfig {
     fn register_defaults(config: &mut GobsConfig) {
         config.set_image_format(GltfConfig::TextureFormat, ImageFormat::R8g8b8a8Srgb);
+        config.set_image_format(GltfConfig::DataTextureFormat, ImageFormat::R8g8b8a8Unorm);
     }
 }
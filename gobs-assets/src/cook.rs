@@ -0,0 +1,326 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use gobs_core::{ImageExtent2D, logger};
+use gobs_render::CookedTexture;
+use gobs_resource::load::{
+    COOKED_VERSION, ContentHasher, CookedHeader, CookedKind, CookedWriter, LoadingError,
+};
+
+use crate::{AssetError, gltf_load::GLTFLoader, model::ModelAsset};
+
+const TEXTURE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "bmp", "tga"];
+const MODEL_EXTENSIONS: [&str; 2] = ["gltf", "glb"];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CookStats {
+    pub cooked: usize,
+    /// Up to date assets
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Convert source assets (images and glTF models) into cooked files. The output mirrors
+/// the layout of the source directory so that it can be mounted as an asset root.
+/// Assets whose source content did not change since the last run are skipped.
+pub struct Cooker {
+    source: PathBuf,
+    output: PathBuf,
+    force: bool,
+}
+
+impl Cooker {
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(source: P, output: Q) -> Self {
+        Self {
+            source: source.as_ref().to_path_buf(),
+            output: output.as_ref().to_path_buf(),
+            force: false,
+        }
+    }
+
+    /// Cook all assets, even if they are up to date
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+
+        self
+    }
+
+    pub fn run(&self) -> Result<CookStats, AssetError> {
+        tracing::info!(target: logger::RESOURCES, "Cook assets from {:?} to {:?}", self.source, self.output);
+
+        let mut files = vec![];
+        Self::list_files(&self.source, &mut files).map_err(|source| LoadingError::ReadError {
+            path: self.source.display().to_string(),
+            source,
+        })?;
+        files.sort();
+
+        let mut stats = CookStats::default();
+
+        for file in files {
+            match self.cook_file(&file) {
+                Ok(Some(true)) => stats.cooked += 1,
+                Ok(Some(false)) => stats.skipped += 1,
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!(target: logger::RESOURCES, "Failed to cook {:?}: {}", file, e);
+                    stats.failed += 1;
+                }
+            }
+        }
+
+        tracing::info!(target: logger::RESOURCES,
+            "{} assets cooked, {} up to date, {} failed",
+            stats.cooked,
+            stats.skipped,
+            stats.failed
+        );
+
+        Ok(stats)
+    }
+
+    fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                Self::list_files(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Location of the cooked file for a source file. The source extension is kept
+    /// so that sources with the same stem do not overwrite each other (`wall.png.gtex`)
+    pub fn output_path(&self, file: &Path) -> Option<PathBuf> {
+        let relative = file.strip_prefix(&self.source).ok()?;
+        let extension = Self::cooked_extension(file)?;
+
+        let mut name = relative.as_os_str().to_owned();
+        name.push(".");
+        name.push(extension);
+
+        Some(self.output.join(name))
+    }
+
+    fn cooked_extension(file: &Path) -> Option<&'static str> {
+        let extension = file.extension()?.to_string_lossy().to_lowercase();
+
+        if TEXTURE_EXTENSIONS.contains(&extension.as_str()) {
+            Some(CookedTexture::EXTENSION)
+        } else if MODEL_EXTENSIONS.contains(&extension.as_str()) {
+            Some(ModelAsset::COOKED_EXTENSION)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `None` for unsupported files, `Some(false)` when the asset is up to date
+    fn cook_file(&self, file: &Path) -> Result<Option<bool>, AssetError> {
+        let (Some(output), Some(extension)) =
+            (self.output_path(file), Self::cooked_extension(file))
+        else {
+            tracing::trace!(target: logger::RESOURCES, "Skip unsupported file {:?}", file);
+            return Ok(None);
+        };
+
+        let is_texture = extension == CookedTexture::EXTENSION;
+
+        let hash = if is_texture {
+            Self::hash_files(&[file.to_path_buf()], extension)?
+        } else {
+            Self::hash_files(&Self::model_files(file)?, extension)?
+        };
+
+        if !self.force
+            && let Ok(header) = CookedHeader::read_file(&output)
+            && header.source_hash == hash
+        {
+            tracing::debug!(target: logger::RESOURCES, "Asset up to date: {:?}", output);
+            return Ok(Some(false));
+        }
+
+        tracing::info!(target: logger::RESOURCES, "Cook {:?}", file);
+
+        let mut writer = CookedWriter::new();
+        let kind = if is_texture {
+            Self::cook_texture(file, &mut writer)?;
+            CookedKind::Texture
+        } else {
+            Self::cook_model(file, &mut writer)?;
+            CookedKind::Model
+        };
+
+        writer
+            .save(&output, kind, hash)
+            .map_err(|source| LoadingError::ReadError {
+                path: output.display().to_string(),
+                source,
+            })?;
+
+        Ok(Some(true))
+    }
+
+    fn cook_texture(file: &Path, writer: &mut CookedWriter) -> Result<(), AssetError> {
+        let image = image::open(file)
+            .map_err(|source| LoadingError::ImageLoadingError {
+                path: file.display().to_string(),
+                source,
+            })?
+            .into_rgba8();
+
+        let (mip_levels, data) = CookedTexture::generate_mips(&image);
+
+        CookedTexture {
+            extent: ImageExtent2D::new(image.width(), image.height()),
+            mip_levels,
+            data: &data,
+        }
+        .write(writer);
+
+        Ok(())
+    }
+
+    fn cook_model(file: &Path, writer: &mut CookedWriter) -> Result<(), AssetError> {
+        let mut asset = GLTFLoader::read(&file)?;
+        asset.generate_mips();
+
+        asset.write_cooked(writer);
+
+        Ok(())
+    }
+
+    /// glTF file with its external buffers and images
+    fn model_files(file: &Path) -> Result<Vec<PathBuf>, AssetError> {
+        let gltf = gltf::Gltf::open(file)?;
+        let dir = file.parent().unwrap_or(Path::new(""));
+
+        let buffers = gltf.buffers().filter_map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Uri(uri) if !uri.starts_with("data:") => Some(uri),
+            _ => None,
+        });
+
+        let images = gltf.images().filter_map(|image| match image.source() {
+            gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => Some(uri),
+            _ => None,
+        });
+
+        let mut files = vec![file.to_path_buf()];
+        files.extend(buffers.chain(images).map(|uri| dir.join(uri)));
+
+        Ok(files)
+    }
+
+    /// Hash of the source files, cooked format version and extension of the cooked kind
+    fn hash_files(files: &[PathBuf], extension: &str) -> Result<u64, AssetError> {
+        let mut hasher = ContentHasher::new();
+        hasher.update(&COOKED_VERSION.to_le_bytes());
+        hasher.update(extension.as_bytes());
+
+        for file in files {
+            let data = fs::read(file).map_err(|source| LoadingError::ReadError {
+                path: file.display().to_string(),
+                source,
+            })?;
+            hasher.update(&data);
+        }
+
+        Ok(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use image::RgbaImage;
+
+    use gobs_core::ImageExtent2D;
+    use gobs_render::CookedTexture;
+    use gobs_resource::load::{CookedAsset, CookedKind};
+
+    use crate::{
+        cook::{CookStats, Cooker},
+        testing::{setup, temp_dir},
+    };
+
+    #[test]
+    fn test_cook() {
+        setup();
+
+        let dir = temp_dir("cook");
+        let source = dir.join("source");
+        let output = dir.join("output");
+
+        fs::create_dir_all(source.join("assets/textures")).unwrap();
+        RgbaImage::from_pixel(4, 4, image::Rgba([0, 255, 0, 255]))
+            .save(source.join("assets/textures/wall.png"))
+            .unwrap();
+        fs::write(source.join("assets/textures/notes.txt"), "ignored").unwrap();
+
+        let cooker = Cooker::new(&source, &output);
+
+        let stats = cooker.run().unwrap();
+        assert_eq!(
+            stats,
+            CookStats {
+                cooked: 1,
+                skipped: 0,
+                failed: 0
+            }
+        );
+
+        let cooked = output.join("assets/textures/wall.png.gtex");
+        let asset = CookedAsset::from_bytes(fs::read(&cooked).unwrap(), "wall.png.gtex")
+            .unwrap()
+            .expect(CookedKind::Texture)
+            .unwrap();
+        let texture = CookedTexture::read(&mut asset.reader()).unwrap();
+        assert_eq!(texture.extent, ImageExtent2D::new(4, 4));
+        assert_eq!(texture.mip_levels, 3);
+        assert!(!output.join("assets/textures/notes.txt.gtex").exists());
+
+        // up to date
+        assert_eq!(cooker.run().unwrap().skipped, 1);
+
+        // source modified
+        RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 255, 255]))
+            .save(source.join("assets/textures/wall.png"))
+            .unwrap();
+        assert_eq!(cooker.run().unwrap().cooked, 1);
+
+        assert_eq!(
+            Cooker::new(&source, &output)
+                .force(true)
+                .run()
+                .unwrap()
+                .cooked,
+            1
+        );
+
+        // same stem, other format
+        RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+            .save(source.join("assets/textures/wall.bmp"))
+            .unwrap();
+        assert_eq!(
+            cooker.run().unwrap(),
+            CookStats {
+                cooked: 1,
+                skipped: 1,
+                failed: 0
+            }
+        );
+        assert!(output.join("assets/textures/wall.bmp.gtex").exists());
+        assert_eq!(cooker.run().unwrap().skipped, 2);
+
+        // invalid source
+        fs::write(source.join("assets/textures/broken.png"), "not an image").unwrap();
+        assert_eq!(cooker.run().unwrap().failed, 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,921 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs,
+    io::Cursor,
+    path::Path,
+    sync::Arc,
+};
+
+use glam::Vec3;
+use gltf::{
+    binary::{Glb, Header},
+    json::{
+        self, Index,
+        accessor::{ComponentType, GenericComponentType, Type},
+        buffer::Target,
+        material::{
+            AlphaCutoff, AlphaMode, EmissiveFactor, NormalTexture, OcclusionTexture,
+            PbrBaseColorFactor, PbrMetallicRoughness, StrengthFactor,
+        },
+        mesh::{Mode, Semantic},
+        texture::{MagFilter, MinFilter},
+        validation::{Checked::Valid, USize64},
+    },
+};
+use image::RgbaImage;
+
+use gobs_core::{ImageFormat, SamplerFilter, logger};
+use gobs_render::{
+    MaterialDataProp, MaterialInstance, Mesh, MeshGeometry, MeshPath, Model, ModelId, Texture,
+    TextureLoader, TexturePath, TextureType, UniformData, VertexData,
+};
+use gobs_resource::{ResourceHandle, ResourceManager};
+use gobs_scene::{
+    components::{NodeId, NodeValue},
+    graph::scenegraph::SceneGraph,
+};
+
+use crate::AssetError;
+
+/// Write a scene graph to a glTF file, e.g. to inspect procedural geometry in an
+/// external editor. Models are exported with their mesh geometry, material factors
+/// and textures. Cameras, lights, skins, morph targets and animations are not exported,
+/// vertex tangents, joints and weights are dropped.
+pub struct GLTFExporter<'a> {
+    resource_manager: &'a ResourceManager,
+    document: GLTFDocument,
+    meshes: HashMap<ModelId, Option<Index<json::Mesh>>>,
+    materials: HashMap<ResourceHandle<MaterialInstance>, Option<Index<json::Material>>>,
+    textures: HashMap<ResourceHandle<Texture>, Option<Index<json::Texture>>>,
+}
+
+impl<'a> GLTFExporter<'a> {
+    const GENERATOR: &'static str = "gobs-engine";
+
+    pub fn new(resource_manager: &'a ResourceManager) -> Self {
+        Self {
+            resource_manager,
+            document: GLTFDocument::default(),
+            meshes: HashMap::new(),
+            materials: HashMap::new(),
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Export the enabled nodes below the scene root. A `.glb` file embeds the buffer,
+    /// any other extension writes a `.gltf` file with the buffer in a `.bin` file
+    pub fn export<P>(mut self, scene: &SceneGraph, file: P) -> Result<(), AssetError>
+    where
+        P: AsRef<Path> + Debug,
+    {
+        tracing::debug!(target: logger::RESOURCES, "Export gltf: {:?}", file);
+
+        let mut nodes = vec![];
+        for child in scene.children(scene.root) {
+            if let Some(node) = self.add_node(scene, child)? {
+                nodes.push(node);
+            }
+        }
+
+        // scenes cannot be empty
+        if !nodes.is_empty() {
+            let scene = self.document.root.push(json::Scene {
+                name: None,
+                nodes,
+                extensions: None,
+                extras: Default::default(),
+            });
+            self.document.root.scene = Some(scene);
+        }
+        self.document.root.asset.generator = Some(Self::GENERATOR.to_string());
+
+        self.document.write(file.as_ref())
+    }
+
+    fn add_node(
+        &mut self,
+        scene: &SceneGraph,
+        key: NodeId,
+    ) -> Result<Option<Index<json::Node>>, AssetError> {
+        let Some(node) = scene.get(key) else {
+            return Ok(None);
+        };
+
+        if !node.base.enabled {
+            return Ok(None);
+        }
+
+        let mesh = match &node.base.value {
+            NodeValue::Model(model) => self.add_mesh(model)?,
+            _ => None,
+        };
+
+        let mut children = vec![];
+        for child in scene.children(key) {
+            if let Some(child) = self.add_node(scene, child)? {
+                children.push(child);
+            }
+        }
+
+        let transform = node.transform();
+
+        Ok(Some(self.document.root.push(json::Node {
+            name: (!node.base.name.is_empty()).then(|| node.base.name.clone()),
+            mesh,
+            translation: Some(transform.translation().into()),
+            rotation: Some(json::scene::UnitQuaternion(transform.rotation().to_array())),
+            scale: Some(transform.scaling().into()),
+            children: (!children.is_empty()).then_some(children),
+            ..Default::default()
+        })))
+    }
+
+    /// One glTF mesh per model, shared by all the nodes of the model
+    fn add_mesh(&mut self, model: &Arc<Model>) -> Result<Option<Index<json::Mesh>>, AssetError> {
+        if let Some(&mesh) = self.meshes.get(&model.id) {
+            return Ok(mesh);
+        }
+
+        let mut primitives = vec![];
+        for (mesh, material) in &model.meshes {
+            let Some(geometry) = self.geometry(mesh) else {
+                continue;
+            };
+
+            let material = match material {
+                Some(material) => self.add_material(*material)?,
+                None => None,
+            };
+
+            primitives.push(self.add_primitive(&geometry, material));
+        }
+
+        let mesh = (!primitives.is_empty()).then(|| {
+            self.document.root.push(json::Mesh {
+                name: Some(model.name.to_string()),
+                primitives,
+                weights: None,
+                extensions: None,
+                extras: Default::default(),
+            })
+        });
+        self.meshes.insert(model.id, mesh);
+
+        Ok(mesh)
+    }
+
+    fn geometry(&self, mesh: &ResourceHandle<Mesh>) -> Option<Arc<MeshGeometry>> {
+        let properties = &self.resource_manager.get(mesh)?.properties;
+
+        let geometry = match &properties.path {
+            MeshPath::Mesh(geometry) => geometry.clone(),
+            MeshPath::File(filename) => match MeshGeometry::load_cooked(filename) {
+                Ok(geometry) => geometry,
+                Err(e) => {
+                    tracing::warn!(target: logger::RESOURCES, "Cannot export mesh {}: {}", properties.name, e);
+                    return None;
+                }
+            },
+            MeshPath::Default | MeshPath::Bytes(_) => {
+                tracing::warn!(target: logger::RESOURCES, "Cannot export mesh {}: no geometry", properties.name);
+                return None;
+            }
+        };
+
+        (!geometry.vertices.is_empty()).then_some(geometry)
+    }
+
+    fn add_primitive(
+        &mut self,
+        geometry: &MeshGeometry,
+        material: Option<Index<json::Material>>,
+    ) -> json::mesh::Primitive {
+        let vertices = &geometry.vertices;
+        let count = vertices.len();
+
+        if !geometry.targets.is_empty() {
+            tracing::warn!(target: logger::RESOURCES, "Export mesh {} without its {} morph targets", geometry.name, geometry.targets.len());
+        }
+
+        let positions = vertices
+            .iter()
+            .map(VertexData::position)
+            .collect::<Vec<_>>();
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), &position| (min.min(position), max.max(position)),
+        );
+
+        let mut attributes = BTreeMap::new();
+        attributes.insert(
+            Valid(Semantic::Positions),
+            self.document.add_accessor(
+                &Self::floats(positions.iter().map(|position| position.to_array())),
+                count,
+                ComponentType::F32,
+                Type::Vec3,
+                Some(Target::ArrayBuffer),
+                Some((min.to_array().to_vec(), max.to_array().to_vec())),
+            ),
+        );
+        attributes.insert(
+            Valid(Semantic::Normals),
+            self.document.add_accessor(
+                &Self::floats(vertices.iter().map(|vertex| vertex.normal().to_array())),
+                count,
+                ComponentType::F32,
+                Type::Vec3,
+                Some(Target::ArrayBuffer),
+                None,
+            ),
+        );
+        attributes.insert(
+            Valid(Semantic::TexCoords(0)),
+            self.document.add_accessor(
+                &Self::floats(vertices.iter().map(|vertex| vertex.texture().to_array())),
+                count,
+                ComponentType::F32,
+                Type::Vec2,
+                Some(Target::ArrayBuffer),
+                None,
+            ),
+        );
+        attributes.insert(
+            Valid(Semantic::Colors(0)),
+            self.document.add_accessor(
+                &Self::floats(
+                    vertices
+                        .iter()
+                        .map(|vertex| <[f32; 4]>::from(vertex.color())),
+                ),
+                count,
+                ComponentType::F32,
+                Type::Vec4,
+                Some(Target::ArrayBuffer),
+                None,
+            ),
+        );
+
+        let indices = (!geometry.indices.is_empty()).then(|| {
+            let data = geometry
+                .indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect::<Vec<u8>>();
+
+            self.document.add_accessor(
+                &data,
+                geometry.indices.len(),
+                ComponentType::U32,
+                Type::Scalar,
+                Some(Target::ElementArrayBuffer),
+                None,
+            )
+        });
+
+        json::mesh::Primitive {
+            attributes,
+            indices,
+            material,
+            mode: Valid(Mode::Triangles),
+            targets: None,
+            extensions: None,
+            extras: Default::default(),
+        }
+    }
+
+    fn floats<const N: usize>(values: impl Iterator<Item = [f32; N]>) -> Vec<u8> {
+        values
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn add_material(
+        &mut self,
+        handle: ResourceHandle<MaterialInstance>,
+    ) -> Result<Option<Index<json::Material>>, AssetError> {
+        if let Some(&material) = self.materials.get(&handle) {
+            return Ok(material);
+        }
+
+        let resource_manager = self.resource_manager;
+
+        let Some(instance) = resource_manager.get(&handle) else {
+            self.materials.insert(handle, None);
+            return Ok(None);
+        };
+        let properties = &instance.properties;
+        let material = resource_manager
+            .get(&properties.material)
+            .map(|material| &material.properties);
+
+        let has_prop = |prop: fn(&MaterialDataProp) -> bool| {
+            properties.material_data.is_some()
+                && material
+                    .is_some_and(|material| material.material_data_layout.layout().iter().any(prop))
+        };
+        let data = properties.material_data.clone().unwrap_or_default();
+
+        let color = if has_prop(|prop| matches!(prop, MaterialDataProp::DiffuseColor)) {
+            data.diffuse_color
+        } else {
+            [1.; 4]
+        };
+        let emission = if has_prop(|prop| matches!(prop, MaterialDataProp::EmissionColor)) {
+            [
+                data.emission_color[0],
+                data.emission_color[1],
+                data.emission_color[2],
+            ]
+        } else {
+            [0.; 3]
+        };
+        // non PBR materials are exported as dielectric
+        let (metallic, roughness) =
+            if has_prop(|prop| matches!(prop, MaterialDataProp::MetallicFactor)) {
+                (data.metallic_factor, data.roughness_factor)
+            } else {
+                (0., 1.)
+            };
+        let occlusion_strength =
+            if has_prop(|prop| matches!(prop, MaterialDataProp::OcclusionStrength)) {
+                data.occlusion_strength
+            } else {
+                1.
+            };
+        let alpha_cutoff = has_prop(|prop| matches!(prop, MaterialDataProp::AlphaCutoff))
+            .then_some(data.alpha_cutoff)
+            .filter(|&cutoff| cutoff > 0.);
+        let alpha_mode = if alpha_cutoff.is_some() {
+            AlphaMode::Mask
+        } else if material.is_some_and(|material| material.blending_enabled) {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        };
+        let double_sided = material.is_some_and(|material| material.is_double_sided());
+
+        let mut gltf_material = json::Material {
+            name: Some(properties.name.clone()),
+            alpha_cutoff: alpha_cutoff.map(AlphaCutoff),
+            alpha_mode: Valid(alpha_mode),
+            double_sided,
+            pbr_metallic_roughness: PbrMetallicRoughness {
+                base_color_factor: PbrBaseColorFactor(color),
+                metallic_factor: StrengthFactor(metallic),
+                roughness_factor: StrengthFactor(roughness),
+                ..Default::default()
+            },
+            emissive_factor: EmissiveFactor(emission),
+            ..Default::default()
+        };
+
+        for texture in &properties.textures {
+            let Some(ty) = resource_manager
+                .get(texture)
+                .map(|texture| texture.properties.format.ty)
+            else {
+                continue;
+            };
+
+            let Some(index) = self.add_texture(*texture)? else {
+                continue;
+            };
+
+            let info = json::texture::Info {
+                index,
+                tex_coord: 0,
+                extensions: None,
+                extras: Default::default(),
+            };
+
+            // first texture of each type
+            match ty {
+                TextureType::Diffuse => {
+                    let pbr = &mut gltf_material.pbr_metallic_roughness;
+                    pbr.base_color_texture = pbr.base_color_texture.take().or(Some(info));
+                }
+                TextureType::MetallicRoughness => {
+                    let pbr = &mut gltf_material.pbr_metallic_roughness;
+                    pbr.metallic_roughness_texture =
+                        pbr.metallic_roughness_texture.take().or(Some(info));
+                }
+                TextureType::Emission => {
+                    gltf_material.emissive_texture =
+                        gltf_material.emissive_texture.take().or(Some(info));
+                }
+                TextureType::Normal => {
+                    gltf_material.normal_texture.get_or_insert(NormalTexture {
+                        index,
+                        scale: 1.,
+                        tex_coord: 0,
+                        extensions: None,
+                        extras: Default::default(),
+                    });
+                }
+                TextureType::Occlusion => {
+                    gltf_material
+                        .occlusion_texture
+                        .get_or_insert(OcclusionTexture {
+                            index,
+                            strength: StrengthFactor(occlusion_strength),
+                            tex_coord: 0,
+                            extensions: None,
+                            extras: Default::default(),
+                        });
+                }
+            }
+        }
+
+        let index = self.document.root.push(gltf_material);
+        self.materials.insert(handle, Some(index));
+
+        Ok(Some(index))
+    }
+
+    /// Textures are embedded as PNG images. Only 8 bits RGBA and BGRA textures are supported
+    fn add_texture(
+        &mut self,
+        handle: ResourceHandle<Texture>,
+    ) -> Result<Option<Index<json::Texture>>, AssetError> {
+        if let Some(&texture) = self.textures.get(&handle) {
+            return Ok(texture);
+        }
+
+        let resource_manager = self.resource_manager;
+
+        let Some(texture) = resource_manager.get(&handle) else {
+            self.textures.insert(handle, None);
+            return Ok(None);
+        };
+        let properties = &texture.properties;
+
+        // placeholder for missing textures
+        if matches!(properties.path, TexturePath::Default) {
+            self.textures.insert(handle, None);
+            return Ok(None);
+        }
+
+        let mut format = properties.format.clone();
+        let mut data = vec![];
+        TextureLoader::get_bytes(&properties.path, &mut format, |bytes| {
+            data.extend_from_slice(bytes)
+        })?;
+
+        let bgra = match format.format {
+            ImageFormat::R8g8b8a8Unorm | ImageFormat::R8g8b8a8Srgb => false,
+            ImageFormat::B8g8r8a8Unorm | ImageFormat::B8g8r8a8Srgb => true,
+            _ => {
+                tracing::warn!(target: logger::RESOURCES, "Cannot export texture {}: unsupported format {:?}", properties.name, format.format);
+                self.textures.insert(handle, None);
+                return Ok(None);
+            }
+        };
+
+        // drop the mip levels
+        let extent = format.extent;
+        let size = (extent.width * extent.height * 4) as usize;
+        if data.len() < size {
+            tracing::warn!(target: logger::RESOURCES, "Cannot export texture {}: invalid size {}", properties.name, data.len());
+            self.textures.insert(handle, None);
+            return Ok(None);
+        }
+        data.truncate(size);
+
+        if bgra {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let mut png = Cursor::new(vec![]);
+        RgbaImage::from_raw(extent.width, extent.height, data)
+            .expect("Invalid texture size")
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|source| AssetError::EncodingError {
+                name: properties.name.clone(),
+                source,
+            })?;
+
+        let view = self.document.add_view(png.get_ref(), None);
+        let image = self.document.root.push(json::Image {
+            buffer_view: Some(view),
+            mime_type: Some(json::image::MimeType("image/png".to_string())),
+            name: Some(properties.name.clone()),
+            uri: None,
+            extensions: None,
+            extras: Default::default(),
+        });
+
+        let sampler = self.document.root.push(json::texture::Sampler {
+            mag_filter: Some(Valid(match format.mag_filter {
+                SamplerFilter::FilterNearest => MagFilter::Nearest,
+                SamplerFilter::FilterLinear => MagFilter::Linear,
+            })),
+            min_filter: Some(Valid(match format.min_filter {
+                SamplerFilter::FilterNearest => MinFilter::Nearest,
+                SamplerFilter::FilterLinear => MinFilter::Linear,
+            })),
+            ..Default::default()
+        });
+
+        let texture = Some(self.document.root.push(json::Texture {
+            name: Some(properties.name.clone()),
+            sampler: Some(sampler),
+            source: image,
+            extensions: None,
+            extras: Default::default(),
+        }));
+        self.textures.insert(handle, texture);
+
+        Ok(texture)
+    }
+}
+
+/// glTF document with all the buffer views in a single binary buffer
+#[derive(Default)]
+pub(crate) struct GLTFDocument {
+    pub(crate) root: json::Root,
+    buffer: Vec<u8>,
+}
+
+impl GLTFDocument {
+    /// Binary or JSON file, depending on the extension
+    pub(crate) fn write(self, file: &Path) -> Result<(), AssetError> {
+        let binary = file
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("glb"));
+
+        if binary {
+            self.write_glb(file)
+        } else {
+            self.write_gltf(file)
+        }
+    }
+
+    fn write_gltf(mut self, file: &Path) -> Result<(), AssetError> {
+        if !self.buffer.is_empty() {
+            let bin = file.with_extension("bin");
+            self.push_buffer(
+                bin.file_name()
+                    .map(|name| name.to_string_lossy().to_string()),
+            );
+            Self::write_file(&bin, &self.buffer)?;
+        }
+
+        Self::write_file(file, &self.root.to_vec_pretty()?)
+    }
+
+    fn write_glb(mut self, file: &Path) -> Result<(), AssetError> {
+        let bin = if self.buffer.is_empty() {
+            None
+        } else {
+            self.push_buffer(None);
+            Some(Cow::Owned(std::mem::take(&mut self.buffer)))
+        };
+
+        let glb = Glb {
+            // length is computed when writing
+            header: Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Owned(self.root.to_vec()?),
+            bin,
+        };
+
+        Self::write_file(file, &glb.to_vec()?)
+    }
+
+    fn write_file(file: &Path, data: &[u8]) -> Result<(), AssetError> {
+        fs::write(file, data).map_err(|source| AssetError::WriteError {
+            path: file.display().to_string(),
+            source,
+        })
+    }
+
+    /// All buffer views are stored in a single buffer
+    fn push_buffer(&mut self, uri: Option<String>) {
+        self.root.push(json::Buffer {
+            byte_length: USize64::from(self.buffer.len()),
+            name: None,
+            uri,
+            extensions: None,
+            extras: Default::default(),
+        });
+    }
+
+    pub(crate) fn add_view(
+        &mut self,
+        data: &[u8],
+        target: Option<Target>,
+    ) -> Index<json::buffer::View> {
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+        // keep views 4 bytes aligned
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+
+        self.root.push(json::buffer::View {
+            buffer: Index::new(0),
+            byte_length: USize64::from(data.len()),
+            byte_offset: Some(USize64::from(offset)),
+            byte_stride: None,
+            name: None,
+            target: target.map(Valid),
+            extensions: None,
+            extras: Default::default(),
+        })
+    }
+
+    pub(crate) fn add_accessor(
+        &mut self,
+        data: &[u8],
+        count: usize,
+        component_type: ComponentType,
+        ty: Type,
+        target: Option<Target>,
+        bounds: Option<(Vec<f32>, Vec<f32>)>,
+    ) -> Index<json::Accessor> {
+        let view = self.add_view(data, target);
+        let (min, max) = match bounds {
+            Some((min, max)) => (Some(min.into()), Some(max.into())),
+            None => (None, None),
+        };
+
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: None,
+            count: USize64::from(count),
+            component_type: Valid(GenericComponentType(component_type)),
+            extensions: None,
+            extras: Default::default(),
+            type_: Valid(ty),
+            min,
+            max,
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, sync::Arc};
+
+    use glam::{Quat, Vec3};
+
+    use gobs_core::{Color, GobsConfig, ImageExtent2D, ImageFormat, Transform};
+    use gobs_render::{
+        Material, MaterialDataPropData, MaterialInstance, MaterialInstanceProperties,
+        MaterialsConfig, MeshGeometry, RenderMeshBuilder, RenderModelBuilder, Shapes,
+        TextureProperties,
+    };
+    use gobs_resource::{ResourceLifetime, ResourceManager};
+    use gobs_scene::{components::NodeValue, graph::scenegraph::SceneGraph};
+
+    use crate::{
+        gltf_export::GLTFExporter,
+        gltf_load::GLTFLoader,
+        model::{ModelAsset, SceneSelection},
+        testing::{setup, temp_dir},
+    };
+
+    const COLORS: [Color; 4] = [Color::RED, Color::GREEN, Color::BLUE, Color::WHITE];
+
+    fn scene(resource_manager: &mut ResourceManager) -> (SceneGraph, Arc<MeshGeometry>) {
+        MaterialsConfig::load_resources_with_data(
+            GobsConfig::default(),
+            include_str!("../resources/gltf_materials.ron"),
+            resource_manager,
+        );
+        let pbr = resource_manager
+            .get_by_name::<Material>("gltf.pbr.double_sided")
+            .unwrap();
+
+        let texture = resource_manager.add(
+            TextureProperties::with_colors(
+                "checker",
+                ImageFormat::R8g8b8a8Srgb,
+                &COLORS,
+                ImageExtent2D::new(2, 2),
+            ),
+            ResourceLifetime::Static,
+            false,
+        );
+
+        let material = resource_manager.add::<MaterialInstance>(
+            MaterialInstanceProperties::new("red", pbr)
+                .prop(MaterialDataPropData::DiffuseColor(Color::RED.into()))
+                .prop(MaterialDataPropData::MetallicFactor(0.25))
+                .prop(MaterialDataPropData::RoughnessFactor(0.5))
+                .textures(&[texture]),
+            ResourceLifetime::Static,
+            false,
+        );
+
+        let geometry = Shapes::triangle(&COLORS[..3], 1.);
+        let mesh = RenderMeshBuilder::new(resource_manager, "triangle")
+            .with_geometry(geometry.clone())
+            .for_material(material)
+            .build();
+        let model = RenderModelBuilder::new(resource_manager, "triangle")
+            .with_mesh(mesh)
+            .with_material(material)
+            .build();
+
+        let mut scene = SceneGraph::new();
+
+        let parent = scene
+            .insert(
+                scene.root,
+                NodeValue::None,
+                Transform::new([1., 2., 3.].into(), Quat::IDENTITY, Vec3::ONE),
+            )
+            .unwrap();
+        scene.set_name(parent, "parent");
+
+        let child = scene
+            .insert(
+                parent,
+                NodeValue::Model(model.clone()),
+                Transform::new(Vec3::ZERO, Quat::from_rotation_y(1.), Vec3::splat(2.)),
+            )
+            .unwrap();
+        scene.set_name(child, "child");
+
+        let instance = scene
+            .insert(parent, NodeValue::Model(model.clone()), Transform::IDENTITY)
+            .unwrap();
+        scene.set_name(instance, "instance");
+
+        let hidden = scene
+            .insert(scene.root, NodeValue::Model(model), Transform::IDENTITY)
+            .unwrap();
+        scene.set_enabled(hidden, false);
+
+        (scene, geometry)
+    }
+
+    fn export(file: &Path) -> (ModelAsset, Arc<MeshGeometry>) {
+        let mut resource_manager = ResourceManager::new(1);
+        let (scene, geometry) = scene(&mut resource_manager);
+
+        GLTFExporter::new(&resource_manager)
+            .export(&scene, file)
+            .unwrap();
+
+        (GLTFLoader::read(&file).unwrap(), geometry)
+    }
+
+    fn check(asset: &ModelAsset, geometry: &MeshGeometry) {
+        let roots = asset.scene_roots(&SceneSelection::Default);
+        assert_eq!(roots.len(), 1);
+
+        let parent = &asset.nodes[roots[0]];
+        assert_eq!(parent.name, "parent");
+        assert_eq!(parent.mesh, None);
+        assert_eq!(parent.transform.translation(), Vec3::new(1., 2., 3.));
+        assert_eq!(parent.children.len(), 2);
+
+        let child = &asset.nodes[parent.children[0]];
+        assert_eq!(child.name, "child");
+        assert_eq!(child.mesh, Some(0));
+        assert!(
+            child
+                .transform
+                .rotation()
+                .abs_diff_eq(Quat::from_rotation_y(1.), 1e-6)
+        );
+        assert_eq!(child.transform.scaling(), Vec3::splat(2.));
+
+        // both nodes share the mesh
+        assert_eq!(asset.nodes[parent.children[1]].mesh, Some(0));
+        assert_eq!(asset.meshes.len(), 1);
+        assert_eq!(asset.meshes[0].name, "triangle");
+        assert_eq!(asset.meshes[0].primitives.len(), 1);
+
+        let primitive = &asset.meshes[0].primitives[0];
+        assert_eq!(primitive.material, Some(0));
+        assert_eq!(primitive.geometry.indices, geometry.indices);
+        assert_eq!(primitive.geometry.vertices.len(), geometry.vertices.len());
+        for (vertex, expected) in primitive.geometry.vertices.iter().zip(&geometry.vertices) {
+            assert_eq!(vertex.position(), expected.position());
+            assert_eq!(vertex.normal(), expected.normal());
+            assert_eq!(vertex.texture(), expected.texture());
+            assert_eq!(vertex.color(), expected.color());
+        }
+
+        assert_eq!(asset.materials.len(), 1);
+        let material = &asset.materials[0];
+        assert_eq!(material.name, "red");
+        assert_eq!(material.color, Color::RED);
+        assert_eq!(material.metallic, 0.25);
+        assert_eq!(material.roughness, 0.5);
+        assert!(material.double_sided);
+        assert_eq!(material.alpha_cutoff, None);
+        assert_eq!(material.diffuse, Some(0));
+        assert_eq!(material.normal, None);
+
+        assert_eq!(asset.textures.len(), 1);
+        let texture = asset.textures[0].as_ref().unwrap();
+        assert_eq!(texture.extent, ImageExtent2D::new(2, 2));
+        assert_eq!(
+            texture.data,
+            COLORS
+                .iter()
+                .flat_map(|&color| Into::<[u8; 4]>::into(color))
+                .collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn test_export_gltf() {
+        setup();
+
+        let dir = temp_dir("gltf-export");
+        let file = dir.join("scene.gltf");
+
+        let (asset, geometry) = export(&file);
+        assert!(dir.join("scene.bin").exists());
+        check(&asset, &geometry);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_glb() {
+        setup();
+
+        let dir = temp_dir("glb-export");
+        let file = dir.join("scene.glb");
+
+        let (asset, geometry) = export(&file);
+        assert!(!dir.join("scene.bin").exists());
+        check(&asset, &geometry);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_texture_formats() {
+        setup();
+
+        let dir = temp_dir("texture-export");
+        let file = dir.join("textures.glb");
+
+        let mut resource_manager = ResourceManager::new(1);
+
+        let mut texture = |format, data: Vec<u8>| {
+            resource_manager.add(
+                TextureProperties::with_data("texture", format, data, ImageExtent2D::new(1, 1)),
+                ResourceLifetime::Static,
+                false,
+            )
+        };
+        let bgra = texture(ImageFormat::B8g8r8a8Unorm, vec![10, 20, 30, 40]);
+        let packed = texture(ImageFormat::A2b10g10r10UnormPack32, vec![0; 4]);
+        let depth = texture(ImageFormat::D32Sfloat, vec![0; 4]);
+        let hdr = texture(ImageFormat::R16g16b16a16Sfloat, vec![0; 8]);
+
+        let mut exporter = GLTFExporter::new(&resource_manager);
+        assert!(exporter.add_texture(bgra).unwrap().is_some());
+        assert!(exporter.add_texture(packed).unwrap().is_none());
+        assert!(exporter.add_texture(depth).unwrap().is_none());
+        assert!(exporter.add_texture(hdr).unwrap().is_none());
+        exporter.export(&SceneGraph::new(), &file).unwrap();
+
+        let asset = GLTFLoader::read(&file).unwrap();
+        assert_eq!(asset.textures.len(), 1);
+        assert_eq!(
+            asset.textures[0].as_ref().unwrap().data,
+            vec![30, 20, 10, 40]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_empty() {
+        setup();
+
+        let dir = temp_dir("empty-export");
+        let file = dir.join("empty.glb");
+
+        let resource_manager = ResourceManager::new(1);
+        GLTFExporter::new(&resource_manager)
+            .export(&SceneGraph::new(), &file)
+            .unwrap();
+
+        let asset = GLTFLoader::read(&file).unwrap();
+        assert!(asset.nodes.is_empty());
+        assert!(asset.meshes.is_empty());
+        assert!(asset.scenes.is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
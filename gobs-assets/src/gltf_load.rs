@@ -1,34 +1,60 @@
-use std::{fmt::Debug, path::Path, sync::Arc};
+use std::{collections::HashMap, fmt::Debug, path::Path, sync::Arc};
 
-use glam::Quat;
+use ::image::{DynamicImage, ImageBuffer};
+use glam::{Mat4, Quat, UVec4, Vec3, Vec4};
 use gltf::{
-    Document, buffer, image,
+    Document,
+    animation::{self, util::ReadOutputs},
+    buffer,
+    camera::Projection,
+    image,
+    khr_lights_punctual::Kind,
     material::AlphaMode,
-    mesh::util::{ReadColors, ReadIndices},
+    mesh::Mode,
 };
 
 use gobs_core::{
     Color, ConfigReader as _, GobsConfig, ImageExtent2D, SamplerFilter, Transform, logger,
 };
 use gobs_render::{
-    BlendMode, MaterialInstance, Mesh, MeshGeometry, Model, RenderMeshBuilder, RenderModelBuilder,
-    TextureProperties, TextureType, VertexData,
+    BlendMode, MaterialInstance, Mesh, MeshGeometry, Model, MorphTarget, RenderMeshBuilder,
+    RenderModelBuilder, Skeleton, TextureProperties, TextureType, VertexData,
+};
+use gobs_resource::{
+    ResourceHandle, ResourceLifetime, ResourceManager,
+    camera::Camera,
+    light::{Light, LightType},
+    worker::{self, LoadTask},
 };
-use gobs_resource::{ResourceHandle, ResourceLifetime, ResourceManager};
 use gobs_scene::{
-    components::{NodeId, NodeValue},
+    animation::{AnimationClip, Interpolation, Keyframes, Track},
+    components::{AnimationComponent, MorphComponent, NodeId, NodeValue, SkinComponent},
     graph::scenegraph::SceneGraph,
 };
 
-use crate::{AssetError, config::GltfConfig, manager::MaterialManager};
+use crate::{
+    AssetError,
+    config::GltfConfig,
+    manager::MaterialManager,
+    model::{
+        AnimationAsset, CameraAsset, ChannelAsset, LightAsset, MaterialAsset, MeshAsset,
+        ModelAsset, NodeAsset, PrimitiveAsset, SceneAsset, SceneSelection, SkinAsset, TextureAsset,
+        UvTransform,
+    },
+};
 
 pub struct GLTFLoader {
     material_manager: MaterialManager,
     pub models: Vec<Arc<Model>>,
     pub scene: SceneGraph,
+    /// Scenes of the model added to the scene graph
+    pub scene_selection: SceneSelection,
 }
 
 impl GLTFLoader {
+    /// Far plane of perspective cameras without one
+    const INFINITE_FAR: f32 = 10000.;
+
     pub fn new(
         config: GobsConfig,
         resource_manager: &mut ResourceManager,
@@ -39,9 +65,16 @@ impl GLTFLoader {
             material_manager,
             models: vec![],
             scene: SceneGraph::new(),
+            scene_selection: SceneSelection::Default,
         })
     }
 
+    pub fn with_scene(mut self, scene_selection: SceneSelection) -> Self {
+        self.scene_selection = scene_selection;
+
+        self
+    }
+
     pub fn load<P>(
         &mut self,
         config: GobsConfig,
@@ -51,155 +84,826 @@ impl GLTFLoader {
     where
         P: AsRef<Path> + Debug,
     {
-        let (doc, buffers, images) = gltf::import(&file)?;
+        let asset = Self::read(&file)?;
+
+        let path = file.as_ref().to_string_lossy();
+        self.load_asset_from(config, resource_manager, asset, Some(&path));
+
+        Ok(())
+    }
+
+    /// Load a model cooked by `gobs-cook` from the models directory
+    pub fn load_cooked(
+        &mut self,
+        config: GobsConfig,
+        resource_manager: &mut ResourceManager,
+        filename: &str,
+    ) -> Result<(), AssetError> {
+        tracing::debug!(target: logger::RESOURCES, "Load cooked model: {}", filename);
 
-        self.load_material(config, resource_manager, &doc, &images);
+        let asset = ModelAsset::load_cooked(filename)?;
 
-        self.load_models(resource_manager, &doc, &buffers);
-        self.load_scene(&doc);
+        self.load_asset_from(config, resource_manager, asset, Some(filename));
 
         Ok(())
     }
 
-    fn load_scene(&mut self, doc: &Document) {
-        tracing::info!(target: logger::RESOURCES, "{} scenes found, default={}", doc.scenes().len(), doc.as_json().scene.unwrap());
-        for scene in doc.scenes() {
-            for node in scene.nodes() {
-                self.add_node(self.scene.root, &node);
+    /// Read and decode the glTF file, buffers and images on a worker thread
+    pub fn import<P>(file: P) -> LoadTask<Result<ModelAsset, AssetError>>
+    where
+        P: AsRef<Path> + Debug + Send + 'static,
+    {
+        worker::pool().spawn(move || Self::read(&file))
+    }
+
+    /// Read and decode the glTF file, buffers and images
+    pub fn read<P>(file: &P) -> Result<ModelAsset, AssetError>
+    where
+        P: AsRef<Path> + Debug,
+    {
+        tracing::debug!(target: logger::RESOURCES, "Import gltf: {:?}", file);
+
+        let (doc, buffers, images) = gltf::import(file)?;
+
+        Ok(ModelAsset {
+            textures: Self::read_textures(&doc, &images),
+            materials: Self::read_materials(&doc),
+            meshes: Self::read_meshes(&doc, &buffers)?,
+            skins: Self::read_skins(&doc, &buffers),
+            cameras: Self::read_cameras(&doc),
+            lights: Self::read_lights(&doc),
+            nodes: Self::read_nodes(&doc),
+            scenes: Self::read_scenes(&doc),
+            default_scene: doc.default_scene().map(|scene| scene.index()),
+            animations: Self::read_animations(&doc, &buffers),
+        })
+    }
+
+    /// Create resources and scene from a decoded model
+    pub fn load_asset(
+        &mut self,
+        config: GobsConfig,
+        resource_manager: &mut ResourceManager,
+        asset: ModelAsset,
+    ) {
+        self.load_asset_from(config, resource_manager, asset, None);
+    }
+
+    fn load_asset_from(
+        &mut self,
+        config: GobsConfig,
+        resource_manager: &mut ResourceManager,
+        asset: ModelAsset,
+        path: Option<&str>,
+    ) {
+        self.load_textures(config, resource_manager, &asset.textures);
+        let skinned_meshes = Self::skinned_meshes(&asset);
+        self.load_materials(
+            resource_manager,
+            &asset.materials,
+            &Self::skinned_materials(&asset, &skinned_meshes),
+        );
+
+        self.load_models(resource_manager, &asset.meshes, &skinned_meshes, path);
+        self.load_scene(&asset);
+    }
+
+    fn read_nodes(doc: &Document) -> Vec<NodeAsset> {
+        doc.nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+
+                NodeAsset {
+                    name: node.name().unwrap_or_default().to_string(),
+                    transform: Transform::new(
+                        translation.into(),
+                        Quat::from_array(rotation),
+                        scale.into(),
+                    ),
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                    camera: node.camera().map(|camera| camera.index()),
+                    light: node.light().map(|light| light.index()),
+                    skin: node.skin().map(|skin| skin.index()),
+                    weights: node.weights().map(<[f32]>::to_vec).unwrap_or_default(),
+                    children: node.children().map(|child| child.index()).collect(),
+                }
+            })
+            .collect()
+    }
+
+    fn read_scenes(doc: &Document) -> Vec<SceneAsset> {
+        tracing::info!(target: logger::RESOURCES, "{} scenes found, default={:?}", doc.scenes().len(), doc.as_json().scene);
+
+        doc.scenes()
+            .map(|scene| SceneAsset {
+                name: scene.name().unwrap_or_default().to_string(),
+                roots: scene.nodes().map(|node| node.index()).collect(),
+            })
+            .collect()
+    }
+
+    fn read_cameras(doc: &Document) -> Vec<CameraAsset> {
+        doc.cameras()
+            .map(|camera| match camera.projection() {
+                Projection::Perspective(p) => CameraAsset::Perspective {
+                    aspect: p.aspect_ratio(),
+                    yfov: p.yfov(),
+                    znear: p.znear(),
+                    zfar: p.zfar(),
+                },
+                Projection::Orthographic(o) => CameraAsset::Ortho {
+                    xmag: o.xmag(),
+                    ymag: o.ymag(),
+                    znear: o.znear(),
+                    zfar: o.zfar(),
+                },
+            })
+            .collect()
+    }
+
+    fn read_lights(doc: &Document) -> Vec<LightAsset> {
+        doc.lights()
+            .into_iter()
+            .flatten()
+            .map(|light| {
+                let [r, g, b] = light.color();
+                let range = light.range();
+
+                LightAsset {
+                    color: [r, g, b, 1.].into(),
+                    intensity: light.intensity(),
+                    ty: match light.kind() {
+                        Kind::Directional => LightType::Directional,
+                        Kind::Point => LightType::Point { range },
+                        Kind::Spot {
+                            inner_cone_angle,
+                            outer_cone_angle,
+                        } => LightType::Spot {
+                            range,
+                            inner_cone_angle,
+                            outer_cone_angle,
+                        },
+                    },
+                }
+            })
+            .collect()
+    }
+
+    fn load_scene(&mut self, asset: &ModelAsset) {
+        let root = self.scene.root;
+
+        let mut keys = vec![None; asset.nodes.len()];
+        for node in asset.scene_roots(&self.scene_selection) {
+            self.add_node(root, asset, node, &mut keys);
+        }
+
+        self.load_skins(asset, &keys);
+        self.load_animations(asset, &keys);
+
+        tracing::info!(target: logger::RESOURCES, "{} scene nodes loaded", self.scene.len());
+    }
+
+    fn add_node(
+        &mut self,
+        parent: NodeId,
+        asset: &ModelAsset,
+        node: usize,
+        keys: &mut [Option<NodeId>],
+    ) {
+        let node_asset = &asset.nodes[node];
+
+        // a node has a single value, cameras and lights of mesh nodes are added as
+        // children after the glTF children
+        let mut values = vec![];
+        if let Some(mesh) = node_asset.mesh {
+            values.push(NodeValue::Model(self.models[mesh].clone()));
+        }
+        if let Some(camera) = node_asset.camera {
+            let rotation = self
+                .scene
+                .get(parent)
+                .map(|parent| parent.global_transform().rotation())
+                .unwrap_or_default()
+                * node_asset.transform.rotation();
+
+            values.push(Self::camera(
+                &node_asset.name,
+                &asset.cameras[camera],
+                rotation,
+            ));
+        }
+        if let Some(light) = node_asset.light {
+            values.push(Self::light(&asset.lights[light]));
+        }
+        let mut values = values.into_iter();
+
+        let node_key = self
+            .scene
+            .insert(
+                parent,
+                values.next().unwrap_or(NodeValue::None),
+                node_asset.transform,
+            )
+            .unwrap();
+        if !node_asset.name.is_empty() {
+            self.scene.set_name(node_key, &node_asset.name);
+        }
+
+        if let Some(mesh) = node_asset.mesh {
+            let mesh = &asset.meshes[mesh];
+            let target_count = Self::target_count(mesh);
+
+            if target_count > 0 {
+                let mut weights = if node_asset.weights.is_empty() {
+                    mesh.weights.clone()
+                } else {
+                    node_asset.weights.clone()
+                };
+                weights.resize(target_count, 0.);
+
+                let morph = MorphComponent::new(weights);
+                self.scene.update(node_key, |node| {
+                    node.morph = Some(morph.clone());
+
+                    false
+                });
             }
-            tracing::info!(target: logger::RESOURCES, "{} scene nodes loaded", self.scene.len());
         }
+
+        for &child in &node_asset.children {
+            self.add_node(node_key, asset, child, keys);
+        }
+
+        for value in values {
+            self.scene.insert(node_key, value, Transform::IDENTITY);
+        }
+
+        keys[node].get_or_insert(node_key);
     }
 
-    fn add_node(&mut self, parent: NodeId, node: &gltf::Node) {
-        let (translation, rotation, scale) = node.transform().decomposed();
-        let transform =
-            Transform::new(translation.into(), Quat::from_array(rotation), scale.into());
+    /// Cameras look along the -Z axis of their node. The view does not use the node
+    /// rotation, it is converted to the yaw and pitch of the camera and the roll is lost.
+    /// Cameras without aspect ratio use a square one until they are resized to the viewport
+    fn camera(name: &str, camera: &CameraAsset, rotation: Quat) -> NodeValue {
+        let dir = rotation * Vec3::NEG_Z;
+        let pitch = dir.y.clamp(-1., 1.).asin();
+        let yaw = dir.x.atan2(-dir.z);
+
+        // the right axis stays horizontal without roll
+        if (rotation * Vec3::X).y.abs() > 1e-3 {
+            tracing::warn!(target: logger::RESOURCES, "Camera roll of node {} is ignored", name);
+        }
 
-        let node_value = match node.mesh() {
-            Some(mesh) => NodeValue::Model(self.models[mesh.index()].clone()),
-            None => NodeValue::None,
+        let camera = match *camera {
+            CameraAsset::Perspective {
+                aspect,
+                yfov,
+                znear,
+                zfar,
+            } => Camera::perspective(
+                aspect.unwrap_or(1.),
+                yfov,
+                znear,
+                zfar.unwrap_or(Self::INFINITE_FAR),
+                yaw,
+                pitch,
+            ),
+            CameraAsset::Ortho {
+                xmag,
+                ymag,
+                znear,
+                zfar,
+            } => Camera::ortho(2. * xmag, 2. * ymag, znear, zfar, yaw, pitch),
         };
 
-        let node_key = self.scene.insert(parent, node_value, transform).unwrap();
+        NodeValue::Camera(camera)
+    }
+
+    fn light(light: &LightAsset) -> NodeValue {
+        NodeValue::Light(Light::with_type(light.color, light.intensity, light.ty))
+    }
+
+    /// Skinned nodes get the joint nodes of the first instance of the model
+    fn load_skins(&mut self, asset: &ModelAsset, keys: &[Option<NodeId>]) {
+        let skeletons = asset
+            .skins
+            .iter()
+            .map(|skin| Skeleton::new(&skin.name, skin.inverse_bind_matrices.clone()))
+            .collect::<Vec<_>>();
 
-        for child in node.children() {
-            self.add_node(node_key, &child);
+        for (node, node_asset) in asset.nodes.iter().enumerate() {
+            let (Some(skin), Some(node_key)) = (node_asset.skin, keys[node]) else {
+                continue;
+            };
+
+            let joints = asset.skins[skin]
+                .joints
+                .iter()
+                .map(|&joint| keys[joint].unwrap_or_default())
+                .collect();
+
+            let skin = SkinComponent::new(skeletons[skin].clone(), joints);
+            self.scene.update(node_key, |node| {
+                node.skin = Some(skin.clone());
+
+                false
+            });
         }
+
+        tracing::info!(target: logger::RESOURCES, "{} skins loaded", skeletons.len());
     }
 
-    fn load_models(
-        &mut self,
-        resource_manager: &mut ResourceManager,
-        doc: &Document,
-        buffers: &[buffer::Data],
-    ) {
-        for m in doc.meshes() {
-            let name = m.name().unwrap_or_default();
-            tracing::debug!(target: logger::RESOURCES,
-                "Mesh #{}: {}, primitives: {}",
-                m.index(),
-                name,
-                m.primitives().len(),
-            );
+    /// Animations are played by a player on the scene root, the tracks target the
+    /// loaded nodes
+    fn load_animations(&mut self, asset: &ModelAsset, keys: &[Option<NodeId>]) {
+        if asset.animations.is_empty() {
+            return;
+        }
 
-            let meshes = m
-                .primitives()
-                .map(|p| {
-                    tracing::debug!(target: logger::RESOURCES,
-                        "Primitive #{}, material {:?}",
-                        p.index(),
-                        p.material().index()
-                    );
-                    let material = match p.material().index() {
-                        Some(mat_idx) => self.material_manager.instances[mat_idx],
-                        None => self.material_manager.default_material_instance,
-                    };
+        let root = self.scene.root;
+        let first_target = self
+            .scene
+            .get(root)
+            .and_then(|node| node.animation.as_ref())
+            .map_or(0, |animation| animation.targets.len());
 
-                    let name = format!("{}.{}", name, p.index());
-                    let mut mesh_data = MeshGeometry::builder(&name);
+        // targets of the player, with the index of each animated glTF node
+        let mut targets = Vec::new();
+        let mut indices = HashMap::new();
+
+        let clips: Vec<Arc<AnimationClip>> = asset
+            .animations
+            .iter()
+            .map(|animation| {
+                let tracks = animation
+                    .channels
+                    .iter()
+                    .filter_map(|channel| {
+                        let key = keys.get(channel.node).copied().flatten()?;
+                        let target = *indices.entry(channel.node).or_insert_with(|| {
+                            targets.push(key);
+                            first_target + targets.len() - 1
+                        });
+
+                        Some(Track::new(
+                            target,
+                            channel.interpolation,
+                            channel.times.clone(),
+                            channel.keyframes.clone(),
+                        ))
+                    })
+                    .collect();
+
+                Arc::new(AnimationClip::new(&animation.name, tracks))
+            })
+            .collect();
+
+        tracing::info!(target: logger::RESOURCES, "{} animations loaded", clips.len());
 
-                    let reader = p.reader(|buffer| Some(&buffers[buffer.index()]));
+        self.scene.update(root, |node| {
+            let animation = node
+                .animation
+                .get_or_insert_with(AnimationComponent::default);
+            animation.clips.extend(clips.iter().cloned());
+            animation.targets.extend(targets.iter().copied());
 
-                    if let Some(read_indices) = reader.read_indices() {
-                        match read_indices {
-                            ReadIndices::U8(iter) => {
-                                for idx in iter {
-                                    mesh_data.index(idx as u32);
-                                }
+            false
+        });
+    }
+
+    fn read_animations(doc: &Document, buffers: &[buffer::Data]) -> Vec<AnimationAsset> {
+        doc.animations()
+            .map(|animation| {
+                let name = match animation.name() {
+                    Some(name) => name.to_string(),
+                    None => format!("animation.{}", animation.index()),
+                };
+
+                tracing::debug!(target: logger::RESOURCES,
+                    "Animation #{}: {}, channels: {}",
+                    animation.index(),
+                    name,
+                    animation.channels().count(),
+                );
+
+                let channels = animation
+                    .channels()
+                    .filter_map(|channel| {
+                        let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+
+                        let times = reader.read_inputs()?.collect();
+                        let keyframes = match reader.read_outputs()? {
+                            ReadOutputs::Translations(iter) => {
+                                Keyframes::Translation(iter.map(Vec3::from).collect())
                             }
-                            ReadIndices::U16(iter) => {
-                                for idx in iter {
-                                    mesh_data.index(idx as u32);
-                                }
+                            ReadOutputs::Rotations(iter) => {
+                                Keyframes::Rotation(iter.into_f32().map(Quat::from_array).collect())
                             }
-                            ReadIndices::U32(iter) => {
-                                for idx in iter {
-                                    mesh_data.index(idx);
-                                }
+                            ReadOutputs::Scales(iter) => {
+                                Keyframes::Scale(iter.map(Vec3::from).collect())
                             }
-                        }
-                    }
+                            ReadOutputs::MorphTargetWeights(iter) => {
+                                Keyframes::Weights(iter.into_f32().collect())
+                            }
+                        };
 
-                    if let Some(iter) = reader.read_positions() {
-                        for pos in iter {
-                            mesh_data.vertex(VertexData::builder().position(pos.into()).build());
-                        }
-                    }
+                        Some(ChannelAsset {
+                            node: channel.target().node().index(),
+                            interpolation: Self::into_interpolation(
+                                channel.sampler().interpolation(),
+                            ),
+                            times,
+                            keyframes,
+                        })
+                    })
+                    .collect();
 
-                    if let Some(iter) = reader.read_normals() {
-                        for (i, normal) in iter.enumerate() {
-                            mesh_data.vertices[i].set_normal(normal.into());
-                        }
-                    }
+                AnimationAsset { name, channels }
+            })
+            .collect()
+    }
 
-                    if let Some(read_tex_coords) = reader.read_tex_coords(0) {
-                        match read_tex_coords {
-                            gltf::mesh::util::ReadTexCoords::U8(_) => todo!(),
-                            gltf::mesh::util::ReadTexCoords::U16(_) => todo!(),
-                            gltf::mesh::util::ReadTexCoords::F32(iter) => {
-                                for (i, texture) in iter.enumerate() {
-                                    mesh_data.vertices[i].set_texture(texture.into());
-                                }
-                            }
-                        }
+    fn into_interpolation(interpolation: animation::Interpolation) -> Interpolation {
+        match interpolation {
+            animation::Interpolation::Step => Interpolation::Step,
+            animation::Interpolation::Linear => Interpolation::Linear,
+            animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+        }
+    }
+
+    fn read_skins(doc: &Document, buffers: &[buffer::Data]) -> Vec<SkinAsset> {
+        doc.skins()
+            .map(|skin| {
+                let name = match skin.name() {
+                    Some(name) => name.to_string(),
+                    None => format!("skin.{}", skin.index()),
+                };
+
+                let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+
+                tracing::debug!(target: logger::RESOURCES,
+                    "Skin #{}: {}, joints: {}",
+                    skin.index(),
+                    name,
+                    joints.len(),
+                );
+
+                // identity matrices when not specified
+                let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+                let inverse_bind_matrices = match reader.read_inverse_bind_matrices() {
+                    Some(iter) => iter.map(|m| Mat4::from_cols_array_2d(&m)).collect(),
+                    None => vec![Mat4::IDENTITY; joints.len()],
+                };
+
+                SkinAsset {
+                    name,
+                    joints,
+                    inverse_bind_matrices,
+                }
+            })
+            .collect()
+    }
+
+    /// Meshes drawn with the skinned materials: meshes of skinned nodes and meshes
+    /// with morph targets
+    fn skinned_meshes(asset: &ModelAsset) -> Vec<bool> {
+        let mut skinned = asset
+            .meshes
+            .iter()
+            .map(|mesh| Self::target_count(mesh) > 0)
+            .collect::<Vec<_>>();
+
+        for node in &asset.nodes {
+            if let (Some(mesh), Some(_)) = (node.mesh, node.skin) {
+                skinned[mesh] = true;
+            }
+        }
+
+        skinned
+    }
+
+    /// Materials used by at least one skinned primitive
+    fn skinned_materials(asset: &ModelAsset, skinned_meshes: &[bool]) -> Vec<bool> {
+        let mut skinned = vec![false; asset.materials.len()];
+
+        for (mesh, _) in asset
+            .meshes
+            .iter()
+            .zip(skinned_meshes)
+            .filter(|(_, skinned)| **skinned)
+        {
+            for primitive in &mesh.primitives {
+                if let Some(material) = primitive.material
+                    && Self::is_skinned(&primitive.geometry)
+                {
+                    skinned[material] = true;
+                }
+            }
+        }
+
+        skinned
+    }
+
+    /// Primitives drawn with the skinned materials, with joint weights or morph targets
+    fn is_skinned(geometry: &MeshGeometry) -> bool {
+        !geometry.targets.is_empty()
+            || geometry
+                .vertices
+                .iter()
+                .any(|vertex| vertex.weights() != Vec4::ZERO)
+    }
+
+    fn target_count(mesh: &MeshAsset) -> usize {
+        mesh.primitives
+            .iter()
+            .map(|primitive| primitive.geometry.targets.len())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn read_meshes(doc: &Document, buffers: &[buffer::Data]) -> Result<Vec<MeshAsset>, AssetError> {
+        doc.meshes()
+            .map(|m| {
+                let name = m.name().unwrap_or_default();
+                tracing::debug!(target: logger::RESOURCES,
+                    "Mesh #{}: {}, primitives: {}",
+                    m.index(),
+                    name,
+                    m.primitives().len(),
+                );
+
+                let mut primitives = vec![];
+                for p in m.primitives() {
+                    tracing::debug!(target: logger::RESOURCES,
+                        "Primitive #{}, material {:?}, mode {:?}",
+                        p.index(),
+                        p.material().index(),
+                        p.mode()
+                    );
+
+                    let name = format!("{}.{}", name, p.index());
+
+                    if let Some(geometry) = Self::read_geometry(&name, &p, buffers)? {
+                        primitives.push(PrimitiveAsset {
+                            geometry,
+                            material: p.material().index(),
+                        });
                     }
+                }
 
-                    if let Some(read_colors) = reader.read_colors(0) {
-                        match read_colors {
-                            ReadColors::RgbaU8(iter) => {
-                                for (i, color) in iter.enumerate() {
-                                    mesh_data.vertices[i].set_color(color.into());
-                                }
-                            }
-                            ReadColors::RgbaF32(iter) => {
-                                for (i, color) in iter.enumerate() {
-                                    mesh_data.vertices[i].set_color(color.into());
-                                }
-                            }
-                            ReadColors::RgbaU16(iter) => {
-                                for (i, color) in iter.enumerate() {
-                                    mesh_data.vertices[i].set_color(color.into());
-                                }
-                            }
-                            _ => todo!(),
-                        }
+                Ok(MeshAsset {
+                    name: name.to_string(),
+                    primitives,
+                    weights: m.weights().map(<[f32]>::to_vec).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns `None` for primitives that cannot be rendered as triangles
+    fn read_geometry(
+        name: &str,
+        p: &gltf::Primitive,
+        buffers: &[buffer::Data],
+    ) -> Result<Option<Arc<MeshGeometry>>, AssetError> {
+        let reader = p.reader(|buffer| Some(&buffers[buffer.index()]));
+
+        let Some(positions) = reader.read_positions() else {
+            tracing::warn!(target: logger::RESOURCES, "Skip primitive {} without positions", name);
+            return Ok(None);
+        };
+
+        let mut mesh_data = MeshGeometry::builder(name);
+
+        for pos in positions {
+            mesh_data.vertex(
+                VertexData::builder()
+                    .position(pos.into())
+                    .color(Color::WHITE)
+                    .build(),
+            );
+        }
+
+        let vertex_count = mesh_data.vertices.len();
+
+        let indices = match reader.read_indices() {
+            Some(read_indices) => read_indices.into_u32().collect(),
+            None => (0..vertex_count as u32).collect::<Vec<u32>>(),
+        };
+
+        if let Some(index) = indices
+            .iter()
+            .find(|&&index| index as usize >= vertex_count)
+        {
+            return Err(AssetError::InvalidData(format!(
+                "{}: index {} out of range ({} vertices)",
+                name, index, vertex_count
+            )));
+        }
+
+        let Some(indices) = Self::triangle_list(name, p.mode(), indices)? else {
+            return Ok(None);
+        };
+        mesh_data.indices(&indices, false);
+
+        if let Some(normals) = reader.read_normals() {
+            Self::set_attribute(
+                name,
+                "NORMAL",
+                &mut mesh_data.vertices,
+                normals,
+                |v, normal| v.set_normal(normal.into()),
+            )?;
+        }
+
+        if let Some(tex_coords) = reader.read_tex_coords(0) {
+            Self::set_attribute(
+                name,
+                "TEXCOORD_0",
+                &mut mesh_data.vertices,
+                tex_coords.into_f32(),
+                |v, texture| v.set_texture(texture.into()),
+            )?;
+        }
+
+        if let Some(joints) = reader.read_joints(0) {
+            Self::set_attribute(
+                name,
+                "JOINTS_0",
+                &mut mesh_data.vertices,
+                joints.into_u16(),
+                |v, joints| v.set_joints(UVec4::from_array(joints.map(u32::from))),
+            )?;
+        }
+
+        if let Some(weights) = reader.read_weights(0) {
+            Self::set_attribute(
+                name,
+                "WEIGHTS_0",
+                &mut mesh_data.vertices,
+                weights.into_f32(),
+                |v, weights| v.set_weights(Vec4::from_array(weights)),
+            )?;
+        }
+
+        if let Some(colors) = reader.read_colors(0) {
+            Self::set_attribute(
+                name,
+                "COLOR_0",
+                &mut mesh_data.vertices,
+                colors.into_rgba_f32(),
+                |v, color| v.set_color(color.into()),
+            )?;
+        }
+
+        for (positions, normals, tangents) in reader.read_morph_targets() {
+            let target = MorphTarget {
+                positions: positions
+                    .map(|iter| iter.map(Vec3::from).collect())
+                    .unwrap_or_default(),
+                normals: normals
+                    .map(|iter| iter.map(Vec3::from).collect())
+                    .unwrap_or_default(),
+                tangents: tangents
+                    .map(|iter| iter.map(Vec3::from).collect())
+                    .unwrap_or_default(),
+            };
+
+            for (attribute, len) in [
+                ("POSITION", target.positions.len()),
+                ("NORMAL", target.normals.len()),
+                ("TANGENT", target.tangents.len()),
+            ] {
+                if len != 0 && len != vertex_count {
+                    return Err(Self::count_error(name, attribute, len, vertex_count));
+                }
+            }
+
+            mesh_data.target(target);
+        }
+
+        Ok(Some(mesh_data.build()))
+    }
+
+    /// Accessors must have one element per vertex
+    fn set_attribute<T, F>(
+        name: &str,
+        attribute: &str,
+        vertices: &mut [VertexData],
+        values: impl Iterator<Item = T>,
+        mut f: F,
+    ) -> Result<(), AssetError>
+    where
+        F: FnMut(&mut VertexData, T),
+    {
+        let values = values.collect::<Vec<T>>();
+        if values.len() != vertices.len() {
+            return Err(Self::count_error(
+                name,
+                attribute,
+                values.len(),
+                vertices.len(),
+            ));
+        }
+
+        for (vertex, value) in vertices.iter_mut().zip(values) {
+            f(vertex, value);
+        }
+
+        Ok(())
+    }
+
+    fn count_error(name: &str, attribute: &str, count: usize, vertex_count: usize) -> AssetError {
+        AssetError::InvalidData(format!(
+            "{}: {} has {} elements for {} vertices",
+            name, attribute, count, vertex_count
+        ))
+    }
+
+    /// Convert strips and fans to triangle lists. Points and lines are rejected
+    fn triangle_list(
+        name: &str,
+        mode: Mode,
+        indices: Vec<u32>,
+    ) -> Result<Option<Vec<u32>>, AssetError> {
+        let triangles = match mode {
+            Mode::Triangles => {
+                if !indices.len().is_multiple_of(3) {
+                    return Err(AssetError::InvalidData(format!(
+                        "{}: {} indices is not a triangle list",
+                        name,
+                        indices.len()
+                    )));
+                }
+                indices
+            }
+            // every other triangle is flipped to keep the winding order
+            Mode::TriangleStrip => indices
+                .windows(3)
+                .enumerate()
+                .flat_map(|(i, w)| {
+                    if i % 2 == 0 {
+                        [w[0], w[1], w[2]]
                     } else {
-                        for i in 0..mesh_data.vertices.len() {
-                            mesh_data.vertices[i].set_color(Color::WHITE);
-                        }
+                        [w[0], w[2], w[1]]
                     }
+                })
+                .collect(),
+            Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+                .flat_map(|i| [indices[i], indices[i + 1], indices[0]])
+                .collect(),
+            // meshes are drawn as triangle lists
+            Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => {
+                return Err(AssetError::Unsupported(format!(
+                    "{}: {:?} primitives cannot be drawn",
+                    name, mode
+                )));
+            }
+        };
+
+        if triangles.is_empty() {
+            tracing::warn!(target: logger::RESOURCES, "Skip primitive {} without triangles", name);
+            return Ok(None);
+        }
+
+        Ok(Some(triangles))
+    }
+
+    fn load_models(
+        &mut self,
+        resource_manager: &mut ResourceManager,
+        meshes: &[MeshAsset],
+        skinned_meshes: &[bool],
+        path: Option<&str>,
+    ) {
+        for (m, &skinned) in meshes.iter().zip(skinned_meshes) {
+            let meshes = m
+                .primitives
+                .iter()
+                .map(|p| {
+                    let skinned = skinned && Self::is_skinned(&p.geometry);
+                    let material = match p.material {
+                        Some(mat_idx) => match self.material_manager.skinned_instances[mat_idx] {
+                            Some(skinned_instance) if skinned => skinned_instance,
+                            _ => self.material_manager.instances[mat_idx],
+                        },
+                        None => self.material_manager.default_material_instance,
+                    };
 
-                    let mesh = RenderMeshBuilder::new(resource_manager, &name)
-                        .with_geometry(mesh_data.build())
+                    let mesh = RenderMeshBuilder::new(resource_manager, &p.geometry.name)
+                        .with_geometry(p.geometry.clone())
                         .for_material(material)
+                        .ref_counted(true)
                         .build();
 
                     (mesh, material)
                 })
                 .collect::<Vec<(ResourceHandle<Mesh>, ResourceHandle<MaterialInstance>)>>();
 
-            let mut model = RenderModelBuilder::new(resource_manager, name);
+            let mut model = RenderModelBuilder::new(resource_manager, &m.name);
+            if let Some(path) = path {
+                model = model.with_path(path);
+            }
             for (mesh, material) in meshes {
                 model = model.with_mesh(mesh).with_material(material);
             }
@@ -210,126 +914,166 @@ impl GLTFLoader {
         tracing::info!(target: logger::RESOURCES, "{} meshes loaded", self.models.iter().map(|m| m.meshes.len()).sum::<usize>());
     }
 
-    fn load_textures(
-        &mut self,
-        config: GobsConfig,
-        resource_manager: &mut ResourceManager,
-        doc: &Document,
-        images: &[image::Data],
-    ) {
+    fn read_textures(doc: &Document, images: &[image::Data]) -> Vec<Option<TextureAsset>> {
         tracing::trace!(target: logger::RESOURCES, "Reading {} images", images.len());
 
-        for t in doc.textures() {
-            let name = t.name().unwrap_or_default();
-            let image = t.source();
-            let data = &images[image.index()];
-            let sampler = t.sampler();
+        doc.textures()
+            .map(|t| {
+                let name = t.name().unwrap_or_default();
+                let image = t.source();
+                let data = &images[image.index()];
+                let sampler = t.sampler();
 
-            let mag_filter = match sampler.mag_filter() {
-                Some(filter) => match filter {
-                    gltf::texture::MagFilter::Nearest => SamplerFilter::FilterNearest,
-                    gltf::texture::MagFilter::Linear => SamplerFilter::FilterLinear,
-                },
-                None => SamplerFilter::FilterLinear,
-            };
+                let mag_filter = match sampler.mag_filter() {
+                    Some(filter) => match filter {
+                        gltf::texture::MagFilter::Nearest => SamplerFilter::FilterNearest,
+                        gltf::texture::MagFilter::Linear => SamplerFilter::FilterLinear,
+                    },
+                    None => SamplerFilter::FilterLinear,
+                };
 
-            let min_filter = match sampler.min_filter() {
-                Some(filter) => match filter {
-                    gltf::texture::MinFilter::Nearest => SamplerFilter::FilterNearest,
-                    gltf::texture::MinFilter::Linear => SamplerFilter::FilterLinear,
-                    gltf::texture::MinFilter::NearestMipmapNearest => SamplerFilter::FilterNearest,
-                    gltf::texture::MinFilter::LinearMipmapNearest => SamplerFilter::FilterNearest,
-                    gltf::texture::MinFilter::NearestMipmapLinear => SamplerFilter::FilterLinear,
-                    gltf::texture::MinFilter::LinearMipmapLinear => SamplerFilter::FilterLinear,
-                },
-                None => SamplerFilter::FilterLinear,
-            };
+                let min_filter = match sampler.min_filter() {
+                    Some(filter) => match filter {
+                        gltf::texture::MinFilter::Nearest => SamplerFilter::FilterNearest,
+                        gltf::texture::MinFilter::Linear => SamplerFilter::FilterLinear,
+                        gltf::texture::MinFilter::NearestMipmapNearest => {
+                            SamplerFilter::FilterNearest
+                        }
+                        gltf::texture::MinFilter::LinearMipmapNearest => {
+                            SamplerFilter::FilterNearest
+                        }
+                        gltf::texture::MinFilter::NearestMipmapLinear => {
+                            SamplerFilter::FilterLinear
+                        }
+                        gltf::texture::MinFilter::LinearMipmapLinear => {
+                            SamplerFilter::FilterLinear
+                        }
+                    },
+                    None => SamplerFilter::FilterLinear,
+                };
 
-            let mut ty = TextureType::Diffuse;
-            for mat in doc.materials() {
-                if let Some(normal) = mat.normal_texture()
-                    && normal.texture().index() == t.index()
-                {
-                    ty = TextureType::Normal;
-                }
-            }
+                let ty = Self::texture_type(doc, t.index());
 
-            let name = format!("Texture #{}: {}", t.index(), name);
+                let name = format!("Texture #{}: {}", t.index(), name);
 
-            tracing::trace!(target: logger::RESOURCES,
-                "{}, image #{}, format: {:?}, type: {:?}",
-                &name,
-                image.index(),
-                data.format,
-                ty
-            );
+                tracing::trace!(target: logger::RESOURCES,
+                    "{}, image #{}, format: {:?}, type: {:?}",
+                    &name,
+                    image.index(),
+                    data.format,
+                    ty
+                );
 
-            // TODO: move to config
-            let texture_format = config.get_image_format(GltfConfig::TextureFormat);
-            match data.format {
-                image::Format::R8G8B8A8 => {
-                    let mut properties = TextureProperties::with_data(
-                        &name,
-                        texture_format,
-                        data.pixels.clone(),
-                        ImageExtent2D::new(data.width, data.height),
-                    );
-                    properties.format.mag_filter = mag_filter;
-                    properties.format.min_filter = min_filter;
+                let Some(pixels) = Self::rgba_pixels(data) else {
+                    tracing::warn!(target: logger::RESOURCES, "Invalid image #{}: {:?}, {}x{}", image.index(), data.format, data.width, data.height);
+                    return None;
+                };
 
-                    let handle = resource_manager.add(properties, ResourceLifetime::Static, false);
+                Some(TextureAsset {
+                    name,
+                    ty,
+                    mag_filter,
+                    min_filter,
+                    extent: ImageExtent2D::new(data.width, data.height),
+                    mip_levels: 1,
+                    data: pixels,
+                })
+            })
+            .collect()
+    }
 
-                    self.material_manager.add_texture(handle);
-                }
-                image::Format::R8G8B8 => {
-                    let mut pixels = vec![];
-                    for (i, pixel) in data.pixels.iter().enumerate() {
-                        pixels.push(*pixel);
-                        if i % 3 == 2 {
-                            pixels.push(255);
-                        }
-                    }
+    /// Convert decoded images to 8 bits RGBA. Images with two channels are
+    /// luminance and alpha. The glTF decoder has no 16 bits float format, 16 bits
+    /// channels are unsigned normalized
+    fn rgba_pixels(data: &image::Data) -> Option<Vec<u8>> {
+        let (width, height) = (data.width, data.height);
+        let pixels = data.pixels.clone();
+        let u16_pixels = || {
+            data.pixels
+                .chunks_exact(2)
+                .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+                .collect::<Vec<u16>>()
+        };
+        let f32_pixels = || {
+            data.pixels
+                .chunks_exact(4)
+                .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect::<Vec<f32>>()
+        };
 
-                    let mut properties = TextureProperties::with_data(
-                        &name,
-                        texture_format,
-                        pixels,
-                        ImageExtent2D::new(data.width, data.height),
-                    );
-                    properties.format.mag_filter = mag_filter;
-                    properties.format.min_filter = min_filter;
+        let image = match data.format {
+            image::Format::R8 => {
+                DynamicImage::ImageLuma8(ImageBuffer::from_raw(width, height, pixels)?)
+            }
+            image::Format::R8G8 => {
+                DynamicImage::ImageLumaA8(ImageBuffer::from_raw(width, height, pixels)?)
+            }
+            image::Format::R8G8B8 => {
+                DynamicImage::ImageRgb8(ImageBuffer::from_raw(width, height, pixels)?)
+            }
+            image::Format::R8G8B8A8 => {
+                DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, pixels)?)
+            }
+            image::Format::R16 => {
+                DynamicImage::ImageLuma16(ImageBuffer::from_raw(width, height, u16_pixels())?)
+            }
+            image::Format::R16G16 => {
+                DynamicImage::ImageLumaA16(ImageBuffer::from_raw(width, height, u16_pixels())?)
+            }
+            image::Format::R16G16B16 => {
+                DynamicImage::ImageRgb16(ImageBuffer::from_raw(width, height, u16_pixels())?)
+            }
+            image::Format::R16G16B16A16 => {
+                DynamicImage::ImageRgba16(ImageBuffer::from_raw(width, height, u16_pixels())?)
+            }
+            image::Format::R32G32B32FLOAT => {
+                DynamicImage::ImageRgb32F(ImageBuffer::from_raw(width, height, f32_pixels())?)
+            }
+            image::Format::R32G32B32A32FLOAT => {
+                DynamicImage::ImageRgba32F(ImageBuffer::from_raw(width, height, f32_pixels())?)
+            }
+        };
 
-                    let handle = resource_manager.add(properties, ResourceLifetime::Static, false);
+        Some(image.into_rgba8().into_raw())
+    }
 
-                    self.material_manager.add_texture(handle);
-                }
-                image::Format::R8 => {
-                    let mut pixels = vec![];
-                    for pixel in &data.pixels {
-                        pixels.push(*pixel);
-                        pixels.push(*pixel);
-                        pixels.push(*pixel);
-                        pixels.push(255);
-                    }
+    fn load_textures(
+        &mut self,
+        config: GobsConfig,
+        resource_manager: &mut ResourceManager,
+        textures: &[Option<TextureAsset>],
+    ) {
+        let texture_format = config.get_image_format(GltfConfig::TextureFormat);
+        let data_texture_format = config.get_image_format(GltfConfig::DataTextureFormat);
+
+        for texture in textures {
+            match texture {
+                Some(texture) => {
+                    let format = match texture.ty {
+                        TextureType::Diffuse | TextureType::Emission => texture_format,
+                        TextureType::Normal
+                        | TextureType::MetallicRoughness
+                        | TextureType::Occlusion => data_texture_format,
+                    };
 
                     let mut properties = TextureProperties::with_data(
-                        &name,
-                        texture_format,
-                        pixels,
-                        ImageExtent2D::new(data.width, data.height),
+                        &texture.name,
+                        format,
+                        texture.data.clone(),
+                        texture.extent,
                     );
-                    properties.format.mag_filter = mag_filter;
-                    properties.format.min_filter = min_filter;
+                    properties.format.mag_filter = texture.mag_filter;
+                    properties.format.min_filter = texture.min_filter;
+                    properties.format.mip_levels = texture.mip_levels;
+                    properties.format.ty = texture.ty;
 
-                    let handle = resource_manager.add(properties, ResourceLifetime::Static, false);
+                    let handle =
+                        resource_manager.add(properties, ResourceLifetime::RefCounted, false);
 
                     self.material_manager.add_texture(handle);
                 }
-                _ => {
-                    self.material_manager.add_default_texture();
-                    tracing::warn!(target: logger::RESOURCES,"Unsupported image format: {:?}", data.format)
-                }
-            };
+                None => self.material_manager.add_default_texture(),
+            }
         }
 
         tracing::info!(target: logger::RESOURCES,
@@ -338,77 +1082,508 @@ impl GLTFLoader {
         );
     }
 
+    /// Usage of the texture in the materials, textures holding color data are in sRGB
+    fn texture_type(doc: &Document, index: usize) -> TextureType {
+        let is = |texture: Option<gltf::Texture>| texture.is_some_and(|t| t.index() == index);
+
+        for mat in doc.materials() {
+            let pbr = mat.pbr_metallic_roughness();
+
+            if is(mat.normal_texture().map(|t| t.texture())) {
+                return TextureType::Normal;
+            }
+            if is(pbr.metallic_roughness_texture().map(|t| t.texture())) {
+                return TextureType::MetallicRoughness;
+            }
+            if is(mat.occlusion_texture().map(|t| t.texture())) {
+                return TextureType::Occlusion;
+            }
+            if is(mat.emissive_texture().map(|t| t.texture())) {
+                return TextureType::Emission;
+            }
+        }
+
+        TextureType::Diffuse
+    }
+
     fn into_blend_mode(alpha: AlphaMode) -> BlendMode {
         match alpha {
             AlphaMode::Blend => BlendMode::Alpha,
-            _ => BlendMode::None,
+            AlphaMode::Opaque | AlphaMode::Mask => BlendMode::None,
         }
     }
 
-    fn load_material(
-        &mut self,
-        config: GobsConfig,
-        resource_manager: &mut ResourceManager,
-        doc: &Document,
-        images: &[image::Data],
-    ) {
-        self.load_textures(config, resource_manager, doc, images);
+    /// Transform of the base color texture, or of the first texture that has one
+    fn uv_transform(mat: &gltf::Material) -> UvTransform {
+        let pbr = mat.pbr_metallic_roughness();
 
-        for mat in doc.materials() {
-            let name = mat.name().unwrap_or_default();
-            if let Some(idx) = mat.index() {
-                tracing::debug!(target: logger::RESOURCES, "Material #{}: {}", idx, name);
+        [
+            pbr.base_color_texture(),
+            pbr.metallic_roughness_texture(),
+            mat.emissive_texture(),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|info| info.texture_transform())
+        .map(|transform| UvTransform {
+            offset: transform.offset().into(),
+            rotation: transform.rotation(),
+            scale: transform.scale().into(),
+        })
+        .unwrap_or_default()
+    }
+
+    fn read_materials(doc: &Document) -> Vec<MaterialAsset> {
+        doc.materials()
+            .map(|mat| {
+                let name = mat.name().unwrap_or_default();
+                tracing::debug!(target: logger::RESOURCES, "Material #{:?}: {}", mat.index(), name);
 
                 let pbr = mat.pbr_metallic_roughness();
-                let color: Color = pbr.base_color_factor().into();
-                let diffuse = pbr.base_color_texture();
-
-                match diffuse {
-                    Some(tex_info) => {
-                        tracing::debug!(target: logger::RESOURCES,
-                            "Using texture #{}: {}",
-                            tex_info.texture().index(),
-                            tex_info.texture().name().unwrap_or_default()
-                        );
-                        let texture = tex_info.texture().index();
-                        match mat.normal_texture() {
-                            Some(normal) => {
-                                let normal_texture = normal.texture().index();
-                                tracing::debug!(target: logger::RESOURCES, "Using normal texture: {}", normal_texture);
-                                self.material_manager.add_texture_normal_instance(
-                                    name,
-                                    resource_manager,
-                                    Self::into_blend_mode(mat.alpha_mode()),
-                                    color,
-                                    texture,
-                                    normal_texture,
-                                )
-                            }
-                            None => {
-                                let alpha = Self::into_blend_mode(mat.alpha_mode());
-                                tracing::debug!(target: logger::RESOURCES, "Using diffuse texture with alpha: {:?}", alpha);
-                                self.material_manager.add_texture_instance(
-                                    name,
-                                    resource_manager,
-                                    Self::into_blend_mode(mat.alpha_mode()),
-                                    color,
-                                    texture,
-                                )
-                            }
-                        };
-                    }
-                    None => {
-                        let alpha = Self::into_blend_mode(mat.alpha_mode());
-                        tracing::debug!(target: logger::RESOURCES, "Using color material: {:?}, alpha={:?}", color, alpha);
-                        self.material_manager
-                            .add_color_instance(resource_manager, alpha, color);
-                    }
+                let alpha_cutoff = match mat.alpha_mode() {
+                    AlphaMode::Mask => Some(mat.alpha_cutoff().unwrap_or(0.5)),
+                    _ => None,
+                };
+                let [r, g, b] = mat.emissive_factor();
+
+                MaterialAsset {
+                    name: name.to_string(),
+                    blend: Self::into_blend_mode(mat.alpha_mode()),
+                    alpha_cutoff,
+                    double_sided: mat.double_sided(),
+                    color: pbr.base_color_factor().into(),
+                    metallic: pbr.metallic_factor(),
+                    roughness: pbr.roughness_factor(),
+                    occlusion_strength: mat
+                        .occlusion_texture()
+                        .map(|occlusion| occlusion.strength())
+                        .unwrap_or(1.),
+                    emission_color: [r, g, b, 1.].into(),
+                    emissive_strength: mat.emissive_strength().unwrap_or(1.),
+                    diffuse: pbr
+                        .base_color_texture()
+                        .map(|tex_info| tex_info.texture().index()),
+                    normal: mat.normal_texture().map(|normal| normal.texture().index()),
+                    metallic_roughness: pbr
+                        .metallic_roughness_texture()
+                        .map(|tex_info| tex_info.texture().index()),
+                    occlusion: mat
+                        .occlusion_texture()
+                        .map(|occlusion| occlusion.texture().index()),
+                    emission: mat
+                        .emissive_texture()
+                        .map(|tex_info| tex_info.texture().index()),
+                    uv_transform: Self::uv_transform(&mat),
                 }
-            } else {
-                tracing::trace!(target: logger::RESOURCES, "Using default material");
-            }
+            })
+            .collect()
+    }
+
+    fn load_materials(
+        &mut self,
+        resource_manager: &mut ResourceManager,
+        materials: &[MaterialAsset],
+        skinned: &[bool],
+    ) {
+        for (mat, &skinned) in materials.iter().zip(skinned) {
+            tracing::debug!(target: logger::RESOURCES, "Using PBR material {}: {:?}, alpha={:?}, cutoff={:?}, double sided={}",
+                mat.name, mat.color, mat.blend, mat.alpha_cutoff, mat.double_sided);
+
+            self.material_manager
+                .add_pbr_instance(resource_manager, mat, skinned);
         }
 
         tracing::info!(target: logger::RESOURCES, "{} materials loaded", self.material_manager.instances.len());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Cursor, path::Path};
+
+    use ::image::{ImageBuffer, ImageFormat, LumaA, Rgb, Rgba};
+    use glam::{Vec2, Vec3};
+    use gltf::{
+        image,
+        json::{
+            self, Index,
+            accessor::{ComponentType, Type},
+            mesh::{Mode, Semantic},
+            validation::Checked::Valid,
+        },
+    };
+
+    use gobs_core::{Color, ImageExtent2D};
+
+    use crate::{
+        AssetError,
+        gltf_export::GLTFDocument,
+        gltf_load::GLTFLoader,
+        model::ModelAsset,
+        testing::{setup, temp_dir},
+    };
+
+    const TRIANGLE: [[f32; 3]; 3] = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+
+    /// glTF document built in memory, with one mesh per primitive
+    #[derive(Default)]
+    struct Fixture {
+        document: GLTFDocument,
+    }
+
+    impl Fixture {
+        fn accessor(
+            &mut self,
+            data: &[u8],
+            count: usize,
+            component_type: ComponentType,
+            ty: Type,
+            normalized: bool,
+        ) -> Index<json::Accessor> {
+            let accessor = self
+                .document
+                .add_accessor(data, count, component_type, ty, None, None);
+            self.document.root.accessors[accessor.value()].normalized = normalized;
+
+            accessor
+        }
+
+        fn positions(&mut self, positions: &[[f32; 3]]) -> Index<json::Accessor> {
+            let data = positions
+                .iter()
+                .flatten()
+                .flat_map(|v| v.to_le_bytes())
+                .collect::<Vec<u8>>();
+            let (min, max) = positions.iter().fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), &p| (min.min(p.into()), max.max(p.into())),
+            );
+
+            self.document.add_accessor(
+                &data,
+                positions.len(),
+                ComponentType::F32,
+                Type::Vec3,
+                None,
+                Some((min.to_array().to_vec(), max.to_array().to_vec())),
+            )
+        }
+
+        fn indices(&mut self, indices: &[u16]) -> Index<json::Accessor> {
+            let data = indices
+                .iter()
+                .flat_map(|i| i.to_le_bytes())
+                .collect::<Vec<u8>>();
+
+            self.accessor(
+                &data,
+                indices.len(),
+                ComponentType::U16,
+                Type::Scalar,
+                false,
+            )
+        }
+
+        fn primitive(
+            &mut self,
+            positions: &[[f32; 3]],
+            attributes: Vec<(Semantic, Index<json::Accessor>)>,
+            indices: Option<Index<json::Accessor>>,
+            mode: Mode,
+        ) {
+            let positions = self.positions(positions);
+            let mut attributes = attributes
+                .into_iter()
+                .map(|(semantic, accessor)| (Valid(semantic), accessor))
+                .collect::<std::collections::BTreeMap<_, _>>();
+            attributes.insert(Valid(Semantic::Positions), positions);
+
+            self.document.root.push(json::Mesh {
+                name: None,
+                primitives: vec![json::mesh::Primitive {
+                    attributes,
+                    indices,
+                    material: None,
+                    mode: Valid(mode),
+                    targets: None,
+                    extensions: None,
+                    extras: Default::default(),
+                }],
+                weights: None,
+                extensions: None,
+                extras: Default::default(),
+            });
+        }
+
+        fn texture(&mut self, png: &[u8]) {
+            let view = self.document.add_view(png, None);
+            let image = self.document.root.push(json::Image {
+                buffer_view: Some(view),
+                mime_type: Some(json::image::MimeType("image/png".to_string())),
+                name: None,
+                uri: None,
+                extensions: None,
+                extras: Default::default(),
+            });
+            self.document.root.push(json::Texture {
+                name: None,
+                sampler: None,
+                source: image,
+                extensions: None,
+                extras: Default::default(),
+            });
+        }
+
+        fn read(self, dir: &Path) -> Result<ModelAsset, AssetError> {
+            let file = dir.join("fixture.glb");
+            self.document.write(&file)?;
+
+            GLTFLoader::read(&file)
+        }
+    }
+
+    fn png<P, C>(image: ImageBuffer<P, C>) -> Vec<u8>
+    where
+        P: ::image::PixelWithColorType,
+        [P::Subpixel]: ::image::EncodableLayout,
+        C: std::ops::Deref<Target = [P::Subpixel]>,
+    {
+        let mut png = Cursor::new(vec![]);
+        image.write_to(&mut png, ImageFormat::Png).unwrap();
+
+        png.into_inner()
+    }
+
+    #[test]
+    fn test_accessor_formats() {
+        setup();
+
+        let dir = temp_dir("gltf-accessors");
+        let mut fixture = Fixture::default();
+
+        let uv_u8 = fixture.accessor(
+            &[0, 0, 255, 0, 0, 255],
+            3,
+            ComponentType::U8,
+            Type::Vec2,
+            true,
+        );
+        let color_u8 = fixture.accessor(
+            &[255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 0],
+            3,
+            ComponentType::U8,
+            Type::Vec4,
+            true,
+        );
+        fixture.primitive(
+            &TRIANGLE,
+            vec![
+                (Semantic::TexCoords(0), uv_u8),
+                (Semantic::Colors(0), color_u8),
+            ],
+            None,
+            Mode::Triangles,
+        );
+
+        let uv_u16 = [0u16, 0, 65535, 0, 0, 65535]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let uv_u16 = fixture.accessor(&uv_u16, 3, ComponentType::U16, Type::Vec2, true);
+        let color_u16 = [65535u16, 0, 0, 0, 65535, 0, 0, 0, 65535]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let color_u16 = fixture.accessor(&color_u16, 3, ComponentType::U16, Type::Vec3, true);
+        fixture.primitive(
+            &TRIANGLE,
+            vec![
+                (Semantic::TexCoords(0), uv_u16),
+                (Semantic::Colors(0), color_u16),
+            ],
+            None,
+            Mode::Triangles,
+        );
+
+        let asset = fixture.read(&dir).unwrap();
+        assert_eq!(asset.meshes.len(), 2);
+
+        let uvs = [Vec2::ZERO, Vec2::X, Vec2::Y];
+
+        let geometry = &asset.meshes[0].primitives[0].geometry;
+        assert_eq!(geometry.indices, vec![0, 1, 2]);
+        for (vertex, uv) in geometry.vertices.iter().zip(uvs) {
+            assert_eq!(vertex.texture(), uv);
+        }
+        assert_eq!(geometry.vertices[0].color(), Color::RED);
+        assert_eq!(geometry.vertices[1].color(), Color::GREEN);
+        assert_eq!(geometry.vertices[2].color(), Color::new(0., 0., 1., 0.));
+
+        let geometry = &asset.meshes[1].primitives[0].geometry;
+        for (vertex, uv) in geometry.vertices.iter().zip(uvs) {
+            assert_eq!(vertex.texture(), uv);
+        }
+        assert_eq!(geometry.vertices[0].color(), Color::RED);
+        assert_eq!(geometry.vertices[1].color(), Color::GREEN);
+        assert_eq!(geometry.vertices[2].color(), Color::BLUE);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_primitive_modes() {
+        setup();
+
+        let dir = temp_dir("gltf-modes");
+        let mut fixture = Fixture::default();
+
+        let quad = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [1., 1., 0.]];
+
+        // non indexed
+        fixture.primitive(&TRIANGLE, vec![], None, Mode::Triangles);
+        fixture.primitive(&quad, vec![], None, Mode::TriangleStrip);
+        let indices = fixture.indices(&[0, 1, 3, 2]);
+        fixture.primitive(&quad, vec![], Some(indices), Mode::TriangleFan);
+
+        let asset = fixture.read(&dir).unwrap();
+
+        assert_eq!(
+            asset.meshes[0].primitives[0].geometry.indices,
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            asset.meshes[1].primitives[0].geometry.indices,
+            vec![0, 1, 2, 1, 3, 2]
+        );
+        assert_eq!(
+            asset.meshes[2].primitives[0].geometry.indices,
+            vec![1, 3, 0, 3, 2, 0]
+        );
+        assert_eq!(asset.meshes[2].primitives[0].geometry.vertices.len(), 4);
+
+        for mode in [Mode::Points, Mode::Lines, Mode::LineLoop, Mode::LineStrip] {
+            let mut fixture = Fixture::default();
+            fixture.primitive(&quad, vec![], None, mode);
+            assert!(matches!(
+                fixture.read(&dir),
+                Err(AssetError::Unsupported(_))
+            ));
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_primitives() {
+        setup();
+
+        let dir = temp_dir("gltf-invalid");
+
+        // attribute count
+        let mut fixture = Fixture::default();
+        let normals = fixture.accessor(&[0; 24], 2, ComponentType::F32, Type::Vec3, false);
+        fixture.primitive(
+            &TRIANGLE,
+            vec![(Semantic::Normals, normals)],
+            None,
+            Mode::Triangles,
+        );
+        assert!(matches!(
+            fixture.read(&dir),
+            Err(AssetError::InvalidData(_))
+        ));
+
+        // index out of range
+        let mut fixture = Fixture::default();
+        let indices = fixture.indices(&[0, 1, 3]);
+        fixture.primitive(&TRIANGLE, vec![], Some(indices), Mode::Triangles);
+        assert!(matches!(
+            fixture.read(&dir),
+            Err(AssetError::InvalidData(_))
+        ));
+
+        // incomplete triangle
+        let mut fixture = Fixture::default();
+        let indices = fixture.indices(&[0, 1, 2, 0]);
+        fixture.primitive(&TRIANGLE, vec![], Some(indices), Mode::Triangles);
+        assert!(matches!(
+            fixture.read(&dir),
+            Err(AssetError::InvalidData(_))
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_image_formats() {
+        setup();
+
+        let dir = temp_dir("gltf-images");
+        let mut fixture = Fixture::default();
+
+        fixture.texture(&png(ImageBuffer::from_pixel(
+            2,
+            2,
+            Rgba([65535u16, 0, 25700, 65535]),
+        )));
+        fixture.texture(&png(ImageBuffer::from_pixel(
+            2,
+            2,
+            Rgb([0u16, 65535, 25700]),
+        )));
+        fixture.texture(&png(ImageBuffer::from_pixel(2, 2, LumaA([100u8, 200]))));
+        fixture.texture(&png(ImageBuffer::from_pixel(
+            2,
+            2,
+            LumaA([25700u16, 65535]),
+        )));
+        fixture.texture(&png(ImageBuffer::from_pixel(
+            2,
+            2,
+            ::image::Luma([25700u16]),
+        )));
+
+        let asset = fixture.read(&dir).unwrap();
+
+        let expected = [
+            [255, 0, 100, 255],
+            [0, 255, 100, 255],
+            [100, 100, 100, 200],
+            [100, 100, 100, 255],
+            [100, 100, 100, 255],
+        ];
+
+        assert_eq!(asset.textures.len(), expected.len());
+        for (texture, pixel) in asset.textures.iter().zip(expected) {
+            let texture = texture.as_ref().unwrap();
+            assert_eq!(texture.extent, ImageExtent2D::new(2, 2));
+            assert_eq!(texture.data, pixel.repeat(4));
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_float_pixels() {
+        setup();
+
+        let data = image::Data {
+            pixels: [1f32, 0.5, 0., 2.]
+                .iter()
+                .flat_map(|v| v.to_ne_bytes())
+                .collect(),
+            format: image::Format::R32G32B32A32FLOAT,
+            width: 1,
+            height: 1,
+        };
+        assert_eq!(GLTFLoader::rgba_pixels(&data), Some(vec![255, 128, 0, 255]));
+
+        let data = image::Data {
+            pixels: vec![0; 6],
+            format: image::Format::R8G8B8,
+            width: 2,
+            height: 2,
+        };
+        assert_eq!(GLTFLoader::rgba_pixels(&data), None);
+    }
+}
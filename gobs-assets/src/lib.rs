@@ -1,10 +1,17 @@
 pub mod config;
+pub mod cook;
+pub mod gltf_export;
 pub mod gltf_load;
 pub mod manager;
+pub mod model;
+
+#[cfg(test)]
+mod testing;
 
 use thiserror::Error;
 
 use gobs_render::RenderError;
+use gobs_resource::load::LoadingError;
 
 #[derive(Debug, Error)]
 pub enum AssetError {
@@ -12,6 +19,24 @@ pub enum AssetError {
     AssetNotFound,
     #[error("GLTF error")]
     GLTFError(#[from] gltf::Error),
+    #[error("invalid glTF data: {0}")]
+    InvalidData(String),
+    #[error("unsupported glTF data: {0}")]
+    Unsupported(String),
+    #[error("JSON error")]
+    JsonError(#[from] gltf::json::Error),
+    #[error("cannot write {path}: {source}")]
+    WriteError {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("cannot encode {name}: {source}")]
+    EncodingError {
+        name: String,
+        source: image::ImageError,
+    },
+    #[error("loading error: {0}")]
+    LoadingError(#[from] LoadingError),
     #[error("render error")]
     RenderError(#[from] RenderError),
 }
@@ -1,4 +1,4 @@
-use gobs_core::{Color, GobsConfig, logger};
+use gobs_core::{Color, GobsConfig, ImageFormat, logger};
 use gobs_render::{
     BlendMode, Material, MaterialDataPropData, MaterialInstance, MaterialInstanceProperties,
     MaterialsConfig, Texture, TextureProperties,
@@ -7,11 +7,13 @@ use gobs_resource::{
     ResourceManager, {ResourceHandle, ResourceLifetime},
 };
 
-use crate::AssetError;
+use crate::{AssetError, model::MaterialAsset};
 
 pub struct TextureManager {
     pub textures: Vec<ResourceHandle<Texture>>,
     pub default_texture: ResourceHandle<Texture>,
+    /// Flat tangent space normal, used by materials without normal map
+    pub default_normal_texture: ResourceHandle<Texture>,
 }
 
 impl TextureManager {
@@ -22,9 +24,20 @@ impl TextureManager {
             false,
         );
 
+        let default_normal_texture = resource_manager.add(
+            TextureProperties::with_color(
+                "Default normal texture",
+                ImageFormat::R8g8b8a8Unorm,
+                Color::from_rgba8(128, 128, 255, 255),
+            ),
+            ResourceLifetime::Static,
+            false,
+        );
+
         TextureManager {
             textures: vec![],
             default_texture,
+            default_normal_texture,
         }
     }
 
@@ -40,6 +53,8 @@ impl TextureManager {
 pub struct MaterialManager {
     pub texture_manager: TextureManager,
     pub instances: Vec<ResourceHandle<MaterialInstance>>,
+    /// Instances using the skinned material, for materials of skinned or morphed meshes
+    pub skinned_instances: Vec<Option<ResourceHandle<MaterialInstance>>>,
     pub default_material_instance: ResourceHandle<MaterialInstance>,
     pub texture: ResourceHandle<Material>,
     pub transparent_texture: ResourceHandle<Material>,
@@ -47,6 +62,20 @@ pub struct MaterialManager {
     pub transparent_texture_normal: ResourceHandle<Material>,
     pub color: ResourceHandle<Material>,
     pub transparent_color: ResourceHandle<Material>,
+    pub texture_skinned: ResourceHandle<Material>,
+    pub transparent_texture_skinned: ResourceHandle<Material>,
+    pub texture_normal_skinned: ResourceHandle<Material>,
+    pub transparent_texture_normal_skinned: ResourceHandle<Material>,
+    pub color_skinned: ResourceHandle<Material>,
+    pub transparent_color_skinned: ResourceHandle<Material>,
+    pub pbr: ResourceHandle<Material>,
+    pub transparent_pbr: ResourceHandle<Material>,
+    pub pbr_double_sided: ResourceHandle<Material>,
+    pub transparent_pbr_double_sided: ResourceHandle<Material>,
+    pub pbr_skinned: ResourceHandle<Material>,
+    pub transparent_pbr_skinned: ResourceHandle<Material>,
+    pub pbr_double_sided_skinned: ResourceHandle<Material>,
+    pub transparent_pbr_double_sided_skinned: ResourceHandle<Material>,
 }
 
 impl MaterialManager {
@@ -74,6 +103,48 @@ impl MaterialManager {
         let transparent_color = resource_manager
             .get_by_name("gltf.color.transparent")
             .ok_or(AssetError::AssetNotFound)?;
+        let texture_skinned = resource_manager
+            .get_by_name("gltf.texture.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_texture_skinned = resource_manager
+            .get_by_name("gltf.texture.transparent.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let texture_normal_skinned = resource_manager
+            .get_by_name("gltf.texture.normal.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_texture_normal_skinned = resource_manager
+            .get_by_name("gltf.texture.transparent.normal.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let color_skinned = resource_manager
+            .get_by_name("gltf.color.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_color_skinned = resource_manager
+            .get_by_name("gltf.color.transparent.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let pbr = resource_manager
+            .get_by_name("gltf.pbr")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_pbr = resource_manager
+            .get_by_name("gltf.pbr.transparent")
+            .ok_or(AssetError::AssetNotFound)?;
+        let pbr_double_sided = resource_manager
+            .get_by_name("gltf.pbr.double_sided")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_pbr_double_sided = resource_manager
+            .get_by_name("gltf.pbr.transparent.double_sided")
+            .ok_or(AssetError::AssetNotFound)?;
+        let pbr_skinned = resource_manager
+            .get_by_name("gltf.pbr.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_pbr_skinned = resource_manager
+            .get_by_name("gltf.pbr.transparent.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let pbr_double_sided_skinned = resource_manager
+            .get_by_name("gltf.pbr.double_sided.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
+        let transparent_pbr_double_sided_skinned = resource_manager
+            .get_by_name("gltf.pbr.transparent.double_sided.skinned")
+            .ok_or(AssetError::AssetNotFound)?;
 
         let texture_manager = TextureManager::new(resource_manager);
 
@@ -90,6 +161,7 @@ impl MaterialManager {
         Ok(MaterialManager {
             texture_manager,
             instances: vec![],
+            skinned_instances: vec![],
             default_material_instance,
             texture,
             transparent_texture,
@@ -97,6 +169,20 @@ impl MaterialManager {
             transparent_texture_normal,
             color,
             transparent_color,
+            texture_skinned,
+            transparent_texture_skinned,
+            texture_normal_skinned,
+            transparent_texture_normal_skinned,
+            color_skinned,
+            transparent_color_skinned,
+            pbr,
+            transparent_pbr,
+            pbr_double_sided,
+            transparent_pbr_double_sided,
+            pbr_skinned,
+            transparent_pbr_skinned,
+            pbr_double_sided_skinned,
+            transparent_pbr_double_sided_skinned,
         })
     }
 
@@ -115,30 +201,26 @@ impl MaterialManager {
         alpha: BlendMode,
         color: Color,
         texture: usize,
+        skinned: bool,
     ) -> ResourceHandle<MaterialInstance> {
         let texture = self.texture_manager.textures[texture];
 
-        let material_instance = match alpha {
-            BlendMode::Alpha => resource_manager.add::<MaterialInstance>(
-                MaterialInstanceProperties::new(name, self.transparent_texture)
-                    .prop(MaterialDataPropData::DiffuseColor(color.into()))
-                    .textures(&[texture]),
-                ResourceLifetime::Static,
-                false,
-            ),
-            _ => resource_manager.add::<MaterialInstance>(
-                MaterialInstanceProperties::new(name, self.texture)
-                    .prop(MaterialDataPropData::DiffuseColor(color.into()))
-                    .textures(&[texture]),
-                ResourceLifetime::Static,
-                false,
-            ),
+        let (material, skinned_material) = match alpha {
+            BlendMode::Alpha => (self.transparent_texture, self.transparent_texture_skinned),
+            _ => (self.texture, self.texture_skinned),
         };
-        self.instances.push(material_instance);
 
-        material_instance
+        self.push_instance(
+            resource_manager,
+            MaterialInstanceProperties::new(name, material)
+                .prop(MaterialDataPropData::DiffuseColor(color.into()))
+                .textures(&[texture]),
+            ResourceLifetime::RefCounted,
+            skinned.then_some(skinned_material),
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_texture_normal_instance(
         &mut self,
         name: &str,
@@ -147,29 +229,27 @@ impl MaterialManager {
         color: Color,
         diffuse: usize,
         normal: usize,
+        skinned: bool,
     ) -> ResourceHandle<MaterialInstance> {
         let diffuse = self.texture_manager.textures[diffuse];
         let normal = self.texture_manager.textures[normal];
 
-        let material_instance = match alpha {
-            BlendMode::Alpha => resource_manager.add::<MaterialInstance>(
-                MaterialInstanceProperties::new(name, self.transparent_texture_normal)
-                    .prop(MaterialDataPropData::DiffuseColor(color.into()))
-                    .textures(&[diffuse, normal]),
-                ResourceLifetime::Static,
-                false,
-            ),
-            _ => resource_manager.add::<MaterialInstance>(
-                MaterialInstanceProperties::new(name, self.texture_normal)
-                    .prop(MaterialDataPropData::DiffuseColor(color.into()))
-                    .textures(&[diffuse, normal]),
-                ResourceLifetime::Static,
-                false,
+        let (material, skinned_material) = match alpha {
+            BlendMode::Alpha => (
+                self.transparent_texture_normal,
+                self.transparent_texture_normal_skinned,
             ),
+            _ => (self.texture_normal, self.texture_normal_skinned),
         };
-        self.instances.push(material_instance);
 
-        material_instance
+        self.push_instance(
+            resource_manager,
+            MaterialInstanceProperties::new(name, material)
+                .prop(MaterialDataPropData::DiffuseColor(color.into()))
+                .textures(&[diffuse, normal]),
+            ResourceLifetime::RefCounted,
+            skinned.then_some(skinned_material),
+        )
     }
 
     pub fn add_color_instance(
@@ -177,20 +257,131 @@ impl MaterialManager {
         resource_manager: &mut ResourceManager,
         alpha: BlendMode,
         color: Color,
+        skinned: bool,
     ) -> ResourceHandle<MaterialInstance> {
-        let material = match alpha {
-            BlendMode::Alpha => self.transparent_color,
-            _ => self.color,
+        let (material, skinned_material) = match alpha {
+            BlendMode::Alpha => (self.transparent_color, self.transparent_color_skinned),
+            _ => (self.color, self.color_skinned),
         };
 
-        let material_instance = resource_manager.add::<MaterialInstance>(
+        self.push_instance(
+            resource_manager,
             MaterialInstanceProperties::new("color", material)
                 .prop(MaterialDataPropData::DiffuseColor(color.into())),
             ResourceLifetime::Static,
-            false,
-        );
+            skinned.then_some(skinned_material),
+        )
+    }
+
+    /// Metallic-roughness material, missing textures are replaced by neutral defaults
+    pub fn add_pbr_instance(
+        &mut self,
+        resource_manager: &mut ResourceManager,
+        material: &MaterialAsset,
+        skinned: bool,
+    ) -> ResourceHandle<MaterialInstance> {
+        let texture = |index: Option<usize>, default| match index {
+            Some(index) => self.texture_manager.textures[index],
+            None => default,
+        };
+        let default_texture = self.texture_manager.default_texture;
+
+        let textures = [
+            texture(material.diffuse, default_texture),
+            texture(material.normal, self.texture_manager.default_normal_texture),
+            texture(material.metallic_roughness, default_texture),
+            texture(material.occlusion, default_texture),
+            texture(material.emission, default_texture),
+        ];
+
+        let (pbr, pbr_skinned) = match (material.blend, material.double_sided) {
+            (BlendMode::Alpha, false) => (self.transparent_pbr, self.transparent_pbr_skinned),
+            (BlendMode::Alpha, true) => (
+                self.transparent_pbr_double_sided,
+                self.transparent_pbr_double_sided_skinned,
+            ),
+            (_, false) => (self.pbr, self.pbr_skinned),
+            (_, true) => (self.pbr_double_sided, self.pbr_double_sided_skinned),
+        };
+
+        self.push_instance(
+            resource_manager,
+            MaterialInstanceProperties::new(&material.name, pbr)
+                .prop(MaterialDataPropData::DiffuseColor(material.color.into()))
+                .prop(MaterialDataPropData::EmissionColor(
+                    material.emission_color.into(),
+                ))
+                .prop(MaterialDataPropData::UvOffsetScale(
+                    material.uv_transform.offset_scale(),
+                ))
+                .prop(MaterialDataPropData::MetallicFactor(material.metallic))
+                .prop(MaterialDataPropData::RoughnessFactor(material.roughness))
+                .prop(MaterialDataPropData::OcclusionStrength(
+                    material.occlusion_strength,
+                ))
+                .prop(MaterialDataPropData::EmissiveStrength(
+                    material.emissive_strength,
+                ))
+                .prop(MaterialDataPropData::AlphaCutoff(
+                    material.alpha_cutoff.unwrap_or_default(),
+                ))
+                .prop(MaterialDataPropData::UvRotation(
+                    material.uv_transform.rotation,
+                ))
+                .textures(&textures),
+            ResourceLifetime::RefCounted,
+            skinned.then_some(pbr_skinned),
+        )
+    }
+
+    /// Add the instance, and a copy using the skinned material for skinned or
+    /// morphed meshes. The skinned material has the same blending and culling
+    fn push_instance(
+        &mut self,
+        resource_manager: &mut ResourceManager,
+        properties: MaterialInstanceProperties,
+        lifetime: ResourceLifetime,
+        skinned_material: Option<ResourceHandle<Material>>,
+    ) -> ResourceHandle<MaterialInstance> {
+        let skinned_instance = skinned_material.map(|material| {
+            let mut properties = properties.clone();
+            properties.name = format!("{}.skinned", properties.name);
+            properties.material = material;
+
+            Self::add_instance(resource_manager, properties, lifetime)
+        });
+
+        let material_instance = Self::add_instance(resource_manager, properties, lifetime);
+
         self.instances.push(material_instance);
+        self.skinned_instances.push(skinned_instance);
+
+        material_instance
+    }
+
+    fn add_instance(
+        resource_manager: &mut ResourceManager,
+        properties: MaterialInstanceProperties,
+        lifetime: ResourceLifetime,
+    ) -> ResourceHandle<MaterialInstance> {
+        let textures = properties.textures.clone();
+
+        let material_instance =
+            resource_manager.add::<MaterialInstance>(properties, lifetime, false);
+        for texture in &textures {
+            resource_manager.add_dependency(&material_instance, texture);
+        }
 
         material_instance
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gobs_render::MaterialsConfig;
+
+    #[test]
+    fn test_parse_materials() {
+        MaterialsConfig::parse(include_str!("../resources/gltf_materials.ron")).unwrap();
+    }
+}
@@ -0,0 +1,1106 @@
+use std::{io, sync::Arc};
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+use image::RgbaImage;
+
+use gobs_core::{Color, ImageExtent2D, SamplerFilter, Transform};
+use gobs_render::{BlendMode, CookedTexture, MeshGeometry, TextureType};
+use gobs_resource::{
+    light::LightType,
+    load::{self, AssetType, CookedKind, CookedReader, CookedWriter, LoadingError},
+};
+use gobs_scene::animation::{Interpolation, Keyframes};
+
+/// Decoded texture with its RGBA8 pixels
+#[derive(Clone, Debug)]
+pub struct TextureAsset {
+    pub name: String,
+    pub ty: TextureType,
+    pub mag_filter: SamplerFilter,
+    pub min_filter: SamplerFilter,
+    pub extent: ImageExtent2D,
+    pub mip_levels: u32,
+    pub data: Vec<u8>,
+}
+
+/// Texture coordinates transform (KHR_texture_transform)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvTransform {
+    pub offset: Vec2,
+    /// Rotation in radians, applied after scaling
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl UvTransform {
+    /// Offset and scale packed as a vec4
+    pub fn offset_scale(&self) -> [f32; 4] {
+        [self.offset.x, self.offset.y, self.scale.x, self.scale.y]
+    }
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            rotation: 0.,
+            scale: Vec2::ONE,
+        }
+    }
+}
+
+/// Metallic-roughness material, defaults follow the glTF specification
+#[derive(Clone, Debug)]
+pub struct MaterialAsset {
+    pub name: String,
+    pub blend: BlendMode,
+    /// Alpha threshold of masked materials
+    pub alpha_cutoff: Option<f32>,
+    /// Back faces are not culled
+    pub double_sided: bool,
+    pub color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub occlusion_strength: f32,
+    pub emission_color: Color,
+    /// Multiplier of the emission color (KHR_materials_emissive_strength)
+    pub emissive_strength: f32,
+    /// Index in the texture list
+    pub diffuse: Option<usize>,
+    /// Index in the texture list
+    pub normal: Option<usize>,
+    /// Index in the texture list, metalness in the blue channel and roughness in the green channel
+    pub metallic_roughness: Option<usize>,
+    /// Index in the texture list
+    pub occlusion: Option<usize>,
+    /// Index in the texture list
+    pub emission: Option<usize>,
+    /// Shared by all the textures of the material
+    pub uv_transform: UvTransform,
+}
+
+impl Default for MaterialAsset {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            blend: BlendMode::None,
+            alpha_cutoff: None,
+            double_sided: false,
+            color: Color::WHITE,
+            metallic: 1.,
+            roughness: 1.,
+            occlusion_strength: 1.,
+            emission_color: Color::BLACK,
+            emissive_strength: 1.,
+            diffuse: None,
+            normal: None,
+            metallic_roughness: None,
+            occlusion: None,
+            emission: None,
+            uv_transform: UvTransform::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrimitiveAsset {
+    pub geometry: Arc<MeshGeometry>,
+    /// Index in the material list, or default material
+    pub material: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MeshAsset {
+    pub name: String,
+    pub primitives: Vec<PrimitiveAsset>,
+    /// Default morph target weights
+    pub weights: Vec<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct NodeAsset {
+    pub name: String,
+    pub transform: Transform,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+    pub light: Option<usize>,
+    /// Skin deforming the mesh of the node
+    pub skin: Option<usize>,
+    /// Morph target weights, replace the default weights of the mesh if not empty
+    pub weights: Vec<f32>,
+    pub children: Vec<usize>,
+}
+
+/// Camera projection, angles in radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraAsset {
+    /// The aspect ratio of the viewport should be used if not set, the far plane is at infinity if not set
+    Perspective {
+        aspect: Option<f32>,
+        yfov: f32,
+        znear: f32,
+        zfar: Option<f32>,
+    },
+    /// Half width and half height of the view
+    Ortho {
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+/// Punctual light, shining along the -Z axis of its node
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightAsset {
+    pub color: Color,
+    pub intensity: f32,
+    pub ty: LightType,
+}
+
+/// Scenes of a model added to the scene graph
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SceneSelection {
+    /// Default scene of the model, or the first scene
+    #[default]
+    Default,
+    Index(usize),
+    /// First scene with this name
+    Name(String),
+    All,
+}
+
+#[derive(Clone, Debug)]
+pub struct SceneAsset {
+    pub name: String,
+    /// Root nodes of the scene
+    pub roots: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SkinAsset {
+    pub name: String,
+    /// Joint nodes, indices in the node list
+    pub joints: Vec<usize>,
+    /// One matrix per joint
+    pub inverse_bind_matrices: Vec<Mat4>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChannelAsset {
+    /// Index in the node list
+    pub node: usize,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub keyframes: Keyframes,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationAsset {
+    pub name: String,
+    pub channels: Vec<ChannelAsset>,
+}
+
+/// Textures, materials, meshes and node hierarchy of a model, independent of the source
+/// format. Models can be cooked into a binary file and loaded back without decoding.
+#[derive(Clone, Debug, Default)]
+pub struct ModelAsset {
+    /// Unsupported textures are replaced by the default texture
+    pub textures: Vec<Option<TextureAsset>>,
+    pub materials: Vec<MaterialAsset>,
+    pub meshes: Vec<MeshAsset>,
+    pub skins: Vec<SkinAsset>,
+    pub cameras: Vec<CameraAsset>,
+    pub lights: Vec<LightAsset>,
+    pub nodes: Vec<NodeAsset>,
+    pub scenes: Vec<SceneAsset>,
+    /// Scene loaded by default
+    pub default_scene: Option<usize>,
+    pub animations: Vec<AnimationAsset>,
+}
+
+impl ModelAsset {
+    pub const COOKED_EXTENSION: &'static str = "gmdl";
+
+    pub fn is_cooked(filename: &str) -> bool {
+        filename.ends_with(&format!(".{}", Self::COOKED_EXTENSION))
+    }
+
+    /// Root nodes of the selected scenes, empty if the scene does not exist
+    pub fn scene_roots(&self, selection: &SceneSelection) -> Vec<usize> {
+        let scene = match selection {
+            SceneSelection::Default => self.default_scene.or(Some(0)),
+            SceneSelection::Index(index) => Some(*index),
+            SceneSelection::Name(name) => self.scenes.iter().position(|s| &s.name == name),
+            SceneSelection::All => {
+                return self
+                    .scenes
+                    .iter()
+                    .flat_map(|s| s.roots.iter().copied())
+                    .collect();
+            }
+        };
+
+        scene
+            .and_then(|scene| self.scenes.get(scene))
+            .map(|scene| scene.roots.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace texture data with the full mip chain
+    pub fn generate_mips(&mut self) {
+        for texture in self.textures.iter_mut().flatten() {
+            if texture.mip_levels > 1 {
+                continue;
+            }
+
+            let Some(image) = RgbaImage::from_raw(
+                texture.extent.width,
+                texture.extent.height,
+                std::mem::take(&mut texture.data),
+            ) else {
+                continue;
+            };
+
+            let (mip_levels, data) = CookedTexture::generate_mips(&image);
+            texture.mip_levels = mip_levels;
+            texture.data = data;
+        }
+    }
+
+    /// Load a cooked model file from the models directory
+    pub fn load_cooked(filename: &str) -> Result<Self, LoadingError> {
+        let asset = load::load_cooked_sync(filename, AssetType::MODEL, CookedKind::Model)?;
+
+        Self::read_cooked(&mut asset.reader()).map_err(|e| asset.error(e))
+    }
+
+    pub fn write_cooked(&self, writer: &mut CookedWriter) {
+        writer.write_u64(self.textures.len() as u64);
+        for texture in &self.textures {
+            writer.write_bool(texture.is_some());
+            if let Some(texture) = texture {
+                writer.write_str(&texture.name);
+                writer.write_u32(texture_type_id(texture.ty));
+                writer.write_u32(filter_id(texture.mag_filter));
+                writer.write_u32(filter_id(texture.min_filter));
+                CookedTexture {
+                    extent: texture.extent,
+                    mip_levels: texture.mip_levels,
+                    data: &texture.data,
+                }
+                .write(writer);
+            }
+        }
+
+        writer.write_u64(self.materials.len() as u64);
+        for material in &self.materials {
+            writer.write_str(&material.name);
+            writer.write_u32(blend_id(material.blend));
+            write_f32_option(writer, material.alpha_cutoff);
+            writer.write_bool(material.double_sided);
+            writer.write_f32s(&Into::<[f32; 4]>::into(material.color));
+            writer.write_f32(material.metallic);
+            writer.write_f32(material.roughness);
+            writer.write_f32(material.occlusion_strength);
+            writer.write_f32s(&Into::<[f32; 4]>::into(material.emission_color));
+            writer.write_f32(material.emissive_strength);
+            write_index(writer, material.diffuse);
+            write_index(writer, material.normal);
+            write_index(writer, material.metallic_roughness);
+            write_index(writer, material.occlusion);
+            write_index(writer, material.emission);
+            writer.write_f32s(&material.uv_transform.offset_scale());
+            writer.write_f32(material.uv_transform.rotation);
+        }
+
+        writer.write_u64(self.meshes.len() as u64);
+        for mesh in &self.meshes {
+            writer.write_str(&mesh.name);
+            writer.write_u64(mesh.primitives.len() as u64);
+            for primitive in &mesh.primitives {
+                primitive.geometry.write_cooked(writer);
+                write_index(writer, primitive.material);
+            }
+            writer.write_f32s(&mesh.weights);
+        }
+
+        writer.write_u64(self.skins.len() as u64);
+        for skin in &self.skins {
+            writer.write_str(&skin.name);
+            writer.write_u32s(&skin.joints.iter().map(|&j| j as u32).collect::<Vec<_>>());
+            writer.write_f32s(
+                &skin
+                    .inverse_bind_matrices
+                    .iter()
+                    .flat_map(|m| m.to_cols_array())
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        writer.write_u64(self.cameras.len() as u64);
+        for camera in &self.cameras {
+            match *camera {
+                CameraAsset::Perspective {
+                    aspect,
+                    yfov,
+                    znear,
+                    zfar,
+                } => {
+                    writer.write_u32(0);
+                    write_f32_option(writer, aspect);
+                    writer.write_f32(yfov);
+                    writer.write_f32(znear);
+                    write_f32_option(writer, zfar);
+                }
+                CameraAsset::Ortho {
+                    xmag,
+                    ymag,
+                    znear,
+                    zfar,
+                } => {
+                    writer.write_u32(1);
+                    writer.write_f32s(&[xmag, ymag, znear, zfar]);
+                }
+            }
+        }
+
+        writer.write_u64(self.lights.len() as u64);
+        for light in &self.lights {
+            writer.write_f32s(&Into::<[f32; 4]>::into(light.color));
+            writer.write_f32(light.intensity);
+            match light.ty {
+                LightType::Directional => writer.write_u32(0),
+                LightType::Point { range } => {
+                    writer.write_u32(1);
+                    write_f32_option(writer, range);
+                }
+                LightType::Spot {
+                    range,
+                    inner_cone_angle,
+                    outer_cone_angle,
+                } => {
+                    writer.write_u32(2);
+                    write_f32_option(writer, range);
+                    writer.write_f32(inner_cone_angle);
+                    writer.write_f32(outer_cone_angle);
+                }
+            }
+        }
+
+        writer.write_u64(self.nodes.len() as u64);
+        for node in &self.nodes {
+            let transform = node.transform;
+            writer.write_str(&node.name);
+            writer.write_f32s(&transform.translation().to_array());
+            writer.write_f32s(&transform.rotation().to_array());
+            writer.write_f32s(&transform.scaling().to_array());
+            write_index(writer, node.mesh);
+            write_index(writer, node.camera);
+            write_index(writer, node.light);
+            write_index(writer, node.skin);
+            writer.write_f32s(&node.weights);
+            writer.write_u32s(&node.children.iter().map(|&c| c as u32).collect::<Vec<_>>());
+        }
+
+        writer.write_u64(self.scenes.len() as u64);
+        for scene in &self.scenes {
+            writer.write_str(&scene.name);
+            writer.write_u32s(&scene.roots.iter().map(|&r| r as u32).collect::<Vec<_>>());
+        }
+        write_index(writer, self.default_scene);
+
+        writer.write_u64(self.animations.len() as u64);
+        for animation in &self.animations {
+            writer.write_str(&animation.name);
+            writer.write_u64(animation.channels.len() as u64);
+            for channel in &animation.channels {
+                writer.write_u32(channel.node as u32);
+                writer.write_u32(interpolation_id(channel.interpolation));
+                writer.write_f32s(&channel.times);
+
+                let (kind, values): (u32, Vec<f32>) = match &channel.keyframes {
+                    Keyframes::Translation(values) => {
+                        (0, values.iter().flat_map(|v| v.to_array()).collect())
+                    }
+                    Keyframes::Rotation(values) => {
+                        (1, values.iter().flat_map(|v| v.to_array()).collect())
+                    }
+                    Keyframes::Scale(values) => {
+                        (2, values.iter().flat_map(|v| v.to_array()).collect())
+                    }
+                    Keyframes::Weights(values) => (3, values.clone()),
+                };
+                writer.write_u32(kind);
+                writer.write_f32s(&values);
+            }
+        }
+    }
+
+    pub fn read_cooked(reader: &mut CookedReader) -> io::Result<Self> {
+        let mut asset = ModelAsset::default();
+
+        for _ in 0..reader.read_u64()? {
+            let texture = if reader.read_bool()? {
+                let name = reader.read_str()?;
+                let ty = texture_type(reader.read_u32()?)?;
+                let mag_filter = filter(reader.read_u32()?)?;
+                let min_filter = filter(reader.read_u32()?)?;
+                let texture = CookedTexture::read(reader)?;
+
+                Some(TextureAsset {
+                    name,
+                    ty,
+                    mag_filter,
+                    min_filter,
+                    extent: texture.extent,
+                    mip_levels: texture.mip_levels,
+                    data: texture.data.to_vec(),
+                })
+            } else {
+                None
+            };
+            asset.textures.push(texture);
+        }
+
+        for _ in 0..reader.read_u64()? {
+            let name = reader.read_str()?;
+            let blend = blend(reader.read_u32()?)?;
+            let alpha_cutoff = read_f32_option(reader)?;
+            let double_sided = reader.read_bool()?;
+            let color = read_color(reader)?;
+            let metallic = reader.read_f32()?;
+            let roughness = reader.read_f32()?;
+            let occlusion_strength = reader.read_f32()?;
+            let emission_color = read_color(reader)?;
+            let emissive_strength = reader.read_f32()?;
+            let diffuse = read_index(reader, asset.textures.len())?;
+            let normal = read_index(reader, asset.textures.len())?;
+            let metallic_roughness = read_index(reader, asset.textures.len())?;
+            let occlusion = read_index(reader, asset.textures.len())?;
+            let emission = read_index(reader, asset.textures.len())?;
+            let offset_scale = read_vec(reader, 4)?;
+            let rotation = reader.read_f32()?;
+
+            asset.materials.push(MaterialAsset {
+                name,
+                blend,
+                alpha_cutoff,
+                double_sided,
+                color,
+                metallic,
+                roughness,
+                occlusion_strength,
+                emission_color,
+                emissive_strength,
+                diffuse,
+                normal,
+                metallic_roughness,
+                occlusion,
+                emission,
+                uv_transform: UvTransform {
+                    offset: Vec2::from_slice(&offset_scale[0..2]),
+                    rotation,
+                    scale: Vec2::from_slice(&offset_scale[2..4]),
+                },
+            });
+        }
+
+        for _ in 0..reader.read_u64()? {
+            let name = reader.read_str()?;
+            let mut primitives = vec![];
+            for _ in 0..reader.read_u64()? {
+                let geometry = MeshGeometry::read_cooked(reader)?;
+                let material = read_index(reader, asset.materials.len())?;
+                primitives.push(PrimitiveAsset { geometry, material });
+            }
+            let weights = reader.read_f32s()?;
+
+            asset.meshes.push(MeshAsset {
+                name,
+                primitives,
+                weights,
+            });
+        }
+
+        for _ in 0..reader.read_u64()? {
+            let name = reader.read_str()?;
+            // joints are checked once the nodes are read
+            let joints = reader
+                .read_u32s()?
+                .into_iter()
+                .map(|j| j as usize)
+                .collect();
+            let matrices = reader.read_f32s()?;
+            if !matrices.len().is_multiple_of(16) {
+                return Err(invalid_data("invalid inverse bind matrices"));
+            }
+
+            asset.skins.push(SkinAsset {
+                name,
+                joints,
+                inverse_bind_matrices: matrices.chunks(16).map(Mat4::from_cols_slice).collect(),
+            });
+        }
+
+        for _ in 0..reader.read_u64()? {
+            let camera = match reader.read_u32()? {
+                0 => CameraAsset::Perspective {
+                    aspect: read_f32_option(reader)?,
+                    yfov: reader.read_f32()?,
+                    znear: reader.read_f32()?,
+                    zfar: read_f32_option(reader)?,
+                },
+                1 => {
+                    let values = read_vec(reader, 4)?;
+                    CameraAsset::Ortho {
+                        xmag: values[0],
+                        ymag: values[1],
+                        znear: values[2],
+                        zfar: values[3],
+                    }
+                }
+                _ => return Err(invalid_data("invalid camera")),
+            };
+            asset.cameras.push(camera);
+        }
+
+        for _ in 0..reader.read_u64()? {
+            let color = read_color(reader)?;
+            let intensity = reader.read_f32()?;
+            let ty = match reader.read_u32()? {
+                0 => LightType::Directional,
+                1 => LightType::Point {
+                    range: read_f32_option(reader)?,
+                },
+                2 => LightType::Spot {
+                    range: read_f32_option(reader)?,
+                    inner_cone_angle: reader.read_f32()?,
+                    outer_cone_angle: reader.read_f32()?,
+                },
+                _ => return Err(invalid_data("invalid light")),
+            };
+            asset.lights.push(LightAsset {
+                color,
+                intensity,
+                ty,
+            });
+        }
+
+        let node_count = reader.read_u64()? as usize;
+        for _ in 0..node_count {
+            let name = reader.read_str()?;
+            let translation = read_vec(reader, 3)?;
+            let rotation = read_vec(reader, 4)?;
+            let scale = read_vec(reader, 3)?;
+            let mesh = read_index(reader, asset.meshes.len())?;
+            let camera = read_index(reader, asset.cameras.len())?;
+            let light = read_index(reader, asset.lights.len())?;
+            let skin = read_index(reader, asset.skins.len())?;
+            let weights = reader.read_f32s()?;
+            let children = read_indices(reader, node_count)?;
+
+            asset.nodes.push(NodeAsset {
+                name,
+                transform: Transform::new(
+                    Vec3::from_slice(&translation),
+                    Quat::from_slice(&rotation),
+                    Vec3::from_slice(&scale),
+                ),
+                mesh,
+                camera,
+                light,
+                skin,
+                weights,
+                children,
+            });
+        }
+
+        if asset
+            .skins
+            .iter()
+            .any(|skin| skin.joints.iter().any(|&joint| joint >= node_count))
+        {
+            return Err(invalid_data("index out of bounds"));
+        }
+
+        for _ in 0..reader.read_u64()? {
+            let name = reader.read_str()?;
+            let roots = read_indices(reader, node_count)?;
+            asset.scenes.push(SceneAsset { name, roots });
+        }
+        asset.default_scene = read_index(reader, asset.scenes.len())?;
+
+        for _ in 0..reader.read_u64()? {
+            let name = reader.read_str()?;
+            let mut channels = vec![];
+            for _ in 0..reader.read_u64()? {
+                let node = reader.read_u32()? as usize;
+                if node >= node_count {
+                    return Err(invalid_data("index out of bounds"));
+                }
+                let interpolation = interpolation(reader.read_u32()?)?;
+                let times = reader.read_f32s()?;
+                let keyframes = keyframes(reader.read_u32()?, reader.read_f32s()?)?;
+
+                channels.push(ChannelAsset {
+                    node,
+                    interpolation,
+                    times,
+                    keyframes,
+                });
+            }
+
+            asset.animations.push(AnimationAsset { name, channels });
+        }
+
+        Ok(asset)
+    }
+}
+
+fn invalid_data(error: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Optional index, stored as `u32::MAX` when missing
+fn write_index(writer: &mut CookedWriter, index: Option<usize>) {
+    writer.write_u32(index.map(|i| i as u32).unwrap_or(u32::MAX));
+}
+
+fn read_index(reader: &mut CookedReader, len: usize) -> io::Result<Option<usize>> {
+    match reader.read_u32()? {
+        u32::MAX => Ok(None),
+        index if (index as usize) < len => Ok(Some(index as usize)),
+        _ => Err(invalid_data("index out of bounds")),
+    }
+}
+
+/// Optional value, preceded by a flag
+fn write_f32_option(writer: &mut CookedWriter, value: Option<f32>) {
+    writer.write_bool(value.is_some());
+    writer.write_f32(value.unwrap_or_default());
+}
+
+fn read_f32_option(reader: &mut CookedReader) -> io::Result<Option<f32>> {
+    let is_some = reader.read_bool()?;
+    let value = reader.read_f32()?;
+
+    Ok(is_some.then_some(value))
+}
+
+fn read_indices(reader: &mut CookedReader, len: usize) -> io::Result<Vec<usize>> {
+    reader
+        .read_u32s()?
+        .into_iter()
+        .map(|index| {
+            if (index as usize) < len {
+                Ok(index as usize)
+            } else {
+                Err(invalid_data("index out of bounds"))
+            }
+        })
+        .collect()
+}
+
+fn read_vec(reader: &mut CookedReader, len: usize) -> io::Result<Vec<f32>> {
+    let values = reader.read_f32s()?;
+
+    if values.len() != len {
+        return Err(invalid_data("invalid vector size"));
+    }
+
+    Ok(values)
+}
+
+fn read_color(reader: &mut CookedReader) -> io::Result<Color> {
+    let color = reader.read_f32s()?;
+    let color = <[f32; 4]>::try_from(color.as_slice())
+        .map_err(|_| invalid_data("invalid material color"))?;
+
+    Ok(color.into())
+}
+
+fn texture_type_id(ty: TextureType) -> u32 {
+    match ty {
+        TextureType::Diffuse => 0,
+        TextureType::Normal => 1,
+        TextureType::Emission => 2,
+        TextureType::MetallicRoughness => 3,
+        TextureType::Occlusion => 4,
+    }
+}
+
+fn texture_type(id: u32) -> io::Result<TextureType> {
+    match id {
+        0 => Ok(TextureType::Diffuse),
+        1 => Ok(TextureType::Normal),
+        2 => Ok(TextureType::Emission),
+        3 => Ok(TextureType::MetallicRoughness),
+        4 => Ok(TextureType::Occlusion),
+        _ => Err(invalid_data("invalid texture type")),
+    }
+}
+
+fn filter_id(filter: SamplerFilter) -> u32 {
+    match filter {
+        SamplerFilter::FilterNearest => 0,
+        SamplerFilter::FilterLinear => 1,
+    }
+}
+
+fn filter(id: u32) -> io::Result<SamplerFilter> {
+    match id {
+        0 => Ok(SamplerFilter::FilterNearest),
+        1 => Ok(SamplerFilter::FilterLinear),
+        _ => Err(invalid_data("invalid sampler filter")),
+    }
+}
+
+fn blend_id(blend: BlendMode) -> u32 {
+    match blend {
+        BlendMode::None => 0,
+        BlendMode::Additive => 1,
+        BlendMode::Alpha => 2,
+        BlendMode::Premultiplied => 3,
+    }
+}
+
+fn blend(id: u32) -> io::Result<BlendMode> {
+    match id {
+        0 => Ok(BlendMode::None),
+        1 => Ok(BlendMode::Additive),
+        2 => Ok(BlendMode::Alpha),
+        3 => Ok(BlendMode::Premultiplied),
+        _ => Err(invalid_data("invalid blend mode")),
+    }
+}
+
+fn interpolation_id(interpolation: Interpolation) -> u32 {
+    match interpolation {
+        Interpolation::Step => 0,
+        Interpolation::Linear => 1,
+        Interpolation::CubicSpline => 2,
+    }
+}
+
+fn interpolation(id: u32) -> io::Result<Interpolation> {
+    match id {
+        0 => Ok(Interpolation::Step),
+        1 => Ok(Interpolation::Linear),
+        2 => Ok(Interpolation::CubicSpline),
+        _ => Err(invalid_data("invalid interpolation")),
+    }
+}
+
+fn keyframes(kind: u32, values: Vec<f32>) -> io::Result<Keyframes> {
+    if kind == 3 {
+        return Ok(Keyframes::Weights(values));
+    }
+
+    let size = if kind == 1 { 4 } else { 3 };
+    if !values.len().is_multiple_of(size) {
+        return Err(invalid_data("invalid keyframes size"));
+    }
+    let chunks = values.chunks_exact(size);
+
+    match kind {
+        0 => Ok(Keyframes::Translation(
+            chunks.map(Vec3::from_slice).collect(),
+        )),
+        1 => Ok(Keyframes::Rotation(chunks.map(Quat::from_slice).collect())),
+        2 => Ok(Keyframes::Scale(chunks.map(Vec3::from_slice).collect())),
+        _ => Err(invalid_data("invalid keyframes")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Quat, UVec4, Vec2, Vec3, Vec4};
+
+    use gobs_core::{Color, ImageExtent2D, SamplerFilter, Transform};
+    use gobs_render::{BlendMode, MeshGeometry, TextureType, VertexData};
+    use gobs_resource::{
+        light::LightType,
+        load::{CookedAsset, CookedKind, CookedWriter},
+    };
+    use gobs_scene::animation::{Interpolation, Keyframes};
+
+    use crate::model::{
+        AnimationAsset, CameraAsset, ChannelAsset, LightAsset, MaterialAsset, MeshAsset,
+        ModelAsset, NodeAsset, PrimitiveAsset, SceneAsset, SceneSelection, SkinAsset, TextureAsset,
+        UvTransform,
+    };
+
+    fn model() -> ModelAsset {
+        let mut geometry = MeshGeometry::builder("triangle");
+        geometry
+            .vertex(
+                VertexData::builder()
+                    .position(Vec3::X)
+                    .joints(UVec4::new(1, 0, 0, 0))
+                    .weights(Vec4::new(0.75, 0.25, 0., 0.))
+                    .build(),
+            )
+            .vertex(VertexData::builder().position(Vec3::Y).build())
+            .vertex(VertexData::builder().position(Vec3::Z).build());
+
+        ModelAsset {
+            textures: vec![
+                Some(TextureAsset {
+                    name: "texture".to_string(),
+                    ty: TextureType::Normal,
+                    mag_filter: SamplerFilter::FilterNearest,
+                    min_filter: SamplerFilter::FilterLinear,
+                    extent: ImageExtent2D::new(2, 2),
+                    mip_levels: 1,
+                    data: vec![255; 16],
+                }),
+                None,
+            ],
+            materials: vec![MaterialAsset {
+                name: "material".to_string(),
+                blend: BlendMode::Alpha,
+                alpha_cutoff: Some(0.25),
+                double_sided: true,
+                color: Color::RED,
+                metallic: 0.5,
+                emission_color: Color::GREEN,
+                emissive_strength: 4.,
+                diffuse: Some(1),
+                normal: Some(0),
+                occlusion: Some(0),
+                uv_transform: UvTransform {
+                    offset: Vec2::new(0.5, 0.),
+                    rotation: 1.,
+                    scale: Vec2::splat(2.),
+                },
+                ..Default::default()
+            }],
+            meshes: vec![MeshAsset {
+                name: "mesh".to_string(),
+                primitives: vec![PrimitiveAsset {
+                    geometry: geometry.build(),
+                    material: Some(0),
+                }],
+                weights: vec![0.5],
+            }],
+            skins: vec![SkinAsset {
+                name: "skin".to_string(),
+                joints: vec![0, 1],
+                inverse_bind_matrices: vec![Mat4::from_translation(-Vec3::ONE), Mat4::IDENTITY],
+            }],
+            cameras: vec![CameraAsset::Perspective {
+                aspect: None,
+                yfov: 0.8,
+                znear: 0.1,
+                zfar: Some(100.),
+            }],
+            lights: vec![LightAsset {
+                color: Color::RED,
+                intensity: 5.,
+                ty: LightType::Spot {
+                    range: None,
+                    inner_cone_angle: 0.2,
+                    outer_cone_angle: 0.4,
+                },
+            }],
+            nodes: vec![
+                NodeAsset {
+                    name: "root".to_string(),
+                    transform: Transform::from_translation(Vec3::ONE),
+                    mesh: None,
+                    camera: Some(0),
+                    light: None,
+                    skin: None,
+                    weights: vec![],
+                    children: vec![1],
+                },
+                NodeAsset {
+                    name: "mesh".to_string(),
+                    transform: Transform::from_rotation(Quat::from_rotation_y(1.)),
+                    mesh: Some(0),
+                    camera: None,
+                    light: Some(0),
+                    skin: Some(0),
+                    weights: vec![1.],
+                    children: vec![],
+                },
+            ],
+            scenes: vec![
+                SceneAsset {
+                    name: "main".to_string(),
+                    roots: vec![0],
+                },
+                SceneAsset {
+                    name: "detail".to_string(),
+                    roots: vec![1],
+                },
+            ],
+            default_scene: Some(1),
+            animations: vec![AnimationAsset {
+                name: "spin".to_string(),
+                channels: vec![
+                    ChannelAsset {
+                        node: 1,
+                        interpolation: Interpolation::Step,
+                        times: vec![0., 1.],
+                        keyframes: Keyframes::Rotation(vec![
+                            Quat::IDENTITY,
+                            Quat::from_rotation_y(2.),
+                        ]),
+                    },
+                    ChannelAsset {
+                        node: 1,
+                        interpolation: Interpolation::Linear,
+                        times: vec![0., 1.],
+                        keyframes: Keyframes::Weights(vec![0., 1.]),
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_scene_roots() {
+        let mut model = model();
+
+        assert_eq!(model.scene_roots(&SceneSelection::Default), vec![1]);
+        assert_eq!(model.scene_roots(&SceneSelection::Index(0)), vec![0]);
+        assert_eq!(
+            model.scene_roots(&SceneSelection::Name("main".to_string())),
+            vec![0]
+        );
+        assert_eq!(model.scene_roots(&SceneSelection::All), vec![0, 1]);
+        assert!(model.scene_roots(&SceneSelection::Index(2)).is_empty());
+        assert!(
+            model
+                .scene_roots(&SceneSelection::Name("missing".to_string()))
+                .is_empty()
+        );
+
+        model.default_scene = None;
+        assert_eq!(model.scene_roots(&SceneSelection::Default), vec![0]);
+    }
+
+    #[test]
+    fn test_cooked_model() {
+        let mut model = model();
+        model.generate_mips();
+
+        let texture = model.textures[0].as_ref().unwrap();
+        assert_eq!(texture.mip_levels, 2);
+        assert_eq!(texture.data.len(), 4 * (4 + 1));
+
+        let mut writer = CookedWriter::new();
+        model.write_cooked(&mut writer);
+
+        let asset =
+            CookedAsset::from_bytes(writer.finish(CookedKind::Model, 0), "model.gmdl").unwrap();
+        let cooked = ModelAsset::read_cooked(&mut asset.reader()).unwrap();
+
+        let texture = cooked.textures[0].as_ref().unwrap();
+        assert_eq!(texture.name, "texture");
+        assert!(matches!(texture.ty, TextureType::Normal));
+        assert!(matches!(texture.mag_filter, SamplerFilter::FilterNearest));
+        assert_eq!(texture.mip_levels, 2);
+        assert_eq!(texture.data, model.textures[0].as_ref().unwrap().data);
+        assert!(cooked.textures[1].is_none());
+
+        let material = &cooked.materials[0];
+        assert_eq!(material.name, "material");
+        assert_eq!(material.blend, BlendMode::Alpha);
+        assert_eq!(material.color, Color::RED);
+        assert_eq!((material.diffuse, material.normal), (Some(1), Some(0)));
+        assert_eq!(material.alpha_cutoff, Some(0.25));
+        assert!(material.double_sided);
+        assert_eq!((material.metallic, material.roughness), (0.5, 1.));
+        assert_eq!(material.emission_color, Color::GREEN);
+        assert_eq!(material.emissive_strength, 4.);
+        assert_eq!(
+            (
+                material.metallic_roughness,
+                material.occlusion,
+                material.emission
+            ),
+            (None, Some(0), None)
+        );
+        assert_eq!(material.uv_transform, model.materials[0].uv_transform);
+
+        let primitive = &cooked.meshes[0].primitives[0];
+        let geometry = &model.meshes[0].primitives[0].geometry;
+        assert_eq!(primitive.material, Some(0));
+        assert_eq!(primitive.geometry.name, geometry.name);
+        assert_eq!(primitive.geometry.indices, geometry.indices);
+        for (v1, v2) in primitive.geometry.vertices.iter().zip(&geometry.vertices) {
+            assert_eq!(v1.position(), v2.position());
+            assert_eq!(v1.tangent(), v2.tangent());
+            assert_eq!(v1.joints(), v2.joints());
+            assert_eq!(v1.weights(), v2.weights());
+        }
+
+        let skin = &cooked.skins[0];
+        assert_eq!(skin.name, "skin");
+        assert_eq!(skin.joints, vec![0, 1]);
+        assert_eq!(
+            skin.inverse_bind_matrices,
+            model.skins[0].inverse_bind_matrices
+        );
+
+        assert_eq!(cooked.nodes[0].children, vec![1]);
+        assert_eq!(cooked.nodes[1].mesh, Some(0));
+        assert_eq!(cooked.nodes[1].skin, Some(0));
+        assert_eq!(cooked.nodes[1].weights, vec![1.]);
+        assert_eq!(cooked.meshes[0].weights, vec![0.5]);
+        assert_eq!(
+            cooked.nodes[1].transform.rotation(),
+            model.nodes[1].transform.rotation()
+        );
+        assert_eq!(cooked.scenes[0].name, "main");
+        assert_eq!(cooked.scenes[0].roots, vec![0]);
+        assert_eq!(cooked.scenes[1].roots, vec![1]);
+        assert_eq!(cooked.default_scene, Some(1));
+        assert_eq!(cooked.cameras, model.cameras);
+        assert_eq!(cooked.lights, model.lights);
+        assert_eq!(cooked.nodes[1].name, "mesh");
+        assert_eq!(
+            (cooked.nodes[0].camera, cooked.nodes[0].light),
+            (Some(0), None)
+        );
+        assert_eq!(
+            (cooked.nodes[1].camera, cooked.nodes[1].light),
+            (None, Some(0))
+        );
+
+        let animation = &cooked.animations[0];
+        assert_eq!(animation.name, "spin");
+        let channel = &animation.channels[0];
+        assert_eq!(channel.node, 1);
+        assert_eq!(channel.interpolation, Interpolation::Step);
+        assert_eq!(channel.times, vec![0., 1.]);
+        assert!(matches!(
+            &channel.keyframes,
+            Keyframes::Rotation(values) if values[1] == Quat::from_rotation_y(2.)
+        ));
+        assert!(matches!(
+            &animation.channels[1].keyframes,
+            Keyframes::Weights(values) if *values == vec![0., 1.]
+        ));
+
+        // corrupted joint
+        model.skins[0].joints = vec![0, 5];
+        let mut writer = CookedWriter::new();
+        model.write_cooked(&mut writer);
+        let asset =
+            CookedAsset::from_bytes(writer.finish(CookedKind::Model, 0), "model.gmdl").unwrap();
+        assert!(ModelAsset::read_cooked(&mut asset.reader()).is_err());
+        model.skins[0].joints = vec![0, 1];
+
+        // corrupted index
+        model.scenes[0].roots = vec![5];
+        let mut writer = CookedWriter::new();
+        model.write_cooked(&mut writer);
+        let asset =
+            CookedAsset::from_bytes(writer.finish(CookedKind::Model, 0), "model.gmdl").unwrap();
+        assert!(ModelAsset::read_cooked(&mut asset.reader()).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tracing::Level;
+use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+pub fn setup() {
+    let sub = FmtSubscriber::builder()
+        .with_max_level(Level::INFO)
+        .with_span_events(FmtSpan::CLOSE)
+        .finish();
+    tracing::subscriber::set_global_default(sub).unwrap_or_default();
+}
+
+/// Empty directory, unique to the test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = env::temp_dir().join(format!("gobs-{}-{}", name, nanos));
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
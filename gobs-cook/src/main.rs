@@ -0,0 +1,46 @@
+use std::{env, process::ExitCode};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{EnvFilter, fmt};
+
+use gobs_assets::cook::Cooker;
+
+const USAGE: &str = "usage: gobs-cook <source> <output> [--force]";
+
+fn main() -> ExitCode {
+    fmt()
+        .with_env_filter(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::INFO.into())
+                .from_env_lossy(),
+        )
+        .init();
+
+    let mut force = false;
+    let mut paths = vec![];
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--force" | "-f" => force = true,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    let [source, output] = paths.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    match Cooker::new(source, output).force(force).run() {
+        Ok(stats) if stats.failed == 0 => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            tracing::error!("Cook failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
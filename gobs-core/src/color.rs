@@ -3,10 +3,10 @@ use std::ops::{Add, Div, Mul};
 use bytemuck::{Pod, Zeroable};
 use glam::Vec3;
 use image::Rgba;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Serialize, Zeroable)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Pod, Serialize, Zeroable)]
 pub struct Color {
     r: f32,
     g: f32,
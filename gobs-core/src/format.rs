@@ -1,4 +1,9 @@
-use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use serde::{
+    Deserialize, Serialize,
+    de::{IntoDeserializer, value::Error},
+};
 
 #[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 pub enum ImageFormat {
@@ -204,7 +209,35 @@ impl ImageFormat {
             ImageFormat::R32g32b32a32Sfloat => 16,
             ImageFormat::D32Sfloat => 4,
             ImageFormat::A2b10g10r10UnormPack32 => 4,
+            ImageFormat::A2r10g10b10UnormPack32 => 4,
             _ => todo!(),
         }
     }
 }
+
+/// Parse a format from its variant name (e.g. `B8g8r8a8Unorm`)
+impl FromStr for ImageFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(s.trim().into_deserializer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ImageFormat;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            "B8g8r8a8Unorm".parse::<ImageFormat>().unwrap(),
+            ImageFormat::B8g8r8a8Unorm
+        );
+        assert_eq!(
+            " A2b10g10r10UnormPack32".parse::<ImageFormat>().unwrap(),
+            ImageFormat::A2b10g10r10UnormPack32
+        );
+        assert!("b8g8r8a8_unorm".parse::<ImageFormat>().is_err());
+    }
+}
@@ -1,8 +1,10 @@
 use std::{fmt::Debug, ops::Mul};
 
 use glam::{Mat4, Quat, Vec3, Vec4, Vec4Swizzles};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(from = "TransformData", into = "TransformData")]
 pub struct Transform {
     translation: Vec3,
     rotation: Quat,
@@ -43,6 +45,31 @@ impl Transform {
         Self::new(Vec3::ZERO, rotation, Vec3::ONE)
     }
 
+    /// Decompose the matrix, shear is lost
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+
+        Transform {
+            translation,
+            rotation,
+            scale,
+            matrix,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::from_matrix(self.matrix.inverse())
+    }
+
+    /// Interpolate each component, rotations use the shortest path
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self::new(
+            self.translation.lerp(other.translation, t),
+            self.rotation.slerp(other.rotation, t),
+            self.scale.lerp(other.scale, t),
+        )
+    }
+
     pub fn matrix(&self) -> &Mat4 {
         &self.matrix
     }
@@ -96,6 +123,39 @@ impl Transform {
     }
 }
 
+/// Serialized form of a transform, the matrix is computed on load
+#[derive(Deserialize, Serialize)]
+struct TransformData {
+    #[serde(default)]
+    translation: Vec3,
+    #[serde(default)]
+    rotation: Quat,
+    #[serde(default = "TransformData::default_scale")]
+    scale: Vec3,
+}
+
+impl TransformData {
+    fn default_scale() -> Vec3 {
+        Vec3::ONE
+    }
+}
+
+impl From<TransformData> for Transform {
+    fn from(data: TransformData) -> Self {
+        Transform::new(data.translation, data.rotation, data.scale)
+    }
+}
+
+impl From<Transform> for TransformData {
+    fn from(transform: Transform) -> Self {
+        TransformData {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            scale: transform.scale,
+        }
+    }
+}
+
 impl Debug for Transform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut builder = &mut f.debug_struct("Transform");
@@ -118,15 +178,7 @@ impl Mul for Transform {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self {
-        let matrix = self.matrix * rhs.matrix;
-        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
-
-        Transform {
-            translation,
-            rotation,
-            scale,
-            matrix,
-        }
+        Self::from_matrix(self.matrix * rhs.matrix)
     }
 }
 
@@ -157,3 +209,105 @@ impl From<Transform> for [[f32; 4]; 4] {
         val.matrix.to_cols_array_2d()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Quat, Vec3};
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::Transform;
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_serialize() {
+        setup();
+
+        let transform = Transform::new(
+            Vec3::new(1., 2., 3.),
+            Quat::from_rotation_y(0.5),
+            Vec3::new(2., 2., 2.),
+        );
+
+        let data = ron::to_string(&transform).unwrap();
+        tracing::info!("{}", data);
+
+        let loaded: Transform = ron::from_str(&data).unwrap();
+        assert_eq!(loaded.translation(), transform.translation());
+        assert_eq!(loaded.rotation(), transform.rotation());
+        assert_eq!(loaded.scaling(), transform.scaling());
+        assert_eq!(loaded.matrix(), transform.matrix());
+    }
+
+    #[test]
+    fn test_inverse() {
+        setup();
+
+        let transform = Transform::new(
+            Vec3::new(1., 2., 3.),
+            Quat::from_rotation_x(0.7),
+            Vec3::splat(0.5),
+        );
+
+        let identity = transform * transform.inverse();
+        assert!(identity.matrix().abs_diff_eq(Mat4::IDENTITY, 1e-5));
+
+        let matrix = *transform.matrix();
+        assert!(
+            Transform::from_matrix(matrix)
+                .matrix()
+                .abs_diff_eq(matrix, 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_lerp() {
+        setup();
+
+        let start = Transform::IDENTITY;
+        let end = Transform::new(
+            Vec3::new(2., 0., 0.),
+            Quat::from_rotation_y(1.),
+            Vec3::splat(3.),
+        );
+
+        let half = start.lerp(&end, 0.5);
+        assert_eq!(half.translation(), Vec3::X);
+        assert!(
+            half.rotation()
+                .abs_diff_eq(Quat::from_rotation_y(0.5), 1e-5)
+        );
+        assert_eq!(half.scaling(), Vec3::splat(2.));
+
+        assert!(
+            start
+                .lerp(&end, 1.)
+                .matrix()
+                .abs_diff_eq(*end.matrix(), 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_defaults() {
+        setup();
+
+        let loaded: Transform = ron::from_str("(translation: (1., 0., 0.))").unwrap();
+        assert_eq!(loaded.translation(), Vec3::X);
+        assert_eq!(loaded.rotation(), Quat::IDENTITY);
+        assert_eq!(loaded.scaling(), Vec3::ONE);
+        assert_eq!(
+            loaded.matrix(),
+            Transform::from_translation(Vec3::X).matrix()
+        );
+
+        let loaded: Transform = ron::from_str("()").unwrap();
+        assert_eq!(loaded.matrix(), Transform::IDENTITY.matrix());
+    }
+}
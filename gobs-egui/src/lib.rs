@@ -1,9 +1,11 @@
+mod memory;
 mod ui;
 
 use thiserror::Error;
 
 use gobs_render::RenderError;
 
+pub use memory::MemoryPanel;
 pub use ui::UIRenderer;
 
 #[derive(Debug, Error)]
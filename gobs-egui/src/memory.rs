@@ -0,0 +1,96 @@
+use egui::{Response, Ui, Widget};
+
+use gobs_render::MemoryReport;
+
+const MB: f32 = 1024. * 1024.;
+
+/// Heap budgets, memory used by each resource category and allocator statistics
+pub struct MemoryPanel<'a> {
+    report: &'a MemoryReport,
+}
+
+impl<'a> MemoryPanel<'a> {
+    pub fn new(report: &'a MemoryReport) -> Self {
+        Self { report }
+    }
+
+    fn show_heaps(&self, ui: &mut Ui) {
+        for (i, heap) in self.report.heaps.iter().enumerate() {
+            let kind = if heap.device_local { "device" } else { "host" };
+
+            match (heap.usage, heap.budget) {
+                (Some(usage), Some(budget)) if budget > 0 => {
+                    ui.add(
+                        egui::ProgressBar::new(usage as f32 / budget as f32).text(format!(
+                            "Heap {} ({}): {:.1} / {:.1} MB",
+                            i,
+                            kind,
+                            usage as f32 / MB,
+                            budget as f32 / MB
+                        )),
+                    );
+                }
+                _ => {
+                    ui.label(format!(
+                        "Heap {} ({}): {:.1} MB, no budget",
+                        i,
+                        kind,
+                        heap.size as f32 / MB
+                    ));
+                }
+            }
+        }
+    }
+
+    fn show_categories(&self, ui: &mut Ui) {
+        egui::Grid::new("memory_categories")
+            .striped(true)
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.strong("category");
+                ui.strong("count");
+                ui.strong("size");
+                ui.end_row();
+
+                for (category, usage) in &self.report.categories {
+                    ui.label(format!("{category:?}"));
+                    ui.label(format!("{}", usage.count));
+                    ui.label(format!("{:.2} MB", usage.size as f32 / MB));
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn show_allocator(&self, ui: &mut Ui) {
+        let stats = &self.report.allocator;
+
+        ui.label(format!("Allocations: {}", stats.allocation_count));
+        ui.label(format!("Blocks: {}", stats.block_count));
+        ui.label(format!(
+            "Allocated: {:.2} / {:.2} MB",
+            stats.allocated as f32 / MB,
+            stats.reserved as f32 / MB
+        ));
+        ui.label(format!(
+            "Fragmentation: {:.0}%",
+            100. * self.report.fragmentation()
+        ));
+    }
+}
+
+impl Widget for MemoryPanel<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            egui::CollapsingHeader::new("Heaps")
+                .default_open(true)
+                .show(ui, |ui| self.show_heaps(ui));
+            egui::CollapsingHeader::new("Resources")
+                .default_open(true)
+                .show(ui, |ui| self.show_categories(ui));
+            egui::CollapsingHeader::new("Allocator")
+                .default_open(true)
+                .show(ui, |ui| self.show_allocator(ui));
+        })
+        .response
+    }
+}
@@ -384,9 +384,15 @@ impl UIRenderer {
                     bytes.len()
                 );
 
-                let texture = resource_manager.get(&material).properties.textures[0];
-                let handle = resource_manager.replace(&texture);
-                let texture = resource_manager.get_mut(&handle);
+                let texture = resource_manager
+                    .get(&material)
+                    .expect("Invalid font material")
+                    .properties
+                    .textures[0];
+                let handle = resource_manager
+                    .replace(&texture)
+                    .expect("Invalid font texture");
+                let texture = resource_manager.get_mut(&handle).unwrap();
                 tracing::trace!(target: logger::UI,
                     "Patching texture original size: {:?}",
                     texture.properties.format.extent
@@ -11,6 +11,7 @@ use winit::{
 use gobs_assets::config::GltfConfig;
 use gobs_core::{ConfigWriter as _, GobsConfig, Input, logger, utils::timer::Timer};
 use gobs_render::{RenderConfig, RenderError, RenderHalConfig};
+use gobs_resource::ResourceConfig;
 
 use crate::{AppError, context::GobsContext};
 
@@ -171,6 +172,10 @@ where
                             match runnable.render(context) {
                                 Ok(_) => {}
                                 Err(RenderError::Lost | RenderError::Outdated) => {}
+                                Err(e @ (RenderError::DeviceLost | RenderError::Timeout)) => {
+                                    tracing::error!(target: logger::EVENTS, "{}, closing", e);
+                                    self.close_requested = true;
+                                }
                                 Err(e) => tracing::error!(target: logger::EVENTS, "{:?}", e),
                             }
 
@@ -225,6 +230,7 @@ where
         config.register::<RenderConfig>();
         config.register::<RenderHalConfig>();
         config.register::<GltfConfig>();
+        config.register::<ResourceConfig>();
 
         Application {
             context: None,
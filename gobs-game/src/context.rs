@@ -2,14 +2,14 @@ use std::fmt::Debug;
 
 use winit::window::Window;
 
-use gobs_assets::gltf_load;
-use gobs_core::{GobsConfig, ImageExtent2D, Input, logger};
+use gobs_assets::{gltf_load, model::ModelAsset};
+use gobs_core::{ConfigReader as _, GobsConfig, ImageExtent2D, Input, logger};
 use gobs_egui::UIRenderer;
 use gobs_render::{
-    GfxContext, Material, MaterialInstance, MaterialInstanceLoader, MaterialLoader,
-    MaterialsConfig, Mesh, MeshLoader, Pipeline, PipelineLoader, RenderBuilder, RenderError,
-    RenderHAL, RenderMaterialBuilder, RenderMeshBuilder, RenderModelBuilder, RenderTextureBuilder,
-    Renderer, Texture, TextureLoader,
+    GfxContext, HotReload, Material, MaterialInstance, MaterialInstanceLoader, MaterialLoader,
+    MaterialsConfig, Mesh, MeshLoader, Pipeline, PipelineLoader, RenderBuilder, RenderConfig,
+    RenderError, RenderHAL, RenderMaterialBuilder, RenderMeshBuilder, RenderModelBuilder,
+    RenderTextureBuilder, Renderer, Texture, TextureLoader,
 };
 use gobs_resource::{ResourceManager, load};
 use gobs_scene::{SceneBuilder, graph::scenegraph::SceneGraph};
@@ -59,10 +59,15 @@ pub struct GameContext {
     resource_manager: ResourceManager,
     renderer: Renderer,
     ui: UIRenderer,
+    hot_reload: Option<HotReload>,
 }
 
 impl GobsContext for GameContext {
     fn new(name: &str, config: GobsConfig, window: Option<Window>, validation: bool) -> Self {
+        if let Err(e) = load::configure(&config) {
+            tracing::error!(target: logger::RESOURCES, "Failed to mount asset roots: {}", e);
+        }
+
         let mut gfx = GfxContext::new(name, window, config.clone(), validation);
         let mut resource_manager = ResourceManager::new(gfx.frames_in_flight());
 
@@ -85,6 +90,16 @@ impl GobsContext for GameContext {
 
         let renderer = Renderer::new(gfx, config.clone(), &mut resource_manager);
 
+        let hot_reload = if config.get_bool(RenderConfig::HotReload) {
+            HotReload::new(config.clone())
+                .inspect_err(
+                    |e| tracing::warn!(target: logger::RESOURCES, "Hot reloading disabled: {}", e),
+                )
+                .ok()
+        } else {
+            None
+        };
+
         Self {
             app_info: AppInfo {
                 name: name.to_string(),
@@ -93,6 +108,7 @@ impl GobsContext for GameContext {
             resource_manager,
             renderer,
             ui,
+            hot_reload,
         }
     }
 
@@ -106,16 +122,21 @@ impl GobsContext for GameContext {
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
     fn pre_update(&mut self, delta: f32) {
         self.renderer.update(delta);
+        if let Some(hot_reload) = &mut self.hot_reload {
+            hot_reload.update(&mut self.renderer, &mut self.resource_manager);
+        }
+        self.resource_manager.new_frame();
+        // dependents first so released dependencies are unloaded in the same frame
         self.resource_manager
-            .update::<Texture>(self.renderer.gfx.hal_mut());
+            .update::<MaterialInstance>(self.renderer.gfx.hal_mut());
         self.resource_manager
-            .update::<Mesh>(self.renderer.gfx.hal_mut());
+            .update::<Material>(self.renderer.gfx.hal_mut());
         self.resource_manager
             .update::<Pipeline>(self.renderer.gfx.hal_mut());
         self.resource_manager
-            .update::<Material>(self.renderer.gfx.hal_mut());
+            .update::<Mesh>(self.renderer.gfx.hal_mut());
         self.resource_manager
-            .update::<MaterialInstance>(self.renderer.gfx.hal_mut());
+            .update::<Texture>(self.renderer.gfx.hal_mut());
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
@@ -202,16 +223,26 @@ impl GobsContext for GameContext {
     async fn load_material(&mut self, filename: &str) {
         MaterialsConfig::load_resources(self.config.clone(), filename, &mut self.resource_manager)
             .await;
+
+        if let Some(hot_reload) = &mut self.hot_reload {
+            hot_reload.watch_materials(filename);
+        }
     }
 
     fn load_gltf(&mut self, filename: &str) -> SceneGraph {
-        let filename = load::get_asset_dir(filename, load::AssetType::MODEL).unwrap();
         let mut gltf_loader =
             gltf_load::GLTFLoader::new(self.config.clone(), &mut self.resource_manager).unwrap();
 
-        gltf_loader
-            .load(self.config(), &mut self.resource_manager, filename)
-            .expect("Load gltf");
+        if ModelAsset::is_cooked(filename) {
+            gltf_loader
+                .load_cooked(self.config(), &mut self.resource_manager, filename)
+                .expect("Load cooked model");
+        } else {
+            let filename = load::get_asset_dir(filename, load::AssetType::MODEL).unwrap();
+            gltf_loader
+                .load(self.config(), &mut self.resource_manager, filename)
+                .expect("Load gltf");
+        }
 
         gltf_loader.scene
     }
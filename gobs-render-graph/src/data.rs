@@ -1,3 +1,10 @@
+mod compute_data;
+mod object_table;
 mod scene_data;
+mod table_buffer;
 
+pub use compute_data::{ComputeDataLayout, ComputeDataProp};
+pub(crate) use object_table::IndirectDraws;
+pub use object_table::{DrawGroup, ObjectTable};
 pub use scene_data::{SceneData, SceneDataLayout, SceneDataProp};
+pub(crate) use table_buffer::TableBuffer;
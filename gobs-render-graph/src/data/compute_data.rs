@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use gobs_render_hal::{AlignMode, Attribute, UniformData, UniformLayout};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum ComputeDataProp {
+    Time,
+    FrameNumber,
+    Extent,
+    CameraViewProj,
+    /// Address of the object table of indirect draws
+    ObjectBuffer,
+    /// Address of the indirect draw commands
+    CommandBuffer,
+    /// Address of the indirect draw counts, one per draw group
+    CountBuffer,
+    ObjectCount,
+}
+
+impl ComputeDataProp {
+    /// Name of the field in the shader push constants struct
+    pub fn label(self) -> &'static str {
+        match self {
+            ComputeDataProp::Time => "time",
+            ComputeDataProp::FrameNumber => "frame_number",
+            ComputeDataProp::Extent => "extent",
+            ComputeDataProp::CameraViewProj => "view_proj",
+            ComputeDataProp::ObjectBuffer => "object_buffer",
+            ComputeDataProp::CommandBuffer => "command_buffer",
+            ComputeDataProp::CountBuffer => "count_buffer",
+            ComputeDataProp::ObjectCount => "object_count",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "time" => Some(ComputeDataProp::Time),
+            "frame_number" => Some(ComputeDataProp::FrameNumber),
+            "extent" => Some(ComputeDataProp::Extent),
+            "view_proj" => Some(ComputeDataProp::CameraViewProj),
+            "object_buffer" => Some(ComputeDataProp::ObjectBuffer),
+            "command_buffer" => Some(ComputeDataProp::CommandBuffer),
+            "count_buffer" => Some(ComputeDataProp::CountBuffer),
+            "object_count" => Some(ComputeDataProp::ObjectCount),
+            _ => None,
+        }
+    }
+}
+
+/// Push constants of a compute dispatch
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComputeDataLayout {
+    layout: Vec<ComputeDataProp>,
+    uniform_layout: UniformLayout,
+}
+
+impl ComputeDataLayout {
+    pub fn new(mode: AlignMode) -> Self {
+        Self {
+            layout: Vec::new(),
+            uniform_layout: UniformLayout::new(mode),
+        }
+    }
+}
+
+impl UniformData<ComputeDataProp> for ComputeDataLayout {
+    fn prop(mut self, prop: ComputeDataProp) -> Self {
+        self.layout.push(prop);
+
+        let attribute = match prop {
+            ComputeDataProp::Time => Attribute::F32,
+            ComputeDataProp::FrameNumber => Attribute::U32,
+            ComputeDataProp::Extent => Attribute::Vec2F,
+            ComputeDataProp::CameraViewProj => Attribute::Mat4F,
+            ComputeDataProp::ObjectBuffer => Attribute::U64,
+            ComputeDataProp::CommandBuffer => Attribute::U64,
+            ComputeDataProp::CountBuffer => Attribute::U64,
+            ComputeDataProp::ObjectCount => Attribute::U32,
+        };
+        self.uniform_layout = self.uniform_layout.prop(prop.label(), attribute);
+
+        self
+    }
+
+    fn layout(&self) -> &[ComputeDataProp] {
+        &self.layout
+    }
+
+    fn uniform_layout(&self) -> &UniformLayout {
+        &self.uniform_layout
+    }
+}
@@ -0,0 +1,262 @@
+use gobs_render_hal::{
+    AlignMode, Attribute, AttributeData, BufferType, DRAW_INDEXED_INDIRECT_SIZE, Handle, RenderHAL,
+    UniformLayout,
+};
+
+use crate::RenderObject;
+
+/// Range of consecutive objects sharing pipeline, materials and index buffer,
+/// drawn with a single indirect call
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawGroup {
+    pub first: u32,
+    pub count: u32,
+}
+
+impl DrawGroup {
+    /// Split a sorted list into groups of consecutive items with the same key
+    pub fn split<T, K, F>(items: &[T], key: F) -> Vec<DrawGroup>
+    where
+        K: PartialEq,
+        F: Fn(&T) -> K,
+    {
+        let mut groups: Vec<DrawGroup> = vec![];
+        let mut last_key = None;
+
+        for (i, item) in items.iter().enumerate() {
+            let item_key = key(item);
+
+            match groups.last_mut() {
+                Some(group) if last_key.as_ref() == Some(&item_key) => group.count += 1,
+                _ => groups.push(DrawGroup {
+                    first: i as u32,
+                    count: 1,
+                }),
+            }
+
+            last_key = Some(item_key);
+        }
+
+        groups
+    }
+}
+
+/// Render list copied to the GPU for culling and indirect draws
+pub struct ObjectTable {
+    pub data: Vec<u8>,
+    pub groups: Vec<DrawGroup>,
+    pub object_count: usize,
+}
+
+impl ObjectTable {
+    /// Layout of an object entry, must match the `Object` struct of the shaders
+    pub fn object_layout() -> UniformLayout {
+        UniformLayout::new(AlignMode::Std430)
+            .prop("world_matrix", Attribute::Mat4F)
+            .prop("bounds_min", Attribute::Vec4F)
+            .prop("bounds_max", Attribute::Vec4F)
+            .prop("vertex_reference", Attribute::U64)
+            .prop("first_index", Attribute::U32)
+            .prop("index_count", Attribute::U32)
+            .prop("vertex_offset", Attribute::U32)
+            .prop("material_index", Attribute::U32)
+            .prop("group", Attribute::U32)
+            .prop("first_command", Attribute::U32)
+    }
+
+    /// The render list must be sorted so that objects of a group are consecutive
+    pub fn new(hal: &dyn RenderHAL, render_list: &[RenderObject]) -> Self {
+        let layout = Self::object_layout();
+        let groups = DrawGroup::split(render_list, |obj| {
+            (
+                obj.layer,
+                obj.render_flags,
+                obj.pipeline,
+                obj.material_data.as_ref().map(|bind| bind.id),
+                obj.material_textures.as_ref().map(|bind| bind.id),
+                obj.index_buffer,
+            )
+        });
+
+        let mut materials = vec![];
+        let mut data = Vec::with_capacity(render_list.len() * layout.size());
+
+        for (group_index, group) in groups.iter().enumerate() {
+            for render_object in
+                &render_list[group.first as usize..(group.first + group.count) as usize]
+            {
+                let material_id = render_object.material_data.as_ref().map(|bind| bind.id);
+                let material_index = match render_object.material_index {
+                    Some(index) => index as usize,
+                    None => match materials.iter().position(|id| *id == material_id) {
+                        Some(index) => index,
+                        None => {
+                            materials.push(material_id);
+                            materials.len() - 1
+                        }
+                    },
+                };
+
+                // w = 0: no bounds, the object is never culled
+                let (bounds_min, bounds_max) = match render_object.bounds {
+                    Some((min, max)) => (min.extend(1.), max.extend(1.)),
+                    None => Default::default(),
+                };
+
+                let props = [
+                    AttributeData::Mat4F(render_object.transform.matrix().to_cols_array_2d()),
+                    AttributeData::Vec4F(bounds_min.into()),
+                    AttributeData::Vec4F(bounds_max.into()),
+                    AttributeData::U64(hal.get_buffer_address(render_object.vertex_buffer)),
+                    AttributeData::U32(0),
+                    AttributeData::U32(render_object.index_len as u32),
+                    AttributeData::U32(0),
+                    AttributeData::U32(material_index as u32),
+                    AttributeData::U32(group_index as u32),
+                    AttributeData::U32(group.first),
+                ];
+
+                let data_start = data.len();
+                for (pos, prop) in props.into_iter().enumerate() {
+                    layout.copy_data(prop, pos, data_start, &mut data);
+                }
+            }
+        }
+
+        Self {
+            data,
+            groups,
+            object_count: render_list.len(),
+        }
+    }
+}
+
+/// Per frame buffers of the object table and of the indirect draw commands
+#[derive(Clone)]
+pub(crate) struct IndirectDraws {
+    pub objects: Handle,
+    pub commands: Handle,
+    pub counts: Handle,
+    pub groups: Vec<DrawGroup>,
+    pub object_count: u32,
+    object_capacity: usize,
+    group_capacity: usize,
+}
+
+impl IndirectDraws {
+    const MIN_CAPACITY: usize = 64;
+
+    fn new(hal: &mut dyn RenderHAL, object_count: usize, group_count: usize) -> Self {
+        let object_capacity = object_count.max(Self::MIN_CAPACITY).next_power_of_two();
+        let group_capacity = group_count.max(Self::MIN_CAPACITY).next_power_of_two();
+
+        let objects = hal.create_buffer(
+            "object table",
+            object_capacity * ObjectTable::object_layout().size(),
+            BufferType::HostStorage,
+        );
+        let commands = hal.create_buffer(
+            "draw commands",
+            object_capacity * DRAW_INDEXED_INDIRECT_SIZE,
+            BufferType::Indirect,
+        );
+        let counts = hal.create_buffer(
+            "draw counts",
+            group_capacity * std::mem::size_of::<u32>(),
+            BufferType::HostStorage,
+        );
+
+        Self {
+            objects,
+            commands,
+            counts,
+            groups: vec![],
+            object_count: 0,
+            object_capacity,
+            group_capacity,
+        }
+    }
+
+    /// Copy the object table and reset draw counts. Buffers must not be in use by the GPU
+    pub(crate) fn upload(
+        hal: &mut dyn RenderHAL,
+        draws: &mut Option<IndirectDraws>,
+        table: &ObjectTable,
+    ) {
+        if let Some(current) = draws
+            && (current.object_capacity < table.object_count
+                || current.group_capacity < table.groups.len())
+        {
+            draws.take().unwrap().destroy(hal);
+        }
+
+        let draws =
+            draws.get_or_insert_with(|| Self::new(hal, table.object_count, table.groups.len()));
+
+        hal.upload_buffer(draws.objects, &table.data, 0);
+        hal.upload_buffer(draws.counts, &vec![0; table.groups.len() * 4], 0);
+
+        draws.groups.clone_from(&table.groups);
+        draws.object_count = table.object_count as u32;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.groups.clear();
+        self.object_count = 0;
+    }
+
+    pub(crate) fn destroy(self, hal: &mut dyn RenderHAL) {
+        hal.destroy_buffer(self.objects);
+        hal.destroy_buffer(self.commands);
+        hal.destroy_buffer(self.counts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::data::object_table::{DrawGroup, ObjectTable};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_draw_groups() {
+        setup();
+
+        let items = [1, 1, 1, 2, 3, 3, 1];
+        let groups = DrawGroup::split(&items, |i| *i);
+
+        assert_eq!(
+            groups,
+            vec![
+                DrawGroup { first: 0, count: 3 },
+                DrawGroup { first: 3, count: 1 },
+                DrawGroup { first: 4, count: 2 },
+                DrawGroup { first: 6, count: 1 },
+            ]
+        );
+
+        assert!(DrawGroup::split(&[] as &[u32], |i| *i).is_empty());
+    }
+
+    #[test]
+    fn test_object_layout() {
+        setup();
+
+        let layout = ObjectTable::object_layout();
+
+        assert_eq!(layout.size(), 128);
+        assert_eq!(
+            layout.offsets(),
+            &[0, 64, 80, 96, 104, 108, 112, 116, 120, 124]
+        );
+    }
+}
@@ -14,6 +14,32 @@ pub enum SceneDataProp {
     LightAmbientColor,
 }
 
+impl SceneDataProp {
+    /// Name of the field in the shader scene data struct
+    pub fn label(self) -> &'static str {
+        match self {
+            SceneDataProp::CameraPosition => "camera_position",
+            SceneDataProp::CameraViewProj => "view_proj",
+            SceneDataProp::CameraViewPort => "screen_size",
+            SceneDataProp::LightDirection => "light_direction",
+            SceneDataProp::LightColor => "light_color",
+            SceneDataProp::LightAmbientColor => "ambient_color",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "camera_position" => Some(SceneDataProp::CameraPosition),
+            "view_proj" => Some(SceneDataProp::CameraViewProj),
+            "screen_size" => Some(SceneDataProp::CameraViewPort),
+            "light_direction" => Some(SceneDataProp::LightDirection),
+            "light_color" => Some(SceneDataProp::LightColor),
+            "ambient_color" => Some(SceneDataProp::LightAmbientColor),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct SceneDataLayout {
     layout: Vec<SceneDataProp>,
@@ -33,24 +59,15 @@ impl UniformData<SceneDataProp> for SceneDataLayout {
     fn prop(mut self, prop: SceneDataProp) -> Self {
         self.layout.push(prop);
 
-        self.uniform_layout = match prop {
-            SceneDataProp::CameraPosition => self
-                .uniform_layout
-                .prop("camera_position", Attribute::Vec3F),
-            SceneDataProp::CameraViewProj => {
-                self.uniform_layout.prop("view_proj", Attribute::Mat4F)
-            }
-            SceneDataProp::CameraViewPort => {
-                self.uniform_layout.prop("screen_size", Attribute::Vec2F)
-            }
-            SceneDataProp::LightDirection => self
-                .uniform_layout
-                .prop("light_direction", Attribute::Vec3F),
-            SceneDataProp::LightColor => self.uniform_layout.prop("light_color", Attribute::Vec4F),
-            SceneDataProp::LightAmbientColor => {
-                self.uniform_layout.prop("ambient_color", Attribute::Vec4F)
-            }
+        let attribute = match prop {
+            SceneDataProp::CameraPosition => Attribute::Vec3F,
+            SceneDataProp::CameraViewProj => Attribute::Mat4F,
+            SceneDataProp::CameraViewPort => Attribute::Vec2F,
+            SceneDataProp::LightDirection => Attribute::Vec3F,
+            SceneDataProp::LightColor => Attribute::Vec4F,
+            SceneDataProp::LightAmbientColor => Attribute::Vec4F,
         };
+        self.uniform_layout = self.uniform_layout.prop(prop.label(), attribute);
 
         self
     }
@@ -70,4 +87,6 @@ pub struct SceneData<'data> {
     pub light_transform: Option<&'data Transform>,
     pub light: Option<&'data Light>,
     pub extent: ImageExtent2D,
+    /// Elapsed time in seconds
+    pub time: f32,
 }
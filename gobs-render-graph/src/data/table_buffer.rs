@@ -0,0 +1,42 @@
+use gobs_render_hal::{BufferType, Handle, RenderHAL};
+
+/// Per frame storage buffer of a table written by the render batch, such as the
+/// constants of bindless materials or the joint matrices of skinned meshes
+pub(crate) struct TableBuffer {
+    pub buffer: Handle,
+    capacity: usize,
+}
+
+impl TableBuffer {
+    const MIN_CAPACITY: usize = 4096;
+
+    fn new(hal: &mut dyn RenderHAL, label: &str, size: usize) -> Self {
+        let capacity = size.max(Self::MIN_CAPACITY).next_power_of_two();
+
+        let buffer = hal.create_buffer(label, capacity, BufferType::HostStorage);
+
+        Self { buffer, capacity }
+    }
+
+    /// Copy the table. Buffer must not be in use by the GPU
+    pub(crate) fn upload(
+        hal: &mut dyn RenderHAL,
+        table: &mut Option<TableBuffer>,
+        label: &str,
+        data: &[u8],
+    ) {
+        if let Some(current) = table
+            && current.capacity < data.len()
+        {
+            table.take().unwrap().destroy(hal);
+        }
+
+        let table = table.get_or_insert_with(|| Self::new(hal, label, data.len()));
+
+        hal.upload_buffer(table.buffer, data, 0);
+    }
+
+    pub(crate) fn destroy(self, hal: &mut dyn RenderHAL) {
+        hal.destroy_buffer(self.buffer);
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use gobs_render_hal::RenderBackendError;
+
 use crate::job::RenderJobError;
 
 #[derive(Debug, Error)]
@@ -8,6 +10,10 @@ pub enum RenderError {
     Lost,
     #[error("swapchain updated")]
     Outdated,
+    #[error("device lost")]
+    DeviceLost,
+    #[error("GPU timeout")]
+    Timeout,
     #[error("pass not found")]
     PassNotFound,
     #[error("render job error")]
@@ -15,3 +21,13 @@ pub enum RenderError {
     #[error("invalid data")]
     InvalidData,
 }
+
+impl From<RenderBackendError> for RenderError {
+    fn from(err: RenderBackendError) -> Self {
+        match err {
+            RenderBackendError::DeviceLost => RenderError::DeviceLost,
+            RenderBackendError::Timeout => RenderError::Timeout,
+            _ => RenderError::Outdated,
+        }
+    }
+}
@@ -1,13 +1,29 @@
+use glam::Mat4;
+
 use gobs_core::logger;
-use gobs_render_hal::{CommandBuffer, CommandQueueType};
+use gobs_render_hal::{CommandBuffer, CommandQueueType, Handle, RenderHAL};
 
-use crate::GfxContext;
+use crate::{
+    GfxContext, RenderError,
+    data::{IndirectDraws, ObjectTable, TableBuffer},
+};
 
 pub struct FrameData {
     pub id: usize,
     pub frame_number: usize,
     pub frames_in_flight: usize,
     pub command: Box<dyn CommandBuffer>,
+    /// Command buffer of the async compute queue, if supported
+    pub compute_command: Option<Box<dyn CommandBuffer>>,
+    compute_recording: bool,
+    compute_async: bool,
+    indirect: Option<IndirectDraws>,
+    materials: Option<TableBuffer>,
+    material_count: usize,
+    joints: Option<TableBuffer>,
+    joint_count: usize,
+    morph_weights: Option<TableBuffer>,
+    morph_weight_count: usize,
 }
 
 impl FrameData {
@@ -16,20 +32,190 @@ impl FrameData {
             .hal_mut()
             .create_command_buffer("Frame", CommandQueueType::Graphics);
 
+        let compute_command = ctx.hal().async_compute().then(|| {
+            ctx.hal_mut()
+                .create_command_buffer("Frame compute", CommandQueueType::Compute)
+        });
+
         FrameData {
             id,
             frame_number: 0,
             frames_in_flight,
             command,
+            compute_command,
+            compute_recording: false,
+            compute_async: false,
+            indirect: None,
+            materials: None,
+            material_count: 0,
+            joints: None,
+            joint_count: 0,
+            morph_weights: None,
+            morph_weight_count: 0,
         }
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    pub fn wait(&mut self, frame_number: usize) {
+    /// The frame resources cannot be reused if the wait fails
+    pub fn wait(&mut self, hal: &dyn RenderHAL, frame_number: usize) -> Result<(), RenderError> {
         tracing::debug!(target: logger::RENDER, "Wait for frame in flight: {} / {}", self.id + 1, self.frames_in_flight);
 
         self.frame_number = frame_number;
 
-        self.command.wait();
+        self.command.wait(hal)?;
+
+        if let Some(compute_command) = &self.compute_command {
+            compute_command.wait(hal)?;
+        }
+
+        Ok(())
+    }
+
+    /// Upload the object table of the frame for GPU culling. Passes fall back to
+    /// CPU draws when there is no table
+    pub fn upload_objects(&mut self, ctx: &mut GfxContext, table: Option<&ObjectTable>) {
+        match table {
+            Some(table) if table.object_count > 0 => {
+                tracing::debug!(target: logger::RENDER, "Upload object table ({} objects, {} groups)", table.object_count, table.groups.len());
+                IndirectDraws::upload(ctx.hal_mut(), &mut self.indirect, table);
+            }
+            _ => {
+                if let Some(indirect) = &mut self.indirect {
+                    indirect.clear();
+                }
+            }
+        }
+    }
+
+    /// Indirect draw buffers, if an object table has been uploaded for this frame
+    pub(crate) fn indirect(&self) -> Option<&IndirectDraws> {
+        self.indirect
+            .as_ref()
+            .filter(|indirect| indirect.object_count > 0)
+    }
+
+    /// Upload the constants of the bindless materials of the frame
+    pub fn upload_materials(&mut self, ctx: &mut GfxContext, data: &[u8], material_count: usize) {
+        self.material_count = material_count;
+
+        if material_count > 0 {
+            tracing::debug!(target: logger::RENDER, "Upload material table ({} materials)", material_count);
+            TableBuffer::upload(ctx.hal_mut(), &mut self.materials, "material table", data);
+        }
+    }
+
+    /// Material table buffer, if bindless materials have been uploaded for this frame
+    pub(crate) fn material_buffer(&self) -> Option<Handle> {
+        self.materials
+            .as_ref()
+            .filter(|_| self.material_count > 0)
+            .map(|materials| materials.buffer)
+    }
+
+    /// Upload the joint matrices of the skinned meshes of the frame
+    pub fn upload_joints(&mut self, ctx: &mut GfxContext, joints: &[Mat4]) {
+        self.joint_count = joints.len();
+
+        if !joints.is_empty() {
+            tracing::debug!(target: logger::RENDER, "Upload joint table ({} joints)", joints.len());
+            TableBuffer::upload(
+                ctx.hal_mut(),
+                &mut self.joints,
+                "joint table",
+                bytemuck::cast_slice(joints),
+            );
+        }
+    }
+
+    /// Joint table buffer, if joint matrices have been uploaded for this frame
+    pub(crate) fn joint_buffer(&self) -> Option<Handle> {
+        self.joints
+            .as_ref()
+            .filter(|_| self.joint_count > 0)
+            .map(|joints| joints.buffer)
+    }
+
+    /// Upload the morph target weights of the morphed meshes of the frame
+    pub fn upload_morph_weights(&mut self, ctx: &mut GfxContext, weights: &[f32]) {
+        self.morph_weight_count = weights.len();
+
+        if !weights.is_empty() {
+            tracing::debug!(target: logger::RENDER, "Upload morph weight table ({} weights)", weights.len());
+            TableBuffer::upload(
+                ctx.hal_mut(),
+                &mut self.morph_weights,
+                "morph weight table",
+                bytemuck::cast_slice(weights),
+            );
+        }
+    }
+
+    /// Morph weight table buffer, if weights have been uploaded for this frame
+    pub(crate) fn morph_weight_buffer(&self) -> Option<Handle> {
+        self.morph_weights
+            .as_ref()
+            .filter(|_| self.morph_weight_count > 0)
+            .map(|weights| weights.buffer)
+    }
+
+    /// Command buffer of the current compute pass: the async compute one when
+    /// the pass has been scheduled on the compute queue, the frame one otherwise
+    pub fn compute_command(&mut self) -> &mut dyn CommandBuffer {
+        match &mut self.compute_command {
+            Some(compute_command) if self.compute_async => compute_command.as_mut(),
+            _ => self.command.as_mut(),
+        }
+    }
+
+    /// Schedule the next compute pass on the async compute queue
+    pub(crate) fn begin_async_compute(&mut self, ctx: &mut GfxContext) -> bool {
+        let Some(compute_command) = &mut self.compute_command else {
+            return false;
+        };
+
+        if !self.compute_recording {
+            tracing::debug!(target: logger::SYNC, "Begin async compute");
+
+            compute_command.reset();
+            compute_command.begin(self.frame_number);
+            compute_command.begin_label(&format!("Compute {}", self.frame_number));
+
+            // graph resources may still be in use by the previous frame
+            let graphics_value = ctx.hal().queue_value(CommandQueueType::Graphics);
+            compute_command.wait_queue(ctx.hal(), CommandQueueType::Graphics, graphics_value);
+
+            self.compute_recording = true;
+        }
+
+        self.compute_async = true;
+
+        true
+    }
+
+    pub(crate) fn end_async_compute(&mut self) {
+        self.compute_async = false;
+    }
+
+    /// Submit recorded async compute work. The frame command buffer waits for its completion
+    pub(crate) fn submit_async_compute(&mut self, ctx: &mut GfxContext) -> Result<(), RenderError> {
+        if !self.compute_recording {
+            return Ok(());
+        }
+
+        if let Some(compute_command) = &mut self.compute_command {
+            tracing::debug!(target: logger::SYNC, "Submit async compute");
+
+            compute_command.end_label();
+            compute_command.end();
+
+            let value = compute_command.submit(ctx.hal_mut())?;
+
+            self.command
+                .wait_queue(ctx.hal(), CommandQueueType::Compute, value);
+        }
+
+        self.compute_recording = false;
+
+        Ok(())
     }
 }
@@ -3,5 +3,5 @@ mod graph_loader;
 mod resource;
 
 pub use frame::FrameGraph;
-pub use graph_loader::GraphConfig;
+pub use graph_loader::{GraphConfig, PassPipeline};
 pub use resource::GraphResourceManager;
@@ -1,9 +1,9 @@
 use crate::{
-    FrameData, GfxContext, GraphConfig, RenderError, RenderObject, RenderPass, data::SceneData,
-    graph::resource::GraphResourceManager, pass::Attachment,
+    FrameData, GfxContext, GraphConfig, PassPipeline, RenderError, RenderObject, RenderPass,
+    data::SceneData, graph::resource::GraphResourceManager, pass::Attachment,
 };
-use gobs_core::logger;
-use gobs_render_hal::{Handle, ImageLayout};
+use gobs_core::{ImageExtent2D, logger};
+use gobs_render_hal::{BufferType, CommandQueueType, Handle, ImageLayout};
 
 pub struct FrameGraphPass {
     pub pass: RenderPass,
@@ -34,7 +34,7 @@ impl FrameGraph {
         pipeline_resolver: F,
     ) -> Result<Self, RenderError>
     where
-        F: FnMut(&str, &mut GfxContext) -> Option<Handle>,
+        F: FnMut(&str, &mut GfxContext) -> Option<PassPipeline>,
     {
         tracing::debug!(target: logger::INIT, "Load graph: {}", graph_name);
         GraphConfig::load_graph(ctx, graph_filename, graph_name, pipeline_resolver)
@@ -62,6 +62,21 @@ impl FrameGraph {
         );
     }
 
+    pub fn register_buffer(
+        &mut self,
+        ctx: &mut GfxContext,
+        label: &str,
+        size: usize,
+        ty: BufferType,
+    ) {
+        self.resource_manager.register_buffer(ctx, label, size, ty);
+    }
+
+    /// Graph buffer by name, to be filled by the application
+    pub fn buffer(&self, label: &str) -> Option<Handle> {
+        self.resource_manager.buffers.get(label).copied()
+    }
+
     pub fn get_pass<F>(&self, cmp: F) -> Result<RenderPass, RenderError>
     where
         F: Fn(&RenderPass) -> bool,
@@ -126,6 +141,66 @@ impl FrameGraph {
         self.get_pass(|pass| pass.name() == pass_name)
     }
 
+    /// Id written in the "pick" attachment at the window position, None if the
+    /// "pick" pass is not enabled. 0 if no object was drawn at this position
+    pub fn pick(&self, ctx: &mut GfxContext, x: f32, y: f32) -> Option<u32> {
+        // attachments are registered for all the graphs of the configuration
+        let picking = self
+            .passes
+            .iter()
+            .any(|pass| pass.enabled && pass.pass.name() == "pick");
+        if !picking || !self.resource_manager.resources.contains_key("pick") {
+            return None;
+        }
+
+        let image = self.resource_manager.image("pick");
+        let extent = ctx.hal().get_image_extent(image);
+        let (width, height): (f32, f32) = ctx.extent().into();
+
+        if x < 0. || y < 0. || x >= width || y >= height {
+            return Some(0);
+        }
+
+        // attachments may be scaled relative to the window
+        let x = ((x * extent.width as f32 / width) as u32).min(extent.width - 1);
+        let y = ((y * extent.height as f32 / height) as u32).min(extent.height - 1);
+
+        let hal = ctx.hal_mut();
+        hal.wait();
+
+        let buffer = hal.create_buffer("pick", std::mem::size_of::<u32>(), BufferType::StagingDst);
+        let mut cmd = hal.create_command_buffer("Pick", CommandQueueType::Graphics);
+
+        cmd.begin(0);
+        cmd.begin_label("Pick");
+        cmd.transition_image_layout(hal, image, ImageLayout::TransferSrc);
+        cmd.copy_image_region_to_buffer(hal, image, buffer, 0, x, y, ImageExtent2D::new(1, 1));
+        cmd.transition_image_layout(hal, image, ImageLayout::Color);
+        cmd.end_label();
+        cmd.end();
+
+        let id = match cmd.submit(hal).and_then(|_| cmd.wait(hal)) {
+            Ok(_) => {
+                let mut data = Vec::new();
+                hal.download_buffer(buffer, &mut data);
+
+                data.first_chunk()
+                    .map(|bytes| u32::from_ne_bytes(*bytes))
+                    .unwrap_or(0)
+            }
+            Err(err) => {
+                tracing::error!(target: logger::RENDER, "Cannot submit pick command: {}", err);
+                0
+            }
+        };
+
+        hal.destroy_buffer(buffer);
+
+        tracing::debug!(target: logger::RENDER, "Pick ({}, {}): {}", x, y, id);
+
+        Some(id)
+    }
+
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
     pub fn begin(
         &mut self,
@@ -146,9 +221,7 @@ impl FrameGraph {
             );
         }
 
-        if ctx.hal_mut().acquire(frame.id).is_err() {
-            return Err(RenderError::Outdated);
-        }
+        ctx.hal_mut().acquire(frame.id)?;
 
         cmd.reset();
 
@@ -158,6 +231,9 @@ impl FrameGraph {
 
         cmd.begin_label(&format!("Frame {}", frame.frame_number));
 
+        // take ownership of resources uploaded on the transfer queue
+        cmd.acquire_resources(ctx.hal_mut());
+
         //TODO: cmd.reset_query_pool(&frame.query_pool, 0, 2);
         //TODO: cmd.write_timestamp(&frame.query_pool, PipelineStage::TopOfPipe, 0);
 
@@ -181,12 +257,12 @@ impl FrameGraph {
 
         cmd.end();
 
-        cmd.submit_graphics(ctx.hal(), frame_id);
+        cmd.submit_graphics(ctx.hal(), frame_id)?;
 
-        let Ok(_) = ctx.hal_mut().present() else {
-            tracing::debug!(target: logger::SYNC, "Exit frame: outdated");
-            return Err(RenderError::Outdated);
-        };
+        if let Err(err) = ctx.hal_mut().present() {
+            tracing::debug!(target: logger::SYNC, "Exit frame: {}", err);
+            return Err(err.into());
+        }
 
         tracing::debug!(target: logger::SYNC, "End frame");
 
@@ -203,6 +279,9 @@ impl FrameGraph {
         render_list: &[RenderObject],
         scene_data: &SceneData,
     ) -> Result<(), RenderError> {
+        // compute passes before the first graphics pass run on the async compute queue
+        let mut graphics_started = false;
+
         for pass in &mut self.passes {
             if !pass.enabled {
                 tracing::debug!(target: logger::RENDER,
@@ -212,6 +291,14 @@ impl FrameGraph {
 
             let pass = &pass.pass;
 
+            let async_compute = !graphics_started
+                && pass.queue() == CommandQueueType::Compute
+                && frame.begin_async_compute(ctx);
+            if !async_compute && !graphics_started {
+                graphics_started = true;
+                frame.submit_async_compute(ctx)?;
+            }
+
             tracing::debug!(target: logger::SYNC, "Begin render pass {}", pass.name());
 
             let span =
@@ -220,7 +307,9 @@ impl FrameGraph {
 
             tracing::debug!(target: logger::RENDER, ">>> Begin rendering pass {}", pass.name());
 
-            pass.render(ctx, frame, &self.resource_manager, render_list, scene_data)?;
+            let res = pass.render(ctx, frame, &self.resource_manager, render_list, scene_data);
+            frame.end_async_compute();
+            res?;
 
             tracing::debug!(target: logger::RENDER, "<<< End rendering pass {}", pass.name());
             span.exit();
@@ -228,6 +317,8 @@ impl FrameGraph {
             tracing::debug!(target: logger::SYNC, "End render pass {}", pass.name());
         }
 
+        frame.submit_async_compute(ctx)?;
+
         Ok(())
     }
 
@@ -241,6 +332,13 @@ impl FrameGraph {
         ctx.hal_mut().resize();
     }
 
+    /// Release graph attachments and buffers. Frames using the graph must be completed.
+    pub fn destroy(&mut self, ctx: &mut GfxContext) {
+        tracing::debug!(target: logger::MEMORY, "Destroy graph attachments");
+
+        self.resource_manager.destroy(ctx.hal_mut());
+    }
+
     pub fn enable_pass(&mut self, name: &str, enabled: bool) {
         for pass in &mut self.passes {
             if pass.pass.name() == name {
@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 use serde::{Deserialize, Serialize};
 
 use gobs_core::{ImageExtent2D, ImageFormat, logger};
-use gobs_render_hal::{AlignMode, Handle, ImageLayout, ImageUsage, UniformData as _};
+use gobs_render_hal::{AlignMode, BufferType, Handle, ImageLayout, ImageUsage, UniformData as _};
 use gobs_resource::{
     ResourceError,
     load::{self, AssetType},
@@ -11,10 +11,12 @@ use gobs_resource::{
 
 use crate::{
     FrameGraph, GfxContext, RenderFlags,
-    data::{SceneDataLayout, SceneDataProp},
+    data::{ComputeDataLayout, ComputeDataProp, SceneDataLayout, SceneDataProp},
     pass::{
         Attachment, AttachmentAccess, AttachmentType, RenderPass, RenderPassType,
-        compute::ComputePass, material::MaterialPass, present::PresentPass,
+        compute::{ComputePass, DEFAULT_WORKGROUP_SIZE, Dispatch, DispatchSize},
+        material::MaterialPass,
+        present::PresentPass,
     },
 };
 
@@ -22,17 +24,31 @@ use crate::{
 const FRAME_WIDTH: u32 = 1920;
 const FRAME_HEIGHT: u32 = 1080;
 
+/// Pipeline resolved by name for a pass
+#[derive(Clone, Debug)]
+pub struct PassPipeline {
+    pub handle: Handle,
+    /// Push constants of compute pipelines
+    pub push_layout: Option<ComputeDataLayout>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GraphConfig {
     graphes: HashMap<String, Vec<String>>,
     passes: HashMap<String, RenderPassConfig>,
     attachments: HashMap<String, ImageAttachmentInfo>,
+    #[serde(default)]
+    buffers: HashMap<String, BufferInfo>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_workgroup_size() -> [u32; 3] {
+    DEFAULT_WORKGROUP_SIZE
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct RenderPassConfig {
     ty: RenderPassType,
@@ -46,6 +62,25 @@ struct RenderPassConfig {
     #[serde(default)]
     flags: RenderFlags,
     target: Option<String>,
+    #[serde(default)]
+    dispatch: Vec<DispatchConfig>,
+    #[serde(default)]
+    buffers: Vec<String>,
+    /// Taken from the pipeline if empty, must match the pipeline otherwise
+    #[serde(default)]
+    push_constants: Vec<ComputeDataProp>,
+    /// Material passes draw with the commands of the culling pass
+    #[serde(default)]
+    indirect: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct DispatchConfig {
+    /// Defaults to the pass pipeline
+    pipeline: Option<String>,
+    #[serde(default = "default_workgroup_size")]
+    workgroup_size: [u32; 3],
+    size: DispatchSize,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -69,6 +104,18 @@ struct ImageAttachmentInfo {
     format: ImageFormat,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+enum BufferUsage {
+    Storage,
+    Uniform,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct BufferInfo {
+    usage: BufferUsage,
+    size: usize,
+}
+
 impl GraphConfig {
     fn load_with_data(data: &str) -> Result<Self, ResourceError> {
         let options = ron::options::Options::default()
@@ -87,7 +134,7 @@ impl GraphConfig {
         pipeline_resolver: F,
     ) -> Result<FrameGraph, ResourceError>
     where
-        F: FnMut(&str, &mut GfxContext) -> Option<Handle>,
+        F: FnMut(&str, &mut GfxContext) -> Option<PassPipeline>,
     {
         let data = load::load_string_sync(filename, AssetType::RESOURCES)?;
 
@@ -101,10 +148,14 @@ impl GraphConfig {
         mut pipeline_resolver: F,
     ) -> Result<FrameGraph, ResourceError>
     where
-        F: FnMut(&str, &mut GfxContext) -> Option<Handle>,
+        F: FnMut(&str, &mut GfxContext) -> Option<PassPipeline>,
     {
         let graph_config = Self::load_with_data(data)?;
 
+        if !graph_config.validate_indirect(name) {
+            return Err(ResourceError::InvalidData);
+        }
+
         let mut graph = FrameGraph::new();
 
         // TODO: only register attachments used by passes
@@ -115,13 +166,25 @@ impl GraphConfig {
             graph.register_attachment(ctx, attach_name, attachment);
         }
 
+        for (buffer_name, buffer_config) in &graph_config.buffers {
+            let ty = match buffer_config.usage {
+                BufferUsage::Storage => BufferType::Storage,
+                BufferUsage::Uniform => BufferType::Uniform,
+            };
+
+            graph.register_buffer(ctx, buffer_name, buffer_config.size, ty);
+        }
+
         tracing::debug!(target: logger::INIT, "Load graph: {}", "scene");
 
         for passname in &graph_config.graphes[name] {
             tracing::debug!(target: logger::INIT, "Load pass: {}", passname);
 
             let pass = Self::load_pass(ctx, &graph_config, passname, &mut pipeline_resolver)
-                .unwrap_or_else(|| panic!("Failed to load pass {}", passname));
+                .ok_or_else(|| {
+                    tracing::error!(target: logger::INIT, "Failed to load pass {}", passname);
+                    ResourceError::InvalidData
+                })?;
 
             let enabled = graph_config.passes.get(passname).is_some_and(|p| p.enabled);
 
@@ -131,6 +194,34 @@ impl GraphConfig {
         Ok(graph)
     }
 
+    /// Indirect material passes draw with the commands written by a culling pass, a compute
+    /// pass dispatched over the objects, that must run before them
+    fn validate_indirect(&self, name: &str) -> bool {
+        let mut culled = false;
+
+        for passname in self.graphes.get(name).into_iter().flatten() {
+            let Some(pass) = self.passes.get(passname).filter(|pass| pass.enabled) else {
+                continue;
+            };
+
+            match pass.ty {
+                RenderPassType::Compute => {
+                    culled |= pass
+                        .dispatch
+                        .iter()
+                        .any(|dispatch| matches!(dispatch.size, DispatchSize::Objects));
+                }
+                RenderPassType::Material if pass.indirect && !culled => {
+                    tracing::error!(target: logger::INIT, "Indirect pass {} has no culling pass before it", passname);
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
+        true
+    }
+
     pub fn load_pass<F>(
         ctx: &mut GfxContext,
         graph: &GraphConfig,
@@ -138,7 +229,7 @@ impl GraphConfig {
         mut pipeline_resolver: F,
     ) -> Option<Arc<dyn RenderPass>>
     where
-        F: FnMut(&str, &mut GfxContext) -> Option<Handle>,
+        F: FnMut(&str, &mut GfxContext) -> Option<PassPipeline>,
     {
         tracing::info!(target: logger::INIT, "Load pass: {}", passname);
 
@@ -150,32 +241,104 @@ impl GraphConfig {
 
         match pass.ty {
             RenderPassType::Compute => {
-                Self::load_compute_pass(ctx, passname, pass, graph, pipeline?)
-            }
-            RenderPassType::Material => {
-                Self::load_material_pass(ctx, passname, pass, graph, pipeline)
+                Self::load_compute_pass(ctx, passname, pass, graph, pipeline?, pipeline_resolver)
             }
+            RenderPassType::Material => Self::load_material_pass(
+                ctx,
+                passname,
+                pass,
+                graph,
+                pipeline.map(|pipeline| pipeline.handle),
+            ),
             RenderPassType::Present => Self::load_present_pass(ctx, passname, pass),
         }
     }
 
-    fn load_compute_pass(
+    fn load_compute_pass<F>(
         ctx: &mut GfxContext,
         passname: &str,
         pass: &RenderPassConfig,
         graph: &GraphConfig,
-        pipeline: Handle,
-    ) -> Option<Arc<dyn RenderPass>> {
-        let mut compute_pass = ComputePass::new(passname, pipeline);
+        pipeline: PassPipeline,
+        mut pipeline_resolver: F,
+    ) -> Option<Arc<dyn RenderPass>>
+    where
+        F: FnMut(&str, &mut GfxContext) -> Option<PassPipeline>,
+    {
+        let push_layout = Self::push_layout(passname, pass, &pipeline)?;
+
+        let mut compute_pass = ComputePass::new(passname, pipeline.handle, push_layout.clone());
 
         for (attach_name, attach_config) in &pass.attachments {
             let attachment = Self::load_attachment_usage(ctx, graph, attach_name, attach_config)?;
             compute_pass.add_attachment(attach_name, attachment);
         }
 
+        for buffer_name in &pass.buffers {
+            if !graph.buffers.contains_key(buffer_name) {
+                tracing::error!(target: logger::INIT, "Missing graph buffer: {}", buffer_name);
+                return None;
+            }
+            compute_pass.add_buffer(buffer_name);
+        }
+
+        for dispatch in &pass.dispatch {
+            let dispatch_pipeline = match &dispatch.pipeline {
+                Some(name) => {
+                    let dispatch_pipeline = pipeline_resolver(name, ctx)?;
+                    // the pass pushes the same constants to all its dispatches
+                    if dispatch_pipeline
+                        .push_layout
+                        .is_some_and(|layout| layout != push_layout)
+                    {
+                        tracing::error!(target: logger::INIT, "Push constants of pipeline {} do not match pass {}", name, passname);
+                        return None;
+                    }
+                    dispatch_pipeline.handle
+                }
+                None => pipeline.handle,
+            };
+
+            if let DispatchSize::Attachment(name) = &dispatch.size
+                && !pass.attachments.contains_key(name)
+            {
+                tracing::error!(target: logger::INIT, "Missing dispatch attachment: {}", name);
+                return None;
+            }
+
+            compute_pass.add_dispatch(Dispatch::new(
+                dispatch_pipeline,
+                dispatch.workgroup_size,
+                dispatch.size.clone(),
+            ));
+        }
+
         Some(Arc::new(compute_pass))
     }
 
+    /// Push constants declared by the pass must be the same as the pipeline ones
+    fn push_layout(
+        passname: &str,
+        pass: &RenderPassConfig,
+        pipeline: &PassPipeline,
+    ) -> Option<ComputeDataLayout> {
+        let mut push_layout = ComputeDataLayout::new(AlignMode::Std430);
+        for prop in &pass.push_constants {
+            push_layout = push_layout.prop(*prop);
+        }
+
+        match &pipeline.push_layout {
+            Some(pipeline_layout) if pass.push_constants.is_empty() => {
+                Some(pipeline_layout.clone())
+            }
+            Some(pipeline_layout) if *pipeline_layout != push_layout => {
+                tracing::error!(target: logger::INIT, "Push constants of pass {} do not match its pipeline", passname);
+                None
+            }
+            _ => Some(push_layout),
+        }
+    }
+
     fn load_present_pass(
         ctx: &mut GfxContext,
         passname: &str,
@@ -207,6 +370,8 @@ impl GraphConfig {
             material_pass.set_fixed_pipeline(pipeline);
         }
 
+        material_pass.set_indirect(pass.indirect);
+
         for (attach_name, attach_config) in &pass.attachments {
             let attachment = Self::load_attachment_usage(ctx, graph, attach_name, attach_config)?;
 
@@ -284,11 +449,13 @@ mod tests {
     use std::collections::HashMap;
 
     use gobs_core::GobsConfig;
+    use gobs_render_hal::{AlignMode, Handle, UniformData as _};
     use tracing::Level;
     use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
 
     use crate::{
-        GfxContext, GraphConfig, RenderFlags,
+        GfxContext, GraphConfig, PassPipeline, RenderFlags,
+        data::{ComputeDataLayout, ComputeDataProp},
         graph::graph_loader::{AttachmentInfo, RenderPassConfig},
         pass::{AttachmentAccess, RenderPassType},
     };
@@ -361,9 +528,14 @@ mod tests {
                     enabled: true,
                     flags: RenderFlags::ENTITY,
                     target: None,
+                    dispatch: Vec::new(),
+                    buffers: Vec::new(),
+                    push_constants: Vec::new(),
+                    indirect: false,
                 },
             )]),
             attachments: HashMap::new(),
+            buffers: HashMap::new(),
         };
 
         let ron = ron::ser::to_string_pretty(&graph, ron::ser::PrettyConfig::default()).unwrap();
@@ -371,6 +543,81 @@ mod tests {
         tracing::info!("Load data: {}", ron);
     }
 
+    #[test]
+    fn test_push_layout() {
+        setup();
+
+        let graph = GraphConfig::load_with_data(
+            r#"GraphConfig(
+                graphes: {},
+                attachments: {},
+                passes: {
+                    "inherit": (ty: Compute, pipeline: "cull"),
+                    "same": (ty: Compute, pipeline: "cull", push_constants: [ObjectBuffer, ObjectCount]),
+                    "other": (ty: Compute, pipeline: "cull", push_constants: [ObjectCount]),
+                },
+            )"#,
+        )
+        .unwrap();
+
+        let layout = ComputeDataLayout::new(AlignMode::Std430)
+            .prop(ComputeDataProp::ObjectBuffer)
+            .prop(ComputeDataProp::ObjectCount);
+        let pipeline = PassPipeline {
+            handle: Handle::default(),
+            push_layout: Some(layout.clone()),
+        };
+
+        let push_layout =
+            |pass: &str, pipeline| GraphConfig::push_layout(pass, &graph.passes[pass], pipeline);
+
+        assert_eq!(push_layout("inherit", &pipeline), Some(layout.clone()));
+        assert_eq!(push_layout("same", &pipeline), Some(layout.clone()));
+        assert_eq!(push_layout("other", &pipeline), None);
+
+        // pipelines without compute layout
+        let pipeline = PassPipeline {
+            handle: Handle::default(),
+            push_layout: None,
+        };
+        assert_eq!(push_layout("same", &pipeline), Some(layout));
+    }
+
+    #[test]
+    fn test_validate_indirect() {
+        setup();
+
+        let graph = GraphConfig::load_with_data(
+            r#"GraphConfig(
+                graphes: {
+                    "culled": ["cull", "draw"],
+                    "unculled": ["draw"],
+                    "late": ["draw", "cull"],
+                    "disabled": ["cull_disabled", "draw"],
+                },
+                attachments: {},
+                passes: {
+                    "cull": (ty: Compute, pipeline: "cull", dispatch: [(size: Objects)]),
+                    "cull_disabled": (ty: Compute, pipeline: "cull", dispatch: [(size: Objects)], enabled: false),
+                    "draw": (ty: Material, indirect: true),
+                },
+            )"#,
+        )
+        .unwrap();
+
+        assert!(graph.validate_indirect("culled"));
+        assert!(!graph.validate_indirect("unculled"));
+        assert!(!graph.validate_indirect("late"));
+        assert!(!graph.validate_indirect("disabled"));
+
+        let graph =
+            GraphConfig::load_with_data(include_str!("../../../examples/resources/graph.ron"))
+                .unwrap();
+        for name in graph.graphes.keys() {
+            assert!(graph.validate_indirect(name), "{}", name);
+        }
+    }
+
     #[test]
     fn test_deserialize() {
         setup();
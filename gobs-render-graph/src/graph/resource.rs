@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use gobs_render_hal::{Handle, ImageUsage, RenderHAL};
+use gobs_render_hal::{BufferType, Handle, ImageUsage, RenderHAL};
 
 use gobs_core::{ImageExtent2D, ImageFormat};
 
@@ -8,12 +8,14 @@ use crate::GfxContext;
 
 pub struct GraphResourceManager {
     pub resources: HashMap<String, Handle>,
+    pub buffers: HashMap<String, Handle>,
 }
 
 impl GraphResourceManager {
     pub fn new() -> Self {
         Self {
             resources: HashMap::new(),
+            buffers: HashMap::new(),
         }
     }
 
@@ -30,12 +32,34 @@ impl GraphResourceManager {
         self.resources.insert(label.to_string(), image);
     }
 
+    pub fn register_buffer(
+        &mut self,
+        ctx: &mut GfxContext,
+        label: &str,
+        size: usize,
+        ty: BufferType,
+    ) {
+        let buffer = ctx.hal_mut().create_buffer(label, size, ty);
+
+        self.buffers.insert(label.to_string(), buffer);
+    }
+
     pub fn invalidate(&self, hal: &mut dyn RenderHAL) {
         for image in self.resources.values() {
             hal.invalidate_image(*image);
         }
     }
 
+    pub fn destroy(&mut self, hal: &mut dyn RenderHAL) {
+        for (_, image) in self.resources.drain() {
+            hal.destroy_image(image);
+        }
+
+        for (_, buffer) in self.buffers.drain() {
+            hal.destroy_buffer(buffer);
+        }
+    }
+
     pub fn image(&self, label: &str) -> Handle {
         assert!(
             self.resources.contains_key(label),
@@ -44,6 +68,12 @@ impl GraphResourceManager {
 
         self.resources[label]
     }
+
+    pub fn buffer(&self, label: &str) -> Handle {
+        assert!(self.buffers.contains_key(label), "Missing buffer {label}",);
+
+        self.buffers[label]
+    }
 }
 
 impl Default for GraphResourceManager {
@@ -1,3 +1,4 @@
+use glam::Mat4;
 use thiserror::Error;
 
 use gobs_core::{
@@ -5,11 +6,12 @@ use gobs_core::{
     logger,
 };
 use gobs_render_hal::{
-    AttributeData, BindingGroupLayout, BindingGroupType, BindingId, DescriptorStage,
-    DescriptorType, Handle, ObjectDataProp, UniformBuffer, UniformData as _, UniformLayout,
+    AttributeData, BindingGroupLayout, BindingGroupType, BindingId, DRAW_INDEXED_INDIRECT_SIZE,
+    DescriptorStage, DescriptorType, Handle, ObjectDataProp, UniformBuffer, UniformData as _,
+    UniformLayout,
 };
 
-use crate::{FrameData, GfxContext, RenderFlags, RenderObject};
+use crate::{FrameData, GfxContext, RenderFlags, RenderObject, data::IndirectDraws};
 
 #[derive(Debug, Error)]
 pub enum RenderJobError {
@@ -23,17 +25,38 @@ struct RenderJobState {
     last_material_data: Option<BindingId>,
     last_material_textures: Option<BindingId>,
     scene_data_bound: bool,
+    bindless_bound: bool,
+    material_buffer: u64,
+    joint_buffer: u64,
+    morph_weight_buffer: u64,
     object_data: FixedBuffer<128>,
 }
 
 impl RenderJobState {
-    pub fn new() -> Self {
+    pub fn new(ctx: &GfxContext, frame: &FrameData) -> Self {
+        let material_buffer = frame
+            .material_buffer()
+            .map(|buffer| ctx.hal().get_buffer_address(buffer))
+            .unwrap_or_default();
+        let joint_buffer = frame
+            .joint_buffer()
+            .map(|buffer| ctx.hal().get_buffer_address(buffer))
+            .unwrap_or_default();
+        let morph_weight_buffer = frame
+            .morph_weight_buffer()
+            .map(|buffer| ctx.hal().get_buffer_address(buffer))
+            .unwrap_or_default();
+
         Self {
             last_pipeline: None,
             last_index_buffer: None,
             last_material_data: None,
             last_material_textures: None,
             scene_data_bound: false,
+            bindless_bound: false,
+            material_buffer,
+            joint_buffer,
+            morph_weight_buffer,
             object_data: FixedBuffer::new(),
         }
     }
@@ -54,12 +77,11 @@ impl RenderJob {
         render_flags: RenderFlags,
     ) -> Self {
         let label = format!("Scene data {}", pass_name);
-        let uniform_bindgroup =
-            BindingGroupLayout::new(BindingGroupType::SceneData).add_binding(
-                DescriptorType::Uniform,
-                DescriptorStage::All,
-                1,
-            );
+        let uniform_bindgroup = BindingGroupLayout::new(BindingGroupType::SceneData).add_binding(
+            DescriptorType::Uniform,
+            DescriptorStage::All,
+            1,
+        );
         let uniform_buffer =
             UniformBuffer::new(&label, ctx.hal_mut(), uniform_bindgroup, scene_data_layout);
 
@@ -104,7 +126,7 @@ impl RenderJob {
         frame: &mut FrameData,
         render_list: &[RenderObject],
     ) -> Result<(), RenderJobError> {
-        let mut state = RenderJobState::new();
+        let mut state = RenderJobState::new(ctx, frame);
 
         for render_object in render_list {
             if !self.should_render(render_object) {
@@ -121,11 +143,11 @@ impl RenderJob {
             // bind camera and lights (push, set=0)
             self.bind_scene_data(ctx, frame, pipeline, &mut state)?;
 
-            // bind materials (ds, set 1=material, 2=textures)
+            // bind materials (ds, set 1=material, 2=textures or bindless textures)
             self.bind_material_data(ctx, frame, render_object, pipeline, &mut state)?;
 
             // push constants + index buffer
-            self.bind_object_data(ctx, frame, render_object, pipeline, &mut state)?;
+            self.bind_object_data(ctx, frame, render_object, pipeline, 0, &mut state)?;
 
             tracing::trace!(target: logger::RENDER, "Draw object ({})", render_object.index_len);
             frame.command.draw_indexed(render_object.index_len, 1);
@@ -134,6 +156,75 @@ impl RenderJob {
         Ok(())
     }
 
+    /// Draw groups with the commands written by the culling pass. Pipelines that
+    /// do not read the object table draw each object of the group instead
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    pub(crate) fn draw_indirect(
+        &self,
+        ctx: &mut GfxContext,
+        frame: &mut FrameData,
+        render_list: &[RenderObject],
+        draws: &IndirectDraws,
+    ) -> Result<(), RenderJobError> {
+        if draws.object_count as usize != render_list.len() {
+            tracing::warn!(target: logger::RENDER, "[{}] Object table does not match render list", &self.pass_name);
+            return self.draw_list(ctx, frame, render_list);
+        }
+
+        let mut state = RenderJobState::new(ctx, frame);
+        let object_buffer = ctx.hal().get_buffer_address(draws.objects);
+
+        for (group_index, group) in draws.groups.iter().enumerate() {
+            let objects = &render_list[group.first as usize..(group.first + group.count) as usize];
+            let render_object = &objects[0];
+
+            if !self.should_render(render_object) {
+                continue;
+            }
+
+            let pipeline = self.get_pipeline(render_object)?;
+
+            self.bind_pipeline(ctx, frame, pipeline, &mut state)?;
+            self.bind_scene_data(ctx, frame, pipeline, &mut state)?;
+            self.bind_material_data(ctx, frame, render_object, pipeline, &mut state)?;
+
+            let indirect = ctx
+                .hal()
+                .get_pipeline_object_layout(pipeline)
+                .layout()
+                .contains(&ObjectDataProp::ObjectBufferAddress);
+
+            if !indirect {
+                for render_object in objects {
+                    self.bind_object_data(ctx, frame, render_object, pipeline, 0, &mut state)?;
+                    frame.command.draw_indexed(render_object.index_len, 1);
+                }
+                continue;
+            }
+
+            self.bind_object_data(
+                ctx,
+                frame,
+                render_object,
+                pipeline,
+                object_buffer,
+                &mut state,
+            )?;
+
+            tracing::trace!(target: logger::RENDER, "Draw group {} indirect ({} objects)", group_index, group.count);
+            frame.command.draw_indexed_indirect_count(
+                ctx.hal(),
+                draws.commands,
+                (group.first as usize * DRAW_INDEXED_INDIRECT_SIZE) as u64,
+                draws.counts,
+                (group_index * std::mem::size_of::<u32>()) as u64,
+                group.count,
+            );
+        }
+
+        Ok(())
+    }
+
     fn get_pipeline(&self, render_object: &RenderObject) -> Result<Handle, RenderJobError> {
         if let Some(pipeline) = self.fixed_pipeline {
             tracing::trace!(target: logger::RENDER, "Use fixed pipeline");
@@ -176,6 +267,23 @@ impl RenderJob {
         state: &mut RenderJobState,
     ) -> Result<(), RenderJobError> {
         if self.fixed_pipeline.is_none() {
+            // bindless materials are read from the material table, only textures are bound once
+            if render_object.material_index.is_some() {
+                if !state.bindless_bound {
+                    tracing::trace!(target: logger::RENDER, "Bind bindless textures");
+
+                    let textures = ctx.hal().bindless_textures();
+                    frame
+                        .command
+                        .bind_resource(ctx.hal_mut(), pipeline, &textures);
+
+                    state.bindless_bound = true;
+                    state.last_material_textures = None;
+                }
+
+                return Ok(());
+            }
+
             let material_data_id = render_object.material_data.as_ref().map(|bind| bind.id);
             let texture_data_id = render_object.material_textures.as_ref().map(|bind| bind.id);
 
@@ -201,6 +309,7 @@ impl RenderJob {
                     .bind_resource(ctx.hal_mut(), pipeline, material_textures);
 
                 state.last_material_textures = texture_data_id;
+                state.bindless_bound = false;
             }
         }
 
@@ -233,6 +342,7 @@ impl RenderJob {
         frame: &mut FrameData,
         render_object: &RenderObject,
         pipeline: Handle,
+        object_buffer: u64,
         state: &mut RenderJobState,
     ) -> Result<(), RenderJobError> {
         tracing::trace!(target: logger::RENDER, "Bind push constants");
@@ -252,6 +362,34 @@ impl RenderJob {
                     ctx.hal().get_buffer_address(render_object.vertex_buffer);
                 AttributeData::U64(vertex_buffer_address)
             }
+            ObjectDataProp::ObjectBufferAddress => AttributeData::U64(object_buffer),
+            ObjectDataProp::MaterialBufferAddress => AttributeData::U64(state.material_buffer),
+            ObjectDataProp::MaterialIndex => {
+                AttributeData::U32(render_object.material_index.unwrap_or_default())
+            }
+            ObjectDataProp::PickId => AttributeData::U32(render_object.pick_id),
+            ObjectDataProp::JointBufferAddress => match render_object.joint_offset {
+                Some(offset) if state.joint_buffer != 0 => AttributeData::U64(
+                    state.joint_buffer + offset as u64 * std::mem::size_of::<Mat4>() as u64,
+                ),
+                _ => AttributeData::U64(0),
+            },
+            ObjectDataProp::MorphTargetAddress => match render_object.morph_targets {
+                Some(targets) => AttributeData::U64(ctx.hal().get_buffer_address(targets)),
+                None => AttributeData::U64(0),
+            },
+            ObjectDataProp::MorphWeightAddress => match render_object.morph_offset {
+                Some(offset) if state.morph_weight_buffer != 0 => AttributeData::U64(
+                    state.morph_weight_buffer + offset as u64 * std::mem::size_of::<f32>() as u64,
+                ),
+                _ => AttributeData::U64(0),
+            },
+            ObjectDataProp::MorphTargetCount => match render_object.morph_offset {
+                Some(_) if state.morph_weight_buffer != 0 => {
+                    AttributeData::U32(render_object.morph_count)
+                }
+                _ => AttributeData::U32(0),
+            },
         });
 
         // TODO: check pipeline object layout compatibility
@@ -10,10 +10,13 @@ mod render_object;
 use std::sync::Arc;
 
 pub use context::GfxContext;
-pub use data::{SceneData, SceneDataLayout, SceneDataProp};
+pub use data::{
+    ComputeDataLayout, ComputeDataProp, DrawGroup, ObjectTable, SceneData, SceneDataLayout,
+    SceneDataProp,
+};
 pub use error::RenderError;
 pub use framedata::FrameData;
-pub use graph::{FrameGraph, GraphConfig};
+pub use graph::{FrameGraph, GraphConfig, PassPipeline};
 pub use job::RenderJob;
 pub use render_object::{
     MaterialId, MaterialInstanceId, MeshId, PassId, RenderFlags, RenderObject,
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 use gobs_core::{ImageExtent2D, ImageFormat};
-use gobs_render_hal::{ImageLayout, ImageUsage};
+use gobs_render_hal::{CommandQueueType, ImageLayout, ImageUsage};
 
 use crate::{
     FrameData, GfxContext, PassId, RenderError, RenderObject, data::SceneData,
@@ -106,6 +106,10 @@ impl Attachment {
 pub trait RenderPass {
     fn id(&self) -> PassId;
     fn name(&self) -> &str;
+    /// Queue the pass can be submitted to
+    fn queue(&self) -> CommandQueueType {
+        CommandQueueType::Graphics
+    }
     fn render(
         &self,
         ctx: &mut GfxContext,
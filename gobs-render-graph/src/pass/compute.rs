@@ -1,42 +1,239 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 use gobs_core::{ImageExtent2D, logger};
-use gobs_render_hal::{BindResource, BindingGroupType, Handle};
+use gobs_render_hal::{
+    AttributeData, BindResource, BindingGroupType, CommandQueueType, Handle, RenderHAL,
+    UniformData as _,
+};
 
 use crate::{
     FrameData, GfxContext, RenderError, RenderObject,
-    data::SceneData,
+    data::{ComputeDataLayout, ComputeDataProp, SceneData},
     graph::GraphResourceManager,
     pass::{Attachment, AttachmentType, PassId, RenderPass},
 };
 
+/// Binding groups a compute pipeline may declare, in set order
+const COMPUTE_GROUPS: [BindingGroupType; 3] = [
+    BindingGroupType::ComputeData,
+    BindingGroupType::MaterialData,
+    BindingGroupType::MaterialTextures,
+];
+
+pub const DEFAULT_WORKGROUP_SIZE: [u32; 3] = [16, 16, 1];
+
+/// Number of workgroups of a dispatch
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum DispatchSize {
+    /// Fixed number of workgroups
+    Groups(u32, u32, u32),
+    /// Fixed number of invocations, rounded up to whole workgroups
+    Threads(u32, u32, u32),
+    /// One invocation per pixel of the attachment
+    Attachment(String),
+    /// One invocation per object of the frame object table
+    Objects,
+}
+
+impl DispatchSize {
+    pub fn group_count(
+        &self,
+        workgroup_size: [u32; 3],
+        extent: ImageExtent2D,
+        object_count: u32,
+    ) -> [u32; 3] {
+        let threads = match self {
+            DispatchSize::Groups(x, y, z) => return [*x, *y, *z],
+            DispatchSize::Threads(x, y, z) => [*x, *y, *z],
+            DispatchSize::Attachment(_) => [extent.width, extent.height, 1],
+            DispatchSize::Objects => [object_count, 1, 1],
+        };
+
+        [
+            threads[0].div_ceil(workgroup_size[0].max(1)),
+            threads[1].div_ceil(workgroup_size[1].max(1)),
+            threads[2].div_ceil(workgroup_size[2].max(1)),
+        ]
+    }
+}
+
+pub struct Dispatch {
+    pub pipeline: Handle,
+    pub workgroup_size: [u32; 3],
+    pub size: DispatchSize,
+}
+
+impl Dispatch {
+    pub fn new(pipeline: Handle, workgroup_size: [u32; 3], size: DispatchSize) -> Self {
+        Self {
+            pipeline,
+            workgroup_size,
+            size,
+        }
+    }
+}
+
 pub struct ComputePass {
     id: PassId,
     name: String,
     attachments: HashMap<String, Attachment>,
     image_attachments: Vec<String>,
+    buffers: Vec<String>,
+    dispatches: Vec<Dispatch>,
+    push_layout: ComputeDataLayout,
     pub pipeline: Handle,
 }
 
 impl ComputePass {
-    pub fn new(name: &str, pipeline: Handle) -> Self {
+    pub fn new(name: &str, pipeline: Handle, push_layout: ComputeDataLayout) -> Self {
         Self {
             id: PassId::new_v4(),
             name: name.to_string(),
             attachments: Default::default(),
             image_attachments: vec![],
+            buffers: vec![],
+            dispatches: vec![],
+            push_layout,
             pipeline,
         }
     }
 
     pub fn add_attachment(&mut self, name: &str, attachment: Attachment) {
         match attachment.ty {
-            AttachmentType::ImageStorage => self.image_attachments.push(name.to_string()),
+            AttachmentType::ImageStorage => {
+                self.image_attachments.push(name.to_string());
+                // bindings follow attachment names order
+                self.image_attachments.sort();
+            }
             _ => todo!(),
         }
 
         self.attachments.insert(name.to_string(), attachment);
     }
+
+    /// Graph buffer bound after the storage images
+    pub fn add_buffer(&mut self, name: &str) {
+        self.buffers.push(name.to_string());
+    }
+
+    /// Dispatches run in sequence with a barrier in between
+    pub fn add_dispatch(&mut self, dispatch: Dispatch) {
+        self.dispatches.push(dispatch);
+    }
+
+    fn default_dispatch(&self) -> Dispatch {
+        let size = match self.image_attachments.first() {
+            Some(name) => DispatchSize::Attachment(name.clone()),
+            None => DispatchSize::Groups(1, 1, 1),
+        };
+
+        Dispatch::new(self.pipeline, DEFAULT_WORKGROUP_SIZE, size)
+    }
+
+    fn push_constants(
+        &self,
+        hal: &dyn RenderHAL,
+        frame: &FrameData,
+        scene_data: &SceneData,
+        extent: ImageExtent2D,
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        let indirect = frame.indirect();
+        let buffer_address = |buffer: Option<Handle>| {
+            AttributeData::U64(buffer.map_or(0, |buffer| hal.get_buffer_address(buffer)))
+        };
+
+        self.push_layout.copy_data(&mut data, |prop| match prop {
+            ComputeDataProp::Time => AttributeData::F32(scene_data.time),
+            ComputeDataProp::FrameNumber => AttributeData::U32(frame.frame_number as u32),
+            ComputeDataProp::Extent => {
+                AttributeData::Vec2F([extent.width as f32, extent.height as f32])
+            }
+            ComputeDataProp::CameraViewProj => AttributeData::Mat4F(
+                scene_data
+                    .camera
+                    .view_proj(scene_data.camera_transform.translation())
+                    .to_cols_array_2d(),
+            ),
+            ComputeDataProp::ObjectBuffer => buffer_address(indirect.map(|draws| draws.objects)),
+            ComputeDataProp::CommandBuffer => buffer_address(indirect.map(|draws| draws.commands)),
+            ComputeDataProp::CountBuffer => buffer_address(indirect.map(|draws| draws.counts)),
+            ComputeDataProp::ObjectCount => {
+                AttributeData::U32(indirect.map_or(0, |draws| draws.object_count))
+            }
+        });
+
+        data
+    }
+
+    fn dispatch(
+        &self,
+        ctx: &mut GfxContext,
+        frame: &mut FrameData,
+        resource_manager: &GraphResourceManager,
+        scene_data: &SceneData,
+        resources: &[Handle],
+        dispatch: &Dispatch,
+    ) -> Result<(), RenderError> {
+        let extent = match &dispatch.size {
+            DispatchSize::Attachment(name) => {
+                ctx.hal().get_image_extent(resource_manager.image(name))
+            }
+            _ => ImageExtent2D::default(),
+        };
+
+        let object_count = frame.indirect().map_or(0, |draws| draws.object_count);
+
+        let [x, y, z] = dispatch
+            .size
+            .group_count(dispatch.workgroup_size, extent, object_count);
+        if x == 0 || y == 0 || z == 0 {
+            tracing::trace!(target: logger::RENDER, "Skip empty dispatch in pass {}", self.name);
+            return Ok(());
+        }
+
+        let push_constants = (!self.push_layout.is_empty())
+            .then(|| self.push_constants(ctx.hal(), frame, scene_data, extent));
+
+        let cmd = frame.compute_command();
+
+        cmd.bind_pipeline(ctx.hal(), dispatch.pipeline);
+
+        let mut resources = resources.iter().copied();
+        for group in &COMPUTE_GROUPS {
+            let Some(binding_layout) = ctx
+                .hal()
+                .get_pipeline_descriptor_layout(dispatch.pipeline, group)
+            else {
+                continue;
+            };
+
+            let group_resources: Vec<Handle> = resources
+                .by_ref()
+                .take(binding_layout.bindings.len())
+                .collect();
+            if group_resources.len() < binding_layout.bindings.len() {
+                tracing::error!(target: logger::RENDER, "Missing resources for {:?} in pass {}", group, self.name);
+                return Err(RenderError::InvalidData);
+            }
+
+            let bind_resource = BindResource::with_resources(binding_layout, group_resources);
+            cmd.bind_resource(ctx.hal_mut(), dispatch.pipeline, &bind_resource);
+        }
+
+        if let Some(push_constants) = push_constants {
+            cmd.push_constants(ctx.hal(), dispatch.pipeline, &push_constants);
+        }
+
+        tracing::trace!(target: logger::RENDER, "Dispatch ({}, {}, {})", x, y, z);
+
+        cmd.dispatch(x, y, z);
+
+        Ok(())
+    }
 }
 
 impl RenderPass for ComputePass {
@@ -48,55 +245,106 @@ impl RenderPass for ComputePass {
         &self.name
     }
 
+    fn queue(&self) -> CommandQueueType {
+        CommandQueueType::Compute
+    }
+
     fn render(
         &self,
         ctx: &mut GfxContext,
         frame: &mut FrameData,
         resource_manager: &GraphResourceManager,
         _render_list: &[RenderObject],
-        _scene_data: &SceneData,
+        scene_data: &SceneData,
     ) -> Result<(), RenderError> {
         tracing::debug!(target: logger::RENDER, "Draw compute");
 
-        let cmd = &mut frame.command;
-
-        cmd.begin_label("Draw compute");
+        frame.compute_command().begin_label("Draw compute");
 
         let mut resources = vec![];
 
-        let mut draw_extent = ImageExtent2D::default();
+        for name in &self.image_attachments {
+            let image = resource_manager.image(name);
 
-        for (name, attachment) in &self.attachments {
-            cmd.transition_image_layout(
+            frame.compute_command().transition_image_layout(
                 ctx.hal_mut(),
-                resource_manager.image(name),
-                attachment.layout,
+                image,
+                self.attachments[name].layout,
             );
 
-            let image = resource_manager.image(name);
-            draw_extent = ctx.hal().get_image_extent(image);
-
             resources.push(image);
         }
 
-        cmd.bind_pipeline(ctx.hal(), self.pipeline);
-
-        if !resources.is_empty() {
-            // TODO: assume one ds
-            let binding_layout = ctx
-                .hal()
-                .get_pipeline_descriptor_layout(self.pipeline, &BindingGroupType::ComputeData)
-                .ok_or(RenderError::InvalidData)?;
+        for name in &self.buffers {
+            resources.push(resource_manager.buffer(name));
+        }
 
-            let bind_resource = BindResource::with_resources(binding_layout.clone(), resources);
-            cmd.bind_resource(ctx.hal_mut(), self.pipeline, &bind_resource);
+        if self.dispatches.is_empty() {
+            let dispatch = self.default_dispatch();
+            self.dispatch(
+                ctx,
+                frame,
+                resource_manager,
+                scene_data,
+                &resources,
+                &dispatch,
+            )?;
         }
 
-        // TODO: hardcoded
-        cmd.dispatch(draw_extent.width / 16 + 1, draw_extent.height / 16 + 1, 1);
+        for (i, dispatch) in self.dispatches.iter().enumerate() {
+            if i > 0 {
+                frame.compute_command().compute_barrier();
+            }
+
+            self.dispatch(
+                ctx,
+                frame,
+                resource_manager,
+                scene_data,
+                &resources,
+                dispatch,
+            )?;
+        }
 
-        cmd.end_label();
+        frame.compute_command().end_label();
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gobs_core::ImageExtent2D;
+
+    use crate::pass::compute::DispatchSize;
+
+    #[test]
+    fn test_group_count() {
+        let extent = ImageExtent2D::new(1920, 1080);
+
+        assert_eq!(
+            DispatchSize::Groups(4, 2, 1).group_count([16, 16, 1], extent, 0),
+            [4, 2, 1]
+        );
+        assert_eq!(
+            DispatchSize::Threads(100, 64, 1).group_count([64, 1, 1], extent, 0),
+            [2, 64, 1]
+        );
+        assert_eq!(
+            DispatchSize::Attachment("draw".to_string()).group_count([16, 16, 1], extent, 0),
+            [120, 68, 1]
+        );
+        assert_eq!(
+            DispatchSize::Attachment("draw".to_string()).group_count([8, 8, 1], extent, 0),
+            [240, 135, 1]
+        );
+        assert_eq!(
+            DispatchSize::Objects.group_count([64, 1, 1], extent, 1000),
+            [16, 1, 1]
+        );
+        assert_eq!(
+            DispatchSize::Objects.group_count([64, 1, 1], extent, 0),
+            [0, 1, 1]
+        );
+    }
+}
@@ -20,6 +20,7 @@ pub struct MaterialPass {
     scene_layout: SceneDataLayout,
     render_jobs: Vec<RenderJob>,
     fixed_pipeline: Option<Handle>,
+    indirect: bool,
 }
 
 impl MaterialPass {
@@ -52,9 +53,15 @@ impl MaterialPass {
             scene_layout,
             render_jobs,
             fixed_pipeline: None,
+            indirect: false,
         }
     }
 
+    /// Draw with the commands of the culling pass when an object table is available
+    pub fn set_indirect(&mut self, indirect: bool) {
+        self.indirect = indirect;
+    }
+
     pub fn set_fixed_pipeline(&mut self, pipeline: Handle) {
         self.fixed_pipeline = Some(pipeline);
         for job in &mut self.render_jobs {
@@ -181,6 +188,16 @@ impl RenderPass for MaterialPass {
     ) -> Result<(), RenderError> {
         tracing::debug!(target: logger::RENDER, "Draw {}", &self.name());
 
+        let indirect = if self.indirect {
+            frame.indirect().cloned()
+        } else {
+            None
+        };
+
+        if indirect.is_some() {
+            frame.command.indirect_barrier();
+        }
+
         self.transition_attachments(ctx.hal_mut(), frame.command.as_mut(), resource_manager);
 
         self.begin_pass(ctx.hal(), frame.command.as_mut(), resource_manager);
@@ -229,8 +246,16 @@ impl RenderPass for MaterialPass {
         tracing::debug!(target: logger::RENDER, "Update uniform (scene data, push)");
         render_job.update_uniform(ctx, &scene_data_bytes);
 
-        tracing::debug!(target: logger::RENDER, "Draw render object list");
-        render_job.draw_list(ctx, frame, render_list)?;
+        match &indirect {
+            Some(draws) => {
+                tracing::debug!(target: logger::RENDER, "Draw indirect");
+                render_job.draw_indirect(ctx, frame, render_list, draws)?;
+            }
+            None => {
+                tracing::debug!(target: logger::RENDER, "Draw render object list");
+                render_job.draw_list(ctx, frame, render_list)?;
+            }
+        }
 
         tracing::debug!(target: logger::RENDER, "Stop render job");
 
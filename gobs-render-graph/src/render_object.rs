@@ -1,6 +1,7 @@
 use std::{cmp::Ordering, sync::Arc};
 
 use bitflags::bitflags;
+use glam::Vec3;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -38,8 +39,21 @@ pub struct RenderObject {
     pub pipeline: Option<Handle>,
     pub material_data: Option<BindResource>,
     pub material_textures: Option<BindResource>,
+    /// Index in the material table for bindless materials
+    pub material_index: Option<u32>,
     pub layer: u32,
     pub render_flags: RenderFlags,
+    /// World space bounds (min, max), used by GPU culling
+    pub bounds: Option<(Vec3, Vec3)>,
+    /// Id written to the picking attachment, 0 if the object cannot be picked
+    pub pick_id: u32,
+    /// Index of the first joint matrix in the joint table for skinned meshes
+    pub joint_offset: Option<u32>,
+    /// Morph target buffer of the mesh
+    pub morph_targets: Option<Handle>,
+    /// Index of the first weight in the morph weight table, for morphed meshes
+    pub morph_offset: Option<u32>,
+    pub morph_count: u32,
 }
 
 impl RenderObject {
@@ -0,0 +1,19 @@
+use gobs_vulkan::physical::{Limits, PhysicalDeviceType};
+
+/// Description of a GPU adapter
+#[derive(Clone, Debug)]
+pub struct AdapterInfo {
+    /// Position in the list of enumerated adapters
+    pub index: usize,
+    pub name: String,
+    pub ty: PhysicalDeviceType,
+    pub driver: String,
+    pub api_version: String,
+    /// Device local memory size in bytes
+    pub vram: u64,
+    pub limits: Limits,
+    /// Supported optional and required features
+    pub features: Vec<&'static str>,
+    /// The adapter supports all required features
+    pub suitable: bool,
+}
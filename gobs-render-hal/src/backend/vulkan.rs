@@ -4,9 +4,23 @@ mod command;
 pub(crate) mod display;
 mod pipeline;
 pub(crate) mod registry;
+mod shaders;
 mod textures;
-
-use std::{any::Any, collections::HashMap, sync::Arc};
+mod timeline;
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    env,
+    fmt::Write as _,
+    fs, io,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use winit::{
     dpi::PhysicalPosition,
@@ -17,8 +31,9 @@ use gobs_core::{ConfigReader as _, GobsConfig, ImageExtent2D, ImageFormat, Sampl
 use gobs_vulkan as vk;
 
 use crate::{
-    BindingGroupLayout, BindingGroupType, CommandBuffer, CommandQueueType, ImageUsage,
-    ObjectDataLayout, RenderBackendError, RenderHalConfig, VertexAttribute,
+    AdapterInfo, AdapterSelector, BindResource, BindingGroupLayout, BindingGroupType,
+    CommandBuffer, CommandQueueType, DisplayConfig, DisplayInfo, ImageUsage, MemoryReport,
+    ObjectDataLayout, RenderBackendError, RenderHalConfig, ShaderReflection, VertexAttribute,
     backend::vulkan::{
         bindings::BindingRegistry,
         buffer::BufferView,
@@ -26,9 +41,12 @@ use crate::{
         display::Display,
         pipeline::{VkComputePipelineBuilder, VkGraphicsPipelineBuilder},
         registry::ResourcesRegistry,
+        shaders::ShaderRegistry,
         textures::TextureRegistry,
+        timeline::{OwnershipTransfer, QueueTimeline},
     },
     hal::{BufferType, Handle, RenderHAL},
+    memory::MemoryTracker,
     pipeline::{ComputePipelineBuilder, GraphicsPipelineBuilder},
 };
 
@@ -49,12 +67,23 @@ impl VulkanHALExt for dyn RenderHAL + '_ {
 
 pub struct VulkanHAL {
     registry: ResourcesRegistry,
+    shaders: Arc<ShaderRegistry>,
+    memory: MemoryTracker,
     bindings: BindingRegistry,
     textures: TextureRegistry,
     frames_in_flight: usize,
+    adapters: Vec<AdapterInfo>,
+    adapter: usize,
+    pipeline_cache: Option<PathBuf>,
+    crash_dir: Option<PathBuf>,
+    crash_reported: AtomicBool,
+    breadcrumbs: Option<Arc<vk::breadcrumbs::Breadcrumbs>>,
     pub display: Display,
     pub graphics_queue: Arc<vk::Queue>,
+    pub compute_queue: Arc<vk::Queue>,
     pub transfer_queue: Arc<vk::Queue>,
+    timelines: HashMap<CommandQueueType, Arc<QueueTimeline>>,
+    pending_transfers: Vec<OwnershipTransfer>,
     pub allocator: Arc<vk::Allocator>,
     pub device: Arc<vk::Device>,
     pub instance: Arc<vk::Instance>,
@@ -91,6 +120,9 @@ impl RenderHAL for VulkanHAL {
             BufferType::Staging => vk::BufferUsage::Staging,
             BufferType::StagingDst => vk::BufferUsage::StagingDst,
             BufferType::Uniform => vk::BufferUsage::Uniform,
+            BufferType::Storage => vk::BufferUsage::Storage,
+            BufferType::HostStorage => vk::BufferUsage::HostStorage,
+            BufferType::Indirect => vk::BufferUsage::Indirect,
         };
 
         let buffer = vk::buffers::Buffer::new(
@@ -101,13 +133,18 @@ impl RenderHAL for VulkanHAL {
             self.allocator.clone(),
         );
 
+        let memory_size = buffer.memory_size();
+
         let buffer_view = BufferView {
             buffer: Arc::new(buffer),
             offset: 0,
             len: size,
         };
 
-        self.registry.buffers.insert(buffer_view)
+        let handle = self.registry.buffers.insert(buffer_view);
+        self.memory.track_buffer(handle, ty.into(), memory_size);
+
+        handle
     }
 
     fn upload_buffer(&mut self, handle: Handle, data: &[u8], offset: u64) {
@@ -116,6 +153,17 @@ impl RenderHAL for VulkanHAL {
         buffer.buffer.copy(data, buffer.offset + offset);
     }
 
+    fn download_buffer(&self, handle: Handle, data: &mut Vec<u8>) {
+        let buffer = self.registry.buffers.get(handle).unwrap();
+
+        let mut bytes = Vec::new();
+        buffer.buffer.get_bytes(&mut bytes);
+
+        let start = (buffer.offset as usize).min(bytes.len());
+        let end = (start + buffer.len).min(bytes.len());
+        data.extend_from_slice(&bytes[start..end]);
+    }
+
     fn get_buffer_address(&self, handle: Handle) -> u64 {
         let buffer = self.registry.buffers.get(handle).unwrap();
 
@@ -123,6 +171,7 @@ impl RenderHAL for VulkanHAL {
     }
 
     fn destroy_buffer(&mut self, buffer: Handle) {
+        self.memory.untrack_buffer(buffer);
         let _ = self.registry.buffers.remove(buffer);
     }
 
@@ -133,16 +182,33 @@ impl RenderHAL for VulkanHAL {
         usage: ImageUsage,
         extent: ImageExtent2D,
     ) -> Handle {
-        let image = vk::images::Image::new(
+        self.create_image_with_mips(name, format, usage, extent, 1)
+    }
+
+    fn create_image_with_mips(
+        &mut self,
+        name: &str,
+        format: ImageFormat,
+        usage: ImageUsage,
+        extent: ImageExtent2D,
+        mip_levels: u32,
+    ) -> Handle {
+        let image = vk::images::Image::with_mip_levels(
             name,
             self.device.clone(),
             format,
             usage,
             extent,
+            mip_levels,
             self.allocator.clone(),
         );
 
-        self.registry.images.insert(image)
+        let memory_size = image.memory_size();
+
+        let handle = self.registry.images.insert(image);
+        self.memory.track_image(handle, usage.into(), memory_size);
+
+        handle
     }
 
     fn invalidate_image(&mut self, image: Handle) {
@@ -158,17 +224,26 @@ impl RenderHAL for VulkanHAL {
     }
 
     fn destroy_image(&mut self, image: Handle) {
+        self.memory.untrack_image(image);
         let _ = self.registry.images.remove(image);
     }
 
-    fn register_texture(&mut self, image: Handle) -> usize {
-        self.textures.register(image)
+    fn register_texture(&mut self, image: Handle, sampler: Handle) -> usize {
+        self.textures.register(image, sampler)
     }
 
     fn allocate_texture_index(&mut self) -> usize {
         self.textures.reserve_index()
     }
 
+    fn unregister_texture(&mut self, image: Handle) {
+        self.textures.unregister(image);
+    }
+
+    fn bindless_textures(&self) -> BindResource {
+        self.textures.binding().clone()
+    }
+
     fn create_sampler(&mut self, mag_filter: SamplerFilter, min_filter: SamplerFilter) -> Handle {
         let sampler = vk::images::Sampler::new(self.device.clone(), mag_filter, min_filter);
 
@@ -184,21 +259,45 @@ impl RenderHAL for VulkanHAL {
         name: &str,
         ty: CommandQueueType,
     ) -> Box<dyn CommandBuffer> {
-        let queue = match ty {
-            CommandQueueType::Graphics => self.graphics_queue.clone(),
-            CommandQueueType::Transfer => self.transfer_queue.clone(),
-            _ => unimplemented!(),
-        };
-
-        Box::new(VkCommandBuffer::new(self.device.clone(), name, queue))
+        Box::new(VkCommandBuffer::new(
+            self.device.clone(),
+            name,
+            self.queue(ty).clone(),
+            ty,
+            self.timeline(ty).clone(),
+            self.breadcrumbs.clone(),
+        ))
     }
 
     fn create_graphics_pipeline(&self, name: &str) -> Box<dyn GraphicsPipelineBuilder> {
-        Box::new(VkGraphicsPipelineBuilder::new(name, self.device.clone()))
+        Box::new(VkGraphicsPipelineBuilder::new(
+            name,
+            self.device.clone(),
+            self.shaders.clone(),
+        ))
     }
 
     fn create_compute_pipeline(&self, name: &str) -> Box<dyn ComputePipelineBuilder> {
-        Box::new(VkComputePipelineBuilder::new(name, self.device.clone()))
+        Box::new(VkComputePipelineBuilder::new(
+            name,
+            self.device.clone(),
+            self.shaders.clone(),
+        ))
+    }
+
+    fn shader_reflection(&self, filename: &str) -> Option<Arc<ShaderReflection>> {
+        self.shaders
+            .load(filename)
+            .inspect_err(|e| {
+                tracing::warn!(target: logger::RESOURCES, "Cannot read shader {} for reflection: {}", filename, e);
+            })
+            .ok()?
+            .reflection
+            .clone()
+    }
+
+    fn invalidate_shader(&mut self, filename: &str) {
+        self.shaders.invalidate(filename);
     }
 
     fn destroy_pipeline(&mut self, pipeline: Handle) {
@@ -234,17 +333,35 @@ impl RenderHAL for VulkanHAL {
     }
 
     fn acquire(&mut self, frame: usize) -> Result<(), RenderBackendError> {
-        self.display.acquire(&mut self.registry, frame)
+        if self.device.is_lost() {
+            return Err(self.device_lost());
+        }
+
+        self.display
+            .acquire(&mut self.registry, frame)
+            .map_err(|err| self.check_error(err))
     }
 
     fn present(&mut self) -> Result<(), RenderBackendError> {
-        self.display.present(&self.graphics_queue)
+        self.display
+            .present(&self.graphics_queue)
+            .map_err(|err| self.check_error(err))
     }
 
     fn resize(&mut self) {
         self.display.resize(&mut self.registry, self.device.clone());
     }
 
+    fn set_display_config(&mut self, config: DisplayConfig) {
+        self.device.wait();
+        self.display
+            .reconfigure(&mut self.registry, self.device.clone(), config);
+    }
+
+    fn display_info(&self) -> Option<DisplayInfo> {
+        self.display.info()
+    }
+
     fn request_redraw(&mut self) {
         match &self.display.surface {
             None => (),
@@ -291,10 +408,56 @@ impl RenderHAL for VulkanHAL {
         self.device.wait();
     }
 
+    fn async_compute(&self) -> bool {
+        self.device.async_compute()
+    }
+
+    fn queue_value(&self, queue: CommandQueueType) -> u64 {
+        self.timeline(queue).value()
+    }
+
+    fn adapters(&self) -> &[AdapterInfo] {
+        &self.adapters
+    }
+
+    fn adapter(&self) -> &AdapterInfo {
+        &self.adapters[self.adapter]
+    }
+
+    fn features(&self) -> vk::Features {
+        self.device.features
+    }
+
+    fn memory_report(&self) -> MemoryReport {
+        MemoryReport {
+            heaps: self.device.memory_heaps(),
+            categories: self.memory.categories(),
+            allocator: self.allocator.stats(),
+        }
+    }
+
     fn info(&self) {
+        let adapter = self.adapter();
+        tracing::info!(target: logger::INIT, "Adapter: {} ({:?}, driver {}, Vulkan {}, VRAM {} MB)",
+            adapter.name, adapter.ty, adapter.driver, adapter.api_version, adapter.vram / (1024 * 1024));
+        tracing::info!(target: logger::INIT, "Limits: {:?}", adapter.limits);
+        tracing::info!(target: logger::INIT, "Features: {:?}", self.device.features.names());
+
+        if let Some(info) = self.display.info() {
+            tracing::info!(target: logger::INIT, "Display: present mode={:?}, format={:?}, color space={:?}, images={}",
+                info.present_mode, info.format, info.color_space, info.image_count);
+        }
+
         tracing::info!(target: logger::MEMORY, "Stats: buffers={}, images={}, samplers={}, pipelines={}",
             self.registry.buffers.len(), self.registry.images.len(), self.registry.samplers.len(), self.registry.pipelines.len());
 
+        let report = self.memory_report();
+        tracing::info!(target: logger::MEMORY, "Allocator: allocations={}, allocated={} KB, reserved={} KB, fragmentation={:.2}",
+            report.allocator.allocation_count, report.allocator.allocated / 1024, report.allocator.reserved / 1024, report.fragmentation());
+        for (category, usage) in &report.categories {
+            tracing::info!(target: logger::MEMORY, "{:?}: count={}, size={} KB", category, usage.count, usage.size / 1024);
+        }
+
         for buffer in self.registry.buffers.values() {
             tracing::info!(target: logger::MEMORY, "{}", buffer.buffer.label());
         }
@@ -314,16 +477,46 @@ impl RenderHAL for VulkanHAL {
 }
 
 impl VulkanHAL {
+    pub(crate) fn queue(&self, ty: CommandQueueType) -> &Arc<vk::Queue> {
+        match ty {
+            CommandQueueType::Graphics => &self.graphics_queue,
+            CommandQueueType::Compute => &self.compute_queue,
+            CommandQueueType::Transfer => &self.transfer_queue,
+        }
+    }
+
+    pub(crate) fn timeline(&self, ty: CommandQueueType) -> &Arc<QueueTimeline> {
+        &self.timelines[&ty]
+    }
+
     pub fn new(name: &str, window: Option<Window>, config: GobsConfig, validation: bool) -> Self {
         let instance = vk::Instance::new(name, 1, window.as_ref(), validation).unwrap();
 
-        let mut display = Display::new(instance.clone(), window);
+        let mut display = Display::new(instance.clone(), window, DisplayConfig::new(&config));
 
-        let device = Self::create_device(instance.clone(), &display);
+        let (device, adapters, adapter) = Self::create_device(instance.clone(), &display, &config);
 
         let graphics_queue = device.clone().graphics_queue();
+        let compute_queue = device.clone().compute_queue();
         let transfer_queue = device.clone().transfer_queue();
 
+        let timelines = HashMap::from([
+            (
+                CommandQueueType::Graphics,
+                QueueTimeline::new(device.clone(), "Graphics"),
+            ),
+            (
+                CommandQueueType::Compute,
+                QueueTimeline::new(device.clone(), "Compute"),
+            ),
+            (
+                CommandQueueType::Transfer,
+                QueueTimeline::new(device.clone(), "Transfer"),
+            ),
+        ]);
+
+        tracing::info!(target: logger::INIT, "Async compute: {}", device.async_compute());
+
         let allocator = vk::Allocator::new(device.clone());
 
         let frames_in_flight = config.get_int(RenderHalConfig::FramesInFlight) as usize;
@@ -332,34 +525,226 @@ impl VulkanHAL {
         let mut registry = ResourcesRegistry::default();
         let bindings = BindingRegistry::new(frames_in_flight);
 
-        let sampler = vk::images::Sampler::new(
-            device.clone(),
-            SamplerFilter::FilterLinear,
-            SamplerFilter::FilterLinear,
-        );
-        let sampler = registry.samplers.insert(sampler);
-
-        let textures = TextureRegistry::new(textures_array_size, sampler);
+        let textures = TextureRegistry::new(textures_array_size);
 
         display.init(&mut registry, device.clone(), frames_in_flight);
 
+        let pipeline_cache = Self::load_pipeline_cache(name, &config, &device);
+        let crash_dir = Self::cache_dir(name, &config).map(|dir| dir.join("crash"));
+
+        let breadcrumbs = match config.get_int(RenderHalConfig::Breadcrumbs) {
+            0 => None,
+            capacity => Some(vk::breadcrumbs::Breadcrumbs::new(
+                device.clone(),
+                allocator.clone(),
+                capacity as usize,
+            )),
+        };
+
         Self {
             registry,
+            shaders: Arc::new(ShaderRegistry::default()),
+            memory: MemoryTracker::default(),
             bindings,
             textures,
             frames_in_flight,
+            adapters,
+            adapter,
+            pipeline_cache,
+            crash_dir,
+            crash_reported: AtomicBool::new(false),
+            breadcrumbs,
             display,
             graphics_queue,
+            compute_queue,
             transfer_queue,
+            timelines,
+            pending_transfers: Vec::new(),
             allocator,
             device,
             instance,
         }
     }
 
-    fn create_device(instance: Arc<vk::Instance>, display: &Display) -> Arc<vk::Device> {
+    /// Cache directory of the application
+    fn cache_dir(name: &str, config: &GobsConfig) -> Option<PathBuf> {
+        let cache_dir = config.get_string(RenderHalConfig::CacheDir);
+
+        let cache_dir = if !cache_dir.is_empty() {
+            PathBuf::from(cache_dir)
+        } else if let Some(dir) = env::var_os("XDG_CACHE_HOME") {
+            PathBuf::from(dir).join("gobs")
+        } else if let Some(dir) = env::var_os("LOCALAPPDATA") {
+            PathBuf::from(dir).join("gobs").join("cache")
+        } else if let Some(dir) = env::var_os("HOME") {
+            PathBuf::from(dir).join(".cache").join("gobs")
+        } else {
+            return None;
+        };
+
+        let name = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+
+        Some(cache_dir.join(name))
+    }
+
+    fn pipeline_cache_path(name: &str, config: &GobsConfig) -> Option<PathBuf> {
+        if !config.get_bool(RenderHalConfig::PipelineCache) {
+            return None;
+        }
+
+        Some(Self::cache_dir(name, config)?.join("pipeline_cache.bin"))
+    }
+
+    fn load_pipeline_cache(
+        name: &str,
+        config: &GobsConfig,
+        device: &vk::Device,
+    ) -> Option<PathBuf> {
+        let path = Self::pipeline_cache_path(name, config)?;
+
+        match fs::read(&path) {
+            Ok(data) if device.load_pipeline_cache(&data) => {
+                tracing::info!(target: logger::INIT, "Pipeline cache loaded from {:?} ({} bytes)", path, data.len());
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                tracing::info!(target: logger::INIT, "No pipeline cache found at {:?}", path);
+            }
+            Err(e) => {
+                tracing::warn!(target: logger::INIT, "Cannot read pipeline cache {:?}: {}", path, e);
+            }
+        }
+
+        Some(path)
+    }
+
+    fn save_pipeline_cache(&self) {
+        let (hits, misses) = self.device.pipeline_cache.stats();
+        tracing::info!(target: logger::INIT, "Pipeline cache: {} hits, {} misses", hits, misses);
+
+        let Some(path) = &self.pipeline_cache else {
+            return;
+        };
+
+        let data = self.device.save_pipeline_cache();
+
+        let tmp_path = path.with_extension("tmp");
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&tmp_path, &data))
+            .and_then(|_| fs::rename(&tmp_path, path));
+
+        match result {
+            Ok(_) => {
+                tracing::info!(target: logger::INIT, "Pipeline cache saved to {:?} ({} bytes)", path, data.len())
+            }
+            Err(e) => {
+                tracing::warn!(target: logger::INIT, "Cannot save pipeline cache {:?}: {}", path, e)
+            }
+        }
+    }
+
+    /// Convert a backend error, writing a crash report if the device is lost or hung
+    pub(crate) fn map_error(&self, err: vk::error::VulkanError) -> RenderBackendError {
+        self.check_error(err.into())
+    }
+
+    fn check_error(&self, err: RenderBackendError) -> RenderBackendError {
+        match err {
+            RenderBackendError::DeviceLost => self.device_lost(),
+            RenderBackendError::Timeout => {
+                self.report_crash("GPU timeout");
+
+                RenderBackendError::Timeout
+            }
+            err => err,
+        }
+    }
+
+    /// Report the first device loss with fault info and breadcrumbs
+    fn device_lost(&self) -> RenderBackendError {
+        self.report_crash("Device lost");
+
+        RenderBackendError::DeviceLost
+    }
+
+    fn report_crash(&self, reason: &str) {
+        if !self.crash_reported.swap(true, Ordering::Relaxed) {
+            self.write_crash_report(reason);
+        }
+    }
+
+    fn crash_report(&self, reason: &str) -> String {
+        let adapter = self.adapter();
+
+        let mut report = String::new();
+        let _ = writeln!(report, "{reason}");
+        let _ = writeln!(
+            report,
+            "Adapter: {} ({:?}, driver {}, Vulkan {})",
+            adapter.name, adapter.ty, adapter.driver, adapter.api_version
+        );
+        let _ = writeln!(report, "Features: {:?}", self.device.features.names());
+        let _ = writeln!(report);
+
+        match self.device.fault_info() {
+            Some(fault) => report.push_str(&fault.to_string()),
+            None => {
+                let _ = writeln!(report, "No device fault info");
+            }
+        }
+        let _ = writeln!(report);
+
+        let _ = writeln!(report, "Breadcrumbs:");
+        match &self.breadcrumbs {
+            Some(breadcrumbs) => {
+                for breadcrumb in breadcrumbs.collect() {
+                    let _ = writeln!(report, "  {breadcrumb}");
+                }
+            }
+            None => {
+                let _ = writeln!(report, "  disabled");
+            }
+        }
+
+        report
+    }
+
+    fn write_crash_report(&self, reason: &str) {
+        let report = self.crash_report(reason);
+        tracing::error!(target: logger::RENDER, "{}", report);
+
+        let Some(dir) = &self.crash_dir else {
+            return;
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        let path = dir.join(format!("crash_{timestamp}.txt"));
+
+        match fs::create_dir_all(dir).and_then(|_| fs::write(&path, &report)) {
+            Ok(_) => {
+                tracing::error!(target: logger::RENDER, "Crash report saved to {:?}", path)
+            }
+            Err(e) => {
+                tracing::warn!(target: logger::RENDER, "Cannot save crash report {:?}: {}", path, e)
+            }
+        }
+    }
+
+    fn create_device(
+        instance: Arc<vk::Instance>,
+        display: &Display,
+        config: &GobsConfig,
+    ) -> (Arc<vk::Device>, Vec<AdapterInfo>, usize) {
+        // fill_mode_non_solid is optional, wireframe falls back to fill mode
         let expected_features = vk::Features::default()
-            .fill_mode_non_solid()
             .shader_draw_parameters()
             .buffer_device_address()
             .descriptor_indexing()
@@ -369,18 +754,57 @@ impl VulkanHAL {
 
         tracing::info!(target: logger::INIT, "Requested features: {:?}", expected_features);
 
-        let p_device = instance
-            .find_adapter(&expected_features, display.surface.as_deref())
-            .unwrap();
+        let surface = display.surface.as_deref();
+
+        let adapters = instance
+            .enumerate_adapters(surface)
+            .iter()
+            .enumerate()
+            .map(|(index, p_device)| AdapterInfo {
+                index,
+                name: p_device.name.clone(),
+                ty: p_device.gpu_type,
+                driver: p_device.driver.clone(),
+                api_version: p_device.api_version.clone(),
+                vram: p_device.vram,
+                limits: p_device.limits,
+                features: p_device.features(&instance).names(),
+                suitable: p_device.supports_surface(surface)
+                    && p_device.check_features(&instance, &expected_features),
+            })
+            .collect::<Vec<_>>();
+
+        for adapter in &adapters {
+            tracing::info!(target: logger::INIT, "Adapter #{}: {} ({:?}, driver {}, Vulkan {}, suitable={})",
+                adapter.index, adapter.name, adapter.ty, adapter.driver, adapter.api_version, adapter.suitable);
+        }
+
+        let selector = AdapterSelector::from_env().unwrap_or_else(|| {
+            config
+                .get_string(RenderHalConfig::Adapter)
+                .parse()
+                .unwrap_or_default()
+        });
+
+        tracing::debug!(target: logger::INIT, "Adapter selection: {}", selector);
+
+        let (adapter, p_device) = instance
+            .select_adapter(&selector, &expected_features, surface)
+            .expect("No suitable adapter");
 
         tracing::info!(target: logger::INIT, "Using adapter {}", p_device.name);
 
-        vk::Device::new(instance.clone(), p_device, display.surface.as_deref()).unwrap()
+        let device = vk::Device::new(instance.clone(), p_device, surface).unwrap();
+
+        tracing::info!(target: logger::INIT, "Enabled features: {:?}", device.features.names());
+
+        (device, adapters, adapter)
     }
 }
 
 impl Drop for VulkanHAL {
     fn drop(&mut self) {
         self.device.wait();
+        self.save_pipeline_cache();
     }
 }
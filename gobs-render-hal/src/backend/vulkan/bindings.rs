@@ -116,11 +116,15 @@ impl BindingRegistry {
 
         debug_assert_eq!(resource.layout().bindings.len(), resource.sets());
 
-        let mut binding_idx = 0;
-
-        for ((ty, stage, count), bindsets) in
-            resource.layout().bindings.iter().zip(resource.bindsets())
+        for (binding_idx, ((ty, stage, count), bindsets)) in resource
+            .layout()
+            .bindings
+            .iter()
+            .zip(resource.bindsets())
+            .enumerate()
         {
+            let binding_idx = binding_idx as u32;
+
             debug_assert!(bindsets.len() <= *count as usize);
             for (handle, index) in bindsets.bindings() {
                 match ty {
@@ -137,6 +141,17 @@ impl BindingRegistry {
                         }
                     }
                     // compute data
+                    vk::DescriptorType::StorageBuffer => {
+                        if let Some(buffer) = registry.buffers.get(*handle) {
+                            update = update.bind_storage_buffer(
+                                binding_idx,
+                                *index as u32,
+                                &buffer.buffer,
+                                buffer.offset,
+                                buffer.len,
+                            );
+                        }
+                    }
                     vk::DescriptorType::StorageImage => {
                         if let Some(image) = registry.images.get(*handle) {
                             update = update.bind_image(
@@ -168,8 +183,6 @@ impl BindingRegistry {
                     vk::DescriptorType::ImageSampler => todo!(),
                 }
             }
-
-            binding_idx += count;
         }
 
         update
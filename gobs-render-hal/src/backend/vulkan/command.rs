@@ -4,11 +4,14 @@ use gobs_core::{ImageExtent2D, logger};
 use gobs_vulkan::{self as vk, descriptor::DescriptorSetUpdates};
 
 use crate::{
-    BindResource, BindingGroupLayout, CommandQueueType, Handle, ImageLayout, RenderHAL,
-    UniformData as _,
+    BindResource, BindingGroupLayout, CommandQueueType, DRAW_INDEXED_INDIRECT_SIZE, Handle,
+    ImageLayout, RenderBackendError, RenderHAL, UniformData as _,
     backend::{
         VulkanHAL, VulkanHALExt,
-        vulkan::pipeline::{self, VkPipeline},
+        vulkan::{
+            pipeline::{self, VkPipeline},
+            timeline::{OwnershipTransfer, QueueTimeline, TransferResource},
+        },
     },
     bindings::BindingLifetime,
     command::CommandBuffer,
@@ -19,10 +22,24 @@ pub struct VkCommandBuffer {
     pub frame_number: usize,
     pub fence: vk::sync::Fence,
     vertex_buffer_slot: usize,
+    queue_type: CommandQueueType,
+    timeline: Arc<QueueTimeline>,
+    waits: Vec<vk::sync::SemaphoreSubmit>,
+    releases: Vec<(TransferResource, CommandQueueType)>,
+    breadcrumbs: Option<Arc<vk::breadcrumbs::Breadcrumbs>>,
+    /// Breadcrumb markers of the open labels
+    markers: Vec<u32>,
 }
 
 impl VkCommandBuffer {
-    pub fn new(device: Arc<vk::Device>, name: &str, queue: Arc<vk::Queue>) -> Self {
+    pub(crate) fn new(
+        device: Arc<vk::Device>,
+        name: &str,
+        queue: Arc<vk::Queue>,
+        queue_type: CommandQueueType,
+        timeline: Arc<QueueTimeline>,
+        breadcrumbs: Option<Arc<vk::breadcrumbs::Breadcrumbs>>,
+    ) -> Self {
         let command_pool = vk::CommandPool::new(device.clone(), &queue.family);
 
         let command = vk::CommandBuffer::new(device.clone(), queue, command_pool, name);
@@ -32,6 +49,12 @@ impl VkCommandBuffer {
             frame_number: 0,
             fence: vk::sync::Fence::new(device.clone(), true, "Command buffer"),
             vertex_buffer_slot: 0,
+            queue_type,
+            timeline,
+            waits: Vec::new(),
+            releases: Vec::new(),
+            breadcrumbs,
+            markers: Vec::new(),
         }
     }
 }
@@ -48,9 +71,20 @@ impl CommandBuffer for VkCommandBuffer {
 
     fn begin_label(&mut self, label: &str) {
         self.command.begin_label(label);
+
+        if let Some(breadcrumbs) = &self.breadcrumbs {
+            self.markers
+                .push(breadcrumbs.begin(&mut self.command, label));
+        }
     }
 
     fn end_label(&mut self) {
+        if let Some(breadcrumbs) = &self.breadcrumbs
+            && let Some(marker) = self.markers.pop()
+        {
+            breadcrumbs.end(&mut self.command, marker);
+        }
+
         self.command.end_label();
     }
 
@@ -114,6 +148,23 @@ impl CommandBuffer for VkCommandBuffer {
             .copy_buffer_to_image(&src.buffer, dst, src.offset + offset);
     }
 
+    fn copy_buffer_to_image_mip(
+        &mut self,
+        hal: &dyn RenderHAL,
+        src: Handle,
+        dst: Handle,
+        offset: u64,
+        mip_level: u32,
+    ) {
+        let hal = hal.get();
+
+        let src = hal.registry.buffers.get(src).unwrap();
+        let dst = hal.registry.images.get(dst).unwrap();
+
+        self.command
+            .copy_buffer_to_image_mip(&src.buffer, dst, src.offset + offset, mip_level);
+    }
+
     fn copy_image_to_buffer(&mut self, hal: &dyn RenderHAL, src: Handle, dst: Handle, offset: u64) {
         let hal = hal.get();
 
@@ -123,6 +174,30 @@ impl CommandBuffer for VkCommandBuffer {
             .copy_image_to_buffer(src, &dst.buffer, dst.offset + offset);
     }
 
+    fn copy_image_region_to_buffer(
+        &mut self,
+        hal: &dyn RenderHAL,
+        src: Handle,
+        dst: Handle,
+        offset: u64,
+        x: u32,
+        y: u32,
+        extent: ImageExtent2D,
+    ) {
+        let hal = hal.get();
+
+        let src = hal.registry.images.get(src).unwrap();
+        let dst = &hal.registry.buffers.get(dst).unwrap();
+        self.command.copy_image_region_to_buffer(
+            src,
+            &dst.buffer,
+            dst.offset + offset,
+            x,
+            y,
+            extent,
+        );
+    }
+
     fn copy_image_to_image(&mut self, hal: &dyn RenderHAL, src: Handle, dst: Handle) {
         let hal = hal.get();
 
@@ -150,10 +225,59 @@ impl CommandBuffer for VkCommandBuffer {
         self.command.dispatch(x, y, z);
     }
 
+    fn compute_barrier(&mut self) {
+        self.command.compute_barrier();
+    }
+
     fn draw_indexed(&mut self, index_count: usize, instance_count: usize) {
         self.command.draw_indexed(index_count, instance_count);
     }
 
+    fn draw_indexed_indirect(
+        &mut self,
+        hal: &dyn RenderHAL,
+        buffer: Handle,
+        offset: u64,
+        draw_count: u32,
+    ) {
+        let hal = hal.get();
+
+        let buffer = hal.registry.buffers.get(buffer).unwrap();
+        self.command.draw_indexed_indirect(
+            &buffer.buffer,
+            offset,
+            draw_count,
+            DRAW_INDEXED_INDIRECT_SIZE as u32,
+        );
+    }
+
+    fn draw_indexed_indirect_count(
+        &mut self,
+        hal: &dyn RenderHAL,
+        buffer: Handle,
+        offset: u64,
+        count_buffer: Handle,
+        count_offset: u64,
+        max_draw_count: u32,
+    ) {
+        let hal = hal.get();
+
+        let buffer = hal.registry.buffers.get(buffer).unwrap();
+        let count_buffer = hal.registry.buffers.get(count_buffer).unwrap();
+        self.command.draw_indexed_indirect_count(
+            &buffer.buffer,
+            offset,
+            &count_buffer.buffer,
+            count_offset,
+            max_draw_count,
+            DRAW_INDEXED_INDIRECT_SIZE as u32,
+        );
+    }
+
+    fn indirect_barrier(&mut self) {
+        self.command.indirect_barrier();
+    }
+
     fn bind_pipeline(&mut self, hal: &dyn RenderHAL, pipeline: Handle) {
         let hal = hal.get();
 
@@ -227,14 +351,14 @@ impl CommandBuffer for VkCommandBuffer {
 
         let pipeline = &hal.registry.pipelines.get(pipeline).unwrap();
 
-        debug_assert!(constants.len() == pipeline.push_layout.uniform_layout().size());
+        debug_assert!(constants.len() == pipeline.push_size);
 
         self.command
             .push_constants(pipeline.pipeline.layout.clone(), constants);
     }
 
-    fn wait(&self) {
-        self.fence.wait();
+    fn wait(&self, hal: &dyn RenderHAL) -> Result<(), RenderBackendError> {
+        self.fence.wait().map_err(|err| hal.get().map_error(err))
     }
 
     fn reset(&mut self) {
@@ -246,57 +370,222 @@ impl CommandBuffer for VkCommandBuffer {
         }
         self.command.reset();
         self.vertex_buffer_slot = 0;
+        self.markers.clear();
     }
 
-    fn run_immediate(&mut self, label: &str, callback: &dyn Fn(&dyn CommandBuffer)) {
+    fn run_immediate(
+        &mut self,
+        label: &str,
+        callback: &dyn Fn(&dyn CommandBuffer),
+    ) -> Result<(), RenderBackendError> {
         self.reset();
 
         self.command.begin();
-        self.command.begin_label(label);
+        self.begin_label(label);
         callback(self);
-        self.command.end_label();
+        self.end_label();
         self.command.end();
-        self.command.submit2(None, None, &self.fence);
+        self.command.submit2(None, None, &self.fence)?;
 
-        self.wait();
+        Ok(self.fence.wait()?)
     }
 
-    fn run_immediate_mut(&mut self, label: &str, callback: &mut dyn FnMut(&mut dyn CommandBuffer)) {
+    fn run_immediate_mut(
+        &mut self,
+        label: &str,
+        callback: &mut dyn FnMut(&mut dyn CommandBuffer),
+    ) -> Result<(), RenderBackendError> {
         self.reset();
 
         self.command.begin();
-        self.command.begin_label(label);
+        self.begin_label(label);
         callback(self);
-        self.command.end_label();
+        self.end_label();
         self.command.end();
-        self.command.submit2(None, None, &self.fence);
+        self.command.submit2(None, None, &self.fence)?;
 
-        self.wait();
+        Ok(self.fence.wait()?)
     }
 
     fn set_viewport(&mut self, width: u32, height: u32) {
         self.command.set_viewport(width, height);
     }
 
-    fn submit_transfer(&mut self) {
-        self.command.submit2(None, None, &self.fence);
+    fn submit(&mut self, hal: &mut dyn RenderHAL) -> Result<u64, RenderBackendError> {
+        let hal = hal.get_mut();
+
+        let value = self.timeline.next();
+        tracing::trace!(target: logger::SYNC, "Submit {:?} with timeline value {}", self.queue_type, value);
+
+        let signal = [vk::sync::SemaphoreSubmit::timeline(
+            &self.timeline.semaphore,
+            value,
+            vk::pipelines::PipelineStage::AllCommands,
+        )];
+
+        let result = self.command.submit(&self.waits, &signal, &self.fence);
+        self.waits.clear();
+        result.map_err(|err| hal.map_error(err))?;
+
+        let src_family = self.command.queue_family();
+        for (resource, dst_queue) in self.releases.drain(..) {
+            hal.pending_transfers.push(OwnershipTransfer {
+                resource,
+                src_queue: self.queue_type,
+                src_family,
+                dst_queue,
+                value,
+            });
+        }
+
+        Ok(value)
     }
 
-    fn submit_graphics(&self, hal: &dyn RenderHAL, frame: usize) {
+    fn submit_graphics(
+        &mut self,
+        hal: &dyn RenderHAL,
+        frame: usize,
+    ) -> Result<u64, RenderBackendError> {
         let hal = hal.get();
 
+        let value = self.timeline.next();
+
         let swapchain_idx = hal.display.swapchain_idx;
-        tracing::trace!(target: logger::SYNC, "Submit with swapchain semaphore: {}, render semaphore: {}", frame, swapchain_idx);
-        let (wait, signal) = if hal.display.swapchain.is_some() {
-            let wait = Some(&hal.display.swapchain_semaphores[frame]);
-            let signal = Some(&hal.display.render_semaphores[swapchain_idx]);
+        tracing::trace!(target: logger::SYNC, "Submit with swapchain semaphore: {}, render semaphore: {}, timeline value: {}", frame, swapchain_idx, value);
+
+        let mut signal = vec![vk::sync::SemaphoreSubmit::timeline(
+            &self.timeline.semaphore,
+            value,
+            vk::pipelines::PipelineStage::AllCommands,
+        )];
+
+        if hal.display.swapchain.is_some() {
+            self.waits.push(vk::sync::SemaphoreSubmit::binary(
+                &hal.display.swapchain_semaphores[frame],
+                vk::pipelines::PipelineStage::ColorAttachmentOutput,
+            ));
+            signal.push(vk::sync::SemaphoreSubmit::binary(
+                &hal.display.render_semaphores[swapchain_idx],
+                vk::pipelines::PipelineStage::AllGraphics,
+            ));
+        }
 
-            (wait, signal)
-        } else {
-            (None, None)
-        };
+        let result = self.command.submit(&self.waits, &signal, &self.fence);
+        self.waits.clear();
+        result.map_err(|err| hal.map_error(err))?;
+
+        Ok(value)
+    }
+
+    fn wait_queue(&mut self, hal: &dyn RenderHAL, queue: CommandQueueType, value: u64) {
+        if queue == self.queue_type || value == 0 {
+            return;
+        }
 
-        self.command.submit2(wait, signal, &self.fence);
+        let hal = hal.get();
+
+        self.waits.push(vk::sync::SemaphoreSubmit::timeline(
+            &hal.timeline(queue).semaphore,
+            value,
+            vk::pipelines::PipelineStage::AllCommands,
+        ));
+    }
+
+    fn release_buffer(&mut self, hal: &mut dyn RenderHAL, buffer: Handle, dst: CommandQueueType) {
+        let hal = hal.get_mut();
+
+        let dst_family = hal.queue(dst).family.index;
+        if dst_family == self.command.queue_family() {
+            return;
+        }
+
+        let view = hal.registry.buffers.get(buffer).unwrap();
+        self.command.release_buffer(&view.buffer, dst_family);
+
+        self.releases.push((TransferResource::Buffer(buffer), dst));
+    }
+
+    fn release_image(
+        &mut self,
+        hal: &mut dyn RenderHAL,
+        image: Handle,
+        layout: ImageLayout,
+        dst: CommandQueueType,
+    ) {
+        let hal = hal.get_mut();
+
+        let dst_family = hal.queue(dst).family.index;
+        let vk_image = hal.registry.images.get_mut(image).unwrap();
+
+        if dst_family == self.command.queue_family() {
+            self.command.transition_image_layout(vk_image, layout);
+            return;
+        }
+
+        let src_layout = self.command.release_image(vk_image, layout, dst_family);
+
+        self.releases.push((
+            TransferResource::Image {
+                image,
+                src_layout,
+                dst_layout: layout,
+            },
+            dst,
+        ));
+    }
+
+    fn acquire_resources(&mut self, hal: &mut dyn RenderHAL) {
+        let hal = hal.get_mut();
+
+        let (transfers, pending): (Vec<_>, Vec<_>) = hal
+            .pending_transfers
+            .drain(..)
+            .partition(|transfer| transfer.dst_queue == self.queue_type);
+        hal.pending_transfers = pending;
+
+        // last release value per source queue
+        let mut wait_values: Vec<(CommandQueueType, u64)> = vec![];
+
+        for transfer in transfers {
+            match wait_values
+                .iter_mut()
+                .find(|(queue, _)| *queue == transfer.src_queue)
+            {
+                Some((_, value)) => *value = (*value).max(transfer.value),
+                None => wait_values.push((transfer.src_queue, transfer.value)),
+            }
+
+            match transfer.resource {
+                TransferResource::Buffer(buffer) => {
+                    if let Some(view) = hal.registry.buffers.get(buffer) {
+                        self.command
+                            .acquire_buffer(&view.buffer, transfer.src_family);
+                    }
+                }
+                TransferResource::Image {
+                    image,
+                    src_layout,
+                    dst_layout,
+                } => {
+                    if let Some(image) = hal.registry.images.get(image) {
+                        self.command.acquire_image(
+                            image,
+                            src_layout,
+                            dst_layout,
+                            transfer.src_family,
+                        );
+                    }
+                }
+            }
+        }
+
+        for (queue, value) in wait_values {
+            self.waits.push(vk::sync::SemaphoreSubmit::timeline(
+                &hal.timeline(queue).semaphore,
+                value,
+                vk::pipelines::PipelineStage::AllCommands,
+            ));
+        }
     }
 
     fn transition_image_layout(
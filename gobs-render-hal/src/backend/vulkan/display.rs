@@ -2,20 +2,14 @@ use std::sync::Arc;
 
 use winit::window::Window;
 
-use gobs_core::{ImageExtent2D, ImageFormat, logger};
+use gobs_core::{ImageExtent2D, logger};
 use gobs_vulkan::{
-    Queue,
-    device::Device,
-    images::{ColorSpace, Image},
-    instance::Instance,
-    surface::Surface,
-    swapchain::{PresentationMode, SwapChain},
+    Queue, device::Device, instance::Instance, surface::Surface, swapchain::SwapChain,
     sync::Semaphore,
 };
 
 use crate::{
-    Handle, RenderBackendError, RenderHAL,
-    backend::{VulkanHAL, vulkan::ResourcesRegistry},
+    DisplayConfig, DisplayInfo, Handle, RenderBackendError, backend::vulkan::ResourcesRegistry,
 };
 
 pub struct Display {
@@ -25,10 +19,11 @@ pub struct Display {
     pub(crate) swapchain_idx: usize,
     pub(crate) swapchain_semaphores: Vec<Semaphore>,
     pub(crate) render_semaphores: Vec<Semaphore>,
+    pub(crate) config: DisplayConfig,
 }
 
 impl Display {
-    pub fn new(instance: Arc<Instance>, window: Option<Window>) -> Self {
+    pub fn new(instance: Arc<Instance>, window: Option<Window>, config: DisplayConfig) -> Self {
         let surface =
             window.map(|window| Arc::new(Surface::new(instance.clone(), window).unwrap()));
 
@@ -39,6 +34,7 @@ impl Display {
             swapchain_idx: 0,
             swapchain_semaphores: Vec::new(), // per frames in flight
             render_semaphores: Vec::new(),    // per swapchain image
+            config,
         }
     }
 
@@ -49,12 +45,8 @@ impl Display {
         frames_in_flight: usize,
     ) {
         if let Some(surface) = &self.surface {
-            // TODO: hardcoded
-            let swapchain = Self::create_swapchain(
-                surface.clone(),
-                device.clone(),
-                &[ImageFormat::B8g8r8a8Unorm],
-            );
+            let swapchain =
+                Self::create_swapchain(surface.clone(), device.clone(), &self.config, None);
             self.swapchain_images = swapchain
                 .create_images()
                 .into_iter()
@@ -108,6 +100,54 @@ impl Display {
         }
     }
 
+    /// Recreate the swapchain with new present mode and format preferences
+    pub fn reconfigure(
+        &mut self,
+        registry: &mut ResourcesRegistry,
+        device: Arc<Device>,
+        config: DisplayConfig,
+    ) {
+        self.config = config;
+
+        if let Some(old_swapchain) = &self.swapchain
+            && let Some(surface) = &self.surface
+        {
+            let swapchain = Self::create_swapchain(
+                surface.clone(),
+                device.clone(),
+                &self.config,
+                Some(old_swapchain),
+            );
+
+            if swapchain.image_count != old_swapchain.image_count {
+                self.render_semaphores = (0..swapchain.image_count)
+                    .map(|_| Semaphore::new(device.clone(), "Render"))
+                    .collect();
+            }
+
+            for image in &self.swapchain_images {
+                registry.images.remove(*image);
+            }
+
+            self.swapchain_images = swapchain
+                .create_images()
+                .into_iter()
+                .map(|image| registry.images.insert(image))
+                .collect();
+
+            self.swapchain = Some(swapchain);
+        }
+    }
+
+    pub fn info(&self) -> Option<DisplayInfo> {
+        self.swapchain.as_ref().map(|swapchain| DisplayInfo {
+            present_mode: swapchain.present,
+            format: swapchain.format.format,
+            color_space: swapchain.format.color_space,
+            image_count: swapchain.image_count,
+        })
+    }
+
     pub(crate) fn acquire(
         &mut self,
         registry: &mut ResourcesRegistry,
@@ -153,14 +193,14 @@ impl Display {
     fn create_swapchain(
         surface: Arc<Surface>,
         device: Arc<Device>,
-        allowed_formats: &[ImageFormat],
+        config: &DisplayConfig,
+        old_swapchain: Option<&SwapChain>,
     ) -> SwapChain {
         let presents = surface.get_available_presentation_modes(device.clone());
 
-        let present = *presents
-            .iter()
-            .find(|&&p| p == PresentationMode::Fifo)
-            .unwrap();
+        tracing::debug!(target: logger::INIT, "Available present modes: {:?}", presents);
+
+        let present = config.select_present_mode(&presents);
 
         let caps = surface.get_capabilities(&device);
 
@@ -177,14 +217,13 @@ impl Display {
 
         let formats = surface.get_available_format(&device.p_device);
 
-        let format = *formats
-            .iter()
-            .find(|f| {
-                allowed_formats.contains(&f.format) && f.color_space == ColorSpace::SrgbNonlinear
-            })
-            .unwrap();
+        tracing::debug!(target: logger::INIT, "Available surface formats: {:?}", formats);
+
+        let format = config
+            .select_format(&formats)
+            .expect("No supported surface format");
 
-        tracing::debug!(target: logger::INIT, "Swapchain format: {:?}", format);
+        tracing::info!(target: logger::INIT, "Swapchain format: {:?}, present mode: {:?}", format, present);
 
         SwapChain::new(
             device.clone(),
@@ -192,7 +231,7 @@ impl Display {
             format,
             present,
             image_count,
-            None,
+            old_swapchain,
         )
     }
 
@@ -4,14 +4,16 @@ use std::sync::Arc;
 use indexmap::IndexMap;
 
 use gobs_core::{ImageFormat, logger};
-use gobs_resource::load;
 use gobs_vulkan as vk;
 
 use crate::BindingGroupType;
 use crate::data::AlignMode;
 use crate::{
     Handle, ObjectDataLayout, RenderHAL, UniformData, VertexAttribute,
-    backend::{VulkanHAL, VulkanHALExt, vulkan::bindings::vk_layout},
+    backend::{
+        VulkanHAL, VulkanHALExt,
+        vulkan::{bindings::vk_layout, shaders::ShaderRegistry},
+    },
     bindings::BindingGroupLayout,
     pipeline::{ComputePipelineBuilder, GraphicsPipelineBuilder},
 };
@@ -19,12 +21,14 @@ use crate::{
 pub struct VkPipeline {
     pub pipeline: vk::Pipeline,
     pub push_layout: ObjectDataLayout,
+    pub push_size: usize,
     pub descriptor_layout: IndexMap<BindingGroupType, Arc<BindingGroupLayout>>,
     pub vertex_attribute: VertexAttribute,
 }
 
 pub(crate) struct VkComputePipelineBuilder {
     device: Arc<vk::Device>,
+    shaders: Arc<ShaderRegistry>,
     builder: vk::ComputePipelineBuilder,
     descriptor_layouts: IndexMap<BindingGroupType, Arc<BindingGroupLayout>>,
     push_constants: usize,
@@ -33,9 +37,12 @@ pub(crate) struct VkComputePipelineBuilder {
 
 impl ComputePipelineBuilder for VkComputePipelineBuilder {
     fn shader(mut self: Box<Self>, filename: &str, entry: &str) -> Box<dyn ComputePipelineBuilder> {
-        let compute_file = load::get_asset_dir(filename, load::AssetType::SHADER).unwrap();
-        let compute_shader = vk::pipelines::Shader::from_file(
-            compute_file,
+        let compute_data = self
+            .shaders
+            .load(filename)
+            .unwrap_or_else(|e| panic!("Failed to load shader {}: {}", filename, e));
+        let compute_shader = vk::pipelines::Shader::from_bytes(
+            &compute_data.code,
             self.device.clone(),
             vk::pipelines::ShaderType::Compute,
         )
@@ -46,6 +53,12 @@ impl ComputePipelineBuilder for VkComputePipelineBuilder {
         self
     }
 
+    fn push_constants(mut self: Box<Self>, size: usize) -> Box<dyn ComputePipelineBuilder> {
+        self.push_constants = size;
+
+        self
+    }
+
     fn binding_group(
         mut self: Box<Self>,
         layout: Arc<BindingGroupLayout>,
@@ -76,6 +89,7 @@ impl ComputePipelineBuilder for VkComputePipelineBuilder {
         hal.registry.pipelines.insert(VkPipeline {
             pipeline,
             push_layout: self.push_layout,
+            push_size: self.push_constants,
             descriptor_layout: self.descriptor_layouts,
             vertex_attribute: VertexAttribute::empty(),
         })
@@ -83,9 +97,10 @@ impl ComputePipelineBuilder for VkComputePipelineBuilder {
 }
 
 impl VkComputePipelineBuilder {
-    pub(crate) fn new(name: &str, device: Arc<vk::Device>) -> Self {
+    pub(crate) fn new(name: &str, device: Arc<vk::Device>, shaders: Arc<ShaderRegistry>) -> Self {
         Self {
             device: device.clone(),
+            shaders,
             builder: vk::pipelines::Pipeline::compute_builder(name, device.clone()),
             descriptor_layouts: IndexMap::new(),
             push_constants: 0,
@@ -96,6 +111,7 @@ impl VkComputePipelineBuilder {
 
 pub(crate) struct VkGraphicsPipelineBuilder {
     device: Arc<vk::Device>,
+    shaders: Arc<ShaderRegistry>,
     builder: vk::GraphicsPipelineBuilder,
     descriptor_layouts: IndexMap<BindingGroupType, Arc<BindingGroupLayout>>,
     push_constants: usize,
@@ -109,10 +125,13 @@ impl GraphicsPipelineBuilder for VkGraphicsPipelineBuilder {
         filename: &str,
         entry: &str,
     ) -> Box<dyn GraphicsPipelineBuilder> {
-        let shader_file = load::get_asset_dir(filename, load::AssetType::SHADER).unwrap();
+        let shader_data = self
+            .shaders
+            .load(filename)
+            .unwrap_or_else(|e| panic!("Failed to load shader {}: {}", filename, e));
 
-        let shader = vk::pipelines::Shader::from_file(
-            shader_file,
+        let shader = vk::pipelines::Shader::from_bytes(
+            &shader_data.code,
             self.device.clone(),
             vk::pipelines::ShaderType::Vertex,
         )
@@ -128,10 +147,13 @@ impl GraphicsPipelineBuilder for VkGraphicsPipelineBuilder {
         filename: &str,
         entry: &str,
     ) -> Box<dyn GraphicsPipelineBuilder> {
-        let shader_file = load::get_asset_dir(filename, load::AssetType::SHADER).unwrap();
+        let shader_data = self
+            .shaders
+            .load(filename)
+            .unwrap_or_else(|e| panic!("Failed to load shader {}: {}", filename, e));
 
-        let shader = vk::pipelines::Shader::from_file(
-            shader_file,
+        let shader = vk::pipelines::Shader::from_bytes(
+            &shader_data.code,
             self.device.clone(),
             vk::pipelines::ShaderType::Fragment,
         )
@@ -303,6 +325,7 @@ impl GraphicsPipelineBuilder for VkGraphicsPipelineBuilder {
         hal.registry.pipelines.insert(VkPipeline {
             pipeline,
             push_layout: self.push_layout,
+            push_size: self.push_constants,
             descriptor_layout: self.descriptor_layouts,
             vertex_attribute: self.vertex_attributes,
         })
@@ -310,9 +333,10 @@ impl GraphicsPipelineBuilder for VkGraphicsPipelineBuilder {
 }
 
 impl VkGraphicsPipelineBuilder {
-    pub(crate) fn new(name: &str, device: Arc<vk::Device>) -> Self {
+    pub(crate) fn new(name: &str, device: Arc<vk::Device>, shaders: Arc<ShaderRegistry>) -> Self {
         Self {
             device: device.clone(),
+            shaders,
             builder: vk::pipelines::Pipeline::graphics_builder(name, device.clone()),
             descriptor_layouts: IndexMap::new(),
             push_constants: 0,
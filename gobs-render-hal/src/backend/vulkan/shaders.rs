@@ -0,0 +1,49 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use gobs_core::logger;
+use gobs_resource::load::{self, AssetType, LoadingError};
+use gobs_vulkan as vk;
+
+/// SPIR-V code of a shader file and its reflection
+pub(crate) struct ShaderSource {
+    pub(crate) code: Vec<u8>,
+    pub(crate) reflection: Option<Arc<vk::pipelines::ShaderReflection>>,
+}
+
+/// Shader files are read and reflected once, then shared by all the pipelines using them
+#[derive(Default)]
+pub(crate) struct ShaderRegistry {
+    sources: Mutex<HashMap<String, Arc<ShaderSource>>>,
+}
+
+impl ShaderRegistry {
+    pub(crate) fn load(&self, filename: &str) -> Result<Arc<ShaderSource>, LoadingError> {
+        let mut sources = self.sources.lock().unwrap();
+
+        if let Some(source) = sources.get(filename) {
+            return Ok(source.clone());
+        }
+
+        let code = load::load_binary_sync(filename, AssetType::SHADER)?;
+
+        let reflection = vk::pipelines::ShaderReflection::from_bytes(&code)
+            .inspect_err(|e| {
+                tracing::warn!(target: logger::RESOURCES, "Cannot reflect shader {}: {}", filename, e);
+            })
+            .ok()
+            .map(Arc::new);
+
+        let source = Arc::new(ShaderSource { code, reflection });
+        sources.insert(filename.to_string(), source.clone());
+
+        Ok(source)
+    }
+
+    /// Modified files are read again on their next use
+    pub(crate) fn invalidate(&self, filename: &str) {
+        self.sources.lock().unwrap().remove(filename);
+    }
+}
@@ -11,27 +11,32 @@ pub struct TextureRegistry {
     allocated: HashMap<Handle, usize>,
 }
 
-const SAMPLER_BINDING: usize = 0;
+/// Each texture is sampled with the sampler of the same index
+const SAMPLERS_BINDING: usize = 0;
 const TEXTURES_BINDING: usize = 1;
 
 impl TextureRegistry {
-    pub fn new(size: usize, sampler: Handle) -> Self {
+    pub fn new(size: usize) -> Self {
         let free_list = (0..size).rev().collect();
         let textures = (0..size).map(|_| None).collect();
 
         let layout = 
             BindingGroupLayout::new(BindingGroupType::BindlessTextures)
-            .add_binding(DescriptorType::Sampler, DescriptorStage::Fragment, 1)
+            .add_binding(
+                DescriptorType::Sampler,
+                DescriptorStage::Fragment,
+                size as u32,
+            )
             .add_binding(
                 DescriptorType::SampledImage,
                 DescriptorStage::Fragment,
                 size as u32,
             );
 
-        let binding = BindResource::new(layout).binding(sampler, 0).next();
+        let binding = BindResource::new(layout).next().next();
 
         debug_assert_eq!(binding.sets(), 2);
-        debug_assert_eq!(SAMPLER_BINDING, 0);
+        debug_assert_eq!(SAMPLERS_BINDING, 0);
         debug_assert_eq!(TEXTURES_BINDING, 1);
 
         Self {
@@ -50,29 +55,41 @@ impl TextureRegistry {
         self.free_list.pop().expect("Not enough texture slots")
     }
 
-    pub fn register(&mut self, texture: Handle) -> usize {
+    pub fn register(&mut self, texture: Handle, sampler: Handle) -> usize {
         if let Some(index) = self.allocated.get(&texture) {
             return *index;
         }
 
         let index = self.reserve_index();
 
-        self.register_with_index(texture, index);
+        self.register_with_index(texture, sampler, index);
 
         index
     }
 
-    pub fn register_with_index(&mut self, texture: Handle, index: usize) {
+    pub fn register_with_index(&mut self, texture: Handle, sampler: Handle, index: usize) {
         debug_assert!(!self.allocated.contains_key(&texture));
         debug_assert!(self.textures[index].is_none());
 
         self.textures[index] = Some(texture);
+        self.binding.add_binding(SAMPLERS_BINDING, sampler, index);
         self.binding.add_binding(TEXTURES_BINDING, texture, index);
         self.allocated.insert(texture, index);
     }
 
+    pub fn unregister(&mut self, texture: Handle) {
+        if let Some(index) = self.allocated.get(&texture).copied() {
+            self.free(index);
+        }
+    }
+
+    pub fn binding(&self) -> &BindResource {
+        &self.binding
+    }
+
     pub fn free(&mut self, index: usize) -> Option<Handle> {
         if let Some(handle) = self.textures[index].take() {
+            self.binding.remove_binding(SAMPLERS_BINDING, index);
             self.binding.remove_binding(TEXTURES_BINDING, index);
             self.free_list.push(index);
             self.allocated.remove(&handle);
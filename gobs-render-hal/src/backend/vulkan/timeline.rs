@@ -0,0 +1,54 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use gobs_vulkan as vk;
+
+use crate::{Handle, ImageLayout, command::CommandQueueType};
+
+/// Timeline semaphore signaled by each submission on a queue
+pub(crate) struct QueueTimeline {
+    pub(crate) semaphore: vk::sync::TimeLineSemaphore,
+    value: AtomicU64,
+}
+
+impl QueueTimeline {
+    pub(crate) fn new(device: Arc<vk::Device>, label: &str) -> Arc<Self> {
+        Arc::new(Self {
+            semaphore: vk::sync::TimeLineSemaphore::new(device, label, 0),
+            value: AtomicU64::new(0),
+        })
+    }
+
+    /// Value to be signaled by the next submission
+    pub(crate) fn next(&self) -> u64 {
+        self.value.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Value signaled by the last submission
+    pub(crate) fn value(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum TransferResource {
+    Buffer(Handle),
+    Image {
+        image: Handle,
+        src_layout: ImageLayout,
+        dst_layout: ImageLayout,
+    },
+}
+
+/// Resource released by a queue family, to be acquired by the destination queue
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OwnershipTransfer {
+    pub(crate) resource: TransferResource,
+    pub(crate) src_queue: CommandQueueType,
+    pub(crate) src_family: u32,
+    pub(crate) dst_queue: CommandQueueType,
+    /// Timeline value of the releasing submission
+    pub(crate) value: u64,
+}
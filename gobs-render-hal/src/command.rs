@@ -1,7 +1,12 @@
 use gobs_core::ImageExtent2D;
 
-use crate::{BindResource, Handle, ImageLayout, RenderHAL};
+use crate::{BindResource, Handle, ImageLayout, RenderBackendError, RenderHAL};
 
+/// Size of an indexed indirect draw command (index count, instance count,
+/// first index, vertex offset, first instance)
+pub const DRAW_INDEXED_INDIRECT_SIZE: usize = 5 * std::mem::size_of::<u32>();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum CommandQueueType {
     Graphics,
     Compute,
@@ -36,22 +41,92 @@ pub trait CommandBuffer {
         dst_offset: u64,
     );
     fn copy_buffer_to_image(&mut self, hal: &dyn RenderHAL, src: Handle, dst: Handle, offset: u64);
+    fn copy_buffer_to_image_mip(
+        &mut self,
+        hal: &dyn RenderHAL,
+        src: Handle,
+        dst: Handle,
+        offset: u64,
+        mip_level: u32,
+    );
     fn copy_image_to_buffer(&mut self, hal: &dyn RenderHAL, src: Handle, dst: Handle, offset: u64);
+    /// Copy the region of the image at (x, y), rows are tightly packed in the buffer
+    #[allow(clippy::too_many_arguments)]
+    fn copy_image_region_to_buffer(
+        &mut self,
+        hal: &dyn RenderHAL,
+        src: Handle,
+        dst: Handle,
+        offset: u64,
+        x: u32,
+        y: u32,
+        extent: ImageExtent2D,
+    );
     fn copy_image_to_image(&mut self, hal: &dyn RenderHAL, src: Handle, dst: Handle);
     fn dispatch(&mut self, x: u32, y: u32, z: u32);
+    fn compute_barrier(&mut self);
     fn draw_indexed(&mut self, index_count: usize, instance_count: usize);
+    /// Draw `draw_count` commands read from `buffer` at `offset`
+    fn draw_indexed_indirect(
+        &mut self,
+        hal: &dyn RenderHAL,
+        buffer: Handle,
+        offset: u64,
+        draw_count: u32,
+    );
+    /// Draw commands read from `buffer`, the count is read from `count_buffer`
+    fn draw_indexed_indirect_count(
+        &mut self,
+        hal: &dyn RenderHAL,
+        buffer: Handle,
+        offset: u64,
+        count_buffer: Handle,
+        count_offset: u64,
+        max_draw_count: u32,
+    );
+    /// Wait for compute shaders writing draw commands
+    fn indirect_barrier(&mut self);
     fn bind_pipeline(&mut self, hal: &dyn RenderHAL, pipeline: Handle);
     fn bind_vertex_buffer(&mut self, hal: &dyn RenderHAL, buffer: Handle);
     fn bind_index_buffer(&mut self, hal: &dyn RenderHAL, buffer: Handle);
     fn bind_resource(&mut self, hal: &mut dyn RenderHAL, pipeline: Handle, resource: &BindResource);
     fn push_constants(&mut self, hal: &dyn RenderHAL, pipeline: Handle, constants: &[u8]);
-    fn wait(&self);
+    /// Wait for the last submission. On error the GPU may still use the command resources
+    fn wait(&self, hal: &dyn RenderHAL) -> Result<(), RenderBackendError>;
     fn reset(&mut self);
-    fn run_immediate(&mut self, label: &str, callback: &dyn Fn(&dyn CommandBuffer));
-    fn run_immediate_mut(&mut self, label: &str, callback: &mut dyn FnMut(&mut dyn CommandBuffer));
+    fn run_immediate(
+        &mut self,
+        label: &str,
+        callback: &dyn Fn(&dyn CommandBuffer),
+    ) -> Result<(), RenderBackendError>;
+    fn run_immediate_mut(
+        &mut self,
+        label: &str,
+        callback: &mut dyn FnMut(&mut dyn CommandBuffer),
+    ) -> Result<(), RenderBackendError>;
     fn set_viewport(&mut self, width: u32, height: u32);
-    fn submit_transfer(&mut self);
-    fn submit_graphics(&self, hal: &dyn RenderHAL, frame: usize);
+    /// Submit to the command queue. Returns the timeline value signaled on completion
+    fn submit(&mut self, hal: &mut dyn RenderHAL) -> Result<u64, RenderBackendError>;
+    /// Submit with swapchain synchronization. Returns the timeline value signaled on completion
+    fn submit_graphics(
+        &mut self,
+        hal: &dyn RenderHAL,
+        frame: usize,
+    ) -> Result<u64, RenderBackendError>;
+    /// Make the next submission wait for the timeline value of another queue
+    fn wait_queue(&mut self, hal: &dyn RenderHAL, queue: CommandQueueType, value: u64);
+    /// Release ownership of the buffer to the destination queue
+    fn release_buffer(&mut self, hal: &mut dyn RenderHAL, buffer: Handle, dst: CommandQueueType);
+    /// Transition the image and release its ownership to the destination queue
+    fn release_image(
+        &mut self,
+        hal: &mut dyn RenderHAL,
+        image: Handle,
+        layout: ImageLayout,
+        dst: CommandQueueType,
+    );
+    /// Acquire resources released to this queue and wait for their release
+    fn acquire_resources(&mut self, hal: &mut dyn RenderHAL);
     fn transition_image_layout(
         &mut self,
         hal: &mut dyn RenderHAL,
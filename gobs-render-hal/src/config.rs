@@ -3,6 +3,22 @@ use gobs_core::{ConfigDefault, ConfigWriter as _, GobsConfig};
 pub enum RenderHalConfig {
     FramesInFlight,
     TextureArraySize,
+    /// Adapter to use: "auto", index, type (discrete, integrated, virtual, cpu) or name.
+    /// Overridden by the GOBS_ADAPTER environment variable
+    Adapter,
+    /// Persist compiled pipelines between runs
+    PipelineCache,
+    /// Directory of the pipeline cache and crash reports. Defaults to the user cache directory
+    CacheDir,
+    /// Number of command labels tracked on the GPU for crash reports. 0 to disable
+    Breadcrumbs,
+    /// Comma separated list of present modes by order of preference
+    /// (fifo, fifo_relaxed, mailbox, immediate). Fifo is used when none is available
+    PresentModes,
+    /// Comma separated list of swapchain formats by order of preference (e.g. B8g8r8a8Unorm)
+    SurfaceFormats,
+    /// Comma separated list of color spaces by order of preference (srgb, scrgb, hdr10, display_p3)
+    ColorSpaces,
 }
 
 impl AsRef<str> for RenderHalConfig {
@@ -10,6 +26,13 @@ impl AsRef<str> for RenderHalConfig {
         match self {
             RenderHalConfig::FramesInFlight => "config.render.hal.frames_in_flight",
             RenderHalConfig::TextureArraySize => "config.render.hal.textures.array_size",
+            RenderHalConfig::Adapter => "config.render.hal.adapter",
+            RenderHalConfig::PipelineCache => "config.render.hal.pipeline_cache",
+            RenderHalConfig::CacheDir => "config.render.hal.cache_dir",
+            RenderHalConfig::Breadcrumbs => "config.render.hal.breadcrumbs",
+            RenderHalConfig::PresentModes => "config.render.hal.display.present_modes",
+            RenderHalConfig::SurfaceFormats => "config.render.hal.display.formats",
+            RenderHalConfig::ColorSpaces => "config.render.hal.display.color_spaces",
         }
     }
 }
@@ -18,5 +41,12 @@ impl ConfigDefault for RenderHalConfig {
     fn register_defaults(config: &mut GobsConfig) {
         config.set_int(RenderHalConfig::FramesInFlight, 2);
         config.set_int(RenderHalConfig::TextureArraySize, 256);
+        config.set_string(RenderHalConfig::Adapter, "auto");
+        config.set_bool(RenderHalConfig::PipelineCache, true);
+        config.set_string(RenderHalConfig::CacheDir, "");
+        config.set_int(RenderHalConfig::Breadcrumbs, 256);
+        config.set_string(RenderHalConfig::PresentModes, "fifo");
+        config.set_string(RenderHalConfig::SurfaceFormats, "B8g8r8a8Unorm");
+        config.set_string(RenderHalConfig::ColorSpaces, "srgb");
     }
 }
@@ -22,6 +22,7 @@ pub enum Attribute {
     Vec2F,
     Vec3F,
     Vec4F,
+    Vec4U,
     Mat3F,
     Mat4F,
 }
@@ -36,6 +37,7 @@ impl Attribute {
             Attribute::Vec2F => 8,
             Attribute::Vec3F => 12,
             Attribute::Vec4F => 16,
+            Attribute::Vec4U => 16,
             Attribute::Mat3F => 48,
             Attribute::Mat4F => 64,
         }
@@ -52,6 +54,7 @@ impl Attribute {
                 Attribute::Vec2F => 4,
                 Attribute::Vec3F => 4,
                 Attribute::Vec4F => 4,
+                Attribute::Vec4U => 4,
                 Attribute::Mat3F => 4,
                 Attribute::Mat4F => 4,
             },
@@ -63,6 +66,7 @@ impl Attribute {
                 Attribute::Vec2F => 8,
                 Attribute::Vec3F => 16,
                 Attribute::Vec4F => 16,
+                Attribute::Vec4U => 16,
                 Attribute::Mat3F => 16,
                 Attribute::Mat4F => 16,
             },
@@ -116,6 +120,7 @@ pub enum AttributeData {
     Vec2F([f32; 2]),
     Vec3F([f32; 3]),
     Vec4F([f32; 4]),
+    Vec4U([u32; 4]),
     Mat3F([[f32; 3]; 3]),
     Mat4F([[f32; 4]; 4]),
 }
@@ -130,6 +135,7 @@ impl AttributeData {
             AttributeData::Vec2F(_) => Attribute::Vec2F,
             AttributeData::Vec3F(_) => Attribute::Vec3F,
             AttributeData::Vec4F(_) => Attribute::Vec4F,
+            AttributeData::Vec4U(_) => Attribute::Vec4U,
             AttributeData::Mat3F(_) => Attribute::Mat3F,
             AttributeData::Mat4F(_) => Attribute::Mat4F,
         }
@@ -144,6 +150,7 @@ impl AttributeData {
             AttributeData::Vec2F(d) => data.write(bytemuck::cast_slice(d)),
             AttributeData::Vec3F(d) => data.write(bytemuck::cast_slice(d)),
             AttributeData::Vec4F(d) => data.write(bytemuck::cast_slice(d)),
+            AttributeData::Vec4U(d) => data.write(bytemuck::cast_slice(d)),
             AttributeData::Mat3F(d) => {
                 // mat3 is padded as mat3x4
                 let d2 = &[
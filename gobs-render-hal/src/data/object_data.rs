@@ -2,10 +2,26 @@ use serde::{Deserialize, Serialize};
 
 use crate::data::{AlignMode, Attribute, UniformLayout, uniform::UniformData};
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum ObjectDataProp {
     WorldMatrix,
     VertexBufferAddress,
+    /// Object table of indirect draws, indexed by the instance index
+    ObjectBufferAddress,
+    /// Material table of bindless materials
+    MaterialBufferAddress,
+    /// Index of the object material in the material table
+    MaterialIndex,
+    /// Id written to the picking attachment
+    PickId,
+    /// Joint matrices of skinned meshes, in the joint table of the frame
+    JointBufferAddress,
+    /// Morph target displacements of the mesh, grouped by vertex
+    MorphTargetAddress,
+    /// Morph target weights of the object, in the weight table of the frame
+    MorphWeightAddress,
+    /// Number of morph targets, 0 when the object is not morphed
+    MorphTargetCount,
 }
 
 #[derive(Clone, Debug)]
@@ -34,6 +50,30 @@ impl UniformData<ObjectDataProp> for ObjectDataLayout {
             ObjectDataProp::VertexBufferAddress => {
                 self.uniform_layout = self.uniform_layout.prop("buffer_reference", Attribute::U64);
             }
+            ObjectDataProp::ObjectBufferAddress => {
+                self.uniform_layout = self.uniform_layout.prop("object_buffer", Attribute::U64);
+            }
+            ObjectDataProp::MaterialBufferAddress => {
+                self.uniform_layout = self.uniform_layout.prop("material_buffer", Attribute::U64);
+            }
+            ObjectDataProp::MaterialIndex => {
+                self.uniform_layout = self.uniform_layout.prop("material_index", Attribute::U32);
+            }
+            ObjectDataProp::PickId => {
+                self.uniform_layout = self.uniform_layout.prop("pick_id", Attribute::U32);
+            }
+            ObjectDataProp::JointBufferAddress => {
+                self.uniform_layout = self.uniform_layout.prop("joint_buffer", Attribute::U64);
+            }
+            ObjectDataProp::MorphTargetAddress => {
+                self.uniform_layout = self.uniform_layout.prop("morph_targets", Attribute::U64);
+            }
+            ObjectDataProp::MorphWeightAddress => {
+                self.uniform_layout = self.uniform_layout.prop("morph_weights", Attribute::U64);
+            }
+            ObjectDataProp::MorphTargetCount => {
+                self.uniform_layout = self.uniform_layout.prop("morph_count", Attribute::U32);
+            }
         }
 
         self
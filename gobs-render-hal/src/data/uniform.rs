@@ -96,6 +96,10 @@ impl UniformLayout {
         self.size
     }
 
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
     pub fn data(&self, props: &[AttributeData]) -> Vec<u8> {
         let mut data = Vec::new();
 
@@ -1,5 +1,5 @@
 use bitflags::bitflags;
-use glam::{Vec2, Vec3};
+use glam::{UVec4, Vec2, Vec3, Vec4};
 use serde::{Deserialize, Serialize};
 
 use gobs_core::{Color, Transform, data::fixed_buffer::DataBuffer as _};
@@ -21,6 +21,9 @@ bitflags! {
         const NORMAL_TEXTURE = 1 << 4;
         const TANGENT = 1 << 5;
         const BITANGENT = 1 << 6;
+        /// Indices of the 4 joints influencing a skinned vertex
+        const JOINTS = 1 << 7;
+        const WEIGHTS = 1 << 8;
     }
 }
 
@@ -40,6 +43,8 @@ impl VertexAttribute {
             VertexAttribute::NORMAL_TEXTURE => Attribute::Vec2F,
             VertexAttribute::TANGENT => Attribute::Vec3F,
             VertexAttribute::BITANGENT => Attribute::Vec3F,
+            VertexAttribute::JOINTS => Attribute::Vec4U,
+            VertexAttribute::WEIGHTS => Attribute::Vec4F,
             _ => unimplemented!(),
         }
     }
@@ -72,6 +77,8 @@ impl From<VertexAttribute> for VertexAttributeFormat {
             VertexAttribute::NORMAL_TEXTURE => VertexAttributeFormat::Vec2,
             VertexAttribute::TANGENT => VertexAttributeFormat::Vec3,
             VertexAttribute::BITANGENT => VertexAttributeFormat::Vec3,
+            VertexAttribute::JOINTS => VertexAttributeFormat::UVec4,
+            VertexAttribute::WEIGHTS => VertexAttributeFormat::Vec4,
             _ => unimplemented!(),
         }
     }
@@ -160,6 +167,28 @@ impl VertexData {
         self.data[VertexAttribute::BITANGENT.idx()] = AttributeData::Vec3F(bitangent.into())
     }
 
+    pub fn joints(&self) -> UVec4 {
+        match self.data[VertexAttribute::JOINTS.idx()] {
+            AttributeData::Vec4U(v) => v.into(),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_joints(&mut self, joints: UVec4) {
+        self.data[VertexAttribute::JOINTS.idx()] = AttributeData::Vec4U(joints.into())
+    }
+
+    pub fn weights(&self) -> Vec4 {
+        match self.data[VertexAttribute::WEIGHTS.idx()] {
+            AttributeData::Vec4F(v) => v.into(),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_weights(&mut self, weights: Vec4) {
+        self.data[VertexAttribute::WEIGHTS.idx()] = AttributeData::Vec4F(weights.into())
+    }
+
     pub fn transform(&self, transform: Transform) -> VertexData {
         let mut vertex = self.clone();
 
@@ -204,6 +233,8 @@ pub struct VertexDataBuilder {
     pub normal_texture: Vec2,
     pub tangent: Vec3,
     pub bitangent: Vec3,
+    pub joints: UVec4,
+    pub weights: Vec4,
 }
 
 impl VertexDataBuilder {
@@ -216,6 +247,8 @@ impl VertexDataBuilder {
             normal_texture: Vec2::splat(0.),
             tangent: Vec3::splat(0.),
             bitangent: Vec3::splat(0.),
+            joints: UVec4::ZERO,
+            weights: Vec4::ZERO,
         }
     }
 
@@ -261,6 +294,18 @@ impl VertexDataBuilder {
         self
     }
 
+    pub fn joints(&mut self, joints: UVec4) -> &mut Self {
+        self.joints = joints;
+
+        self
+    }
+
+    pub fn weights(&mut self, weights: Vec4) -> &mut Self {
+        self.weights = weights;
+
+        self
+    }
+
     pub fn build(&mut self) -> VertexData {
         let data = [
             AttributeData::Vec3F(self.position.into()),
@@ -270,6 +315,8 @@ impl VertexDataBuilder {
             AttributeData::Vec2F(self.normal_texture.into()),
             AttributeData::Vec3F(self.tangent.into()),
             AttributeData::Vec3F(self.bitangent.into()),
+            AttributeData::Vec4U(self.joints.into()),
+            AttributeData::Vec4F(self.weights.into()),
         ];
 
         VertexData { data }
@@ -284,11 +331,12 @@ impl Default for VertexDataBuilder {
 
 #[cfg(test)]
 mod tests {
+    use glam::{UVec4, Vec4};
     use tracing::Level;
     use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
 
     use crate::{
-        VertexAttribute,
+        VertexAttribute, VertexData,
         data::{AlignMode, Attribute},
     };
 
@@ -334,5 +382,39 @@ mod tests {
             VertexAttribute::NORMAL | VertexAttribute::TANGENT | VertexAttribute::BITANGENT;
         let _offsets = Attribute::offsets(&vertex_attributes.attributes(), mode);
         assert_eq!(vertex_attributes.size(mode), 36);
+
+        let vertex_attributes =
+            VertexAttribute::POSITION | VertexAttribute::JOINTS | VertexAttribute::WEIGHTS;
+        let offsets = Attribute::offsets(&vertex_attributes.attributes(), mode);
+        assert_eq!(vertex_attributes.size(mode), 44);
+        assert_eq!(offsets, [0, 12, 28]);
+        assert_eq!(
+            vertex_attributes.offset_of(VertexAttribute::WEIGHTS, mode),
+            Some(28)
+        );
+    }
+
+    #[test]
+    fn test_vertex_skin() {
+        setup();
+
+        let mut vertex = VertexData::builder()
+            .joints(UVec4::new(1, 2, 0, 0))
+            .weights(Vec4::new(0.25, 0.75, 0., 0.))
+            .build();
+        assert_eq!(vertex.joints(), UVec4::new(1, 2, 0, 0));
+        assert_eq!(vertex.weights(), Vec4::new(0.25, 0.75, 0., 0.));
+
+        vertex.set_joints(UVec4::splat(3));
+        assert_eq!(vertex.joints(), UVec4::splat(3));
+
+        let mut data = vec![];
+        VertexData::copy_data(
+            &[vertex],
+            VertexAttribute::JOINTS,
+            &mut data,
+            AlignMode::Scalar,
+        );
+        assert_eq!(data, bytemuck::cast_slice::<u32, u8>(&[3, 3, 3, 3]));
     }
 }
@@ -0,0 +1,261 @@
+use std::{fmt::Display, str::FromStr};
+
+use gobs_core::{ConfigReader as _, GobsConfig, ImageFormat, logger};
+use gobs_vulkan::{images::ColorSpace, surface::SurfaceFormat, swapchain::PresentationMode};
+
+use crate::RenderHalConfig;
+
+/// Swapchain preferences. Each list is ordered by preference, the first
+/// value supported by the surface is selected
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayConfig {
+    pub present_modes: Vec<PresentationMode>,
+    pub formats: Vec<ImageFormat>,
+    pub color_spaces: Vec<ColorSpace>,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            present_modes: vec![PresentationMode::Fifo],
+            formats: vec![ImageFormat::B8g8r8a8Unorm],
+            color_spaces: vec![ColorSpace::SrgbNonlinear],
+        }
+    }
+}
+
+impl DisplayConfig {
+    pub fn new(config: &GobsConfig) -> Self {
+        let default = Self::default();
+
+        let present_modes = Self::parse_list(&config.get_string(RenderHalConfig::PresentModes));
+        let formats = Self::parse_list(&config.get_string(RenderHalConfig::SurfaceFormats));
+        let color_spaces = Self::parse_list(&config.get_string(RenderHalConfig::ColorSpaces));
+
+        Self {
+            present_modes: if present_modes.is_empty() {
+                default.present_modes
+            } else {
+                present_modes
+            },
+            formats: if formats.is_empty() {
+                default.formats
+            } else {
+                formats
+            },
+            color_spaces: if color_spaces.is_empty() {
+                default.color_spaces
+            } else {
+                color_spaces
+            },
+        }
+    }
+
+    /// Prefer tearing-free low latency presentation, fall back to vsync
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.present_modes = if vsync {
+            vec![PresentationMode::Fifo]
+        } else {
+            vec![PresentationMode::Mailbox, PresentationMode::Immediate]
+        };
+
+        self
+    }
+
+    pub fn present_modes(mut self, present_modes: &[PresentationMode]) -> Self {
+        self.present_modes = present_modes.to_vec();
+
+        self
+    }
+
+    pub fn formats(mut self, formats: &[ImageFormat], color_spaces: &[ColorSpace]) -> Self {
+        self.formats = formats.to_vec();
+        self.color_spaces = color_spaces.to_vec();
+
+        self
+    }
+
+    fn parse_list<T>(value: &str) -> Vec<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        value
+            .split(',')
+            .filter(|item| !item.trim().is_empty())
+            .filter_map(|item| match item.parse() {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!(target: logger::CONFIG, "Ignore display option {}: {}", item, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Fifo is always supported
+    pub(crate) fn select_present_mode(&self, available: &[PresentationMode]) -> PresentationMode {
+        self.present_modes
+            .iter()
+            .find(|mode| available.contains(mode))
+            .copied()
+            .unwrap_or(PresentationMode::Fifo)
+    }
+
+    /// Color spaces take precedence over formats. Falls back to the first sRGB format
+    pub(crate) fn select_format(&self, available: &[SurfaceFormat]) -> Option<SurfaceFormat> {
+        self.color_spaces
+            .iter()
+            .find_map(|&color_space| {
+                self.formats.iter().find_map(|&format| {
+                    available
+                        .iter()
+                        .find(|f| f.format == format && f.color_space == color_space)
+                })
+            })
+            .or_else(|| {
+                available
+                    .iter()
+                    .find(|f| f.color_space == ColorSpace::SrgbNonlinear)
+            })
+            .or(available.first())
+            .copied()
+    }
+}
+
+/// Swapchain settings actually in use
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayInfo {
+    pub present_mode: PresentationMode,
+    pub format: ImageFormat,
+    pub color_space: ColorSpace,
+    pub image_count: usize,
+}
+
+impl DisplayInfo {
+    pub fn is_hdr(&self) -> bool {
+        self.color_space.is_hdr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_core::{ConfigWriter as _, GobsConfig, ImageFormat};
+    use gobs_vulkan::{images::ColorSpace, surface::SurfaceFormat, swapchain::PresentationMode};
+
+    use crate::{RenderHalConfig, display::DisplayConfig};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn surface_format(format: ImageFormat, color_space: ColorSpace) -> SurfaceFormat {
+        SurfaceFormat {
+            format,
+            color_space,
+        }
+    }
+
+    #[test]
+    fn test_display_config() {
+        setup();
+
+        let mut config = GobsConfig::default();
+        config.register::<RenderHalConfig>();
+        assert_eq!(DisplayConfig::new(&config), DisplayConfig::default());
+
+        config.set_string(RenderHalConfig::PresentModes, "mailbox, immediate,vsync");
+        config.set_string(
+            RenderHalConfig::SurfaceFormats,
+            "R16g16b16a16Sfloat,A2b10g10r10UnormPack32",
+        );
+        config.set_string(RenderHalConfig::ColorSpaces, "scrgb,hdr10");
+        let display_config = DisplayConfig::new(&config);
+        assert_eq!(
+            display_config.present_modes,
+            vec![PresentationMode::Mailbox, PresentationMode::Immediate]
+        );
+        assert_eq!(
+            display_config.formats,
+            vec![
+                ImageFormat::R16g16b16a16Sfloat,
+                ImageFormat::A2b10g10r10UnormPack32
+            ]
+        );
+        assert_eq!(
+            display_config.color_spaces,
+            vec![ColorSpace::ExtendedSrgbLinear, ColorSpace::Hdr10St2084]
+        );
+
+        config.set_string(RenderHalConfig::PresentModes, "");
+        assert_eq!(
+            DisplayConfig::new(&config).present_modes,
+            vec![PresentationMode::Fifo]
+        );
+    }
+
+    #[test]
+    fn test_select_present_mode() {
+        setup();
+
+        let config = DisplayConfig::default().vsync(false);
+
+        let available = [PresentationMode::Fifo, PresentationMode::Immediate];
+        assert_eq!(
+            config.select_present_mode(&available),
+            PresentationMode::Immediate
+        );
+
+        let available = [PresentationMode::Fifo, PresentationMode::Mailbox];
+        assert_eq!(
+            config.select_present_mode(&available),
+            PresentationMode::Mailbox
+        );
+
+        let available = [PresentationMode::Fifo];
+        assert_eq!(
+            config.select_present_mode(&available),
+            PresentationMode::Fifo
+        );
+    }
+
+    #[test]
+    fn test_select_format() {
+        setup();
+
+        let available = [
+            surface_format(ImageFormat::B8g8r8a8Srgb, ColorSpace::SrgbNonlinear),
+            surface_format(ImageFormat::B8g8r8a8Unorm, ColorSpace::SrgbNonlinear),
+            surface_format(ImageFormat::A2b10g10r10UnormPack32, ColorSpace::Hdr10St2084),
+        ];
+
+        let config = DisplayConfig::default();
+        let format = config.select_format(&available).unwrap();
+        assert_eq!(format.format, ImageFormat::B8g8r8a8Unorm);
+
+        let config = DisplayConfig::default().formats(
+            &[
+                ImageFormat::R16g16b16a16Sfloat,
+                ImageFormat::A2b10g10r10UnormPack32,
+            ],
+            &[ColorSpace::ExtendedSrgbLinear, ColorSpace::Hdr10St2084],
+        );
+        let format = config.select_format(&available).unwrap();
+        assert_eq!(format.format, ImageFormat::A2b10g10r10UnormPack32);
+        assert_eq!(format.color_space, ColorSpace::Hdr10St2084);
+
+        // HDR not available
+        let format = config.select_format(&available[..2]).unwrap();
+        assert_eq!(format.format, ImageFormat::B8g8r8a8Srgb);
+        assert_eq!(format.color_space, ColorSpace::SrgbNonlinear);
+
+        assert!(config.select_format(&[]).is_none());
+    }
+}
@@ -5,5 +5,19 @@ use gobs_vulkan::error::VulkanError;
 #[derive(Debug, Error)]
 pub enum RenderBackendError {
     #[error("vulkan error")]
-    VulkanBackendError(#[from] VulkanError),
+    VulkanBackendError(VulkanError),
+    #[error("device lost")]
+    DeviceLost,
+    #[error("GPU timeout")]
+    Timeout,
+}
+
+impl From<VulkanError> for RenderBackendError {
+    fn from(err: VulkanError) -> Self {
+        match err {
+            VulkanError::DeviceLost => RenderBackendError::DeviceLost,
+            VulkanError::Timeout => RenderBackendError::Timeout,
+            err => RenderBackendError::VulkanBackendError(err),
+        }
+    }
 }
@@ -6,8 +6,9 @@ use winit::window::Window;
 use gobs_core::{GobsConfig, ImageExtent2D, ImageFormat, SamplerFilter};
 
 use crate::{
-    BindingGroupLayout, BindingGroupType, CommandQueueType, ImageUsage, ObjectDataLayout,
-    RenderBackendError, VertexAttribute,
+    AdapterInfo, BindResource, BindingGroupLayout, BindingGroupType, CommandQueueType,
+    DisplayConfig, DisplayInfo, Features, ImageUsage, MemoryReport, ObjectDataLayout,
+    RenderBackendError, ShaderReflection, VertexAttribute,
     backend::VulkanHAL,
     command::CommandBuffer,
     pipeline::{ComputePipelineBuilder, GraphicsPipelineBuilder},
@@ -31,6 +32,11 @@ pub enum BufferType {
     Staging,
     StagingDst,
     Uniform,
+    Storage,
+    /// Storage updated by the host every frame
+    HostStorage,
+    /// Indirect draw commands
+    Indirect,
 }
 
 pub trait RenderHAL {
@@ -43,6 +49,8 @@ pub trait RenderHAL {
 
     fn create_buffer(&mut self, name: &str, size: usize, ty: BufferType) -> Handle;
     fn upload_buffer(&mut self, buffer: Handle, data: &[u8], offset: u64);
+    /// Read back a host visible buffer
+    fn download_buffer(&self, buffer: Handle, data: &mut Vec<u8>);
     fn get_buffer_address(&self, buffer: Handle) -> u64;
     fn destroy_buffer(&mut self, buffer: Handle);
 
@@ -53,11 +61,23 @@ pub trait RenderHAL {
         usage: ImageUsage,
         extent: ImageExtent2D,
     ) -> Handle;
+    fn create_image_with_mips(
+        &mut self,
+        name: &str,
+        format: ImageFormat,
+        usage: ImageUsage,
+        extent: ImageExtent2D,
+        mip_levels: u32,
+    ) -> Handle;
     fn invalidate_image(&mut self, image: Handle);
     fn get_image_extent(&self, image: Handle) -> ImageExtent2D;
     fn destroy_image(&mut self, image: Handle);
-    fn register_texture(&mut self, image: Handle) -> usize;
+    /// The texture is sampled with its own sampler in the bindless textures
+    fn register_texture(&mut self, image: Handle, sampler: Handle) -> usize;
     fn allocate_texture_index(&mut self) -> usize;
+    fn unregister_texture(&mut self, image: Handle);
+    /// Descriptor set of the registered textures, indexed by their texture index
+    fn bindless_textures(&self) -> BindResource;
 
     fn create_sampler(&mut self, mag_filter: SamplerFilter, min_filter: SamplerFilter) -> Handle;
     fn destroy_sampler(&mut self, sampler: Handle);
@@ -67,6 +87,10 @@ pub trait RenderHAL {
 
     fn create_graphics_pipeline(&self, name: &str) -> Box<dyn GraphicsPipelineBuilder>;
     fn create_compute_pipeline(&self, name: &str) -> Box<dyn ComputePipelineBuilder>;
+    /// Reflection of a shader file, computed once when the file is first loaded
+    fn shader_reflection(&self, filename: &str) -> Option<Arc<ShaderReflection>>;
+    /// Forget the cached code of a modified shader file
+    fn invalidate_shader(&mut self, filename: &str);
     fn destroy_pipeline(&mut self, pipeline: Handle);
 
     fn get_pipeline_object_layout(&self, pipeline: Handle) -> &ObjectDataLayout;
@@ -81,6 +105,9 @@ pub trait RenderHAL {
     fn acquire(&mut self, frame: usize) -> Result<(), RenderBackendError>;
     fn present(&mut self) -> Result<(), RenderBackendError>;
     fn resize(&mut self);
+    /// Recreate the swapchain with the new preferences
+    fn set_display_config(&mut self, config: DisplayConfig);
+    fn display_info(&self) -> Option<DisplayInfo>;
     fn request_redraw(&mut self);
     fn is_minimized(&self) -> bool;
     fn lock_mouse(&mut self, lock: bool);
@@ -89,6 +116,19 @@ pub trait RenderHAL {
     fn get_extent(&self) -> ImageExtent2D;
 
     fn wait(&mut self);
+    /// Compute passes can be submitted on a dedicated queue
+    fn async_compute(&self) -> bool;
+    /// Timeline value of the last submission on the queue
+    fn queue_value(&self, queue: CommandQueueType) -> u64;
+
+    /// All adapters found on the system
+    fn adapters(&self) -> &[AdapterInfo];
+    /// Adapter in use
+    fn adapter(&self) -> &AdapterInfo;
+    /// Features enabled on the device
+    fn features(&self) -> Features;
+    /// Heap budgets, memory used by each resource category and allocator statistics
+    fn memory_report(&self) -> MemoryReport;
 
     fn info(&self);
 }
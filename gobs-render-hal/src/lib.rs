@@ -1,27 +1,39 @@
+mod adapter;
 #[allow(unused)]
 mod backend;
 mod bindings;
 mod command;
 mod config;
 mod data;
+mod display;
 mod error;
 mod hal;
+mod memory;
 mod pipeline;
 
 pub use gobs_vulkan::{
     descriptor::{DescriptorStage, DescriptorType},
-    images::{ImageLayout, ImageUsage},
+    feature::Features,
+    images::{ColorSpace, ImageLayout, ImageUsage},
+    memory::MemoryHeap,
+    physical::{AdapterSelector, Limits, PhysicalDeviceType},
     pipelines::{
-        BlendMode, CompareOp, CullMode, DynamicStateElem, FrontFace, PolygonMode, Rect2D, Viewport,
+        BlendMode, BlockLayout, BlockMember, CompareOp, CullMode, DynamicStateElem, FrontFace,
+        MemberType, PolygonMode, Rect2D, ReflectedDescriptorType, ShaderReflection, ShaderType,
+        Viewport,
     },
+    swapchain::PresentationMode,
 };
 
+pub use adapter::AdapterInfo;
 pub use bindings::{BindResource, BindingGroupLayout, BindingGroupType, BindingId};
-pub use command::{CommandBuffer, CommandQueueType};
+pub use command::{CommandBuffer, CommandQueueType, DRAW_INDEXED_INDIRECT_SIZE};
 pub use config::RenderHalConfig;
 pub use data::{
     AlignMode, Attribute, AttributeData, ObjectDataLayout, ObjectDataProp, UniformBuffer,
     UniformData, UniformLayout, VertexAttribute, VertexData,
 };
+pub use display::{DisplayConfig, DisplayInfo};
 pub use error::RenderBackendError;
 pub use hal::{BufferType, Handle, RenderHAL, create_hal};
+pub use memory::{CategoryUsage, MemoryCategory, MemoryReport};
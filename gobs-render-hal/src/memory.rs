@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use gobs_vulkan::{alloc::AllocatorStats, memory::MemoryHeap};
+
+use crate::{BufferType, Handle, ImageUsage};
+
+/// Resource category of an allocation
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryCategory {
+    /// Vertex and index buffers
+    Mesh,
+    Texture,
+    /// Graph attachments (color and depth images)
+    Attachment,
+    /// Upload and readback buffers
+    Staging,
+    Uniform,
+    /// Storage and indirect buffers
+    Storage,
+}
+
+impl MemoryCategory {
+    pub const ALL: [MemoryCategory; 6] = [
+        MemoryCategory::Mesh,
+        MemoryCategory::Texture,
+        MemoryCategory::Attachment,
+        MemoryCategory::Staging,
+        MemoryCategory::Uniform,
+        MemoryCategory::Storage,
+    ];
+}
+
+impl From<BufferType> for MemoryCategory {
+    fn from(ty: BufferType) -> Self {
+        match ty {
+            BufferType::Vertex | BufferType::Index => MemoryCategory::Mesh,
+            BufferType::Staging | BufferType::StagingDst => MemoryCategory::Staging,
+            BufferType::Uniform => MemoryCategory::Uniform,
+            BufferType::Storage | BufferType::HostStorage | BufferType::Indirect => {
+                MemoryCategory::Storage
+            }
+        }
+    }
+}
+
+impl From<ImageUsage> for MemoryCategory {
+    fn from(usage: ImageUsage) -> Self {
+        match usage {
+            ImageUsage::Texture | ImageUsage::File => MemoryCategory::Texture,
+            ImageUsage::Swapchain | ImageUsage::Color | ImageUsage::Depth => {
+                MemoryCategory::Attachment
+            }
+        }
+    }
+}
+
+/// Allocations of a category
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CategoryUsage {
+    pub count: usize,
+    pub size: u64,
+}
+
+/// GPU memory usage
+#[derive(Clone, Debug, Default)]
+pub struct MemoryReport {
+    pub heaps: Vec<MemoryHeap>,
+    pub categories: Vec<(MemoryCategory, CategoryUsage)>,
+    pub allocator: AllocatorStats,
+}
+
+impl MemoryReport {
+    /// Share of the free memory of the allocator blocks that is not in the
+    /// largest free region. 0 when all the free memory is contiguous
+    pub fn fragmentation(&self) -> f32 {
+        let free = self
+            .allocator
+            .reserved
+            .saturating_sub(self.allocator.allocated);
+
+        if free == 0 {
+            0.
+        } else {
+            1. - self.allocator.largest_free.min(free) as f32 / free as f32
+        }
+    }
+
+    /// Memory of the device local heaps used by this process
+    pub fn device_local_usage(&self) -> Option<u64> {
+        self.heaps
+            .iter()
+            .filter(|heap| heap.device_local)
+            .map(|heap| heap.usage)
+            .sum()
+    }
+}
+
+/// Category and size of the buffers and images created by the HAL
+#[derive(Default)]
+pub(crate) struct MemoryTracker {
+    buffers: HashMap<Handle, (MemoryCategory, u64)>,
+    images: HashMap<Handle, (MemoryCategory, u64)>,
+}
+
+impl MemoryTracker {
+    pub fn track_buffer(&mut self, buffer: Handle, category: MemoryCategory, size: u64) {
+        self.buffers.insert(buffer, (category, size));
+    }
+
+    pub fn untrack_buffer(&mut self, buffer: Handle) {
+        self.buffers.remove(&buffer);
+    }
+
+    pub fn track_image(&mut self, image: Handle, category: MemoryCategory, size: u64) {
+        self.images.insert(image, (category, size));
+    }
+
+    pub fn untrack_image(&mut self, image: Handle) {
+        self.images.remove(&image);
+    }
+
+    pub fn categories(&self) -> Vec<(MemoryCategory, CategoryUsage)> {
+        let mut usage: HashMap<MemoryCategory, CategoryUsage> = HashMap::new();
+
+        for (category, size) in self.buffers.values().chain(self.images.values()) {
+            let usage = usage.entry(*category).or_default();
+            usage.count += 1;
+            usage.size += size;
+        }
+
+        MemoryCategory::ALL
+            .iter()
+            .map(|category| (*category, usage.get(category).copied().unwrap_or_default()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use slotmap::SlotMap;
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_vulkan::{alloc::AllocatorStats, memory::MemoryHeap};
+
+    use crate::{
+        BufferType, Handle, ImageUsage,
+        memory::{CategoryUsage, MemoryCategory, MemoryReport, MemoryTracker},
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_categories() {
+        setup();
+
+        let mut handles: SlotMap<Handle, ()> = SlotMap::with_key();
+        let vertex = handles.insert(());
+        let index = handles.insert(());
+        let staging = handles.insert(());
+
+        let mut tracker = MemoryTracker::default();
+        tracker.track_buffer(vertex, BufferType::Vertex.into(), 256);
+        tracker.track_buffer(index, BufferType::Index.into(), 64);
+        tracker.track_buffer(staging, BufferType::Staging.into(), 1024);
+        // buffer and image handles come from different registries
+        tracker.track_image(vertex, ImageUsage::Texture.into(), 4096);
+        tracker.track_image(index, ImageUsage::Depth.into(), 2048);
+
+        let categories = tracker.categories();
+        assert_eq!(categories.len(), MemoryCategory::ALL.len());
+        assert_eq!(
+            categories[0],
+            (
+                MemoryCategory::Mesh,
+                CategoryUsage {
+                    count: 2,
+                    size: 320
+                }
+            )
+        );
+        assert_eq!(categories[1].1.size, 4096);
+        assert_eq!(categories[2].1.size, 2048);
+        assert_eq!(categories[3].1.size, 1024);
+
+        tracker.untrack_buffer(staging);
+        tracker.untrack_image(vertex);
+
+        let categories = tracker.categories();
+        assert_eq!(categories[0].1.count, 2);
+        assert_eq!(categories[1].1, CategoryUsage::default());
+        assert_eq!(categories[3].1, CategoryUsage::default());
+    }
+
+    #[test]
+    fn test_report() {
+        setup();
+
+        let mut report = MemoryReport::default();
+        assert_eq!(report.fragmentation(), 0.);
+        assert_eq!(report.device_local_usage(), Some(0));
+
+        report.allocator = AllocatorStats {
+            allocation_count: 3,
+            block_count: 1,
+            allocated: 600,
+            reserved: 1000,
+            largest_free: 300,
+        };
+        assert_eq!(report.fragmentation(), 0.25);
+
+        report.allocator.largest_free = 400;
+        assert_eq!(report.fragmentation(), 0.);
+
+        report.heaps = vec![
+            MemoryHeap {
+                size: 1000,
+                device_local: true,
+                usage: Some(200),
+                budget: Some(800),
+            },
+            MemoryHeap {
+                size: 2000,
+                device_local: false,
+                usage: Some(100),
+                budget: Some(1500),
+            },
+        ];
+        assert_eq!(report.device_local_usage(), Some(200));
+
+        report.heaps[0].usage = None;
+        assert_eq!(report.device_local_usage(), None);
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
 
 pub trait ComputePipelineBuilder {
     fn shader(self: Box<Self>, filename: &str, entry: &str) -> Box<dyn ComputePipelineBuilder>;
+    fn push_constants(self: Box<Self>, size: usize) -> Box<dyn ComputePipelineBuilder>;
     fn binding_group(
         self: Box<Self>,
         binding_group_layout: Arc<BindingGroupLayout>,
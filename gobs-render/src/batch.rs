@@ -1,17 +1,24 @@
 use std::{collections::hash_map::Entry, sync::Arc};
 
 use ahash::HashMap;
+use glam::Mat4;
 
 use gobs_core::{ImageExtent2D, Transform, logger};
-use gobs_render_graph::{GfxContext, RenderFlags, RenderObject, SceneData, SceneDataLayout};
+use gobs_render_graph::{
+    GfxContext, ObjectTable, RenderFlags, RenderObject, SceneData, SceneDataLayout,
+};
 use gobs_render_hal::{
-    AlignMode, BindResource, BindingGroupType, DescriptorType, Handle, RenderHAL, VertexData,
+    AlignMode, BindResource, BindingGroupType, DescriptorType, Handle, RenderHAL, UniformData as _,
+    VertexData,
+};
+use gobs_resource::{
+    ResourceError, ResourceHandle, ResourceLifetime, ResourceManager, camera::Camera, light::Light,
 };
-use gobs_resource::{ResourceError, ResourceHandle, ResourceManager, camera::Camera, light::Light};
 
 use crate::{
     BoundingBox, GraphicsPipelineProperties, Material, MaterialInstance, Mesh, Pipeline,
-    PipelineProperties, RenderMeshBuilder, RenderModelBuilder, ShapeBuilder, Texture, model::Model,
+    PipelineProperties, RenderMeshBuilder, RenderModelBuilder, ShapeBuilder, Texture,
+    TextureProperties, data::TextureDataProp, model::Model,
 };
 
 #[derive(Clone)]
@@ -21,6 +28,7 @@ struct MaterialData {
     pipeline_properties: Option<GraphicsPipelineProperties>,
     material_data: Option<BindResource>,
     material_textures: Option<BindResource>,
+    material_index: Option<u32>,
 }
 
 pub struct RenderBatch {
@@ -30,9 +38,21 @@ pub struct RenderBatch {
     pub(crate) camera_transform: Transform,
     pub(crate) lights: Vec<(Light, Transform)>,
     pub(crate) extent: ImageExtent2D,
+    pub(crate) time: f32,
     generate_bounds: bool,
+    gpu_driven: bool,
+    object_table: Option<ObjectTable>,
+    material_table: Vec<u8>,
+    material_count: usize,
     bounding_geometry: Option<ShapeBuilder>,
+    pick_id: u32,
+    joint_table: Vec<Mat4>,
+    joint_offset: Option<u32>,
+    morph_weights: Vec<f32>,
+    /// Offset and count of the weights of the next morphed models
+    morph_offset: Option<(u32, u32)>,
     material_cache: HashMap<ResourceHandle<MaterialInstance>, MaterialData>,
+    placeholder: Option<ResourceHandle<Texture>>,
 }
 
 impl RenderBatch {
@@ -46,16 +66,35 @@ impl RenderBatch {
             camera_transform: Transform::default(),
             lights: vec![],
             extent: ImageExtent2D::default(),
+            time: 0.,
             generate_bounds: false,
+            gpu_driven: false,
+            object_table: None,
+            material_table: Vec::new(),
+            material_count: 0,
             bounding_geometry: None,
+            pick_id: 0,
+            joint_table: Vec::new(),
+            joint_offset: None,
+            morph_weights: Vec::new(),
+            morph_offset: None,
             material_cache: HashMap::default(),
+            placeholder: None,
         }
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
     pub fn reset(&mut self) {
         self.render_list.clear();
+        self.object_table = None;
+        self.material_table.clear();
+        self.material_count = 0;
         self.bounding_geometry = None;
+        self.pick_id = 0;
+        self.joint_table.clear();
+        self.joint_offset = None;
+        self.morph_weights.clear();
+        self.morph_offset = None;
         self.material_cache.clear();
     }
 
@@ -63,6 +102,69 @@ impl RenderBatch {
         self.generate_bounds = generate_bounds;
     }
 
+    /// Upload an object table for GPU culling and indirect draws
+    pub fn gpu_driven(&mut self, gpu_driven: bool) {
+        self.gpu_driven = gpu_driven;
+    }
+
+    /// Id written to the picking attachment by the models added next. 0 disables picking
+    pub fn set_pick_id(&mut self, pick_id: u32) {
+        self.pick_id = pick_id;
+    }
+
+    /// Joint matrices used by the skinned models added next. An empty slice
+    /// disables skinning
+    pub fn set_joints(&mut self, joints: &[Mat4]) {
+        if joints.is_empty() {
+            self.joint_offset = None;
+        } else {
+            self.joint_offset = Some(self.joint_table.len() as u32);
+            self.joint_table.extend_from_slice(joints);
+        }
+    }
+
+    /// Joint matrices of the skinned models of the batch
+    pub fn joint_table(&self) -> &[Mat4] {
+        &self.joint_table
+    }
+
+    /// Morph target weights used by the models added next. An empty slice
+    /// disables morphing
+    pub fn set_morph_weights(&mut self, weights: &[f32]) {
+        if weights.is_empty() {
+            self.morph_offset = None;
+        } else {
+            self.morph_offset = Some((self.morph_weights.len() as u32, weights.len() as u32));
+            self.morph_weights.extend_from_slice(weights);
+        }
+    }
+
+    /// Morph target weights of the morphed models of the batch
+    pub fn morph_weights(&self) -> &[f32] {
+        &self.morph_weights
+    }
+
+    pub fn object_table(&self) -> Option<&ObjectTable> {
+        self.object_table.as_ref()
+    }
+
+    /// Constants of the bindless materials of the batch
+    pub fn material_table(&self) -> (&[u8], usize) {
+        (&self.material_table, self.material_count)
+    }
+
+    /// Texture used in place of textures still loading in background
+    fn placeholder(&mut self, resource_manager: &mut ResourceManager) -> ResourceHandle<Texture> {
+        *self.placeholder.get_or_insert_with(|| {
+            let properties = TextureProperties {
+                name: "placeholder".to_string(),
+                ..Default::default()
+            };
+
+            resource_manager.add(properties, ResourceLifetime::Static, false)
+        })
+    }
+
     fn get_material(
         &mut self,
         ctx: &mut GfxContext,
@@ -72,6 +174,8 @@ impl RenderBatch {
         let mut render_flags = RenderFlags::default();
 
         if let Some(material_instance_handle) = material_instance_handle {
+            let placeholder = self.placeholder(resource_manager);
+
             match self.material_cache.entry(*material_instance_handle) {
                 Entry::Occupied(e) => Ok(e.get().clone()),
                 Entry::Vacant(e) => {
@@ -82,11 +186,29 @@ impl RenderBatch {
                         &mut render_flags,
                     )?;
 
-                    let (material_data, material_textures) = Self::get_material_data(
-                        ctx.hal_mut(),
-                        resource_manager,
-                        *material_instance_handle,
-                    )?;
+                    let bindless = Self::is_bindless(resource_manager, *material_instance_handle)?;
+
+                    let (material_data, material_textures, material_index) = if bindless {
+                        Self::write_bindless_material(
+                            ctx.hal_mut(),
+                            resource_manager,
+                            *material_instance_handle,
+                            placeholder,
+                            &mut self.material_table,
+                        )?;
+                        self.material_count += 1;
+
+                        (None, None, Some(self.material_count as u32 - 1))
+                    } else {
+                        let (material_data, material_textures) = Self::get_material_data(
+                            ctx.hal_mut(),
+                            resource_manager,
+                            *material_instance_handle,
+                            placeholder,
+                        )?;
+
+                        (material_data, material_textures, None)
+                    };
 
                     let material = MaterialData {
                         render_flags,
@@ -94,6 +216,7 @@ impl RenderBatch {
                         pipeline_properties: Some(pipeline_properties),
                         material_data,
                         material_textures,
+                        material_index,
                     };
                     Ok(e.insert(material).clone())
                 }
@@ -105,6 +228,7 @@ impl RenderBatch {
                 pipeline_properties: None,
                 material_data: None,
                 material_textures: None,
+                material_index: None,
             })
         }
     }
@@ -137,8 +261,19 @@ impl RenderBatch {
 
             tracing::debug!(target: logger::RENDER, "Add mesh: {} to render list [{:?}]", model.name(), render_flags);
 
-            let (vertex_buffer, index_buffer, index_len, vertex_attribute, layer) = {
-                let mesh_data = resource_manager.get_data(ctx.hal_mut(), mesh)?;
+            let (vertex_buffer, index_buffer, index_len, vertex_attribute, layer, targets) = {
+                // failed meshes are reported once, when their load fails
+                let mesh_data = match resource_manager.try_get_data(ctx.hal_mut(), mesh) {
+                    Ok(Some(mesh_data)) => mesh_data,
+                    Ok(None) | Err(ResourceError::LoadFailed) => {
+                        tracing::debug!(target: logger::RENDER, "Mesh not ready for model {}, skipping", model.name());
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!(target: logger::RENDER, "Mesh of model {} failed to load, skipping: {}", model.name(), e);
+                        continue;
+                    }
+                };
 
                 (
                     mesh_data.data.vertex_view,
@@ -146,9 +281,25 @@ impl RenderBatch {
                     mesh_data.data.index_len,
                     mesh_data.properties.vertex_attributes,
                     mesh_data.properties.layer,
+                    mesh_data
+                        .data
+                        .target_view
+                        .map(|view| (view, mesh_data.data.target_count as u32)),
                 )
             };
 
+            // the targets are indexed with the weight count
+            let morph = match (targets, self.morph_offset) {
+                (Some((targets, target_count)), Some((offset, count))) if count == target_count => {
+                    Some((targets, offset, count))
+                }
+                (Some(_), Some(_)) => {
+                    tracing::warn!(target: logger::RENDER, "Morph weights do not match the targets of model {}", model.name());
+                    None
+                }
+                _ => None,
+            };
+
             let scene_layout = match material.pipeline_properties {
                 Some(properties) => properties.scene_data_layout,
                 None => SceneDataLayout::new(AlignMode::Std140),
@@ -166,7 +317,14 @@ impl RenderBatch {
                 layer,
                 material_data: material.material_data,
                 material_textures: material.material_textures,
+                material_index: material.material_index,
                 render_flags,
+                bounds: bounding_box.map(|bounds| (bounds.bottom_left(), bounds.top_right())),
+                pick_id: self.pick_id,
+                joint_offset: self.joint_offset,
+                morph_targets: morph.map(|(targets, _, _)| targets),
+                morph_offset: morph.map(|(_, offset, _)| offset),
+                morph_count: morph.map(|(_, _, count)| count).unwrap_or_default(),
             };
 
             self.render_list.push(render_object);
@@ -179,6 +337,7 @@ impl RenderBatch {
         hal: &mut dyn RenderHAL,
         resource_manager: &mut ResourceManager,
         material_instance_handle: ResourceHandle<MaterialInstance>,
+        placeholder: ResourceHandle<Texture>,
     ) -> Result<(Option<BindResource>, Option<BindResource>), ResourceError> {
         let (material_buffer, material, textures) = {
             let resource_data = resource_manager.get_data(hal, &material_instance_handle)?;
@@ -190,7 +349,10 @@ impl RenderBatch {
             )
         };
 
-        let material_properties = &resource_manager.get(&material).properties;
+        let material_properties = &resource_manager
+            .get(&material)
+            .ok_or(ResourceError::InvalidHandle)?
+            .properties;
 
         let material_data = material_buffer.map(|material_buffer| {
             let material_data_layout = material_properties
@@ -218,11 +380,7 @@ impl RenderBatch {
 
                 let tex_data = textures
                     .iter()
-                    .map(|t| {
-                        let data = resource_manager.get_data(&mut *hal, t)?;
-
-                        Ok((data.data.image, data.data.sampler))
-                    })
+                    .map(|t| Self::texture_data(&mut *hal, resource_manager, t, &placeholder))
                     .collect::<Result<Vec<_>, ResourceError>>()?;
 
                 let mut texture_idx = 0;
@@ -260,15 +418,111 @@ impl RenderBatch {
         Ok((material_data, material_textures))
     }
 
+    /// Image and sampler of the texture. The placeholder is used while the texture
+    /// loads in background, and in place of textures that failed to load. Failed
+    /// textures are reported once, when their load fails
+    fn texture_data(
+        hal: &mut dyn RenderHAL,
+        resource_manager: &mut ResourceManager,
+        texture: &ResourceHandle<Texture>,
+        placeholder: &ResourceHandle<Texture>,
+    ) -> Result<(Handle, Handle), ResourceError> {
+        match resource_manager.try_get_data(&mut *hal, texture) {
+            Ok(Some(data)) => return Ok((data.data.image, data.data.sampler)),
+            Ok(None) | Err(ResourceError::LoadFailed) => {}
+            Err(e) => {
+                tracing::warn!(target: logger::RENDER, "Texture {:?} failed to load, using placeholder: {}", texture, e);
+            }
+        }
+
+        let data = resource_manager.get_data(hal, placeholder)?;
+
+        Ok((data.data.image, data.data.sampler))
+    }
+
+    fn is_bindless(
+        resource_manager: &ResourceManager,
+        material_instance_handle: ResourceHandle<MaterialInstance>,
+    ) -> Result<bool, ResourceError> {
+        let material = resource_manager
+            .get(&material_instance_handle)
+            .ok_or(ResourceError::InvalidHandle)?
+            .properties
+            .material;
+
+        Ok(resource_manager
+            .get(&material)
+            .ok_or(ResourceError::InvalidHandle)?
+            .properties
+            .bindless)
+    }
+
+    /// Append the material constants to the material table, with the bindless
+    /// index of its textures
+    fn write_bindless_material(
+        hal: &mut dyn RenderHAL,
+        resource_manager: &mut ResourceManager,
+        material_instance_handle: ResourceHandle<MaterialInstance>,
+        placeholder: ResourceHandle<Texture>,
+        material_table: &mut Vec<u8>,
+    ) -> Result<(), ResourceError> {
+        let (material, mut material_data, textures) = {
+            let resource_data = resource_manager.get_data(hal, &material_instance_handle)?;
+
+            (
+                resource_data.properties.material,
+                resource_data
+                    .properties
+                    .material_data
+                    .clone()
+                    .unwrap_or_default(),
+                resource_data.properties.textures.clone(),
+            )
+        };
+
+        let material_properties = &resource_manager
+            .get(&material)
+            .ok_or(ResourceError::InvalidHandle)?
+            .properties;
+        let texture_layout = material_properties.texture_data_layout.layout.clone();
+        let material_data_layout = material_properties.material_data_layout.clone();
+
+        for (texture_prop, texture) in texture_layout.iter().zip(&textures) {
+            let (image, sampler) =
+                Self::texture_data(&mut *hal, resource_manager, texture, &placeholder)?;
+
+            let index = hal.register_texture(image, sampler) as u32;
+
+            match texture_prop {
+                TextureDataProp::Diffuse => material_data.diffuse_index = index,
+                TextureDataProp::Normal => material_data.normal_index = index,
+                TextureDataProp::Emission => material_data.emission_index = index,
+                TextureDataProp::Specular => material_data.specular_index = index,
+                TextureDataProp::MetallicRoughness => {
+                    material_data.metallic_roughness_index = index
+                }
+                TextureDataProp::Occlusion => material_data.occlusion_index = index,
+            }
+        }
+
+        material_data_layout.copy_data(material_table, |prop| material_data.prop_data(prop));
+
+        Ok(())
+    }
+
     fn get_pipeline(
         hal: &mut dyn RenderHAL,
         resource_manager: &mut ResourceManager,
         material_instance_handle: ResourceHandle<MaterialInstance>,
         render_flags: &mut RenderFlags,
     ) -> Result<(Handle, GraphicsPipelineProperties), ResourceError> {
-        let material_instance = resource_manager.get(&material_instance_handle);
+        let material_instance = resource_manager
+            .get(&material_instance_handle)
+            .ok_or(ResourceError::InvalidHandle)?;
         let material_handle = material_instance.properties.material;
-        let material = resource_manager.get(&material_handle);
+        let material = resource_manager
+            .get(&material_handle)
+            .ok_or(ResourceError::InvalidHandle)?;
 
         if material.properties.blending_enabled {
             *render_flags |= RenderFlags::TRANSPARENT;
@@ -351,6 +605,10 @@ impl RenderBatch {
         self.extent = extent;
     }
 
+    pub fn add_time_data(&mut self, time: f32) {
+        self.time = time;
+    }
+
     pub fn scene_data(&'_ self) -> SceneData<'_> {
         let default_light = &self.lights.first();
 
@@ -360,6 +618,7 @@ impl RenderBatch {
             light: default_light.map(|l| &l.0),
             light_transform: default_light.map(|l| &l.1),
             extent: self.extent,
+            time: self.time,
         }
     }
 
@@ -407,6 +666,9 @@ impl RenderBatch {
                 .with_mesh(mesh)
                 .build();
 
+            self.pick_id = 0;
+            self.joint_offset = None;
+            self.morph_offset = None;
             self.add_model(
                 ctx,
                 resource_manager,
@@ -425,14 +687,25 @@ impl RenderBatch {
 
         self.sort();
 
+        if self.gpu_driven {
+            if ctx.hal().features().supports_draw_indirect_count() {
+                self.object_table = Some(ObjectTable::new(ctx.hal(), &self.render_list));
+            } else {
+                tracing::debug!(target: logger::RENDER, "Indirect draw count not supported, using CPU draws");
+            }
+        }
+
         self.recording = false;
 
+        resource_manager.poll::<Texture>(ctx.hal_mut());
+        resource_manager.poll::<Mesh>(ctx.hal_mut());
+
         tracing::debug!(target: logger::RENDER, "Flush resource loaders");
-        resource_manager.flush::<Texture>();
-        resource_manager.flush::<Mesh>();
-        resource_manager.flush::<Pipeline>();
-        resource_manager.flush::<Material>();
-        resource_manager.flush::<MaterialInstance>();
+        resource_manager.flush::<Texture>(ctx.hal_mut());
+        resource_manager.flush::<Mesh>(ctx.hal_mut());
+        resource_manager.flush::<Pipeline>(ctx.hal_mut());
+        resource_manager.flush::<Material>(ctx.hal_mut());
+        resource_manager.flush::<MaterialInstance>(ctx.hal_mut());
 
         tracing::debug!(target: logger::RENDER, "<<< Finish render batch");
     }
@@ -53,9 +53,20 @@ impl<'a> RenderMaterialBuilder<'a> {
         self
     }
 
+    pub fn ref_counted(mut self, ref_counted: bool) -> Self {
+        if ref_counted {
+            self.lifetime = ResourceLifetime::RefCounted;
+        } else {
+            self.lifetime = ResourceLifetime::Static;
+        }
+
+        self
+    }
+
     pub fn build(self) -> ResourceHandle<MaterialInstance> {
-        let mut material_instance_properties =
-            MaterialInstanceProperties::new(self.name, self.material.unwrap());
+        let material = self.material.unwrap();
+
+        let mut material_instance_properties = MaterialInstanceProperties::new(self.name, material);
 
         if !self.textures.is_empty() {
             material_instance_properties = material_instance_properties.textures(&self.textures);
@@ -67,7 +78,15 @@ impl<'a> RenderMaterialBuilder<'a> {
             }
         }
 
-        self.resource_manager
-            .add(material_instance_properties, self.lifetime, false)
+        let handle = self
+            .resource_manager
+            .add(material_instance_properties, self.lifetime, false);
+
+        self.resource_manager.add_dependency(&handle, &material);
+        for texture in &self.textures {
+            self.resource_manager.add_dependency(&handle, texture);
+        }
+
+        handle
     }
 }
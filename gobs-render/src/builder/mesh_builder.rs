@@ -61,6 +61,16 @@ impl<'a> RenderMeshBuilder<'a> {
         self
     }
 
+    pub fn ref_counted(mut self, ref_counted: bool) -> Self {
+        if ref_counted {
+            self.lifetime = ResourceLifetime::RefCounted;
+        } else {
+            self.lifetime = ResourceLifetime::Static;
+        }
+
+        self
+    }
+
     pub fn for_material(mut self, material: ResourceHandle<MaterialInstance>) -> Self {
         self.vertex_attributes = self.get_vertex_attributes(material);
 
@@ -94,10 +104,12 @@ impl<'a> RenderMeshBuilder<'a> {
     ) -> VertexAttribute {
         let material_instance = self
             .resource_manager
-            .get::<MaterialInstance>(&material_instance);
+            .get::<MaterialInstance>(&material_instance)
+            .expect("Invalid material instance handle");
         let material = self
             .resource_manager
-            .get::<Material>(&material_instance.properties.material);
+            .get::<Material>(&material_instance.properties.material)
+            .expect("Invalid material handle");
 
         material.properties.pipeline_properties.vertex_attributes
     }
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use gobs_render_hal::VertexAttribute;
-use gobs_resource::{ResourceHandle, ResourceManager};
+use gobs_resource::{ResourceHandle, ResourceManager, ResourceRef};
 
 use crate::{
     Bounded, BoundingBox, Material, MaterialInstance, Mesh, Model, ModelId, resources::MeshPath,
@@ -9,11 +9,13 @@ use crate::{
 
 pub struct RenderModelBuilder<'a> {
     name: &'a str,
+    path: Option<String>,
     resource_manager: &'a mut ResourceManager,
     meshes: Vec<(
         ResourceHandle<Mesh>,
         Option<ResourceHandle<MaterialInstance>>,
     )>,
+    refs: Vec<ResourceRef>,
     bounding_box: BoundingBox,
 }
 
@@ -21,14 +23,27 @@ impl<'a> RenderModelBuilder<'a> {
     pub fn new(resource_manager: &'a mut ResourceManager, name: &'a str) -> Self {
         Self {
             name,
+            path: None,
             resource_manager,
             meshes: Vec::new(),
+            refs: Vec::new(),
             bounding_box: BoundingBox::default(),
         }
     }
 
+    /// Asset the model is loaded from, used to reference the model in saved scenes
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+
+        self
+    }
+
     pub fn with_mesh(mut self, mesh: ResourceHandle<Mesh>) -> Self {
-        let mesh_properties = &self.resource_manager.get(&mesh).properties;
+        let mesh_properties = &self
+            .resource_manager
+            .get(&mesh)
+            .expect("Invalid mesh handle")
+            .properties;
 
         match &mesh_properties.path {
             MeshPath::Mesh(geometry) => {
@@ -39,6 +54,7 @@ impl<'a> RenderModelBuilder<'a> {
             _ => unimplemented!(),
         }
 
+        self.refs.extend(self.resource_manager.acquire(&mesh));
         self.meshes.push((mesh, None));
 
         self
@@ -47,10 +63,16 @@ impl<'a> RenderModelBuilder<'a> {
     pub fn with_material(mut self, material: ResourceHandle<MaterialInstance>) -> Self {
         let vertex_attributes = self.get_vertex_attributes(material);
 
+        self.refs.extend(self.resource_manager.acquire(&material));
+
         if let Some((mesh, material_instance)) = self.meshes.last_mut() {
             *material_instance = Some(material);
 
-            let mesh_properties = &self.resource_manager.get(mesh).properties;
+            let mesh_properties = &self
+                .resource_manager
+                .get(mesh)
+                .expect("Invalid mesh handle")
+                .properties;
             debug_assert_eq!(mesh_properties.vertex_attributes, vertex_attributes);
         } else {
             panic!("Missing mesh in model");
@@ -62,7 +84,9 @@ impl<'a> RenderModelBuilder<'a> {
         Arc::new(Model {
             name: Arc::new(self.name.to_string()),
             id: ModelId::new_v4(),
+            path: self.path.map(Arc::new),
             meshes: self.meshes,
+            _refs: self.refs,
             bounding_box: self.bounding_box,
         })
     }
@@ -73,10 +97,12 @@ impl<'a> RenderModelBuilder<'a> {
     ) -> VertexAttribute {
         let material_instance = self
             .resource_manager
-            .get::<MaterialInstance>(&material_instance);
+            .get::<MaterialInstance>(&material_instance)
+            .expect("Invalid material instance handle");
         let material = self
             .resource_manager
-            .get::<Material>(&material_instance.properties.material);
+            .get::<Material>(&material_instance.properties.material)
+            .expect("Invalid material handle");
 
         material.properties.pipeline_properties.vertex_attributes
     }
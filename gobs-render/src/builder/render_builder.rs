@@ -63,6 +63,13 @@ impl<'a> RenderBuilder<'a> {
         self
     }
 
+    /// Cull on the GPU and draw indirect in passes that support it
+    pub fn gpu_driven(mut self, gpu_driven: bool) -> Self {
+        self.batch.gpu_driven(gpu_driven);
+
+        self
+    }
+
     pub fn draw_wire(self, draw_wire: bool) -> Self {
         self.renderer.enable_pass("wire", draw_wire);
 
@@ -30,6 +30,16 @@ impl<'a> RenderTextureBuilder<'a> {
         self
     }
 
+    pub fn ref_counted(mut self, ref_counted: bool) -> Self {
+        if ref_counted {
+            self.lifetime = ResourceLifetime::RefCounted;
+        } else {
+            self.lifetime = ResourceLifetime::Static;
+        }
+
+        self
+    }
+
     pub fn diffuse(mut self, filename: &str, format: ImageFormat) -> Self {
         let properties = TextureProperties::with_file(self.name, format, filename);
 
@@ -79,6 +89,6 @@ impl<'a> RenderTextureBuilder<'a> {
 
     pub fn build(self) -> ResourceHandle<Texture> {
         self.resource_manager
-            .add(self.properties.unwrap(), ResourceLifetime::Static, false)
+            .add(self.properties.unwrap(), self.lifetime, false)
     }
 }
@@ -5,6 +5,7 @@ pub enum RenderConfig {
     GraphName,
     PipelineFileName,
     LoadGraph,
+    HotReload,
 }
 
 impl AsRef<str> for RenderConfig {
@@ -14,6 +15,7 @@ impl AsRef<str> for RenderConfig {
             RenderConfig::GraphName => "config.render.graph.name",
             RenderConfig::PipelineFileName => "config.render.pipeline.filename",
             RenderConfig::LoadGraph => "config.render.graph.load",
+            RenderConfig::HotReload => "config.render.hot_reload",
         }
     }
 }
@@ -24,5 +26,6 @@ impl ConfigDefault for RenderConfig {
         config.set_string(RenderConfig::GraphName, "scene");
         config.set_string(RenderConfig::PipelineFileName, "pipelines.ron");
         config.set_bool(RenderConfig::LoadGraph, true);
+        config.set_bool(RenderConfig::HotReload, cfg!(debug_assertions));
     }
 }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use gobs_render_hal::{AlignMode, Attribute, UniformLayout};
+use gobs_render_hal::{AlignMode, Attribute, AttributeData, UniformLayout};
 
 use crate::UniformData;
 
@@ -15,6 +15,18 @@ pub enum MaterialDataPropData {
     NormalIndex(u32),
     EmissionIndex(u32),
     SpecularIndex(u32),
+    MetallicFactor(f32),
+    RoughnessFactor(f32),
+    OcclusionStrength(f32),
+    EmissiveStrength(f32),
+    /// Fragments with a lower alpha are discarded, 0 disables alpha masking
+    AlphaCutoff(f32),
+    /// UV offset (xy) and scale (zw)
+    UvOffsetScale([f32; 4]),
+    /// UV rotation in radians, applied after scaling
+    UvRotation(f32),
+    MetallicRoughnessIndex(u32),
+    OcclusionIndex(u32),
 }
 
 // TODO: Emissive, Specular, Opacity, Glossiness, ...
@@ -28,6 +40,15 @@ pub enum MaterialDataProp {
     NormalIndex,
     EmissionIndex,
     SpecularIndex,
+    MetallicFactor,
+    RoughnessFactor,
+    OcclusionStrength,
+    EmissiveStrength,
+    AlphaCutoff,
+    UvOffsetScale,
+    UvRotation,
+    MetallicRoughnessIndex,
+    OcclusionIndex,
 }
 
 #[derive(Clone, Debug)]
@@ -43,6 +64,19 @@ impl MaterialDataLayout {
             uniform_layout: UniformLayout::new(mode),
         }
     }
+
+    /// Layout of a material table entry, must match the `Material` struct of bindless shaders
+    pub fn bindless() -> Self {
+        Self::new(AlignMode::Std430)
+            .prop(MaterialDataProp::DiffuseColor)
+            .prop(MaterialDataProp::EmissionColor)
+            .prop(MaterialDataProp::SpecularColor)
+            .prop(MaterialDataProp::SpecularPower)
+            .prop(MaterialDataProp::DiffuseIndex)
+            .prop(MaterialDataProp::NormalIndex)
+            .prop(MaterialDataProp::EmissionIndex)
+            .prop(MaterialDataProp::SpecularIndex)
+    }
 }
 
 impl UniformData<MaterialDataProp> for MaterialDataLayout {
@@ -66,13 +100,38 @@ impl UniformData<MaterialDataProp> for MaterialDataLayout {
                 self.uniform_layout.prop("diffuse index", Attribute::U32)
             }
             MaterialDataProp::NormalIndex => {
-                self.uniform_layout.prop("diffuse index", Attribute::U32)
+                self.uniform_layout.prop("normal index", Attribute::U32)
             }
             MaterialDataProp::EmissionIndex => {
-                self.uniform_layout.prop("diffuse index", Attribute::U32)
+                self.uniform_layout.prop("emission index", Attribute::U32)
             }
             MaterialDataProp::SpecularIndex => {
-                self.uniform_layout.prop("diffuse index", Attribute::U32)
+                self.uniform_layout.prop("specular index", Attribute::U32)
+            }
+            MaterialDataProp::MetallicFactor => {
+                self.uniform_layout.prop("metallic factor", Attribute::F32)
+            }
+            MaterialDataProp::RoughnessFactor => {
+                self.uniform_layout.prop("roughness factor", Attribute::F32)
+            }
+            MaterialDataProp::OcclusionStrength => self
+                .uniform_layout
+                .prop("occlusion strength", Attribute::F32),
+            MaterialDataProp::EmissiveStrength => self
+                .uniform_layout
+                .prop("emissive strength", Attribute::F32),
+            MaterialDataProp::AlphaCutoff => {
+                self.uniform_layout.prop("alpha cutoff", Attribute::F32)
+            }
+            MaterialDataProp::UvOffsetScale => self
+                .uniform_layout
+                .prop("uv offset scale", Attribute::Vec4F),
+            MaterialDataProp::UvRotation => self.uniform_layout.prop("uv rotation", Attribute::F32),
+            MaterialDataProp::MetallicRoughnessIndex => self
+                .uniform_layout
+                .prop("metallic roughness index", Attribute::U32),
+            MaterialDataProp::OcclusionIndex => {
+                self.uniform_layout.prop("occlusion index", Attribute::U32)
             }
         };
 
@@ -88,7 +147,7 @@ impl UniformData<MaterialDataProp> for MaterialDataLayout {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct MaterialConstantData {
     pub diffuse_color: [f32; 4],
     pub emission_color: [f32; 4],
@@ -98,4 +157,145 @@ pub struct MaterialConstantData {
     pub normal_index: u32,
     pub emission_index: u32,
     pub specular_index: u32,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub occlusion_strength: f32,
+    pub emissive_strength: f32,
+    pub alpha_cutoff: f32,
+    pub uv_offset_scale: [f32; 4],
+    pub uv_rotation: f32,
+    pub metallic_roughness_index: u32,
+    pub occlusion_index: u32,
+}
+
+impl Default for MaterialConstantData {
+    fn default() -> Self {
+        Self {
+            diffuse_color: [0.; 4],
+            emission_color: [0.; 4],
+            specular_color: [0.; 4],
+            specular_power: 0.,
+            diffuse_index: 0,
+            normal_index: 0,
+            emission_index: 0,
+            specular_index: 0,
+            metallic_factor: 1.,
+            roughness_factor: 1.,
+            occlusion_strength: 1.,
+            emissive_strength: 1.,
+            alpha_cutoff: 0.,
+            uv_offset_scale: [0., 0., 1., 1.],
+            uv_rotation: 0.,
+            metallic_roughness_index: 0,
+            occlusion_index: 0,
+        }
+    }
+}
+
+impl MaterialConstantData {
+    pub fn prop_data(&self, prop: &MaterialDataProp) -> AttributeData {
+        match prop {
+            MaterialDataProp::DiffuseColor => AttributeData::Vec4F(self.diffuse_color),
+            MaterialDataProp::EmissionColor => AttributeData::Vec4F(self.emission_color),
+            MaterialDataProp::SpecularColor => AttributeData::Vec4F(self.specular_color),
+            MaterialDataProp::SpecularPower => AttributeData::F32(self.specular_power),
+            MaterialDataProp::DiffuseIndex => AttributeData::U32(self.diffuse_index),
+            MaterialDataProp::NormalIndex => AttributeData::U32(self.normal_index),
+            MaterialDataProp::EmissionIndex => AttributeData::U32(self.emission_index),
+            MaterialDataProp::SpecularIndex => AttributeData::U32(self.specular_index),
+            MaterialDataProp::MetallicFactor => AttributeData::F32(self.metallic_factor),
+            MaterialDataProp::RoughnessFactor => AttributeData::F32(self.roughness_factor),
+            MaterialDataProp::OcclusionStrength => AttributeData::F32(self.occlusion_strength),
+            MaterialDataProp::EmissiveStrength => AttributeData::F32(self.emissive_strength),
+            MaterialDataProp::AlphaCutoff => AttributeData::F32(self.alpha_cutoff),
+            MaterialDataProp::UvOffsetScale => AttributeData::Vec4F(self.uv_offset_scale),
+            MaterialDataProp::UvRotation => AttributeData::F32(self.uv_rotation),
+            MaterialDataProp::MetallicRoughnessIndex => {
+                AttributeData::U32(self.metallic_roughness_index)
+            }
+            MaterialDataProp::OcclusionIndex => AttributeData::U32(self.occlusion_index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_render_hal::{AlignMode, UniformData as _};
+
+    use crate::data::{MaterialConstantData, MaterialDataLayout, MaterialDataProp};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_bindless_layout() {
+        setup();
+
+        let layout = MaterialDataLayout::bindless();
+
+        assert_eq!(layout.uniform_layout().size(), 80);
+        assert_eq!(
+            layout.uniform_layout().offsets(),
+            &[0, 16, 32, 48, 52, 56, 60, 64]
+        );
+
+        let material = MaterialConstantData {
+            diffuse_index: 3,
+            normal_index: 7,
+            ..Default::default()
+        };
+
+        let mut data = vec![];
+        layout.copy_data(&mut data, |prop| material.prop_data(prop));
+
+        assert_eq!(data.len(), 80);
+        assert_eq!(data[52..56], 3u32.to_ne_bytes());
+        assert_eq!(data[56..60], 7u32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_pbr_layout() {
+        setup();
+
+        // must match the `Material` struct of gltf_pbr.slang
+        let layout = MaterialDataLayout::new(AlignMode::Std140)
+            .prop(MaterialDataProp::DiffuseColor)
+            .prop(MaterialDataProp::EmissionColor)
+            .prop(MaterialDataProp::UvOffsetScale)
+            .prop(MaterialDataProp::MetallicFactor)
+            .prop(MaterialDataProp::RoughnessFactor)
+            .prop(MaterialDataProp::OcclusionStrength)
+            .prop(MaterialDataProp::EmissiveStrength)
+            .prop(MaterialDataProp::AlphaCutoff)
+            .prop(MaterialDataProp::UvRotation);
+
+        assert_eq!(
+            layout.uniform_layout().offsets(),
+            &[0, 16, 32, 48, 52, 56, 60, 64, 68]
+        );
+        assert_eq!(layout.uniform_layout().size(), 80);
+
+        let material = MaterialConstantData {
+            metallic_factor: 0.25,
+            alpha_cutoff: 0.5,
+            ..Default::default()
+        };
+
+        let mut data = vec![];
+        layout.copy_data(&mut data, |prop| material.prop_data(prop));
+
+        assert_eq!(data.len(), 80);
+        assert_eq!(data[40..44], 1f32.to_ne_bytes());
+        assert_eq!(data[48..52], 0.25f32.to_ne_bytes());
+        assert_eq!(data[52..56], 1f32.to_ne_bytes());
+        assert_eq!(data[64..68], 0.5f32.to_ne_bytes());
+    }
 }
@@ -7,6 +7,9 @@ pub enum TextureDataProp {
     Normal,
     Emission,
     Specular,
+    /// Metalness in the blue channel and roughness in the green channel
+    MetallicRoughness,
+    Occlusion,
 }
 
 #[derive(Clone, Debug, Default)]
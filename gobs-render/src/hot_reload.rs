@@ -0,0 +1,161 @@
+use gobs_core::{ConfigReader as _, GobsConfig, logger};
+use gobs_resource::{
+    ResourceError, ResourceHandle, ResourceLifetime, ResourceManager, ResourceType,
+    load::AssetType,
+    watcher::{AssetChange, AssetWatcher},
+};
+
+use crate::{
+    Material, MaterialInstance, MaterialsConfig, Pipeline, PipelinesConfig, RenderConfig, Renderer,
+    Texture,
+};
+
+/// Reload configuration files, shaders and textures when they are modified on disk
+pub struct HotReload {
+    config: GobsConfig,
+    watcher: AssetWatcher,
+    material_files: Vec<String>,
+}
+
+impl HotReload {
+    pub fn new(config: GobsConfig) -> Result<Self, ResourceError> {
+        Ok(Self {
+            config,
+            watcher: AssetWatcher::new()?,
+            material_files: Vec::new(),
+        })
+    }
+
+    /// Material files are reloaded when modified
+    pub fn watch_materials(&mut self, filename: &str) {
+        if !self.material_files.iter().any(|file| file == filename) {
+            self.material_files.push(filename.to_string());
+        }
+    }
+
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    pub fn update(&mut self, renderer: &mut Renderer, resource_manager: &mut ResourceManager) {
+        let changes = self.watcher.poll_changes();
+
+        let mut reload_graph = false;
+
+        for change in changes {
+            tracing::info!(target: logger::RESOURCES, "Reload modified file: {}", change.file_name);
+
+            match change.ty {
+                AssetType::RESOURCES => {
+                    reload_graph |= self.reload_config(&change, renderer, resource_manager);
+                }
+                AssetType::SHADER => {
+                    renderer.gfx.hal_mut().invalidate_shader(&change.file_name);
+                    // graph passes keep a reference on their pipeline
+                    reload_graph |= Self::reload_shader(&change, resource_manager);
+                }
+                AssetType::IMAGE => {
+                    Self::reload_texture(&change, resource_manager);
+                }
+                AssetType::MODEL | AssetType::DATA => {
+                    tracing::debug!(target: logger::RESOURCES, "Reloading not supported: {}", change.file_name);
+                }
+            }
+        }
+
+        if reload_graph && let Err(e) = renderer.reload_graph(resource_manager) {
+            tracing::error!(target: logger::RESOURCES, "Failed to reload graph: {}", e);
+        }
+    }
+
+    /// Returns true if the frame graph needs to be rebuilt
+    fn reload_config(
+        &self,
+        change: &AssetChange,
+        renderer: &mut Renderer,
+        resource_manager: &mut ResourceManager,
+    ) -> bool {
+        if change.file_name == self.config.get_string(RenderConfig::GraphFileName) {
+            return true;
+        }
+
+        if change.file_name == self.config.get_string(RenderConfig::PipelineFileName) {
+            match PipelinesConfig::reload_resources(
+                &renderer.gfx,
+                &change.file_name,
+                resource_manager,
+            ) {
+                Ok(()) => return true,
+                Err(e) => {
+                    tracing::error!(target: logger::RESOURCES, "Failed to reload pipelines: {}", e);
+                    return false;
+                }
+            }
+        }
+
+        if self.material_files.contains(&change.file_name) {
+            match MaterialsConfig::reload_resources(
+                self.config.clone(),
+                &change.file_name,
+                resource_manager,
+            ) {
+                Ok(materials) => Self::reload_material_instances(&materials, resource_manager),
+                Err(e) => {
+                    tracing::error!(target: logger::RESOURCES, "Failed to reload materials: {}", e);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Material instance data depends on the material layout
+    fn reload_material_instances(
+        materials: &[ResourceHandle<Material>],
+        resource_manager: &mut ResourceManager,
+    ) {
+        let instances = Self::find::<MaterialInstance, _>(resource_manager, |properties| {
+            materials
+                .iter()
+                .any(|material| material.id == properties.material.id)
+        });
+
+        for instance in instances {
+            resource_manager.reload(&instance);
+        }
+    }
+
+    /// Returns true if a pipeline has been reloaded
+    fn reload_shader(change: &AssetChange, resource_manager: &mut ResourceManager) -> bool {
+        let pipelines = Self::find::<Pipeline, _>(resource_manager, |properties| {
+            properties.uses_shader(&change.file_name)
+        });
+
+        for pipeline in &pipelines {
+            resource_manager.reload(pipeline);
+        }
+
+        !pipelines.is_empty()
+    }
+
+    fn reload_texture(change: &AssetChange, resource_manager: &mut ResourceManager) {
+        let textures = Self::find::<Texture, _>(resource_manager, |properties| {
+            properties.path.uses_file(&change.file_name)
+        });
+
+        for texture in textures {
+            resource_manager.reload(&texture);
+        }
+    }
+
+    /// Live resources matching `filter`. Retired copies are ignored.
+    fn find<R, F>(resource_manager: &ResourceManager, filter: F) -> Vec<ResourceHandle<R>>
+    where
+        R: ResourceType + 'static,
+        F: Fn(&R::ResourceProperties) -> bool,
+    {
+        resource_manager
+            .values::<R>()
+            .filter(|resource| resource.lifetime != ResourceLifetime::Transient)
+            .filter(|resource| filter(&resource.properties))
+            .map(|resource| resource.handle)
+            .collect()
+    }
+}
@@ -2,17 +2,20 @@ mod batch;
 mod builder;
 mod config;
 mod data;
+mod hot_reload;
 mod model;
 mod renderable;
 mod renderer;
 mod resources;
+mod skeleton;
 
 pub use gobs_render_graph::{GfxContext, RenderError, RenderFlags};
 pub use gobs_render_hal::{
-    AlignMode, Attribute, AttributeData, BlendMode, BufferType, CommandBuffer, CommandQueueType,
-    CullMode, DynamicStateElem, FrontFace, Handle, ImageLayout, ObjectDataLayout, ObjectDataProp,
-    Rect2D, RenderHAL, RenderHalConfig, UniformData, UniformLayout, VertexAttribute, VertexData,
-    Viewport,
+    AlignMode, Attribute, AttributeData, BlendMode, BufferType, CategoryUsage, ColorSpace,
+    CommandBuffer, CommandQueueType, CullMode, DisplayConfig, DisplayInfo, DynamicStateElem,
+    FrontFace, Handle, ImageLayout, MemoryCategory, MemoryHeap, MemoryReport, ObjectDataLayout,
+    ObjectDataProp, PresentationMode, Rect2D, RenderHAL, RenderHalConfig, UniformData,
+    UniformLayout, VertexAttribute, VertexData, Viewport,
 };
 
 pub use batch::RenderBatch;
@@ -21,15 +24,18 @@ pub use builder::{
     RenderTextureBuilder, RenderType,
 };
 pub use config::RenderConfig;
-pub use data::MaterialDataPropData;
+pub use data::{MaterialDataProp, MaterialDataPropData};
+pub use hot_reload::HotReload;
 pub use model::{Model, ModelId};
 pub use renderable::Renderable;
 pub use renderer::Renderer;
+pub use skeleton::Skeleton;
 
 pub use resources::{
-    Bounded, BoundingBox, GraphicsPipelineProperties, Material, MaterialData, MaterialInstance,
-    MaterialInstanceLoader, MaterialInstanceProperties, MaterialLoader, MaterialProperties,
-    MaterialsConfig, Mesh, MeshBuilder, MeshData, MeshGeometry, MeshLoader, MeshProperties,
-    Pipeline, PipelineLoader, PipelineProperties, PipelinesConfig, ShapeBuilder, Shapes, Texture,
-    TextureData, TextureLoader, TexturePath, TextureProperties, TextureType, TextureUpdate,
+    Bounded, BoundingBox, CookedTexture, GraphicsPipelineProperties, Material, MaterialData,
+    MaterialInstance, MaterialInstanceLoader, MaterialInstanceProperties, MaterialLoader,
+    MaterialProperties, MaterialsConfig, Mesh, MeshBuilder, MeshData, MeshGeometry, MeshLoader,
+    MeshPath, MeshProperties, MorphTarget, Pipeline, PipelineLayoutError, PipelineLoader,
+    PipelineProperties, PipelinesConfig, ShapeBuilder, Shapes, Texture, TextureData, TextureLoader,
+    TexturePath, TextureProperties, TextureType, TextureUpdate,
 };
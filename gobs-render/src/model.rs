@@ -7,7 +7,7 @@ use uuid::Uuid;
 use gobs_core::{Transform, logger};
 use gobs_render_graph::{GfxContext, RenderFlags};
 use gobs_resource::{
-    ResourceManager, {ResourceError, ResourceHandle},
+    ResourceManager, ResourceRef, {ResourceError, ResourceHandle},
 };
 
 use crate::{Bounded, BoundingBox, MaterialInstance, Mesh, Renderable, batch::RenderBatch};
@@ -18,10 +18,15 @@ pub type ModelId = Uuid;
 pub struct Model {
     pub name: Arc<String>,
     pub id: ModelId,
+    /// Asset the model was loaded from
+    pub path: Option<Arc<String>>,
     pub meshes: Vec<(
         ResourceHandle<Mesh>,
         Option<ResourceHandle<MaterialInstance>>,
     )>,
+    /// Keep ref-counted meshes and materials alive while the model exists
+    #[serde(skip)]
+    pub(crate) _refs: Vec<ResourceRef>,
     #[serde(skip)]
     pub bounding_box: BoundingBox,
 }
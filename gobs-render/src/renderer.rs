@@ -1,14 +1,20 @@
 use gobs_core::{ConfigReader as _, GobsConfig, ImageExtent2D, logger};
-use gobs_render_graph::{FrameData, FrameGraph, GfxContext, RenderError};
+use gobs_render_graph::{FrameData, FrameGraph, GfxContext, PassPipeline, RenderError};
 use gobs_resource::ResourceManager;
 
-use crate::{Pipeline, PipelinesConfig, RenderBatch, RenderConfig};
+use crate::{
+    DisplayConfig, DisplayInfo, Pipeline, PipelineProperties, PipelinesConfig, RenderBatch,
+    RenderConfig,
+};
 
 pub struct Renderer {
     pub graph: FrameGraph,
     pub gfx: GfxContext,
     pub frames: Vec<FrameData>,
     pub frame_number: usize,
+    /// Elapsed time in seconds
+    pub time: f32,
+    config: GobsConfig,
 }
 
 impl Renderer {
@@ -25,19 +31,7 @@ impl Renderer {
             )
             .expect("Load pipelines");
 
-            FrameGraph::load(
-                &mut gfx,
-                &config.get_string(RenderConfig::GraphFileName),
-                &config.get_string(RenderConfig::GraphName),
-                |pipeline, ctx| {
-                    let pipeline_handle = resource_manager.get_by_name::<Pipeline>(pipeline)?;
-
-                    let pipeline = resource_manager.get_data(ctx.hal_mut(), &pipeline_handle);
-
-                    pipeline.ok().map(|data| data.data.pipeline)
-                },
-            )
-            .unwrap()
+            Self::load_graph(&mut gfx, &config, resource_manager).unwrap()
         } else {
             FrameGraph::default()
         };
@@ -53,7 +47,60 @@ impl Renderer {
             gfx,
             frames,
             frame_number: 0,
+            time: 0.,
+            config,
+        }
+    }
+
+    fn load_graph(
+        gfx: &mut GfxContext,
+        config: &GobsConfig,
+        resource_manager: &mut ResourceManager,
+    ) -> Result<FrameGraph, RenderError> {
+        FrameGraph::load(
+            gfx,
+            &config.get_string(RenderConfig::GraphFileName),
+            &config.get_string(RenderConfig::GraphName),
+            |pipeline, ctx| {
+                let pipeline_handle = resource_manager.get_by_name::<Pipeline>(pipeline)?;
+
+                let pipeline = resource_manager
+                    .get_data(ctx.hal_mut(), &pipeline_handle)
+                    .ok()?;
+
+                let push_layout = match pipeline.properties {
+                    PipelineProperties::Compute(props) => Some(props.compute_data_layout.clone()),
+                    PipelineProperties::Graphics(_) => None,
+                };
+
+                Some(PassPipeline {
+                    handle: pipeline.data.pipeline,
+                    push_layout,
+                })
+            },
+        )
+    }
+
+    /// Rebuild the frame graph from its configuration file. The current graph is kept on error.
+    pub fn reload_graph(
+        &mut self,
+        resource_manager: &mut ResourceManager,
+    ) -> Result<(), RenderError> {
+        if !self.config.get_bool(RenderConfig::LoadGraph) {
+            return Ok(());
         }
+
+        tracing::info!(target: logger::RENDER, "Reload frame graph");
+
+        self.gfx.hal_mut().wait();
+
+        let mut graph = Self::load_graph(&mut self.gfx, &self.config, resource_manager)?;
+        graph.render_scaling = self.graph.render_scaling;
+
+        let mut old_graph = std::mem::replace(&mut self.graph, graph);
+        old_graph.destroy(&mut self.gfx);
+
+        Ok(())
     }
 
     pub fn extent(&self) -> ImageExtent2D {
@@ -64,7 +111,17 @@ impl Renderer {
         self.graph.resize(&mut self.gfx);
     }
 
+    /// Switch present mode or surface format at runtime
+    pub fn set_display_config(&mut self, config: DisplayConfig) {
+        self.gfx.hal_mut().set_display_config(config);
+    }
+
+    pub fn display_info(&self) -> Option<DisplayInfo> {
+        self.gfx.hal().display_info()
+    }
+
     pub fn update(&mut self, delta: f32) {
+        self.time += delta;
         self.graph.update(&self.gfx, delta);
     }
 
@@ -72,6 +129,12 @@ impl Renderer {
         self.graph.enable_pass(name, enabled);
     }
 
+    /// Id of the object drawn at the window position, None if the graph has no
+    /// picking pass
+    pub fn pick(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.graph.pick(&mut self.gfx, x, y)
+    }
+
     pub fn get_batch(&self) -> RenderBatch {
         RenderBatch::new()
     }
@@ -88,12 +151,22 @@ impl Renderer {
         let frame_id = self.gfx.frame_id(self.frame_number);
 
         let frame = &mut self.frames[frame_id];
-        frame.wait(self.frame_number);
+        frame.wait(self.gfx.hal(), self.frame_number)?;
 
         self.gfx.new_frame(self.frame_number);
 
         self.graph.begin(&mut self.gfx, frame)?;
 
+        batch.add_time_data(self.time);
+
+        frame.upload_objects(&mut self.gfx, batch.object_table());
+
+        let (material_table, material_count) = batch.material_table();
+        frame.upload_materials(&mut self.gfx, material_table, material_count);
+
+        frame.upload_joints(&mut self.gfx, batch.joint_table());
+        frame.upload_morph_weights(&mut self.gfx, batch.morph_weights());
+
         self.graph.render(
             &mut self.gfx,
             frame,
@@ -2,9 +2,9 @@ use gobs_core::ImageFormat;
 use gobs_render_graph::SceneDataLayout;
 use gobs_render_hal::{
     AlignMode, BindingGroupType, BlendMode, CompareOp, CullMode, DescriptorStage, DescriptorType,
-    FrontFace, ObjectDataLayout, RenderHAL, UniformData as _, VertexAttribute,
+    FrontFace, ObjectDataLayout, ObjectDataProp, RenderHAL, UniformData as _, VertexAttribute,
 };
-use gobs_resource::{ResourceHandle, ResourceProperties, ResourceType};
+use gobs_resource::{ResourceHandle, ResourceProperties, ResourceRef, ResourceType};
 
 use crate::{
     MaterialLoader, Pipeline,
@@ -27,6 +27,8 @@ pub struct MaterialProperties {
     pub name: String,
     pub pipeline_properties: GraphicsPipelineProperties,
     pub blending_enabled: bool,
+    /// Constants are stored in the material table and textures in the bindless texture array
+    pub bindless: bool,
     pub texture_data_layout: TextureDataLayout,
     pub material_data_layout: MaterialDataLayout,
 }
@@ -71,6 +73,7 @@ impl MaterialProperties {
             name: name.to_string(),
             pipeline_properties,
             blending_enabled: false,
+            bindless: false,
             texture_data_layout: TextureDataLayout::default(),
             material_data_layout: MaterialDataLayout::new(AlignMode::Std140),
         }
@@ -126,13 +129,11 @@ impl MaterialProperties {
         } else {
             for &prop in props {
                 match prop {
-                    TextureDataProp::Diffuse => {
-                        self.pipeline_properties = self
-                            .pipeline_properties
-                            .binding(DescriptorType::SampledImage, DescriptorStage::Fragment, 1)
-                            .binding(DescriptorType::Sampler, DescriptorStage::Fragment, 1);
-                    }
-                    TextureDataProp::Normal => {
+                    TextureDataProp::Diffuse
+                    | TextureDataProp::Normal
+                    | TextureDataProp::MetallicRoughness
+                    | TextureDataProp::Occlusion
+                    | TextureDataProp::Emission => {
                         self.pipeline_properties = self
                             .pipeline_properties
                             .binding(DescriptorType::SampledImage, DescriptorStage::Fragment, 1)
@@ -150,6 +151,40 @@ impl MaterialProperties {
         self
     }
 
+    /// Use the material table and the bindless textures instead of per instance
+    /// descriptor sets. Material properties use the bindless layout
+    pub fn bindless(mut self, props: &[TextureDataProp], array_size: u32) -> Self {
+        debug_assert!(array_size > 0);
+
+        let object_data_layout = self
+            .pipeline_properties
+            .object_data_layout
+            .clone()
+            .prop(ObjectDataProp::MaterialBufferAddress)
+            .prop(ObjectDataProp::MaterialIndex);
+
+        self.pipeline_properties = self
+            .pipeline_properties
+            .object_data_layout(object_data_layout)
+            .binding_group(BindingGroupType::BindlessTextures)
+            .binding(DescriptorType::Sampler, DescriptorStage::Fragment, 1)
+            .binding(
+                DescriptorType::SampledImage,
+                DescriptorStage::Fragment,
+                array_size,
+            );
+
+        self.bindless = true;
+        self.material_data_layout = MaterialDataLayout::bindless();
+        self.texture_data_layout = TextureDataLayout::default();
+
+        for &prop in props {
+            self.texture_data_layout = self.texture_data_layout.prop(prop);
+        }
+
+        self
+    }
+
     pub fn no_culling(mut self) -> Self {
         self.pipeline_properties = self.pipeline_properties.cull_mode(CullMode::None);
 
@@ -174,9 +209,16 @@ impl MaterialProperties {
 
         self
     }
+
+    /// Back faces are not culled
+    pub fn is_double_sided(&self) -> bool {
+        matches!(self.pipeline_properties.cull_mode, CullMode::None)
+    }
 }
 
 #[derive(Clone)]
 pub struct MaterialData {
     pub pipeline: ResourceHandle<Pipeline>,
+    /// The pipeline is released with the material data
+    pub(crate) _pipeline_ref: ResourceRef,
 }
@@ -9,7 +9,7 @@ use gobs_render_hal::{
     UniformData as _, VertexAttribute,
 };
 use gobs_resource::{
-    ResourceLifetime, ResourceManager,
+    ResourceError, ResourceHandle, ResourceLifetime, ResourceManager, ResourceProperties as _,
     load::{self, AssetType},
 };
 
@@ -51,7 +51,12 @@ struct MaterialConfig {
     #[serde(default)]
     texture_indexing: bool,
     #[serde(default)]
+    bindless: bool,
+    #[serde(default)]
     material_layout: Vec<MaterialDataProp>,
+    /// Replaces the default object layout, e.g. to add joint matrices
+    #[serde(default)]
+    object_layout: Vec<ObjectDataProp>,
     #[serde(default)]
     scene_layout: Vec<SceneDataProp>,
 }
@@ -84,15 +89,63 @@ impl MaterialsConfig {
         data: &str,
         resource_manager: &mut ResourceManager,
     ) {
-        let material_config: MaterialsConfig = ron::from_str(data).unwrap();
+        let material_config = Self::parse(data).unwrap();
 
         material_config.load_materials(config, resource_manager);
     }
 
+    /// Update materials after the config file has been modified. Existing materials are
+    /// reloaded in place and new materials are added. Returns the reloaded materials.
+    pub fn reload_resources(
+        config: GobsConfig,
+        filename: &str,
+        resource_manager: &mut ResourceManager,
+    ) -> Result<Vec<ResourceHandle<Material>>, ResourceError> {
+        let data = load::load_string_sync(filename, AssetType::RESOURCES)?;
+
+        let material_config = Self::parse(&data)?;
+
+        let mut reloaded = vec![];
+
+        for props in material_config.material_properties(config) {
+            match resource_manager.get_by_name::<Material>(props.name()) {
+                Some(handle) => {
+                    if let Some(resource) = resource_manager.get_mut(&handle) {
+                        resource.properties = props;
+                    }
+                    resource_manager.reload(&handle);
+                    reloaded.push(handle);
+                }
+                None => {
+                    resource_manager.add::<Material>(props, ResourceLifetime::Static, true);
+                }
+            }
+        }
+
+        Ok(reloaded)
+    }
+
+    /// Optional fields can be written without `Some(...)`
+    pub fn parse(data: &str) -> Result<Self, ResourceError> {
+        let options = ron::options::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
+
+        options.from_str(data).map_err(|e| {
+            tracing::error!(target: logger::RESOURCES, "Invalid material config: {}", e);
+            ResourceError::InvalidData
+        })
+    }
+
     fn load_materials(&self, config: GobsConfig, resource_manager: &mut ResourceManager) {
-        let mut object_layout = ObjectDataLayout::new(AlignMode::Std140);
+        for props in self.material_properties(config) {
+            resource_manager.add::<Material>(props, ResourceLifetime::Static, true);
+        }
+    }
+
+    fn material_properties(&self, config: GobsConfig) -> Vec<MaterialProperties> {
+        let mut default_object_layout = ObjectDataLayout::new(AlignMode::Std140);
         for prop in &self.default.object_layout {
-            object_layout = object_layout.prop(*prop);
+            default_object_layout = default_object_layout.prop(*prop);
         }
 
         let mut default_scene_layout = SceneDataLayout::new(AlignMode::Std140);
@@ -100,12 +153,24 @@ impl MaterialsConfig {
             default_scene_layout = default_scene_layout.prop(*prop);
         }
 
+        let mut materials = vec![];
+
         for (name, material) in &self.materials {
             let vertex_attributes = match material.vertex_attributes {
                 Some(vertex_attributes) => vertex_attributes,
                 None => self.default.vertex_attributes,
             };
 
+            let object_layout = if material.object_layout.is_empty() {
+                default_object_layout.clone()
+            } else {
+                let mut object_layout = ObjectDataLayout::new(AlignMode::Std140);
+                for prop in &material.object_layout {
+                    object_layout = object_layout.prop(*prop);
+                }
+                object_layout
+            };
+
             let scene_layout = if material.scene_layout.is_empty() {
                 default_scene_layout.clone()
             } else {
@@ -123,7 +188,7 @@ impl MaterialsConfig {
                 &material.fragment_shader,
                 &material.fragment_entry,
                 vertex_attributes,
-                object_layout.clone(),
+                object_layout,
                 scene_layout,
                 self.default.color_format,
                 self.default.depth_format,
@@ -134,17 +199,30 @@ impl MaterialsConfig {
             tracing::debug!(target: logger::INIT, "Loading material {} with texture bindings: {:#?}", name, &material.texture_layout);
             tracing::debug!(target: logger::INIT, "Loading material {} with material bindings: {:#?}", name, &material.material_layout);
 
-            for prop in &material.material_layout {
-                props = props.property(*prop);
-            }
+            if material.bindless {
+                if !material.material_layout.is_empty() {
+                    tracing::warn!(target: logger::INIT, "Material layout of bindless material {} is ignored", name);
+                }
 
-            props = props.textures(
-                &material.texture_layout,
-                material.texture_indexing,
-                config.get_int(RenderHalConfig::TextureArraySize),
-            );
+                props = props.bindless(
+                    &material.texture_layout,
+                    config.get_int(RenderHalConfig::TextureArraySize),
+                );
+            } else {
+                for prop in &material.material_layout {
+                    props = props.property(*prop);
+                }
 
-            resource_manager.add::<Material>(props, ResourceLifetime::Static, true);
+                props = props.textures(
+                    &material.texture_layout,
+                    material.texture_indexing,
+                    config.get_int(RenderHalConfig::TextureArraySize),
+                );
+            }
+
+            materials.push(props);
         }
+
+        materials
     }
 }
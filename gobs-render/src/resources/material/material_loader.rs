@@ -1,7 +1,8 @@
 use gobs_core::logger;
 use gobs_render_hal::RenderHAL;
 use gobs_resource::{
-    ResourceRegistry, {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
+    ResourceLifetime, ResourceRegistry,
+    {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
 };
 
 use crate::resources::{MaterialData, Pipeline, PipelineProperties, material::Material};
@@ -28,25 +29,27 @@ impl ResourceLoader<Material> for MaterialLoader {
         handle: &ResourceHandle<Material>,
         registry: &mut ResourceRegistry,
     ) -> Result<MaterialData, ResourceError> {
-        let (pipeline_properties, lifetime) = {
-            let resource = registry.get(handle);
+        let pipeline_properties = {
+            let resource = registry.get(handle).ok_or(ResourceError::InvalidHandle)?;
             tracing::debug!(target: logger::RESOURCES, "Load material resource {}", resource.properties.name());
-            (
-                PipelineProperties::Graphics(resource.properties.pipeline_properties.clone()),
-                resource.lifetime,
-            )
+            PipelineProperties::Graphics(resource.properties.pipeline_properties.clone())
         };
 
-        let pipeline_handle = registry.add::<Pipeline>(pipeline_properties, lifetime, false);
+        let pipeline_handle =
+            registry.add::<Pipeline>(pipeline_properties, ResourceLifetime::RefCounted, false);
+        let pipeline_ref = registry
+            .acquire(&pipeline_handle)
+            .ok_or(ResourceError::InvalidHandle)?;
 
         Ok(MaterialData {
             pipeline: pipeline_handle,
+            _pipeline_ref: pipeline_ref,
         })
     }
 
     fn unload<'a>(&mut self, _hal: &mut (dyn RenderHAL + 'a), _data: MaterialData) {
-        todo!()
+        // pipeline is a separate resource, released when the data is dropped
     }
 
-    fn flush(&mut self) {}
+    fn flush<'a>(&mut self, _hal: &mut (dyn RenderHAL + 'a)) {}
 }
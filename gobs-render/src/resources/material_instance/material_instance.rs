@@ -74,6 +74,33 @@ impl MaterialInstanceProperties {
             MaterialDataPropData::SpecularIndex(index) => {
                 material_data.specular_index = index;
             }
+            MaterialDataPropData::MetallicFactor(factor) => {
+                material_data.metallic_factor = factor;
+            }
+            MaterialDataPropData::RoughnessFactor(factor) => {
+                material_data.roughness_factor = factor;
+            }
+            MaterialDataPropData::OcclusionStrength(strength) => {
+                material_data.occlusion_strength = strength;
+            }
+            MaterialDataPropData::EmissiveStrength(strength) => {
+                material_data.emissive_strength = strength;
+            }
+            MaterialDataPropData::AlphaCutoff(cutoff) => {
+                material_data.alpha_cutoff = cutoff;
+            }
+            MaterialDataPropData::UvOffsetScale(offset_scale) => {
+                material_data.uv_offset_scale = offset_scale;
+            }
+            MaterialDataPropData::UvRotation(rotation) => {
+                material_data.uv_rotation = rotation;
+            }
+            MaterialDataPropData::MetallicRoughnessIndex(index) => {
+                material_data.metallic_roughness_index = index;
+            }
+            MaterialDataPropData::OcclusionIndex(index) => {
+                material_data.occlusion_index = index;
+            }
         }
     }
 
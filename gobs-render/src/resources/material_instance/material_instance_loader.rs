@@ -1,12 +1,12 @@
 use gobs_core::logger;
-use gobs_render_hal::{AttributeData, BufferType, Handle, RenderHAL, UniformData as _};
+use gobs_render_hal::{BufferType, Handle, RenderHAL, UniformData as _};
 use gobs_resource::{
     ResourceRegistry, {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
 };
 
 use crate::{
     MaterialDataPropData, MaterialInstanceProperties, MaterialProperties,
-    data::{MaterialConstantData, MaterialDataLayout, MaterialDataProp, TextureDataProp},
+    data::{MaterialConstantData, MaterialDataLayout, TextureDataProp},
     resources::{MaterialInstance, MaterialInstanceData},
 };
 
@@ -33,31 +33,40 @@ impl ResourceLoader<MaterialInstance> for MaterialInstanceLoader {
         registry: &mut ResourceRegistry,
     ) -> Result<MaterialInstanceData, ResourceError> {
         let material_properties = {
-            let resource = registry.get(handle);
+            let resource = registry.get(handle).ok_or(ResourceError::InvalidHandle)?;
             let properties = &resource.properties;
             let material_handle = properties.material;
-            let material_resource = registry.get(&material_handle);
+            let material_resource = registry
+                .get(&material_handle)
+                .ok_or(ResourceError::InvalidHandle)?;
             let material_properties = &material_resource.properties;
 
             material_properties.clone()
         };
 
         let properties = {
-            let resource = registry.get_mut(handle);
+            let resource = registry
+                .get_mut(handle)
+                .ok_or(ResourceError::InvalidHandle)?;
 
             &mut resource.properties
         };
 
-        Self::update_textures_index(hal, properties, &material_properties);
+        // bindless materials are written to the material table of the render batch
+        let material_buffer = if material_properties.bindless {
+            None
+        } else {
+            Self::update_textures_index(hal, properties, &material_properties);
 
-        tracing::warn!(target: logger::RESOURCES, "Layout {:?}", &material_properties.material_data_layout);
+            tracing::warn!(target: logger::RESOURCES, "Layout {:?}", &material_properties.material_data_layout);
 
-        let material_buffer = self.create_buffer(
-            hal,
-            properties.name(),
-            &material_properties.material_data_layout,
-            properties.material_data.as_ref(),
-        );
+            self.create_buffer(
+                hal,
+                properties.name(),
+                &material_properties.material_data_layout,
+                properties.material_data.as_ref(),
+            )
+        };
 
         let data = MaterialInstanceData {
             material: properties.material,
@@ -74,7 +83,7 @@ impl ResourceLoader<MaterialInstance> for MaterialInstanceLoader {
         }
     }
 
-    fn flush(&mut self) {}
+    fn flush<'a>(&mut self, _hal: &mut (dyn RenderHAL + 'a)) {}
 }
 
 impl MaterialInstanceLoader {
@@ -112,6 +121,10 @@ impl MaterialInstanceLoader {
                     TextureDataProp::Normal => MaterialDataPropData::NormalIndex(index),
                     TextureDataProp::Emission => MaterialDataPropData::EmissionIndex(index),
                     TextureDataProp::Specular => MaterialDataPropData::SpecularIndex(index),
+                    TextureDataProp::MetallicRoughness => {
+                        MaterialDataPropData::MetallicRoughnessIndex(index)
+                    }
+                    TextureDataProp::Occlusion => MaterialDataPropData::OcclusionIndex(index),
                 };
 
                 properties.add_prop(prop);
@@ -129,20 +142,7 @@ impl MaterialInstanceLoader {
         let mut data = Vec::new();
 
         if let Some(material_data) = material_data {
-            material_data_layout.copy_data(&mut data, |prop| match prop {
-                MaterialDataProp::DiffuseColor => AttributeData::Vec4F(material_data.diffuse_color),
-                MaterialDataProp::EmissionColor => {
-                    AttributeData::Vec4F(material_data.emission_color)
-                }
-                MaterialDataProp::SpecularColor => {
-                    AttributeData::Vec4F(material_data.specular_color)
-                }
-                MaterialDataProp::SpecularPower => AttributeData::F32(material_data.specular_power),
-                MaterialDataProp::DiffuseIndex => AttributeData::U32(material_data.diffuse_index),
-                MaterialDataProp::NormalIndex => AttributeData::U32(material_data.normal_index),
-                MaterialDataProp::EmissionIndex => AttributeData::U32(material_data.emission_index),
-                MaterialDataProp::SpecularIndex => AttributeData::U32(material_data.specular_index),
-            });
+            material_data_layout.copy_data(&mut data, |prop| material_data.prop_data(prop));
 
             tracing::warn!(target: logger::RESOURCES, "Data {:?}", &data);
 
@@ -7,6 +7,6 @@ mod shape;
 
 pub use bounds::{Bounded, BoundingBox};
 pub use mesh::{Mesh, MeshData, MeshPath, MeshPrimitiveType, MeshProperties};
-pub use mesh_geometry::{MeshBuilder, MeshGeometry};
+pub use mesh_geometry::{MeshBuilder, MeshGeometry, MorphTarget};
 pub use mesh_loader::MeshLoader;
 pub use shape::{ShapeBuilder, Shapes};
@@ -52,6 +52,21 @@ impl MeshProperties {
         }
     }
 
+    /// Cooked mesh file from the models directory
+    pub fn with_file(
+        name: &str,
+        filename: &str,
+        vertex_attributes: VertexAttribute,
+        layer: u32,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            path: MeshPath::File(filename.to_string()),
+            vertex_attributes,
+            layer,
+        }
+    }
+
     pub fn with_bytes(
         name: &str,
         bytes: Vec<u8>,
@@ -79,4 +94,9 @@ pub struct MeshData {
     pub vertex_view: Handle,
     pub index_view: Handle,
     pub index_len: usize,
+    pub vertices_size: usize,
+    /// Morph target displacements, see [`MeshGeometry::target_data`]
+    pub target_view: Option<Handle>,
+    pub target_count: usize,
+    pub targets_size: usize,
 }
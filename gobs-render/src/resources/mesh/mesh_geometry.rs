@@ -1,37 +1,108 @@
 use std::{
     collections::{HashMap, hash_map},
+    io,
     sync::Arc,
 };
 
-use glam::{Vec2, Vec3};
+use glam::{Vec2, Vec3, Vec4};
 use serde::Serialize;
 use uuid::Uuid;
 
-use gobs_core::{Transform, logger};
+use gobs_core::{Color, Transform, logger};
 use gobs_render_hal::VertexData;
+use gobs_resource::load::{self, AssetType, CookedKind, CookedReader, CookedWriter, LoadingError};
 
 use crate::resources::{Bounded, BoundingBox};
 
 pub type MeshId = Uuid;
 
+/// Displacements added to the vertices when the target is fully weighted, one
+/// per vertex. Attributes that are not morphed are left empty
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MorphTarget {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec3>,
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct MeshGeometry {
     pub id: MeshId,
     pub name: String,
     pub vertices: Vec<VertexData>,
     pub indices: Vec<u32>,
+    pub targets: Vec<MorphTarget>,
 }
 
 impl MeshGeometry {
-    fn new(name: String, vertices: Vec<VertexData>, indices: Vec<u32>) -> Arc<Self> {
+    fn new(
+        name: String,
+        vertices: Vec<VertexData>,
+        indices: Vec<u32>,
+        targets: Vec<MorphTarget>,
+    ) -> Arc<Self> {
         Arc::new(Self {
             id: MeshId::new_v4(),
             name,
             vertices,
             indices,
+            targets,
         })
     }
 
+    /// Blend the morph targets on the CPU, e.g. to upload a mesh with fixed
+    /// weights. Missing weights count as 0
+    pub fn morph(&self, weights: &[f32]) -> Arc<Self> {
+        let mut vertices = self.vertices.clone();
+
+        for (target, &weight) in self.targets.iter().zip(weights) {
+            if weight == 0. {
+                continue;
+            }
+
+            for (vertex, delta) in vertices.iter_mut().zip(&target.positions) {
+                vertex.set_position(vertex.position() + weight * *delta);
+            }
+            for (vertex, delta) in vertices.iter_mut().zip(&target.normals) {
+                vertex.set_normal(vertex.normal() + weight * *delta);
+            }
+            for (vertex, delta) in vertices.iter_mut().zip(&target.tangents) {
+                vertex.set_tangent(vertex.tangent() + weight * *delta);
+            }
+        }
+
+        if self.targets.iter().any(|target| !target.normals.is_empty()) {
+            for vertex in &mut vertices {
+                vertex.set_normal(vertex.normal().normalize_or_zero());
+            }
+        }
+
+        Self::new(
+            self.name.clone(),
+            vertices,
+            self.indices.clone(),
+            self.targets.clone(),
+        )
+    }
+
+    /// Morph targets uploaded for GPU blending: position, normal and tangent
+    /// displacements of each target, padded to 4 floats and grouped by vertex
+    pub fn target_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(12 * self.targets.len() * self.vertices.len());
+
+        let delta = |deltas: &[Vec3], i: usize| deltas.get(i).copied().unwrap_or_default();
+
+        for i in 0..self.vertices.len() {
+            for target in &self.targets {
+                data.extend_from_slice(&delta(&target.positions, i).extend(0.).to_array());
+                data.extend_from_slice(&delta(&target.normals, i).extend(0.).to_array());
+                data.extend_from_slice(&delta(&target.tangents, i).extend(0.).to_array());
+            }
+        }
+
+        data
+    }
+
     pub fn builder(name: &str) -> MeshBuilder {
         MeshBuilder::new(name, 0, 0)
     }
@@ -41,6 +112,110 @@ impl MeshGeometry {
     }
 }
 
+/// Number of floats per vertex in cooked meshes: position, color, texture, normal,
+/// normal texture, tangent, bitangent, joints and weights. Joint indices are
+/// stored as floats, exact up to 2^24
+const COOKED_VERTEX_SIZE: usize = 28;
+
+impl MeshGeometry {
+    pub const COOKED_EXTENSION: &'static str = "gmsh";
+
+    pub fn write_cooked(&self, writer: &mut CookedWriter) {
+        let mut data = Vec::with_capacity(COOKED_VERTEX_SIZE * self.vertices.len());
+
+        for vertex in &self.vertices {
+            data.extend_from_slice(&vertex.position().to_array());
+            data.extend_from_slice(&Into::<[f32; 4]>::into(vertex.color()));
+            data.extend_from_slice(&vertex.texture().to_array());
+            data.extend_from_slice(&vertex.normal().to_array());
+            data.extend_from_slice(&vertex.normal_texture().to_array());
+            data.extend_from_slice(&vertex.tangent().to_array());
+            data.extend_from_slice(&vertex.bitangent().to_array());
+            data.extend_from_slice(&vertex.joints().as_vec4().to_array());
+            data.extend_from_slice(&vertex.weights().to_array());
+        }
+
+        writer.write_str(&self.name);
+        writer.write_f32s(&data);
+        writer.write_u32s(&self.indices);
+
+        writer.write_u64(self.targets.len() as u64);
+        for target in &self.targets {
+            for deltas in [&target.positions, &target.normals, &target.tangents] {
+                writer.write_f32s(&deltas.iter().flat_map(|d| d.to_array()).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    pub fn read_cooked(reader: &mut CookedReader) -> io::Result<Arc<Self>> {
+        let name = reader.read_str()?;
+        let data = reader.read_f32s()?;
+        let indices = reader.read_u32s()?;
+
+        if data.len() % COOKED_VERTEX_SIZE != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid vertex data for mesh {}", name),
+            ));
+        }
+
+        let vertices = data
+            .chunks_exact(COOKED_VERTEX_SIZE)
+            .map(|v| {
+                VertexData::builder()
+                    .position(Vec3::from_slice(&v[0..3]))
+                    .color(Color::from(<[f32; 4]>::try_from(&v[3..7]).unwrap()))
+                    .texture(Vec2::from_slice(&v[7..9]))
+                    .normal(Vec3::from_slice(&v[9..12]))
+                    .normal_texture(Vec2::from_slice(&v[12..14]))
+                    .tangent(Vec3::from_slice(&v[14..17]))
+                    .bitangent(Vec3::from_slice(&v[17..20]))
+                    .joints(Vec4::from_slice(&v[20..24]).as_uvec4())
+                    .weights(Vec4::from_slice(&v[24..28]))
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        if indices.iter().any(|&i| i as usize >= vertices.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid index for mesh {}", name),
+            ));
+        }
+
+        let mut targets = vec![];
+        for _ in 0..reader.read_u64()? {
+            let mut deltas = [vec![], vec![], vec![]];
+            for delta in &mut deltas {
+                let data = reader.read_f32s()?;
+                if !data.is_empty() && data.len() != 3 * vertices.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid morph target for mesh {}", name),
+                    ));
+                }
+                *delta = data.chunks_exact(3).map(Vec3::from_slice).collect();
+            }
+
+            let [positions, normals, tangents] = deltas;
+            targets.push(MorphTarget {
+                positions,
+                normals,
+                tangents,
+            });
+        }
+
+        Ok(Self::new(name, vertices, indices, targets))
+    }
+
+    /// Load a cooked mesh file from the models directory
+    pub fn load_cooked(filename: &str) -> Result<Arc<Self>, LoadingError> {
+        let asset = load::load_cooked_sync(filename, AssetType::MODEL, CookedKind::Mesh)?;
+
+        Self::read_cooked(&mut asset.reader()).map_err(|e| asset.error(e))
+    }
+}
+
 impl Bounded for MeshGeometry {
     fn boundings(&self) -> BoundingBox {
         let mut bounding_box = BoundingBox::default();
@@ -57,6 +232,7 @@ pub struct MeshBuilder {
     pub name: String,
     pub vertices: Vec<VertexData>,
     pub indices: Vec<u32>,
+    pub targets: Vec<MorphTarget>,
     pub generate_tangents: bool,
 }
 
@@ -78,6 +254,7 @@ impl MeshBuilder {
             name: name.to_string(),
             vertices,
             indices,
+            targets: Vec::new(),
             generate_tangents: true,
         }
     }
@@ -124,6 +301,12 @@ impl MeshBuilder {
         self
     }
 
+    pub fn target(&mut self, target: MorphTarget) -> &mut Self {
+        self.targets.push(target);
+
+        self
+    }
+
     pub fn generate_tangents(&mut self, generate_tangents: bool) -> &mut Self {
         self.generate_tangents = generate_tangents;
 
@@ -143,6 +326,12 @@ impl MeshBuilder {
             return self;
         }
 
+        // morph targets are given per vertex, vertices are kept as is
+        if !self.targets.is_empty() {
+            self.indices = (0..self.vertices.len() as u32).collect();
+            return self;
+        }
+
         tracing::trace!(target: logger::RESOURCES, "Indexing {} vertices", self.vertices.len());
 
         let mut unique = HashMap::with_capacity(self.vertices.len());
@@ -279,6 +468,86 @@ impl MeshBuilder {
             self.indices.len()
         );
 
-        MeshGeometry::new(self.name, self.vertices, self.indices)
+        MeshGeometry::new(self.name, self.vertices, self.indices, self.targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_render_hal::VertexData;
+    use gobs_resource::load::{CookedAsset, CookedKind, CookedWriter};
+
+    use crate::resources::{MeshGeometry, MorphTarget};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    /// Triangle with a target raising the first vertex and a target moving
+    /// all vertices along X
+    fn geometry() -> std::sync::Arc<MeshGeometry> {
+        let mut builder = MeshGeometry::builder("triangle");
+        builder
+            .vertex(VertexData::builder().position(Vec3::ZERO).build())
+            .vertex(VertexData::builder().position(Vec3::X).build())
+            .vertex(VertexData::builder().position(Vec3::Y).build())
+            .target(MorphTarget {
+                positions: vec![Vec3::Z, Vec3::ZERO, Vec3::ZERO],
+                ..Default::default()
+            })
+            .target(MorphTarget {
+                positions: vec![Vec3::X; 3],
+                ..Default::default()
+            })
+            .generate_tangents(false);
+
+        builder.build()
+    }
+
+    #[test]
+    fn test_morph() {
+        setup();
+
+        let geometry = geometry();
+        assert_eq!(geometry.indices, vec![0, 1, 2]);
+
+        let morphed = geometry.morph(&[0.5, 1.]);
+        assert_eq!(morphed.vertices[0].position(), Vec3::new(1., 0., 0.5));
+        assert_eq!(morphed.vertices[2].position(), Vec3::new(1., 1., 0.));
+
+        // missing weights
+        let morphed = geometry.morph(&[1.]);
+        assert_eq!(morphed.vertices[0].position(), Vec3::Z);
+        assert_eq!(morphed.vertices[1].position(), Vec3::X);
+
+        // 2 targets, 3 vec4 each, for 3 vertices
+        let data = geometry.target_data();
+        assert_eq!(data.len(), 3 * 2 * 12);
+        assert_eq!(&data[0..4], &[0., 0., 1., 0.]);
+        assert_eq!(&data[12..16], &[1., 0., 0., 0.]);
+    }
+
+    #[test]
+    fn test_cooked_targets() {
+        setup();
+
+        let geometry = geometry();
+
+        let mut writer = CookedWriter::new();
+        geometry.write_cooked(&mut writer);
+        let asset =
+            CookedAsset::from_bytes(writer.finish(CookedKind::Mesh, 0), "mesh.gmsh").unwrap();
+        let cooked = MeshGeometry::read_cooked(&mut asset.reader()).unwrap();
+
+        assert_eq!(cooked.targets, geometry.targets);
+        assert!(cooked.targets[0].normals.is_empty());
     }
 }
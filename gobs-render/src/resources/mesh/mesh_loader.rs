@@ -1,13 +1,18 @@
+use std::sync::Arc;
+
 use gobs_core::logger;
 use gobs_render_graph::GfxContext;
 use gobs_render_hal::{
     AlignMode, BufferType, CommandBuffer, CommandQueueType, RenderHAL, VertexAttribute, VertexData,
 };
 use gobs_resource::{
-    ResourceRegistry, {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
+    PrefetchData, PrefetchJob, ResourceRegistry,
+    {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
 };
 
-use crate::resources::{BufferPool, Mesh, MeshData, MeshGeometry, MeshPath, MeshPrimitiveType};
+use crate::resources::{
+    BufferPool, Mesh, MeshData, MeshGeometry, MeshPath, MeshPrimitiveType, MeshProperties,
+};
 
 pub struct MeshLoader {
     cmd: Box<dyn CommandBuffer>,
@@ -38,24 +43,40 @@ impl MeshLoader {
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    fn stop_recording(&mut self) {
+    fn stop_recording(&mut self, hal: &mut dyn RenderHAL) {
         tracing::debug!(target: logger::RENDER, "Submit mesh loading command");
         self.cmd.end_label();
         self.cmd.end();
-        self.cmd.submit_transfer();
-
-        self.cmd.wait();
-
-        self.buffer_pool.recycle_all();
+        match self.cmd.submit(hal) {
+            Ok(_) => match self.cmd.wait(hal) {
+                Ok(()) => self.buffer_pool.recycle_all(),
+                // staging buffers may still be read by the GPU
+                Err(err) => {
+                    tracing::error!(target: logger::RENDER, "Cannot wait for mesh loading command: {}", err)
+                }
+            },
+            Err(err) => {
+                tracing::error!(target: logger::RENDER, "Cannot submit mesh loading command: {}", err);
+                self.buffer_pool.recycle_all();
+            }
+        }
 
         self.recording = false;
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    fn load_data(&mut self, hal: &mut dyn RenderHAL, vertices: &[u8], indices: &[u32]) -> MeshData {
+    fn load_data(
+        &mut self,
+        hal: &mut dyn RenderHAL,
+        vertices: &[u8],
+        indices: &[u32],
+        targets: &[f32],
+        target_count: usize,
+    ) -> MeshData {
         let vertices_size = vertices.len();
         let indices_size = std::mem::size_of_val(indices);
-        let staging_size = indices_size + vertices_size;
+        let targets_size = std::mem::size_of_val(targets);
+        let staging_size = indices_size + vertices_size + targets_size;
 
         if !self.recording {
             self.start_recording();
@@ -86,11 +107,46 @@ impl MeshLoader {
             0,
         );
 
+        let target_view = (targets_size > 0).then(|| {
+            let target_view = hal.create_buffer("morph targets", targets_size, BufferType::Vertex);
+
+            let offset = (vertices_size + indices_size) as u64;
+            hal.upload_buffer(staging.buffer, bytemuck::cast_slice(targets), offset);
+            self.cmd.copy_buffer_to_buffer(
+                hal,
+                staging.buffer,
+                target_view,
+                targets_size,
+                offset,
+                0,
+            );
+
+            target_view
+        });
+
+        // ownership is acquired by the graphics queue at the beginning of the next frame
+        self.cmd
+            .release_buffer(hal, vertex_view, CommandQueueType::Graphics);
+        self.cmd
+            .release_buffer(hal, index_view, CommandQueueType::Graphics);
+        if let Some(target_view) = target_view {
+            self.cmd
+                .release_buffer(hal, target_view, CommandQueueType::Graphics);
+        }
+
         MeshData {
             ty: MeshPrimitiveType::Triangle,
             vertex_view,
             index_view,
             index_len: indices.len(),
+            vertices_size,
+            target_view,
+            target_count: if target_view.is_some() {
+                target_count
+            } else {
+                0
+            },
+            targets_size,
         }
     }
 
@@ -116,7 +172,13 @@ impl MeshLoader {
         debug_assert!(!vertices.is_empty());
         debug_assert!(!indices.is_empty());
 
-        self.load_data(hal, &vertices, indices)
+        self.load_data(
+            hal,
+            &vertices,
+            indices,
+            &geometry.target_data(),
+            geometry.targets.len(),
+        )
     }
 }
 
@@ -128,15 +190,20 @@ impl ResourceLoader<Mesh> for MeshLoader {
         handle: &ResourceHandle<Mesh>,
         registry: &mut ResourceRegistry,
     ) -> Result<MeshData, ResourceError> {
-        let resource = registry.get_mut(handle);
+        let resource = registry
+            .get_mut(handle)
+            .ok_or(ResourceError::InvalidHandle)?;
         let properties = &mut resource.properties;
 
         tracing::debug!(target: logger::RESOURCES, "Load mesh resource {}", properties.name());
 
         let data = match &properties.path {
             MeshPath::Default => todo!(),
-            MeshPath::File(_) => todo!(),
-            MeshPath::Bytes((vertices, indices)) => self.load_data(hal, vertices, indices),
+            MeshPath::File(filename) => {
+                let geometry = MeshGeometry::load_cooked(filename)?;
+                self.load_geometry(hal, &geometry, properties.vertex_attributes)
+            }
+            MeshPath::Bytes((vertices, indices)) => self.load_data(hal, vertices, indices, &[], 0),
             MeshPath::Mesh(geometry) => {
                 self.load_geometry(hal, geometry, properties.vertex_attributes)
             }
@@ -145,14 +212,57 @@ impl ResourceLoader<Mesh> for MeshLoader {
         Ok(data)
     }
 
+    fn prefetch(&self, properties: &MeshProperties) -> Option<PrefetchJob> {
+        // decode cooked meshes off-thread
+        match &properties.path {
+            MeshPath::File(filename) => {
+                let filename = filename.clone();
+
+                Some(Box::new(move || {
+                    let geometry = MeshGeometry::load_cooked(&filename)?;
+
+                    Ok(Box::new(geometry) as PrefetchData)
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    fn load_prefetched<'a>(
+        &mut self,
+        hal: &mut (dyn RenderHAL + 'a),
+        handle: &ResourceHandle<Mesh>,
+        registry: &mut ResourceRegistry,
+        data: PrefetchData,
+    ) -> Result<MeshData, ResourceError> {
+        let geometry = data
+            .downcast::<Arc<MeshGeometry>>()
+            .map_err(|_| ResourceError::InvalidData)?;
+
+        let resource = registry.get(handle).ok_or(ResourceError::InvalidHandle)?;
+        let properties = &resource.properties;
+
+        tracing::debug!(target: logger::RESOURCES, "Upload prefetched mesh resource {}", properties.name());
+
+        Ok(self.load_geometry(hal, &geometry, properties.vertex_attributes))
+    }
+
+    fn size(&self, _properties: &MeshProperties, data: &MeshData) -> usize {
+        data.vertices_size + data.index_len * std::mem::size_of::<u32>() + data.targets_size
+    }
+
     fn unload<'a>(&mut self, hal: &mut (dyn RenderHAL + 'a), data: MeshData) {
         hal.destroy_buffer(data.vertex_view);
         hal.destroy_buffer(data.index_view);
+        if let Some(target_view) = data.target_view {
+            hal.destroy_buffer(target_view);
+        }
     }
 
-    fn flush(&mut self) {
+    fn flush<'a>(&mut self, hal: &mut (dyn RenderHAL + 'a)) {
         if self.recording {
-            self.stop_recording();
+            self.stop_recording(hal);
         }
     }
 }
@@ -1,8 +1,10 @@
 #[allow(clippy::module_inception)]
 mod pipeline;
 mod pipeline_config;
+mod pipeline_layout;
 mod pipeline_loader;
 
 pub use pipeline::{GraphicsPipelineProperties, Pipeline, PipelineProperties};
 pub use pipeline_config::PipelinesConfig;
+pub use pipeline_layout::PipelineLayoutError;
 pub use pipeline_loader::PipelineLoader;
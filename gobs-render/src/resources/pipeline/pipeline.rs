@@ -1,15 +1,18 @@
 use std::{fmt::Debug, sync::Arc};
 
 use gobs_core::ImageFormat;
-use gobs_render_graph::SceneDataLayout;
+use gobs_render_graph::{ComputeDataLayout, SceneDataLayout};
 use gobs_render_hal::{
     AlignMode, BindingGroupLayout, BindingGroupType, BlendMode, CompareOp, CullMode,
     DescriptorStage, DescriptorType, FrontFace, Handle, ObjectDataLayout, PolygonMode, RenderHAL,
-    VertexAttribute,
+    ShaderType, UniformData as _, VertexAttribute,
 };
 use gobs_resource::{ResourceProperties, ResourceType};
 
-use crate::resources::PipelineLoader;
+use crate::resources::{
+    PipelineLoader,
+    pipeline::pipeline_layout::{PipelineLayoutError, ShaderLayout},
+};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Pipeline;
@@ -50,6 +53,24 @@ impl PipelineProperties {
     pub fn graphics(name: &str) -> GraphicsPipelineProperties {
         GraphicsPipelineProperties::new(name)
     }
+
+    /// Check the declared bindings and layouts against the SPIR-V of the shaders
+    pub fn validate(&self, hal: &dyn RenderHAL) -> Result<(), PipelineLayoutError> {
+        match self {
+            PipelineProperties::Compute(props) => props.validate(hal),
+            PipelineProperties::Graphics(props) => props.validate(hal),
+        }
+    }
+
+    pub fn uses_shader(&self, filename: &str) -> bool {
+        match self {
+            PipelineProperties::Compute(props) => props.compute_shader.as_deref() == Some(filename),
+            PipelineProperties::Graphics(props) => {
+                props.vertex_shader.as_deref() == Some(filename)
+                    || props.fragment_shader.as_deref() == Some(filename)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -214,6 +235,28 @@ impl GraphicsPipelineProperties {
         self
     }
 
+    pub(crate) fn shader_layout(&self, hal: &dyn RenderHAL) -> ShaderLayout {
+        let mut shaders = vec![];
+
+        if let (Some(file), Some(entry)) = (&self.vertex_shader, &self.vertex_entry) {
+            shaders.push((file.as_str(), entry.as_str(), ShaderType::Vertex));
+        }
+        if let (Some(file), Some(entry)) = (&self.fragment_shader, &self.fragment_entry) {
+            shaders.push((file.as_str(), entry.as_str(), ShaderType::Fragment));
+        }
+
+        ShaderLayout::new(hal, &shaders)
+    }
+
+    pub fn validate(&self, hal: &dyn RenderHAL) -> Result<(), PipelineLayoutError> {
+        let shaders = self.shader_layout(hal);
+
+        shaders.validate_bindings(&self.binding_groups)?;
+        shaders.validate_push_constants(self.object_data_layout.uniform_layout())?;
+        shaders.validate_scene_data(self.scene_data_layout.uniform_layout())?;
+        shaders.validate_vertex_inputs(self.vertex_attributes)
+    }
+
     pub fn wrap(self) -> PipelineProperties {
         PipelineProperties::Graphics(self)
     }
@@ -225,6 +268,7 @@ pub struct ComputePipelineProperties {
     pub(crate) compute_entry: String,
     pub(crate) compute_shader: Option<String>,
     pub(crate) binding_groups: Vec<Arc<BindingGroupLayout>>,
+    pub(crate) compute_data_layout: ComputeDataLayout,
 }
 
 impl ComputePipelineProperties {
@@ -234,6 +278,7 @@ impl ComputePipelineProperties {
             compute_entry: "main".to_string(),
             compute_shader: None,
             binding_groups: Vec::new(),
+            compute_data_layout: ComputeDataLayout::new(AlignMode::Std430),
         }
     }
 
@@ -265,6 +310,28 @@ impl ComputePipelineProperties {
         self
     }
 
+    pub fn compute_data_layout(mut self, layout: ComputeDataLayout) -> Self {
+        self.compute_data_layout = layout;
+
+        self
+    }
+
+    pub(crate) fn shader_layout(&self, hal: &dyn RenderHAL) -> ShaderLayout {
+        match &self.compute_shader {
+            Some(file) => {
+                ShaderLayout::new(hal, &[(file, &self.compute_entry, ShaderType::Compute)])
+            }
+            None => ShaderLayout::new(hal, &[]),
+        }
+    }
+
+    pub fn validate(&self, hal: &dyn RenderHAL) -> Result<(), PipelineLayoutError> {
+        let shaders = self.shader_layout(hal);
+
+        shaders.validate_bindings(&self.binding_groups)?;
+        shaders.validate_push_constants(self.compute_data_layout.uniform_layout())
+    }
+
     pub fn wrap(self) -> PipelineProperties {
         PipelineProperties::Compute(self)
     }
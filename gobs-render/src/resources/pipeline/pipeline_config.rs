@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use gobs_render_graph::{GfxContext, SceneDataLayout, SceneDataProp};
+use gobs_render_graph::{
+    ComputeDataLayout, ComputeDataProp, GfxContext, SceneDataLayout, SceneDataProp,
+};
 use serde::{Deserialize, Serialize};
 
 use gobs_core::{ImageFormat, logger};
@@ -9,7 +11,7 @@ use gobs_render_hal::{
     ObjectDataLayout, ObjectDataProp, PolygonMode, UniformData as _, VertexAttribute,
 };
 use gobs_resource::{
-    ResourceManager,
+    ResourceManager, ResourceProperties as _,
     load::{self, AssetType},
     {ResourceError, ResourceLifetime},
 };
@@ -25,17 +27,26 @@ pub struct PipelinesConfig {
 #[derive(Debug, Serialize, Deserialize)]
 struct ComputePipelineConfig {
     compute_shader: Option<ShaderConfig>,
+    /// Inferred from the shader if empty
     #[serde(default)]
     bindings: Vec<BindingConfig>,
+    /// Inferred from the shader push constants if empty
+    #[serde(default)]
+    push_constants: Vec<ComputeDataProp>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct GraphicsPipelineConfig {
     vertex_shader: Option<ShaderConfig>,
     fragment_shader: Option<ShaderConfig>,
+    /// Inferred from the shader push constants if empty
+    #[serde(default)]
     object_layout: Vec<ObjectDataProp>,
+    /// Inferred from the shader scene data uniform if empty
+    #[serde(default)]
     scene_layout: Vec<SceneDataProp>,
     vertex_attributes: VertexAttribute,
+    /// Inferred from the shaders if empty
     #[serde(default)]
     bindings: Vec<BindingConfig>,
     polygon_mode: PolygonMode,
@@ -90,30 +101,68 @@ impl PipelinesConfig {
         data: &str,
         resource_manager: &mut ResourceManager,
     ) -> Result<(), ResourceError> {
-        let options = ron::options::Options::default()
-            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
-        let config: PipelinesConfig = options
-            .from_str(data)
-            .map_err(|_| ResourceError::InvalidData)?;
-
-        for pipeline_name in config.compute_pipelines.keys() {
-            let pipeline = Self::load_compute_pipeline(&config, pipeline_name);
-            if let Some(pipeline) = pipeline {
-                resource_manager.add::<Pipeline>(pipeline, ResourceLifetime::Static, true);
-            }
+        for pipeline in Self::load_pipelines(ctx, data)? {
+            resource_manager.add::<Pipeline>(pipeline, ResourceLifetime::Static, true);
         }
 
-        for pipeline_name in config.graphics_pipelines.keys() {
-            let pipeline = Self::load_graphics_pipeline(ctx, &config, pipeline_name);
-            if let Some(pipeline) = pipeline {
-                resource_manager.add::<Pipeline>(pipeline, ResourceLifetime::Static, true);
+        Ok(())
+    }
+
+    /// Update pipelines after the config file has been modified. Existing pipelines are
+    /// reloaded in place and new pipelines are added.
+    pub fn reload_resources(
+        ctx: &GfxContext,
+        filename: &str,
+        resource_manager: &mut ResourceManager,
+    ) -> Result<(), ResourceError> {
+        let data = load::load_string_sync(filename, AssetType::RESOURCES)?;
+
+        for pipeline in Self::load_pipelines(ctx, &data)? {
+            match resource_manager.get_by_name::<Pipeline>(pipeline.name()) {
+                Some(handle) => {
+                    if let Some(resource) = resource_manager.get_mut(&handle) {
+                        resource.properties = pipeline;
+                    }
+                    resource_manager.reload(&handle);
+                }
+                None => {
+                    resource_manager.add::<Pipeline>(pipeline, ResourceLifetime::Static, true);
+                }
             }
         }
 
         Ok(())
     }
 
-    fn load_compute_pipeline(config: &PipelinesConfig, name: &str) -> Option<PipelineProperties> {
+    fn load_pipelines(
+        ctx: &GfxContext,
+        data: &str,
+    ) -> Result<Vec<PipelineProperties>, ResourceError> {
+        let options = ron::options::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
+        let config: PipelinesConfig = options.from_str(data).map_err(|e| {
+            tracing::error!(target: logger::RESOURCES, "Invalid pipeline config: {}", e);
+            ResourceError::InvalidData
+        })?;
+
+        let compute_pipelines = config
+            .compute_pipelines
+            .keys()
+            .filter_map(|pipeline_name| Self::load_compute_pipeline(ctx, &config, pipeline_name));
+
+        let graphics_pipelines = config
+            .graphics_pipelines
+            .keys()
+            .filter_map(|pipeline_name| Self::load_graphics_pipeline(ctx, &config, pipeline_name));
+
+        Ok(compute_pipelines.chain(graphics_pipelines).collect())
+    }
+
+    fn load_compute_pipeline(
+        ctx: &GfxContext,
+        config: &PipelinesConfig,
+        name: &str,
+    ) -> Option<PipelineProperties> {
         let pipeline = config.compute_pipelines.get(name)?;
 
         let mut props = PipelineProperties::compute(name);
@@ -124,13 +173,34 @@ impl PipelinesConfig {
                 .compute_entry(&shader.entry);
         }
 
+        let shaders = (pipeline.bindings.is_empty() || pipeline.push_constants.is_empty())
+            .then(|| props.shader_layout(ctx.hal()));
+
+        let push_constants = match &shaders {
+            Some(shaders) if pipeline.push_constants.is_empty() => {
+                shaders.compute_layout().unwrap_or_default()
+            }
+            _ => pipeline.push_constants.clone(),
+        };
+        let mut compute_layout = ComputeDataLayout::new(AlignMode::Std430);
+        for prop in push_constants {
+            compute_layout = compute_layout.prop(prop);
+        }
+
+        props = props.compute_data_layout(compute_layout);
+
+        let bindings = match &shaders {
+            Some(shaders) if pipeline.bindings.is_empty() => shaders.bindings(),
+            _ => Self::bindings(&pipeline.bindings),
+        };
+
         let mut last_group = BindingGroupType::None;
-        for binding in &pipeline.bindings {
-            if binding.group != last_group {
-                props = props.binding_group(binding.group);
-                last_group = binding.group;
+        for (group, ty, stage, count) in bindings {
+            if group != last_group {
+                props = props.binding_group(group);
+                last_group = group;
             }
-            props = props.binding(binding.descriptor_type, binding.stage, binding.count);
+            props = props.binding(ty, stage, count);
         }
 
         Some(PipelineProperties::Compute(props))
@@ -143,33 +213,70 @@ impl PipelinesConfig {
     ) -> Option<PipelineProperties> {
         let pipeline = config.graphics_pipelines.get(name)?;
 
+        let mut props = PipelineProperties::graphics(name)
+            .pool_size(ctx.frames_in_flight())
+            .vertex_attributes(pipeline.vertex_attributes)
+            .polygon_mode(pipeline.polygon_mode)
+            .cull_mode(pipeline.cull_mode)
+            .front_face(pipeline.front_face);
+
+        if let Some(shader) = &pipeline.vertex_shader {
+            props = props
+                .vertex_shader(&shader.file)
+                .vertex_entry(&shader.entry);
+        }
+
+        if let Some(shader) = &pipeline.fragment_shader {
+            props = props
+                .fragment_shader(&shader.file)
+                .fragment_entry(&shader.entry);
+        }
+
+        let shaders = (pipeline.bindings.is_empty()
+            || pipeline.object_layout.is_empty()
+            || pipeline.scene_layout.is_empty())
+        .then(|| props.shader_layout(ctx.hal()));
+
+        let object_layout_props = match &shaders {
+            Some(shaders) if pipeline.object_layout.is_empty() => {
+                shaders.object_layout().unwrap_or_default()
+            }
+            _ => pipeline.object_layout.clone(),
+        };
         let mut object_layout = ObjectDataLayout::new(AlignMode::Std140);
-        for prop in &pipeline.object_layout {
-            object_layout = object_layout.prop(*prop);
+        for prop in object_layout_props {
+            object_layout = object_layout.prop(prop);
         }
 
+        let scene_layout_props = match &shaders {
+            Some(shaders) if pipeline.scene_layout.is_empty() => {
+                shaders.scene_layout().unwrap_or_default()
+            }
+            _ => pipeline.scene_layout.clone(),
+        };
         let mut scene_layout = SceneDataLayout::new(AlignMode::Std140);
-        for prop in &pipeline.scene_layout {
-            scene_layout = scene_layout.prop(*prop);
+        for prop in scene_layout_props {
+            scene_layout = scene_layout.prop(prop);
         }
 
-        let mut props = PipelineProperties::graphics(name)
+        props = props
             .object_data_layout(object_layout)
-            .pool_size(ctx.frames_in_flight())
-            .vertex_attributes(pipeline.vertex_attributes)
-            .polygon_mode(pipeline.polygon_mode)
-            .cull_mode(pipeline.cull_mode)
-            .front_face(pipeline.front_face);
+            .scene_data_layout(scene_layout);
+
+        let bindings = match &shaders {
+            Some(shaders) if pipeline.bindings.is_empty() => shaders.bindings(),
+            _ => Self::bindings(&pipeline.bindings),
+        };
 
-        tracing::debug!(target: logger::INIT, "Loading pipeline {} with bindings: {:#?}", name, &pipeline.bindings);
+        tracing::debug!(target: logger::INIT, "Loading pipeline {} with bindings: {:#?}", name, &bindings);
 
         let mut last_group = BindingGroupType::None;
-        for binding in &pipeline.bindings {
-            if binding.group != last_group {
-                props = props.binding_group(binding.group);
-                last_group = binding.group;
+        for (group, ty, stage, count) in bindings {
+            if group != last_group {
+                props = props.binding_group(group);
+                last_group = group;
             }
-            props = props.binding(binding.descriptor_type, binding.stage, binding.count);
+            props = props.binding(ty, stage, count);
         }
 
         if let Some(format) = pipeline.attachments.color_format {
@@ -179,18 +286,6 @@ impl PipelinesConfig {
             props = props.depth_format(format);
         }
 
-        if let Some(shader) = &pipeline.vertex_shader {
-            props = props
-                .vertex_shader(&shader.file)
-                .vertex_entry(&shader.entry);
-        }
-
-        if let Some(shader) = &pipeline.fragment_shader {
-            props = props
-                .fragment_shader(&shader.file)
-                .fragment_entry(&shader.entry);
-        }
-
         if pipeline.depth_test.enable {
             props = props.depth_test_enable(
                 pipeline.depth_test.write_enable,
@@ -202,6 +297,15 @@ impl PipelinesConfig {
 
         Some(PipelineProperties::Graphics(props))
     }
+
+    fn bindings(
+        bindings: &[BindingConfig],
+    ) -> Vec<(BindingGroupType, DescriptorType, DescriptorStage, u32)> {
+        bindings
+            .iter()
+            .map(|b| (b.group, b.descriptor_type, b.stage, b.count))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,566 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use gobs_core::logger;
+use gobs_render_graph::{ComputeDataProp, SceneDataProp};
+use gobs_render_hal::{
+    BindingGroupLayout, BindingGroupType, BlockLayout, DescriptorStage, DescriptorType, MemberType,
+    ObjectDataProp, ReflectedDescriptorType, RenderHAL, ShaderReflection, ShaderType,
+    UniformLayout, VertexAttribute,
+};
+
+#[derive(Debug, Error)]
+pub enum PipelineLayoutError {
+    #[error("{shader}: binding ({set}, {binding}) `{name}` is not declared")]
+    MissingBinding {
+        shader: String,
+        name: String,
+        set: u32,
+        binding: u32,
+    },
+    #[error(
+        "{shader}: binding ({set}, {binding}) `{name}` is declared as {declared:?} but the shader uses {reflected:?}"
+    )]
+    DescriptorTypeMismatch {
+        shader: String,
+        name: String,
+        set: u32,
+        binding: u32,
+        declared: DescriptorType,
+        reflected: ReflectedDescriptorType,
+    },
+    #[error(
+        "{shader}: binding ({set}, {binding}) `{name}` is declared with {declared} descriptors but the shader uses {reflected}"
+    )]
+    CountMismatch {
+        shader: String,
+        name: String,
+        set: u32,
+        binding: u32,
+        declared: u32,
+        reflected: u32,
+    },
+    #[error(
+        "{shader}: binding ({set}, {binding}) `{name}` is declared for stage {declared:?} but used by the {stage:?} stage"
+    )]
+    StageMismatch {
+        shader: String,
+        name: String,
+        set: u32,
+        binding: u32,
+        declared: DescriptorStage,
+        stage: ShaderType,
+    },
+    #[error("{shader}: {block} member `{member}` at offset {offset} is not declared")]
+    MissingMember {
+        shader: String,
+        block: String,
+        member: String,
+        offset: u32,
+    },
+    #[error(
+        "{shader}: {block} member `{member}` is at offset {reflected} but the layout puts it at offset {declared}"
+    )]
+    MemberOffsetMismatch {
+        shader: String,
+        block: String,
+        member: String,
+        declared: usize,
+        reflected: u32,
+    },
+    #[error("{shader}: {block} declares {declared} members but the shader uses {reflected}")]
+    MemberCountMismatch {
+        shader: String,
+        block: String,
+        declared: usize,
+        reflected: usize,
+    },
+    #[error("{shader}: {declared} vertex attributes declared but the shader reads {reflected}")]
+    VertexInputMismatch {
+        shader: String,
+        declared: usize,
+        reflected: usize,
+    },
+}
+
+/// Reflected entry point of a pipeline shader
+struct ShaderStage {
+    file: String,
+    entry: String,
+    stage: ShaderType,
+    reflection: Arc<ShaderReflection>,
+}
+
+impl ShaderStage {
+    fn load(hal: &dyn RenderHAL, file: &str, entry: &str, stage: ShaderType) -> Option<Self> {
+        let reflection = hal.shader_reflection(file)?;
+
+        if reflection.entry_point(entry).is_none() {
+            tracing::warn!(target: logger::RESOURCES, "Entry point {} not found in shader {}", entry, file);
+        }
+
+        Some(Self {
+            file: file.to_string(),
+            entry: entry.to_string(),
+            stage,
+            reflection,
+        })
+    }
+
+    fn label(&self) -> String {
+        format!("{}:{}", self.file, self.entry)
+    }
+
+    fn visible(&self, stage: DescriptorStage) -> bool {
+        matches!(
+            (stage, self.stage),
+            (
+                DescriptorStage::All,
+                ShaderType::Vertex | ShaderType::Fragment
+            ) | (DescriptorStage::Compute, ShaderType::Compute)
+                | (DescriptorStage::Vertex, ShaderType::Vertex)
+                | (DescriptorStage::Fragment, ShaderType::Fragment)
+        )
+    }
+}
+
+/// Resources used by the shaders of a pipeline, as declared in their SPIR-V code.
+/// Shaders that cannot be read or reflected are skipped.
+pub(crate) struct ShaderLayout {
+    stages: Vec<ShaderStage>,
+}
+
+impl ShaderLayout {
+    /// Shaders given as (file, entry point, stage)
+    pub(crate) fn new(hal: &dyn RenderHAL, shaders: &[(&str, &str, ShaderType)]) -> Self {
+        let stages = shaders
+            .iter()
+            .filter_map(|(file, entry, stage)| ShaderStage::load(hal, file, entry, *stage))
+            .collect();
+
+        Self { stages }
+    }
+
+    /// Check that every binding used by the shaders is declared with a compatible type
+    pub(crate) fn validate_bindings(
+        &self,
+        binding_groups: &[Arc<BindingGroupLayout>],
+    ) -> Result<(), PipelineLayoutError> {
+        for stage in &self.stages {
+            for reflected in stage.reflection.bindings(&stage.entry) {
+                let declared = binding_groups
+                    .iter()
+                    .filter(|group| group.binding_group_type.set() == reflected.set)
+                    .find_map(|group| group.bindings.get(reflected.binding as usize));
+
+                let Some(&(ty, descriptor_stage, count)) = declared else {
+                    return Err(PipelineLayoutError::MissingBinding {
+                        shader: stage.label(),
+                        name: reflected.name.clone(),
+                        set: reflected.set,
+                        binding: reflected.binding,
+                    });
+                };
+
+                if !reflected.ty.is_compatible(ty) {
+                    return Err(PipelineLayoutError::DescriptorTypeMismatch {
+                        shader: stage.label(),
+                        name: reflected.name.clone(),
+                        set: reflected.set,
+                        binding: reflected.binding,
+                        declared: ty,
+                        reflected: reflected.ty,
+                    });
+                }
+
+                // runtime sized arrays accept any count
+                if reflected.count != 0 && reflected.count != count {
+                    return Err(PipelineLayoutError::CountMismatch {
+                        shader: stage.label(),
+                        name: reflected.name.clone(),
+                        set: reflected.set,
+                        binding: reflected.binding,
+                        declared: count,
+                        reflected: reflected.count,
+                    });
+                }
+
+                if !stage.visible(descriptor_stage) {
+                    return Err(PipelineLayoutError::StageMismatch {
+                        shader: stage.label(),
+                        name: reflected.name.clone(),
+                        set: reflected.set,
+                        binding: reflected.binding,
+                        declared: descriptor_stage,
+                        stage: stage.stage,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn validate_push_constants(
+        &self,
+        layout: &UniformLayout,
+    ) -> Result<(), PipelineLayoutError> {
+        for stage in &self.stages {
+            if let Some(block) = stage.reflection.push_constants(&stage.entry) {
+                Self::validate_block(stage, &block, layout)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the scene data uniform against the layout. Skipped when the layout is empty
+    /// as the scene data is then provided by the render pass.
+    pub(crate) fn validate_scene_data(
+        &self,
+        layout: &UniformLayout,
+    ) -> Result<(), PipelineLayoutError> {
+        if layout.is_empty() {
+            return Ok(());
+        }
+
+        for stage in &self.stages {
+            if let Some(block) = Self::scene_data(stage) {
+                Self::validate_block(stage, block, layout)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Only checked for shaders that read vertex attributes from the input assembly
+    pub(crate) fn validate_vertex_inputs(
+        &self,
+        vertex_attributes: VertexAttribute,
+    ) -> Result<(), PipelineLayoutError> {
+        for stage in self.stages.iter().filter(|s| s.stage == ShaderType::Vertex) {
+            let reflected = stage.reflection.inputs(&stage.entry).count();
+            let declared = vertex_attributes.iter().count();
+
+            if reflected > 0 && reflected != declared {
+                return Err(PipelineLayoutError::VertexInputMismatch {
+                    shader: stage.label(),
+                    declared,
+                    reflected,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_block(
+        stage: &ShaderStage,
+        block: &BlockLayout,
+        layout: &UniformLayout,
+    ) -> Result<(), PipelineLayoutError> {
+        for (i, member) in block.members.iter().enumerate() {
+            match layout.offsets().get(i) {
+                None => {
+                    return Err(PipelineLayoutError::MissingMember {
+                        shader: stage.label(),
+                        block: block.name.clone(),
+                        member: member.name.clone(),
+                        offset: member.offset,
+                    });
+                }
+                Some(&offset) if offset != member.offset as usize => {
+                    return Err(PipelineLayoutError::MemberOffsetMismatch {
+                        shader: stage.label(),
+                        block: block.name.clone(),
+                        member: member.name.clone(),
+                        declared: offset,
+                        reflected: member.offset,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if layout.len() > block.members.len() {
+            return Err(PipelineLayoutError::MemberCountMismatch {
+                shader: stage.label(),
+                block: block.name.clone(),
+                declared: layout.len(),
+                reflected: block.members.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn scene_data(stage: &ShaderStage) -> Option<&BlockLayout> {
+        let set = BindingGroupType::SceneData.set();
+
+        stage
+            .reflection
+            .bindings(&stage.entry)
+            .find(|b| {
+                b.set == set && b.binding == 0 && b.ty == ReflectedDescriptorType::UniformBuffer
+            })
+            .and_then(|b| b.block.as_ref())
+    }
+
+    /// Binding groups used by the shaders, in set and binding order
+    pub(crate) fn bindings(&self) -> Vec<(BindingGroupType, DescriptorType, DescriptorStage, u32)> {
+        let mut bindings: Vec<(
+            u32,
+            u32,
+            BindingGroupType,
+            DescriptorType,
+            DescriptorStage,
+            u32,
+        )> = Vec::new();
+
+        for stage in &self.stages {
+            for reflected in stage.reflection.bindings(&stage.entry) {
+                let descriptor_stage = match stage.stage {
+                    ShaderType::Compute => DescriptorStage::Compute,
+                    ShaderType::Vertex => DescriptorStage::Vertex,
+                    ShaderType::Fragment => DescriptorStage::Fragment,
+                };
+
+                if let Some(binding) = bindings
+                    .iter_mut()
+                    .find(|b| b.0 == reflected.set && b.1 == reflected.binding)
+                {
+                    if binding.4 != descriptor_stage {
+                        binding.4 = DescriptorStage::All;
+                    }
+                    continue;
+                }
+
+                let ty = reflected.ty.descriptor_type();
+
+                if reflected.count == 0 {
+                    tracing::warn!(target: logger::RESOURCES, "Binding {} in {} is a runtime array and must be declared", reflected.name, stage.label());
+                    continue;
+                }
+
+                let group = match (stage.stage, reflected.set) {
+                    (ShaderType::Compute, 0) => BindingGroupType::ComputeData,
+                    (_, 0) => BindingGroupType::SceneData,
+                    (_, 1) => BindingGroupType::MaterialData,
+                    (_, 2) => BindingGroupType::MaterialTextures,
+                    (_, set) => {
+                        tracing::warn!(target: logger::RESOURCES, "No binding group for set {} in {}", set, stage.label());
+                        continue;
+                    }
+                };
+
+                bindings.push((
+                    reflected.set,
+                    reflected.binding,
+                    group,
+                    ty,
+                    descriptor_stage,
+                    reflected.count,
+                ));
+            }
+        }
+
+        bindings.sort_by_key(|b| (b.0, b.1));
+
+        bindings
+            .into_iter()
+            .map(|(_, _, group, ty, stage, count)| (group, ty, stage, count))
+            .collect()
+    }
+
+    /// Object data matching the push constants of the shaders
+    pub(crate) fn object_layout(&self) -> Option<Vec<ObjectDataProp>> {
+        let (stage, block) = self
+            .stages
+            .iter()
+            .find_map(|s| s.reflection.push_constants(&s.entry).map(|b| (s, b)))?;
+
+        block
+            .members
+            .iter()
+            .map(|member| match member.ty {
+                MemberType::Matrix {
+                    columns: 4,
+                    rows: 4,
+                } => Some(ObjectDataProp::WorldMatrix),
+                MemberType::Pointer if member.name == "object_buffer" => {
+                    Some(ObjectDataProp::ObjectBufferAddress)
+                }
+                MemberType::Pointer if member.name == "material_buffer" => {
+                    Some(ObjectDataProp::MaterialBufferAddress)
+                }
+                MemberType::Pointer if member.name == "joint_buffer" => {
+                    Some(ObjectDataProp::JointBufferAddress)
+                }
+                MemberType::Pointer if member.name == "morph_targets" => {
+                    Some(ObjectDataProp::MorphTargetAddress)
+                }
+                MemberType::Pointer if member.name == "morph_weights" => {
+                    Some(ObjectDataProp::MorphWeightAddress)
+                }
+                MemberType::Scalar if member.name == "morph_count" => {
+                    Some(ObjectDataProp::MorphTargetCount)
+                }
+                MemberType::Scalar if member.name == "material_index" => {
+                    Some(ObjectDataProp::MaterialIndex)
+                }
+                MemberType::Scalar if member.name == "pick_id" => Some(ObjectDataProp::PickId),
+                MemberType::Pointer => Some(ObjectDataProp::VertexBufferAddress),
+                _ => {
+                    tracing::warn!(target: logger::RESOURCES, "Cannot infer object data for member {} in {}", member.name, stage.label());
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Compute data matching the push constants of a compute shader, by field name
+    pub(crate) fn compute_layout(&self) -> Option<Vec<ComputeDataProp>> {
+        let (stage, block) = self
+            .stages
+            .iter()
+            .find_map(|s| s.reflection.push_constants(&s.entry).map(|b| (s, b)))?;
+
+        block
+            .members
+            .iter()
+            .map(|member| {
+                let prop = ComputeDataProp::from_label(&member.name);
+                if prop.is_none() {
+                    tracing::warn!(target: logger::RESOURCES, "Cannot infer compute data for member {} in {}", member.name, stage.label());
+                }
+                prop
+            })
+            .collect()
+    }
+
+    /// Scene data matching the scene uniform of the shaders, by field name
+    pub(crate) fn scene_layout(&self) -> Option<Vec<SceneDataProp>> {
+        let (stage, block) = self
+            .stages
+            .iter()
+            .find_map(|s| Self::scene_data(s).map(|b| (s, b)))?;
+
+        block
+            .members
+            .iter()
+            .map(|member| {
+                let prop = SceneDataProp::from_label(&member.name);
+                if prop.is_none() {
+                    tracing::warn!(target: logger::RESOURCES, "Cannot infer scene data for member {} in {}", member.name, stage.label());
+                }
+                prop
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_render_hal::{
+        AlignMode, BlockLayout, BlockMember, MemberType, ObjectDataLayout, ObjectDataProp,
+        ShaderReflection, ShaderType, UniformData,
+    };
+
+    use crate::resources::pipeline::pipeline_layout::{
+        PipelineLayoutError, ShaderLayout, ShaderStage,
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn member(name: &str, offset: u32, size: u32, ty: MemberType) -> BlockMember {
+        BlockMember {
+            name: name.to_string(),
+            offset,
+            size,
+            ty,
+        }
+    }
+
+    #[test]
+    fn test_validate_push_constants() {
+        setup();
+
+        let stage = ShaderStage {
+            file: "color.spv".to_string(),
+            entry: "vertex_main".to_string(),
+            stage: ShaderType::Vertex,
+            reflection: Arc::new(ShaderReflection::default()),
+        };
+
+        let block = BlockLayout {
+            name: "PushConstants".to_string(),
+            size: 72,
+            members: vec![
+                member(
+                    "world_matrix",
+                    0,
+                    64,
+                    MemberType::Matrix {
+                        columns: 4,
+                        rows: 4,
+                    },
+                ),
+                member("vertex_reference", 64, 8, MemberType::Pointer),
+            ],
+        };
+
+        let layout = ObjectDataLayout::new(AlignMode::Std140)
+            .prop(ObjectDataProp::WorldMatrix)
+            .prop(ObjectDataProp::VertexBufferAddress);
+        ShaderLayout::validate_block(&stage, &block, layout.uniform_layout()).unwrap();
+
+        let layout = ObjectDataLayout::new(AlignMode::Std140)
+            .prop(ObjectDataProp::VertexBufferAddress)
+            .prop(ObjectDataProp::WorldMatrix);
+        let err =
+            ShaderLayout::validate_block(&stage, &block, layout.uniform_layout()).unwrap_err();
+        assert!(matches!(
+            err,
+            PipelineLayoutError::MemberOffsetMismatch {
+                declared: 16,
+                reflected: 64,
+                ..
+            }
+        ));
+        tracing::info!("{}", err);
+
+        let layout = ObjectDataLayout::new(AlignMode::Std140).prop(ObjectDataProp::WorldMatrix);
+        let err =
+            ShaderLayout::validate_block(&stage, &block, layout.uniform_layout()).unwrap_err();
+        assert!(matches!(
+            err,
+            PipelineLayoutError::MissingMember { offset: 64, .. }
+        ));
+        tracing::info!("{}", err);
+
+        let layout = ObjectDataLayout::new(AlignMode::Std140)
+            .prop(ObjectDataProp::WorldMatrix)
+            .prop(ObjectDataProp::VertexBufferAddress)
+            .prop(ObjectDataProp::WorldMatrix);
+        assert!(matches!(
+            ShaderLayout::validate_block(&stage, &block, layout.uniform_layout()),
+            Err(PipelineLayoutError::MemberCountMismatch {
+                declared: 3,
+                reflected: 2,
+                ..
+            })
+        ));
+    }
+}
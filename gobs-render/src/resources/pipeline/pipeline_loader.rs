@@ -1,5 +1,5 @@
 use gobs_core::logger;
-use gobs_render_hal::{DynamicStateElem, Rect2D, RenderHAL, Viewport};
+use gobs_render_hal::{DynamicStateElem, Rect2D, RenderHAL, UniformData as _, Viewport};
 use gobs_resource::{
     ResourceRegistry, {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
 };
@@ -27,6 +27,11 @@ impl PipelineLoader {
             pipeline = pipeline.shader(shader, &properties.compute_entry);
         }
 
+        let push_size = properties.compute_data_layout.uniform_layout().size();
+        if push_size > 0 {
+            pipeline = pipeline.push_constants(push_size);
+        }
+
         for binding_group_layout in &properties.binding_groups {
             pipeline = pipeline.binding_group(binding_group_layout.clone());
         }
@@ -103,11 +108,18 @@ impl ResourceLoader<Pipeline> for PipelineLoader {
         handle: &ResourceHandle<Pipeline>,
         registry: &mut ResourceRegistry,
     ) -> Result<PipelineData, ResourceError> {
-        let resource = registry.get_mut(handle);
+        let resource = registry
+            .get_mut(handle)
+            .ok_or(ResourceError::InvalidHandle)?;
         let properties = &mut resource.properties;
 
         tracing::debug!(target: logger::RESOURCES, "Load pipeline resource {}", properties.name());
 
+        if let Err(e) = properties.validate(hal) {
+            tracing::error!(target: logger::RESOURCES, "Invalid layout for pipeline {}: {}", properties.name(), e);
+            return Err(ResourceError::InvalidData);
+        }
+
         let data = match &properties {
             PipelineProperties::Compute(properties) => self.load_compute(hal, properties),
             PipelineProperties::Graphics(properties) => self.load_graphics(hal, properties),
@@ -120,5 +132,5 @@ impl ResourceLoader<Pipeline> for PipelineLoader {
         hal.destroy_pipeline(data.pipeline);
     }
 
-    fn flush(&mut self) {}
+    fn flush<'a>(&mut self, _hal: &mut (dyn RenderHAL + 'a)) {}
 }
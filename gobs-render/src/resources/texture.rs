@@ -1,8 +1,10 @@
 #[allow(clippy::module_inception)]
 mod texture;
+mod texture_cooked;
 mod texture_loader;
 
 pub use texture::{
     Texture, TextureData, TextureFormat, TexturePath, TextureProperties, TextureType, TextureUpdate,
 };
+pub use texture_cooked::CookedTexture;
 pub use texture_loader::TextureLoader;
@@ -4,7 +4,7 @@ use gobs_resource::{Resource, ResourceHandle, ResourceProperties, ResourceType};
 
 use crate::resources::TextureLoader;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub struct Texture;
 
 impl ResourceType for Texture {
@@ -25,6 +25,16 @@ pub enum TexturePath {
     Checker(Color, Color),
 }
 
+impl TexturePath {
+    pub fn uses_file(&self, filename: &str) -> bool {
+        match self {
+            TexturePath::File(file) => file == filename,
+            TexturePath::Atlas(files, _) => files.iter().any(|file| file == filename),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TextureFormat {
     pub ty: TextureType,
@@ -32,6 +42,27 @@ pub struct TextureFormat {
     pub extent: ImageExtent2D,
     pub mag_filter: SamplerFilter,
     pub min_filter: SamplerFilter,
+    pub mip_levels: u32,
+}
+
+impl TextureFormat {
+    pub fn mip_extent(&self, level: u32) -> ImageExtent2D {
+        ImageExtent2D::new(
+            (self.extent.width >> level).max(1),
+            (self.extent.height >> level).max(1),
+        )
+    }
+
+    /// Size in bytes of the image, including all mip levels
+    pub fn data_size(&self) -> usize {
+        (0..self.mip_levels)
+            .map(|level| {
+                let extent = self.mip_extent(level);
+
+                (extent.width * extent.height * self.format.pixel_size()) as usize
+            })
+            .sum()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -63,6 +94,7 @@ impl TextureProperties {
                 extent,
                 mag_filter: SamplerFilter::FilterLinear,
                 min_filter: SamplerFilter::FilterLinear,
+                mip_levels: 1,
             },
         }
     }
@@ -77,6 +109,7 @@ impl TextureProperties {
                 extent: ImageExtent2D::new(0, 0),
                 mag_filter: SamplerFilter::FilterLinear,
                 min_filter: SamplerFilter::FilterLinear,
+                mip_levels: 1,
             },
         }
     }
@@ -91,6 +124,7 @@ impl TextureProperties {
                 extent: ImageExtent2D::new(1, 1),
                 mag_filter: SamplerFilter::FilterLinear,
                 min_filter: SamplerFilter::FilterLinear,
+                mip_levels: 1,
             },
         }
     }
@@ -105,6 +139,7 @@ impl TextureProperties {
                 extent: ImageExtent2D::new(1, 1),
                 mag_filter: SamplerFilter::FilterLinear,
                 min_filter: SamplerFilter::FilterLinear,
+                mip_levels: 1,
             },
         }
     }
@@ -124,6 +159,7 @@ impl TextureProperties {
                 extent,
                 mag_filter: SamplerFilter::FilterLinear,
                 min_filter: SamplerFilter::FilterLinear,
+                mip_levels: 1,
             },
         }
     }
@@ -140,6 +176,7 @@ impl Default for TextureProperties {
                 extent: ImageExtent2D::new(1, 1),
                 mag_filter: SamplerFilter::FilterLinear,
                 min_filter: SamplerFilter::FilterLinear,
+                mip_levels: 1,
             },
         }
     }
@@ -203,5 +240,8 @@ pub struct TextureData {
 pub enum TextureType {
     Diffuse,
     Normal,
-    // TODO: Emissive, Specular, Opacity, Glossiness, ...
+    Emission,
+    MetallicRoughness,
+    Occlusion,
+    // TODO: Specular, Opacity, Glossiness, ...
 }
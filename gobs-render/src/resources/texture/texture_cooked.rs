@@ -0,0 +1,144 @@
+use std::io;
+
+use image::{RgbaImage, imageops::FilterType};
+
+use gobs_core::ImageExtent2D;
+use gobs_resource::load::{CookedReader, CookedWriter};
+
+const PIXEL_SIZE: usize = 4;
+
+/// Texture in the cooked format: RGBA8 pixels of the full mip chain, largest level first
+pub struct CookedTexture<'a> {
+    pub extent: ImageExtent2D,
+    pub mip_levels: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a> CookedTexture<'a> {
+    pub const EXTENSION: &'static str = "gtex";
+
+    pub fn is_cooked(filename: &str) -> bool {
+        filename.ends_with(&format!(".{}", Self::EXTENSION))
+    }
+
+    /// Number of levels down to a 1x1 image
+    pub fn max_mip_levels(extent: ImageExtent2D) -> u32 {
+        32 - extent.width.max(extent.height).max(1).leading_zeros()
+    }
+
+    fn data_size(extent: ImageExtent2D, mip_levels: u32) -> usize {
+        (0..mip_levels)
+            .map(|level| {
+                let width = (extent.width >> level).max(1) as usize;
+                let height = (extent.height >> level).max(1) as usize;
+
+                PIXEL_SIZE * width * height
+            })
+            .sum()
+    }
+
+    /// Downsample the image into a full mip chain
+    pub fn generate_mips(image: &RgbaImage) -> (u32, Vec<u8>) {
+        let extent = ImageExtent2D::new(image.width(), image.height());
+        let mip_levels = Self::max_mip_levels(extent);
+
+        let mut data = Vec::with_capacity(Self::data_size(extent, mip_levels));
+        data.extend_from_slice(image.as_raw());
+
+        let mut mip = image.clone();
+        for level in 1..mip_levels {
+            let width = (extent.width >> level).max(1);
+            let height = (extent.height >> level).max(1);
+
+            mip = image::imageops::resize(&mip, width, height, FilterType::Triangle);
+            data.extend_from_slice(mip.as_raw());
+        }
+
+        (mip_levels, data)
+    }
+
+    pub fn write(&self, writer: &mut CookedWriter) {
+        writer.write_u32(self.extent.width);
+        writer.write_u32(self.extent.height);
+        writer.write_u32(self.mip_levels);
+        writer.write_bytes(self.data);
+    }
+
+    pub fn read(reader: &mut CookedReader<'a>) -> io::Result<Self> {
+        let extent = ImageExtent2D::new(reader.read_u32()?, reader.read_u32()?);
+        let mip_levels = reader.read_u32()?;
+        let data = reader.read_bytes()?;
+
+        if mip_levels == 0
+            || mip_levels > Self::max_mip_levels(extent)
+            || data.len() != Self::data_size(extent, mip_levels)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "invalid texture data ({}x{}, {} levels, {} bytes)",
+                    extent.width,
+                    extent.height,
+                    mip_levels,
+                    data.len()
+                ),
+            ));
+        }
+
+        Ok(Self {
+            extent,
+            mip_levels,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbaImage;
+
+    use gobs_core::ImageExtent2D;
+    use gobs_resource::load::{CookedAsset, CookedKind, CookedWriter};
+
+    use crate::resources::texture::texture_cooked::CookedTexture;
+
+    #[test]
+    fn test_cooked_texture() {
+        let image = RgbaImage::from_pixel(8, 4, image::Rgba([255, 0, 0, 255]));
+
+        let (mip_levels, data) = CookedTexture::generate_mips(&image);
+        assert_eq!(mip_levels, 4);
+        assert_eq!(data.len(), 4 * (8 * 4 + 4 * 2 + 2 + 1));
+
+        let mut writer = CookedWriter::new();
+        CookedTexture {
+            extent: ImageExtent2D::new(8, 4),
+            mip_levels,
+            data: &data,
+        }
+        .write(&mut writer);
+
+        let asset =
+            CookedAsset::from_bytes(writer.finish(CookedKind::Texture, 0), "test.gtex").unwrap();
+        let texture = CookedTexture::read(&mut asset.reader()).unwrap();
+        assert_eq!(texture.extent, ImageExtent2D::new(8, 4));
+        assert_eq!(texture.mip_levels, 4);
+        assert_eq!(texture.data, data.as_slice());
+        assert_eq!(&texture.data[texture.data.len() - 4..], &[255, 0, 0, 255]);
+
+        let mut writer = CookedWriter::new();
+        CookedTexture {
+            extent: ImageExtent2D::new(8, 4),
+            mip_levels: 5,
+            data: &data,
+        }
+        .write(&mut writer);
+
+        let asset =
+            CookedAsset::from_bytes(writer.finish(CookedKind::Texture, 0), "test.gtex").unwrap();
+        assert!(CookedTexture::read(&mut asset.reader()).is_err());
+
+        assert!(CookedTexture::is_cooked("wall.gtex"));
+        assert!(!CookedTexture::is_cooked("wall.png"));
+    }
+}
@@ -1,6 +1,4 @@
-use futures::future::try_join_all;
 use image::{DynamicImage, GenericImage, GenericImageView, ImageBuffer, imageops::FilterType};
-use pollster::FutureExt;
 
 use gobs_core::{Color, ImageExtent2D, logger};
 use gobs_render_graph::GfxContext;
@@ -8,12 +6,21 @@ use gobs_render_hal::{
     BufferType, CommandBuffer, CommandQueueType, ImageLayout, ImageUsage, RenderHAL,
 };
 use gobs_resource::{
-    ResourceRegistry,
-    load::{self, AssetType},
+    PrefetchData, PrefetchJob, ResourceRegistry,
+    load::{self, AssetType, CookedKind, LoadingError},
     {ResourceError, ResourceHandle, ResourceLoader, ResourceProperties},
 };
 
-use crate::resources::{BufferPool, Texture, TextureData, TextureFormat, texture::TexturePath};
+use crate::resources::{
+    BufferPool, CookedTexture, Texture, TextureData, TextureFormat, TextureProperties,
+    texture::TexturePath,
+};
+
+struct TexturePrefetch {
+    data: Vec<u8>,
+    extent: ImageExtent2D,
+    mip_levels: u32,
+}
 
 pub struct TextureLoader {
     cmd: Box<dyn CommandBuffer>,
@@ -44,32 +51,69 @@ impl TextureLoader {
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    fn stop_recording(&mut self) {
+    fn stop_recording(&mut self, hal: &mut dyn RenderHAL) {
         tracing::debug!(target: logger::RENDER, "Submit texture loading command");
         self.cmd.end_label();
         self.cmd.end();
-        self.cmd.submit_transfer();
-
-        self.cmd.wait();
-
-        self.buffer_pool.recycle_all();
+        match self.cmd.submit(hal) {
+            Ok(_) => match self.cmd.wait(hal) {
+                Ok(()) => self.buffer_pool.recycle_all(),
+                // staging buffers may still be read by the GPU
+                Err(err) => {
+                    tracing::error!(target: logger::RENDER, "Cannot wait for texture loading command: {}", err)
+                }
+            },
+            Err(err) => {
+                tracing::error!(target: logger::RENDER, "Cannot submit texture loading command: {}", err);
+                self.buffer_pool.recycle_all();
+            }
+        }
 
         self.recording = false;
     }
 
-    fn load_file<F>(filename: &str, format: &mut TextureFormat, mut f: F)
+    fn load_file<F>(
+        filename: &str,
+        format: &mut TextureFormat,
+        mut f: F,
+    ) -> Result<(), LoadingError>
     where
         F: FnMut(&[u8]),
     {
         tracing::debug!(target: logger::RESOURCES, "Load file: {:?}", &filename);
 
-        let img = load::load_image(filename, AssetType::IMAGE);
-        let img = img.block_on().unwrap();
+        if CookedTexture::is_cooked(filename) {
+            return Self::load_cooked(filename, format, f);
+        }
+
+        let img = load::load_image_sync(filename, AssetType::IMAGE)?;
         let data = &img.to_rgba8().into_raw();
 
         format.extent = ImageExtent2D::new(img.width(), img.height());
 
-        f(data)
+        f(data);
+
+        Ok(())
+    }
+
+    /// Cooked textures are mapped in memory and copied as is to the staging buffer
+    fn load_cooked<F>(
+        filename: &str,
+        format: &mut TextureFormat,
+        mut f: F,
+    ) -> Result<(), LoadingError>
+    where
+        F: FnMut(&[u8]),
+    {
+        let asset = load::load_cooked_sync(filename, AssetType::IMAGE, CookedKind::Texture)?;
+        let texture = CookedTexture::read(&mut asset.reader()).map_err(|e| asset.error(e))?;
+
+        format.extent = texture.extent;
+        format.mip_levels = texture.mip_levels;
+
+        f(texture.data);
+
+        Ok(())
     }
 
     const CHECKER_SIZE: usize = 8;
@@ -121,7 +165,12 @@ impl TextureLoader {
         f(data)
     }
 
-    fn load_atlas<F>(texture_files: &[String], cols: usize, format: &mut TextureFormat, mut f: F)
+    fn load_atlas<F>(
+        texture_files: &[String],
+        cols: usize,
+        format: &mut TextureFormat,
+        mut f: F,
+    ) -> Result<(), LoadingError>
     where
         F: FnMut(&[u8]),
     {
@@ -132,9 +181,8 @@ impl TextureLoader {
 
         let images = texture_files
             .iter()
-            .map(|file| load::load_image(file, AssetType::IMAGE));
-
-        let images = try_join_all(images).block_on().unwrap();
+            .map(|file| load::load_image_sync(file, AssetType::IMAGE))
+            .collect::<Result<Vec<_>, _>>()?;
 
         for img in &images {
             if img.width() > width {
@@ -167,6 +215,8 @@ impl TextureLoader {
         format.extent = ImageExtent2D::new(img.dimensions().0, img.dimensions().1);
 
         f(data);
+
+        Ok(())
     }
 
     fn load_default<F>(f: F)
@@ -176,42 +226,33 @@ impl TextureLoader {
         Self::load_color(Color::WHITE, f);
     }
 
-    pub fn get_bytes<F>(path: &TexturePath, format: &mut TextureFormat, mut f: F)
+    pub fn get_bytes<F>(
+        path: &TexturePath,
+        format: &mut TextureFormat,
+        mut f: F,
+    ) -> Result<(), LoadingError>
     where
         F: FnMut(&[u8]),
     {
         match path {
             TexturePath::Default => Self::load_default(f),
-            TexturePath::File(filename) => Self::load_file(filename, format, f),
+            TexturePath::File(filename) => Self::load_file(filename, format, f)?,
             TexturePath::Bytes(items) => f(items),
-            TexturePath::Atlas(files, cols) => Self::load_atlas(files, *cols, format, f),
+            TexturePath::Atlas(files, cols) => Self::load_atlas(files, *cols, format, f)?,
             TexturePath::Color(color) => Self::load_color(*color, f),
             TexturePath::Colors(colors) => Self::load_colors(colors, f),
             TexturePath::Checker(color1, color2) => Self::load_checker(*color1, *color2, f),
         }
+
+        Ok(())
     }
-}
 
-impl ResourceLoader<Texture> for TextureLoader {
-    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    fn load<'a>(
+    fn upload(
         &mut self,
-        hal: &mut (dyn RenderHAL + 'a),
-        handle: &ResourceHandle<Texture>,
-        registry: &mut ResourceRegistry,
-    ) -> Result<TextureData, ResourceError> {
-        let resource = registry.get_mut(handle);
-        let properties = &mut resource.properties;
-
-        tracing::debug!(target: logger::RESOURCES, "Load texture resource {}", properties.name());
-        tracing::trace!(target: logger::RESOURCES, "Texture properties: {:?}", properties.format);
-
-        let mut staging_data = vec![];
-
-        Self::get_bytes(&properties.path, &mut properties.format, |data| {
-            staging_data.extend_from_slice(data);
-        });
-
+        hal: &mut dyn RenderHAL,
+        properties: &TextureProperties,
+        staging_data: &[u8],
+    ) -> TextureData {
         if !self.recording {
             self.start_recording();
         }
@@ -222,14 +263,15 @@ impl ResourceLoader<Texture> for TextureLoader {
             staging_data.len(),
             BufferType::Staging,
         );
-        hal.upload_buffer(staging.buffer, &staging_data, 0);
+        hal.upload_buffer(staging.buffer, staging_data, 0);
 
         let image_format = properties.format.format;
-        let image = hal.create_image(
+        let image = hal.create_image_with_mips(
             properties.name(),
             image_format,
             ImageUsage::Texture,
             properties.format.extent,
+            properties.format.mip_levels,
         );
 
         let sampler =
@@ -237,25 +279,116 @@ impl ResourceLoader<Texture> for TextureLoader {
 
         self.cmd
             .transition_image_layout(hal, image, ImageLayout::TransferDst);
-        self.cmd.copy_buffer_to_image(hal, staging.buffer, image, 0);
+
+        let mut offset = 0;
+        for level in 0..properties.format.mip_levels {
+            self.cmd
+                .copy_buffer_to_image_mip(hal, staging.buffer, image, offset, level);
+
+            let extent = properties.format.mip_extent(level);
+            offset += (extent.width * extent.height * image_format.pixel_size()) as u64;
+        }
+
+        // ownership is acquired by the graphics queue at the beginning of the next frame
         self.cmd
-            .transition_image_layout(hal, image, ImageLayout::Shader);
+            .release_image(hal, image, ImageLayout::Shader, CommandQueueType::Graphics);
 
-        Ok(TextureData {
+        TextureData {
             format: image_format,
             image,
             sampler,
-        })
+        }
+    }
+}
+
+impl ResourceLoader<Texture> for TextureLoader {
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    fn load<'a>(
+        &mut self,
+        hal: &mut (dyn RenderHAL + 'a),
+        handle: &ResourceHandle<Texture>,
+        registry: &mut ResourceRegistry,
+    ) -> Result<TextureData, ResourceError> {
+        let resource = registry
+            .get_mut(handle)
+            .ok_or(ResourceError::InvalidHandle)?;
+        let properties = &mut resource.properties;
+
+        tracing::debug!(target: logger::RESOURCES, "Load texture resource {}", properties.name());
+        tracing::trace!(target: logger::RESOURCES, "Texture properties: {:?}", properties.format);
+
+        let mut staging_data = vec![];
+
+        Self::get_bytes(&properties.path, &mut properties.format, |data| {
+            staging_data.extend_from_slice(data);
+        })?;
+
+        Ok(self.upload(hal, properties, &staging_data))
+    }
+
+    fn prefetch(&self, properties: &TextureProperties) -> Option<PrefetchJob> {
+        // only file based textures are worth decoding off-thread
+        match &properties.path {
+            TexturePath::File(_) | TexturePath::Atlas(_, _) => {
+                let path = properties.path.clone();
+                let mut format = properties.format.clone();
+
+                Some(Box::new(move || {
+                    let mut data = vec![];
+
+                    Self::get_bytes(&path, &mut format, |bytes| {
+                        data.extend_from_slice(bytes);
+                    })?;
+
+                    Ok(Box::new(TexturePrefetch {
+                        data,
+                        extent: format.extent,
+                        mip_levels: format.mip_levels,
+                    }) as PrefetchData)
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    fn load_prefetched<'a>(
+        &mut self,
+        hal: &mut (dyn RenderHAL + 'a),
+        handle: &ResourceHandle<Texture>,
+        registry: &mut ResourceRegistry,
+        data: PrefetchData,
+    ) -> Result<TextureData, ResourceError> {
+        let prefetch = data
+            .downcast::<TexturePrefetch>()
+            .map_err(|_| ResourceError::InvalidData)?;
+
+        let resource = registry
+            .get_mut(handle)
+            .ok_or(ResourceError::InvalidHandle)?;
+        let properties = &mut resource.properties;
+
+        tracing::debug!(target: logger::RESOURCES, "Upload prefetched texture resource {}", properties.name());
+
+        properties.format.extent = prefetch.extent;
+        properties.format.mip_levels = prefetch.mip_levels;
+
+        Ok(self.upload(hal, properties, &prefetch.data))
+    }
+
+    fn size(&self, properties: &TextureProperties, _data: &TextureData) -> usize {
+        properties.format.data_size()
     }
 
     fn unload<'a>(&mut self, hal: &mut (dyn RenderHAL + 'a), data: TextureData) {
+        hal.unregister_texture(data.image);
         hal.destroy_image(data.image);
         hal.destroy_sampler(data.sampler);
     }
 
-    fn flush(&mut self) {
+    fn flush<'a>(&mut self, hal: &mut (dyn RenderHAL + 'a)) {
         if self.recording {
-            self.stop_recording();
+            self.stop_recording(hal);
         }
     }
 }
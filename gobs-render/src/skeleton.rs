@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use glam::{Mat4, Vec3};
+
+use gobs_core::Transform;
+use gobs_render_hal::VertexData;
+
+/// Joints of a skin, with the inverse bind matrices that move vertices from
+/// the mesh space to the space of each joint in the bind pose
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    pub name: String,
+    pub inverse_bind_matrices: Vec<Mat4>,
+}
+
+impl Skeleton {
+    pub fn new(name: &str, inverse_bind_matrices: Vec<Mat4>) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.to_string(),
+            inverse_bind_matrices,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.inverse_bind_matrices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inverse_bind_matrices.is_empty()
+    }
+
+    /// Matrices uploaded to the joint table, in the mesh space: the world matrix
+    /// of the mesh is applied on top of the skinning. Missing joints keep the
+    /// bind pose
+    pub fn joint_matrices(&self, mesh: &Transform, joints: &[Option<Transform>]) -> Vec<Mat4> {
+        let mesh_inverse = mesh.matrix().inverse();
+
+        self.inverse_bind_matrices
+            .iter()
+            .enumerate()
+            .map(|(i, inverse_bind)| match joints.get(i).copied().flatten() {
+                Some(joint) => mesh_inverse * *joint.matrix() * *inverse_bind,
+                None => Mat4::IDENTITY,
+            })
+            .collect()
+    }
+
+    /// Skinning matrix of the vertex, as computed by the skinned vertex shader
+    pub fn skin_matrix(vertex: &VertexData, joint_matrices: &[Mat4]) -> Mat4 {
+        let joints = vertex.joints().to_array();
+        let weights = vertex.weights().to_array();
+
+        joints
+            .iter()
+            .zip(weights)
+            .filter(|(_, weight)| *weight > 0.)
+            .map(|(&joint, weight)| {
+                weight
+                    * joint_matrices
+                        .get(joint as usize)
+                        .copied()
+                        .unwrap_or(Mat4::IDENTITY)
+            })
+            .fold(Mat4::ZERO, |skin, matrix| skin + matrix)
+    }
+
+    /// CPU skinning of the vertex position, in the mesh space
+    pub fn skin_position(vertex: &VertexData, joint_matrices: &[Mat4]) -> Vec3 {
+        Self::skin_matrix(vertex, joint_matrices).transform_point3(vertex.position())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Quat, UVec4, Vec3, Vec4};
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_core::Transform;
+    use gobs_render_hal::VertexData;
+
+    use crate::skeleton::Skeleton;
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    /// Two joints along the Y axis, the second one 1 unit above the first
+    fn skeleton() -> (std::sync::Arc<Skeleton>, [Transform; 2]) {
+        let bind = [Transform::IDENTITY, Transform::from_translation(Vec3::Y)];
+
+        let skeleton = Skeleton::new("arm", bind.iter().map(|t| t.matrix().inverse()).collect());
+
+        (skeleton, bind)
+    }
+
+    #[test]
+    fn test_bind_pose() {
+        setup();
+
+        let (skeleton, bind) = skeleton();
+        let mesh = Transform::from_translation(Vec3::new(5., 0., 0.));
+
+        // joints are placed in world space, relative to the mesh
+        let joints = bind.map(|t| Some(mesh * t));
+        let matrices = skeleton.joint_matrices(&mesh, &joints);
+
+        assert_eq!(matrices.len(), 2);
+        for matrix in matrices {
+            assert!(matrix.abs_diff_eq(Mat4::IDENTITY, 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_skin_position() {
+        setup();
+
+        let (skeleton, bind) = skeleton();
+
+        // bend the second joint by 90° around Z
+        let joints = [
+            Some(bind[0]),
+            Some(Transform::new(
+                Vec3::Y,
+                Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+                Vec3::ONE,
+            )),
+        ];
+        let matrices = skeleton.joint_matrices(&Transform::IDENTITY, &joints);
+
+        let vertex = VertexData::builder()
+            .position(Vec3::new(0., 2., 0.))
+            .joints(UVec4::new(1, 0, 0, 0))
+            .weights(Vec4::new(1., 0., 0., 0.))
+            .build();
+        assert!(
+            Skeleton::skin_position(&vertex, &matrices).abs_diff_eq(Vec3::new(-1., 1., 0.), 1e-5)
+        );
+
+        // half way between both joints
+        let vertex = VertexData::builder()
+            .position(Vec3::new(0., 2., 0.))
+            .joints(UVec4::new(0, 1, 0, 0))
+            .weights(Vec4::new(0.5, 0.5, 0., 0.))
+            .build();
+        assert!(
+            Skeleton::skin_position(&vertex, &matrices).abs_diff_eq(Vec3::new(-0.5, 1.5, 0.), 1e-5)
+        );
+
+        // missing joints keep the bind pose
+        let matrices = skeleton.joint_matrices(&Transform::IDENTITY, &[joints[0], None]);
+        assert_eq!(matrices[1], Mat4::IDENTITY);
+    }
+}
@@ -0,0 +1,20 @@
+use gobs_core::{ConfigDefault, ConfigWriter as _, GobsConfig};
+
+pub enum ResourceConfig {
+    /// Asset roots (directories or zip/pak archives) separated by the platform path separator
+    Roots,
+}
+
+impl AsRef<str> for ResourceConfig {
+    fn as_ref(&self) -> &str {
+        match self {
+            ResourceConfig::Roots => "config.resource.roots",
+        }
+    }
+}
+
+impl ConfigDefault for ResourceConfig {
+    fn register_defaults(config: &mut GobsConfig) {
+        config.set_string(ResourceConfig::Roots, "");
+    }
+}
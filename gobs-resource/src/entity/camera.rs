@@ -7,11 +7,12 @@ use glam::{
         view::look_to_mat4,
     },
 };
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use gobs_core::Transform;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[allow(dead_code)]
 pub enum ProjectionMode {
     Ortho(OrthoProjection),
@@ -34,7 +35,7 @@ impl ProjectionMode {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PerspectiveProjection {
     pub aspect: f32,
     pub fovy: f32,
@@ -42,7 +43,7 @@ pub struct PerspectiveProjection {
     pub far: f32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct OrthoProjection {
     pub width: f32,
     pub height: f32,
@@ -52,7 +53,7 @@ pub struct OrthoProjection {
 
 pub type CameraId = Uuid;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Camera {
     pub id: CameraId,
     pub mode: ProjectionMode,
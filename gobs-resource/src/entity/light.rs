@@ -1,29 +1,57 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use gobs_core::Color;
 
 pub type LightId = Uuid;
 
-#[derive(Clone, Debug)]
+/// Range is the distance where the light intensity reaches zero, infinite if not set
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum LightType {
+    #[default]
+    Directional,
+    Point {
+        range: Option<f32>,
+    },
+    /// Cone angles in radians, from the direction of the light
+    Spot {
+        range: Option<f32>,
+        inner_cone_angle: f32,
+        outer_cone_angle: f32,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Light {
     pub id: LightId,
     pub colour: Color,
+    #[serde(default = "Light::default_intensity")]
+    pub intensity: f32,
+    #[serde(default)]
+    pub ty: LightType,
 }
 
 impl Light {
     pub fn new(colour: Color) -> Self {
+        Self::with_type(colour, Self::default_intensity(), LightType::Directional)
+    }
+
+    pub fn with_type(colour: Color, intensity: f32, ty: LightType) -> Self {
         Light {
             id: LightId::new_v4(),
             colour,
+            intensity,
+            ty,
         }
     }
+
+    fn default_intensity() -> f32 {
+        1.
+    }
 }
 
 impl Default for Light {
     fn default() -> Self {
-        Self {
-            id: LightId::new_v4(),
-            colour: Color::WHITE,
-        }
+        Self::new(Color::WHITE)
     }
 }
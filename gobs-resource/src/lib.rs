@@ -1,13 +1,19 @@
+mod config;
 mod entity;
 mod manager;
+mod reference;
 mod resource;
 mod tiles;
 
+pub use config::ResourceConfig;
 pub use entity::{camera, light};
-pub use manager::{ResourceManager, ResourceRegistry};
+pub use manager::{LoadProgress, ResourceManager, ResourceRegistry};
+pub use reference::ResourceRef;
 pub use resource::{
-    Resource, ResourceError, ResourceHandle, ResourceLifetime, ResourceLoader, ResourceProperties,
-    ResourceType,
+    PrefetchData, PrefetchJob, Resource, ResourceError, ResourceHandle, ResourceLifetime,
+    ResourceLoader, ResourceProperties, ResourceType,
 };
 
 pub mod load;
+pub mod watcher;
+pub mod worker;
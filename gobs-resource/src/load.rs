@@ -1,12 +1,27 @@
+mod archive;
+mod cooked;
+mod vfs;
+
 use std::env;
+use std::io;
 use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-use futures::io;
 use image::DynamicImage;
 use thiserror::Error;
 
-use gobs_core::logger;
+use gobs_core::{ConfigReader as _, GobsConfig, logger};
+
+use crate::{ResourceConfig, worker};
 
+pub use archive::{PakMount, ZipMount};
+pub use cooked::{
+    COOKED_VERSION, ContentHasher, CookedAsset, CookedHeader, CookedKind, CookedReader,
+    CookedWriter, content_hash,
+};
+pub use vfs::{DirMount, MemoryMount, Mount, Vfs};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AssetType {
     SHADER,
     IMAGE,
@@ -15,62 +30,254 @@ pub enum AssetType {
     RESOURCES,
 }
 
+impl AssetType {
+    /// Folder of the asset type, relative to the asset roots
+    pub fn dir(&self) -> &'static str {
+        match self {
+            AssetType::SHADER => "shaders",
+            AssetType::MODEL => "assets/models",
+            AssetType::IMAGE => "assets/textures",
+            AssetType::DATA => "assets/data",
+            AssetType::RESOURCES => "resources",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum LoadingError {
-    #[error("asset not found")]
+    #[error("asset not found: {0}")]
     AssetNotFound(String),
     #[error("io error")]
     IOError(#[from] io::Error),
-    #[error("cannot load image")]
-    ImageLoadingError(#[from] image::ImageError),
+    #[error("cannot read {path}: {source}")]
+    ReadError { path: String, source: io::Error },
+    #[error("cannot load image {path}: {source}")]
+    ImageLoadingError {
+        path: String,
+        source: image::ImageError,
+    },
+    #[error("invalid mount: {0}")]
+    InvalidMount(String),
+    #[error("loading cancelled")]
+    LoadCancelled,
+    #[error("cannot watch asset directory")]
+    WatchError(#[from] notify::Error),
 }
 
-pub fn get_asset_dir(file_name: &str, ty: AssetType) -> Result<PathBuf, LoadingError> {
+static VFS: OnceLock<RwLock<Vfs>> = OnceLock::new();
+
+fn global_vfs() -> &'static RwLock<Vfs> {
+    VFS.get_or_init(|| {
+        let mut vfs = Vfs::new();
+
+        match exe_dir() {
+            Ok(dir) => vfs.mount(Arc::new(DirMount::new(dir)), 0),
+            Err(e) => {
+                tracing::warn!(target: logger::RESOURCES, "Cannot locate executable directory: {}", e)
+            }
+        }
+
+        RwLock::new(vfs)
+    })
+}
+
+/// Virtual file system used to load assets. Defaults to the directory of the executable.
+pub fn vfs() -> RwLockReadGuard<'static, Vfs> {
+    global_vfs().read().unwrap()
+}
+
+/// Mount or unmount asset sources
+pub fn vfs_mut() -> RwLockWriteGuard<'static, Vfs> {
+    global_vfs().write().unwrap()
+}
+
+fn exe_dir() -> Result<PathBuf, LoadingError> {
     let current_exe = env::current_exe()?;
-    let current_dir = current_exe
+
+    current_exe
         .parent()
-        .ok_or(LoadingError::AssetNotFound(file_name.to_owned()))?;
-    let path = match ty {
-        AssetType::SHADER => current_dir.join("shaders"),
-        AssetType::MODEL => current_dir.join("assets/models"),
-        AssetType::IMAGE => current_dir.join("assets/textures"),
-        AssetType::DATA => current_dir.join("assets/data"),
-        AssetType::RESOURCES => current_dir.join("resources"),
-    };
+        .map(|dir| dir.to_path_buf())
+        .ok_or(LoadingError::AssetNotFound(
+            current_exe.display().to_string(),
+        ))
+}
+
+/// Replace the mounts with the roots listed in [ResourceConfig::Roots]. The first root has
+/// the highest priority. Relative roots are resolved from the directory of the executable.
+pub fn configure(config: &GobsConfig) -> Result<(), LoadingError> {
+    let roots = config.get_string(ResourceConfig::Roots);
+    if roots.is_empty() {
+        return Ok(());
+    }
+
+    let exe_dir = exe_dir()?;
+    let roots = env::split_paths(&roots)
+        .map(|root| exe_dir.join(root))
+        .collect::<Vec<_>>();
+
+    let mut vfs = Vfs::new();
+    for (i, root) in roots.iter().enumerate() {
+        vfs.mount_path(root, (roots.len() - i) as i32)?;
+    }
+
+    tracing::info!(target: logger::RESOURCES, "Asset roots: {:?}", roots);
+
+    *vfs_mut() = vfs;
+
+    Ok(())
+}
+
+/// Path of the asset in the virtual file system
+pub fn asset_path(file_name: &str, ty: AssetType) -> String {
+    if file_name.is_empty() {
+        ty.dir().to_string()
+    } else {
+        format!("{}/{}", ty.dir(), file_name)
+    }
+}
+
+/// Location of the asset on disk. Only assets from directory mounts can be resolved.
+/// Missing files resolve to the directory with the highest priority.
+pub fn get_asset_dir(file_name: &str, ty: AssetType) -> Result<PathBuf, LoadingError> {
+    let path = asset_path(file_name, ty);
+    let vfs = vfs();
+
+    vfs.real_path(&path)
+        .or_else(|| vfs.dirs().first().map(|dir| dir.join(&path)))
+        .ok_or(LoadingError::AssetNotFound(path))
+}
 
-    Ok(path.join(file_name))
+/// Asset directories from all directory mounts, by decreasing priority
+pub fn asset_dirs(ty: AssetType) -> Vec<PathBuf> {
+    vfs()
+        .dirs()
+        .into_iter()
+        .map(|dir| dir.join(ty.dir()))
+        .collect()
 }
 
 pub async fn load_string(file_name: &str, ty: AssetType) -> Result<String, LoadingError> {
-    load_string_sync(file_name, ty)
+    let path = asset_path(file_name, ty);
+
+    worker::pool().spawn(move || read_string(&path)).await?
 }
 
 pub fn load_string_sync(file_name: &str, ty: AssetType) -> Result<String, LoadingError> {
-    let path = get_asset_dir(file_name, ty)
-        .map_err(|_| LoadingError::AssetNotFound(file_name.to_string()))?;
-
-    tracing::debug!(target: logger::RESOURCES, "Loading string: {:?}", path);
+    read_string(&asset_path(file_name, ty))
+}
 
-    let txt = std::fs::read_to_string(path)
-        .map_err(|_| LoadingError::AssetNotFound(file_name.to_string()))?;
+fn read_string(path: &str) -> Result<String, LoadingError> {
+    tracing::debug!(target: logger::RESOURCES, "Loading string: {}", path);
 
-    Ok(txt)
+    vfs().read_string(path)
 }
 
 pub async fn load_binary(file_name: &str, ty: AssetType) -> Result<Vec<u8>, LoadingError> {
-    let path = get_asset_dir(file_name, ty)?;
+    let path = asset_path(file_name, ty);
+
+    worker::pool().spawn(move || read_binary(&path)).await?
+}
 
-    tracing::debug!(target: logger::RESOURCES, "Loading bin: {:?}", path);
+pub fn load_binary_sync(file_name: &str, ty: AssetType) -> Result<Vec<u8>, LoadingError> {
+    read_binary(&asset_path(file_name, ty))
+}
 
-    let data =
-        std::fs::read(path).map_err(|_| LoadingError::AssetNotFound(file_name.to_string()))?;
+fn read_binary(path: &str) -> Result<Vec<u8>, LoadingError> {
+    tracing::debug!(target: logger::RESOURCES, "Loading bin: {}", path);
 
-    Ok(data)
+    vfs().read(path)
 }
 
 pub async fn load_image(file_name: &str, ty: AssetType) -> Result<DynamicImage, LoadingError> {
-    let bytes = load_binary(file_name, ty).await?;
-    let img = image::load_from_memory(&bytes)?;
+    let path = asset_path(file_name, ty);
+
+    worker::pool().spawn(move || read_image(&path)).await?
+}
+
+pub fn load_image_sync(file_name: &str, ty: AssetType) -> Result<DynamicImage, LoadingError> {
+    read_image(&asset_path(file_name, ty))
+}
+
+fn read_image(path: &str) -> Result<DynamicImage, LoadingError> {
+    let bytes = read_binary(path)?;
+
+    image::load_from_memory(&bytes).map_err(|source| LoadingError::ImageLoadingError {
+        path: vfs().resolve(path),
+        source,
+    })
+}
+
+pub async fn load_cooked(
+    file_name: &str,
+    ty: AssetType,
+    kind: CookedKind,
+) -> Result<CookedAsset, LoadingError> {
+    let path = asset_path(file_name, ty);
+
+    worker::pool()
+        .spawn(move || read_cooked(&path, kind))
+        .await?
+}
+
+pub fn load_cooked_sync(
+    file_name: &str,
+    ty: AssetType,
+    kind: CookedKind,
+) -> Result<CookedAsset, LoadingError> {
+    read_cooked(&asset_path(file_name, ty), kind)
+}
+
+fn read_cooked(path: &str, kind: CookedKind) -> Result<CookedAsset, LoadingError> {
+    tracing::debug!(target: logger::RESOURCES, "Loading cooked {:?}: {}", kind, path);
+
+    CookedAsset::open(&vfs(), path)?.expect(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::load::{self, AssetType, LoadingError, MemoryMount};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_memory_mount() {
+        setup();
+
+        let mount = MemoryMount::new("test_memory_mount")
+            .with_file("resources/test_memory_mount.ron", b"()".as_slice())
+            .with_file(
+                "assets/textures/test_memory_mount.png",
+                b"invalid".as_slice(),
+            );
+        load::vfs_mut().mount(Arc::new(mount), 100);
+
+        assert_eq!(
+            load::load_string_sync("test_memory_mount.ron", AssetType::RESOURCES).unwrap(),
+            "()"
+        );
+
+        let err = load::load_image_sync("test_memory_mount.png", AssetType::IMAGE).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("test_memory_mount:assets/textures/test_memory_mount.png")
+        );
+
+        assert!(matches!(
+            load::load_binary_sync("test_memory_mount.ron", AssetType::SHADER),
+            Err(LoadingError::AssetNotFound(path)) if path == "shaders/test_memory_mount.ron"
+        ));
 
-    Ok(img)
+        load::vfs_mut().unmount("test_memory_mount");
+    }
 }
@@ -0,0 +1,248 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use zip::ZipArchive;
+
+use gobs_core::logger;
+
+use crate::load::{LoadingError, vfs::Mount};
+
+/// Files from a zip archive
+pub struct ZipMount {
+    name: String,
+    archive: Mutex<ZipArchive<BufReader<File>>>,
+}
+
+impl ZipMount {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, LoadingError> {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+
+        let file = File::open(path).map_err(|source| LoadingError::ReadError {
+            path: name.clone(),
+            source,
+        })?;
+
+        let archive =
+            ZipArchive::new(BufReader::new(file)).map_err(|source| LoadingError::ReadError {
+                path: name.clone(),
+                source: source.into(),
+            })?;
+
+        tracing::debug!(target: logger::RESOURCES, "Open zip archive {} ({} files)", name, archive.len());
+
+        Ok(Self {
+            name,
+            archive: Mutex::new(archive),
+        })
+    }
+}
+
+impl Mount for ZipMount {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.archive.lock().unwrap().index_for_name(path).is_some()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let mut archive = self.archive.lock().unwrap();
+        let mut file = archive.by_name(path)?;
+
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+
+        Ok(data)
+    }
+}
+
+const PAK_MAGIC: &[u8; 4] = b"PACK";
+const PAK_HEADER_SIZE: usize = 12;
+const PAK_ENTRY_SIZE: usize = 64;
+const PAK_NAME_SIZE: usize = 56;
+
+/// Files from a pak archive: a `PACK` header followed by a directory of 64 bytes entries
+/// (56 bytes name, offset and size as little endian u32)
+pub struct PakMount {
+    name: String,
+    path: PathBuf,
+    entries: HashMap<String, (u64, usize)>,
+}
+
+impl PakMount {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, LoadingError> {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+
+        let entries = Self::read_entries(path).map_err(|source| LoadingError::ReadError {
+            path: name.clone(),
+            source,
+        })?;
+
+        tracing::debug!(target: logger::RESOURCES, "Open pak archive {} ({} files)", name, entries.len());
+
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    fn read_entries(path: &Path) -> io::Result<HashMap<String, (u64, usize)>> {
+        let mut file = BufReader::new(File::open(path)?);
+
+        let mut header = [0; PAK_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+
+        if &header[0..4] != PAK_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid pak header",
+            ));
+        }
+
+        let dir_offset = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let dir_size = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+
+        if !dir_size.is_multiple_of(PAK_ENTRY_SIZE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid pak directory",
+            ));
+        }
+
+        let mut dir = vec![0; dir_size];
+        file.seek(SeekFrom::Start(dir_offset as u64))?;
+        file.read_exact(&mut dir)?;
+
+        let entries = dir
+            .chunks_exact(PAK_ENTRY_SIZE)
+            .map(|entry| {
+                let name = &entry[0..PAK_NAME_SIZE];
+                let len = name.iter().position(|&c| c == 0).unwrap_or(PAK_NAME_SIZE);
+                let name = String::from_utf8_lossy(&name[..len]).to_string();
+
+                let offset = u32::from_le_bytes(entry[56..60].try_into().unwrap()) as u64;
+                let size = u32::from_le_bytes(entry[60..64].try_into().unwrap()) as usize;
+
+                (name, (offset, size))
+            })
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+impl Mount for PakMount {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let (offset, size) = *self.entries.get(path).ok_or(io::ErrorKind::NotFound)?;
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut data = vec![0; size];
+        file.read_exact(&mut data)?;
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Write};
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use crate::load::{
+        LoadingError,
+        vfs::{Vfs, tests::temp_dir},
+    };
+
+    fn write_pak(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = vec![0; 12];
+        let mut dir = vec![];
+
+        for (name, content) in files {
+            let offset = data.len() as u32;
+            data.extend_from_slice(content);
+
+            let mut entry = [0; 64];
+            entry[..name.len()].copy_from_slice(name.as_bytes());
+            entry[56..60].copy_from_slice(&offset.to_le_bytes());
+            entry[60..64].copy_from_slice(&(content.len() as u32).to_le_bytes());
+            dir.extend_from_slice(&entry);
+        }
+
+        let dir_offset = data.len() as u32;
+        data.extend_from_slice(&dir);
+
+        data[0..4].copy_from_slice(b"PACK");
+        data[4..8].copy_from_slice(&dir_offset.to_le_bytes());
+        data[8..12].copy_from_slice(&(dir.len() as u32).to_le_bytes());
+
+        data
+    }
+
+    #[test]
+    fn test_archives() {
+        let dir = temp_dir("archive");
+
+        let zip_path = dir.join("base.zip");
+        let mut zip = ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        zip.start_file("resources/graph.ron", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"zip").unwrap();
+        zip.start_file("resources/pipelines.ron", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"zip").unwrap();
+        zip.finish().unwrap();
+
+        let pak_path = dir.join("patch.pak");
+        fs::write(
+            &pak_path,
+            write_pak(&[
+                ("resources/graph.ron", b"pak"),
+                ("shaders/mesh.spv", b"spv"),
+            ]),
+        )
+        .unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount_path(&zip_path, 0).unwrap();
+        vfs.mount_path(&pak_path, 1).unwrap();
+
+        assert_eq!(vfs.read_string("resources/graph.ron").unwrap(), "pak");
+        assert_eq!(vfs.read_string("resources/pipelines.ron").unwrap(), "zip");
+        assert_eq!(vfs.read("shaders/mesh.spv").unwrap(), b"spv");
+        assert_eq!(
+            vfs.resolve("resources/pipelines.ron"),
+            format!("{}:resources/pipelines.ron", zip_path.display())
+        );
+        assert!(vfs.real_path("resources/graph.ron").is_none());
+
+        let invalid = dir.join("invalid.pak");
+        fs::write(&invalid, b"invalid pak file").unwrap();
+        let err = vfs.mount_path(&invalid, 0).unwrap_err();
+        assert!(
+            matches!(&err, LoadingError::ReadError { path, .. } if path.contains("invalid.pak"))
+        );
+        assert!(err.to_string().contains("invalid.pak"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
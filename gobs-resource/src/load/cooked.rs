@@ -0,0 +1,536 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+
+use memmap2::Mmap;
+
+use gobs_core::logger;
+
+use crate::load::{LoadingError, vfs::Vfs};
+
+const COOKED_MAGIC: &[u8; 4] = b"GOBS";
+/// Bumped when the layout of cooked assets changes. Older files are rejected and cooked again.
+pub const COOKED_VERSION: u32 = 6;
+const HEADER_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CookedKind {
+    Mesh,
+    Texture,
+    Model,
+}
+
+impl CookedKind {
+    fn id(&self) -> u32 {
+        match self {
+            CookedKind::Mesh => 1,
+            CookedKind::Texture => 2,
+            CookedKind::Model => 3,
+        }
+    }
+
+    fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(CookedKind::Mesh),
+            2 => Some(CookedKind::Texture),
+            3 => Some(CookedKind::Model),
+            _ => None,
+        }
+    }
+}
+
+/// Header of cooked files: magic, version, kind, hash of the source content and payload size
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CookedHeader {
+    pub kind: CookedKind,
+    pub source_hash: u64,
+}
+
+impl CookedHeader {
+    fn write(&self, data: &mut Vec<u8>, payload_len: usize) {
+        data.extend_from_slice(COOKED_MAGIC);
+        data.extend_from_slice(&COOKED_VERSION.to_le_bytes());
+        data.extend_from_slice(&self.kind.id().to_le_bytes());
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        data.extend_from_slice(&self.source_hash.to_le_bytes());
+        data.extend_from_slice(&(payload_len as u64).to_le_bytes());
+    }
+
+    /// Returns the header and the size of the payload
+    fn parse(data: &[u8]) -> io::Result<(Self, usize)> {
+        let mut reader = CookedReader::new(data);
+
+        if reader.read_bytes_raw(4)? != COOKED_MAGIC {
+            return Err(invalid_data("not a cooked asset"));
+        }
+
+        let version = reader.read_u32()?;
+        if version != COOKED_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported version {} (expected {})",
+                version, COOKED_VERSION
+            )));
+        }
+
+        let kind = reader.read_u32()?;
+        let kind = CookedKind::from_id(kind)
+            .ok_or_else(|| invalid_data(format!("unknown asset kind {}", kind)))?;
+        let _reserved = reader.read_u32()?;
+        let source_hash = reader.read_u64()?;
+        let payload_len = reader.read_u64()? as usize;
+
+        Ok((Self { kind, source_hash }, payload_len))
+    }
+
+    /// Read the header of a cooked file on disk, without loading the payload
+    pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+
+        let mut data = [0; HEADER_SIZE];
+        file.read_exact(&mut data)?;
+
+        let (header, payload_len) = Self::parse(&data)?;
+        if len < HEADER_SIZE + payload_len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(header)
+    }
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+enum CookedBytes {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl CookedBytes {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            CookedBytes::Mapped(mmap) => mmap,
+            CookedBytes::Owned(data) => data,
+        }
+    }
+}
+
+/// Cooked file loaded from the [Vfs]. Files from directory mounts are memory-mapped.
+pub struct CookedAsset {
+    header: CookedHeader,
+    path: String,
+    bytes: CookedBytes,
+    payload_len: usize,
+}
+
+impl CookedAsset {
+    pub(crate) fn open(vfs: &Vfs, path: &str) -> Result<Self, LoadingError> {
+        let resolved = vfs.resolve(path);
+
+        let bytes = match vfs.real_path(path) {
+            Some(real_path) => {
+                tracing::debug!(target: logger::RESOURCES, "Map cooked asset: {}", resolved);
+
+                let file = File::open(real_path).map_err(|source| LoadingError::ReadError {
+                    path: resolved.clone(),
+                    source,
+                })?;
+
+                // cooked files are replaced atomically by the cooker and never modified in place
+                let mmap =
+                    unsafe { Mmap::map(&file) }.map_err(|source| LoadingError::ReadError {
+                        path: resolved.clone(),
+                        source,
+                    })?;
+
+                CookedBytes::Mapped(mmap)
+            }
+            None => CookedBytes::Owned(vfs.read(path)?),
+        };
+
+        Self::with_bytes(bytes, resolved)
+    }
+
+    pub fn from_bytes(data: Vec<u8>, path: &str) -> Result<Self, LoadingError> {
+        Self::with_bytes(CookedBytes::Owned(data), path.to_string())
+    }
+
+    fn with_bytes(bytes: CookedBytes, path: String) -> Result<Self, LoadingError> {
+        let (header, payload_len) = CookedHeader::parse(bytes.as_slice())
+            .and_then(|(header, payload_len)| {
+                if bytes.as_slice().len() < HEADER_SIZE + payload_len {
+                    Err(io::ErrorKind::UnexpectedEof.into())
+                } else {
+                    Ok((header, payload_len))
+                }
+            })
+            .map_err(|source| LoadingError::ReadError {
+                path: path.clone(),
+                source,
+            })?;
+
+        Ok(Self {
+            header,
+            path,
+            bytes,
+            payload_len,
+        })
+    }
+
+    pub fn kind(&self) -> CookedKind {
+        self.header.kind
+    }
+
+    pub fn source_hash(&self) -> u64 {
+        self.header.source_hash
+    }
+
+    /// Resolved location of the file, for error messages
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.bytes, CookedBytes::Mapped(_))
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.bytes.as_slice()[HEADER_SIZE..HEADER_SIZE + self.payload_len]
+    }
+
+    pub fn reader(&self) -> CookedReader<'_> {
+        CookedReader::new(self.payload())
+    }
+
+    pub fn expect(self, kind: CookedKind) -> Result<Self, LoadingError> {
+        if self.header.kind != kind {
+            return Err(self.error(invalid_data(format!(
+                "expected {:?} asset, found {:?}",
+                kind, self.header.kind
+            ))));
+        }
+
+        Ok(self)
+    }
+
+    /// Decoding error with the location of the file
+    pub fn error(&self, source: io::Error) -> LoadingError {
+        LoadingError::ReadError {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+/// Little endian encoder for cooked assets
+#[derive(Default)]
+pub struct CookedWriter {
+    data: Vec<u8>,
+}
+
+impl CookedWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u32(value as u32);
+    }
+
+    pub fn write_u32s(&mut self, values: &[u32]) {
+        self.write_u64(values.len() as u64);
+        for value in values {
+            self.write_u32(*value);
+        }
+    }
+
+    pub fn write_f32s(&mut self, values: &[f32]) {
+        self.write_u64(values.len() as u64);
+        for value in values {
+            self.write_f32(*value);
+        }
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.write_u64(value.len() as u64);
+        self.data.extend_from_slice(value);
+    }
+
+    pub fn finish(self, kind: CookedKind, source_hash: u64) -> Vec<u8> {
+        let header = CookedHeader { kind, source_hash };
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + self.data.len());
+        header.write(&mut data, self.data.len());
+        data.extend_from_slice(&self.data);
+
+        data
+    }
+
+    /// Write the cooked file. The file is replaced atomically so that it can be safely
+    /// memory-mapped while being cooked again.
+    pub fn save<P: AsRef<Path>>(
+        self,
+        path: P,
+        kind: CookedKind,
+        source_hash: u64,
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.finish(kind, source_hash))?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Little endian decoder for cooked assets. Byte arrays are borrowed from the payload.
+pub struct CookedReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CookedReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_bytes_raw(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        Ok(self.read_bytes_raw(N)?.try_into().unwrap())
+    }
+
+    fn read_len(&mut self, item_size: usize) -> io::Result<usize> {
+        let len = self.read_u64()? as usize;
+
+        if len.saturating_mul(item_size) > self.data.len() - self.pos {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(len)
+    }
+
+    pub fn read_u32(&mut self) -> io::Result<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> io::Result<u64> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    pub fn read_f32(&mut self) -> io::Result<f32> {
+        self.read_array().map(f32::from_le_bytes)
+    }
+
+    pub fn read_bool(&mut self) -> io::Result<bool> {
+        self.read_u32().map(|value| value != 0)
+    }
+
+    pub fn read_u32s(&mut self) -> io::Result<Vec<u32>> {
+        let len = self.read_len(4)?;
+
+        (0..len).map(|_| self.read_u32()).collect()
+    }
+
+    pub fn read_f32s(&mut self) -> io::Result<Vec<f32>> {
+        let len = self.read_len(4)?;
+
+        (0..len).map(|_| self.read_f32()).collect()
+    }
+
+    pub fn read_str(&mut self) -> io::Result<String> {
+        let bytes = self.read_bytes()?;
+
+        String::from_utf8(bytes.to_vec()).map_err(invalid_data)
+    }
+
+    pub fn read_bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.read_len(1)?;
+
+        self.read_bytes_raw(len)
+    }
+}
+
+/// FNV-1a hash of source files, used to skip assets that are already cooked
+#[derive(Clone, Copy, Debug)]
+pub struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl ContentHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+pub fn content_hash(data: &[u8]) -> u64 {
+    let mut hasher = ContentHasher::new();
+    hasher.update(data);
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::load::{
+        LoadingError, MemoryMount,
+        cooked::{
+            COOKED_VERSION, CookedAsset, CookedHeader, CookedKind, CookedWriter, content_hash,
+        },
+        vfs::{Vfs, tests::temp_dir},
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn cooked_data() -> CookedWriter {
+        let mut writer = CookedWriter::new();
+        writer.write_str("mesh");
+        writer.write_u32s(&[0, 1, 2]);
+        writer.write_f32s(&[1., 2.]);
+        writer.write_bytes(&[1, 2, 3, 4]);
+        writer.write_bool(true);
+
+        writer
+    }
+
+    #[test]
+    fn test_cooked_roundtrip() {
+        setup();
+
+        let hash = content_hash(b"source");
+        assert_ne!(hash, content_hash(b"other source"));
+
+        let data = cooked_data().finish(CookedKind::Mesh, hash);
+
+        let asset = CookedAsset::from_bytes(data.clone(), "mesh.gmsh").unwrap();
+        assert_eq!(asset.kind(), CookedKind::Mesh);
+        assert_eq!(asset.source_hash(), hash);
+        assert!(!asset.is_mapped());
+
+        let mut reader = asset.reader();
+        assert_eq!(reader.read_str().unwrap(), "mesh");
+        assert_eq!(reader.read_u32s().unwrap(), vec![0, 1, 2]);
+        assert_eq!(reader.read_f32s().unwrap(), vec![1., 2.]);
+        assert_eq!(reader.read_bytes().unwrap(), &[1, 2, 3, 4]);
+        assert!(reader.read_bool().unwrap());
+        assert!(reader.read_u32().is_err());
+
+        let err = CookedAsset::from_bytes(data.clone(), "mesh.gmsh")
+            .unwrap()
+            .expect(CookedKind::Texture)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("mesh.gmsh"));
+
+        // truncated payload
+        let err = CookedAsset::from_bytes(data[..data.len() - 1].to_vec(), "mesh.gmsh")
+            .err()
+            .unwrap();
+        assert!(matches!(err, LoadingError::ReadError { path, .. } if path == "mesh.gmsh"));
+
+        // outdated version
+        let mut outdated = data;
+        outdated[4..8].copy_from_slice(&(COOKED_VERSION + 1).to_le_bytes());
+        assert!(CookedAsset::from_bytes(outdated, "mesh.gmsh").is_err());
+    }
+
+    #[test]
+    fn test_cooked_mount() {
+        setup();
+
+        let dir = temp_dir("cooked");
+        let path = dir.join("assets/models/mesh.gmsh");
+        cooked_data().save(&path, CookedKind::Mesh, 42).unwrap();
+
+        assert_eq!(
+            CookedHeader::read_file(&path).unwrap(),
+            CookedHeader {
+                kind: CookedKind::Mesh,
+                source_hash: 42
+            }
+        );
+
+        let memory = MemoryMount::new("memory").with_file(
+            "assets/models/memory.gmsh",
+            cooked_data().finish(CookedKind::Mesh, 1),
+        );
+
+        let mut vfs = Vfs::new();
+        vfs.mount_path(&dir, 0).unwrap();
+        vfs.mount(Arc::new(memory), 0);
+
+        let asset = CookedAsset::open(&vfs, "assets/models/mesh.gmsh").unwrap();
+        assert!(asset.is_mapped());
+        assert_eq!(asset.source_hash(), 42);
+        assert_eq!(asset.reader().read_str().unwrap(), "mesh");
+
+        let asset = CookedAsset::open(&vfs, "assets/models/memory.gmsh").unwrap();
+        assert!(!asset.is_mapped());
+        assert_eq!(asset.reader().read_str().unwrap(), "mesh");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use gobs_core::logger;
+
+use crate::load::{
+    LoadingError,
+    archive::{PakMount, ZipMount},
+};
+
+/// Source of files mounted in the [Vfs]. Paths are relative to the mount root and use `/`
+/// as separator (e.g. `assets/textures/wall.png`).
+pub trait Mount: Send + Sync {
+    /// Name of the mount, used to unmount it and in error messages
+    fn name(&self) -> &str;
+
+    fn contains(&self, path: &str) -> bool;
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+
+    /// Location of the file on disk. Only available for directory mounts.
+    fn real_path(&self, _path: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Location used in logs and error messages
+    fn resolve(&self, path: &str) -> String {
+        format!("{}:{}", self.name(), path)
+    }
+}
+
+/// Files from a directory on disk
+pub struct DirMount {
+    name: String,
+    root: PathBuf,
+}
+
+impl DirMount {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        let root = root.as_ref().to_path_buf();
+
+        Self {
+            name: root.display().to_string(),
+            root,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+}
+
+impl Mount for DirMount {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.path(path).is_file()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path(path))
+    }
+
+    fn real_path(&self, path: &str) -> Option<PathBuf> {
+        Some(self.path(path))
+    }
+
+    fn resolve(&self, path: &str) -> String {
+        self.path(path).display().to_string()
+    }
+}
+
+/// Files stored in memory, mostly useful for tests
+#[derive(Default)]
+pub struct MemoryMount {
+    name: String,
+    files: RwLock<HashMap<String, Arc<[u8]>>>,
+}
+
+impl MemoryMount {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            files: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_file(self, path: &str, data: impl Into<Arc<[u8]>>) -> Self {
+        self.insert(path, data);
+
+        self
+    }
+
+    pub fn insert(&self, path: &str, data: impl Into<Arc<[u8]>>) {
+        self.files
+            .write()
+            .unwrap()
+            .insert(path.to_string(), data.into());
+    }
+
+    pub fn remove(&self, path: &str) {
+        self.files.write().unwrap().remove(path);
+    }
+}
+
+impl Mount for MemoryMount {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.files.read().unwrap().contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        self.files
+            .read()
+            .unwrap()
+            .get(path)
+            .map(|data| data.to_vec())
+            .ok_or(io::ErrorKind::NotFound.into())
+    }
+}
+
+struct MountEntry {
+    priority: i32,
+    mount: Arc<dyn Mount>,
+}
+
+/// Virtual file system overlaying directories, archives and in-memory mounts.
+/// Files are searched in mounts with the highest priority first. For mounts with the
+/// same priority, the last mounted wins.
+#[derive(Default)]
+pub struct Vfs {
+    mounts: Vec<MountEntry>,
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mount(&mut self, mount: Arc<dyn Mount>, priority: i32) {
+        tracing::debug!(target: logger::RESOURCES, "Mount {} (priority={})", mount.name(), priority);
+
+        let pos = self
+            .mounts
+            .iter()
+            .position(|entry| entry.priority <= priority)
+            .unwrap_or(self.mounts.len());
+
+        self.mounts.insert(pos, MountEntry { priority, mount });
+    }
+
+    /// Mount a directory, or a `.zip` / `.pak` archive
+    pub fn mount_path<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        priority: i32,
+    ) -> Result<(), LoadingError> {
+        let path = path.as_ref();
+
+        let mount: Arc<dyn Mount> = if path.is_dir() {
+            Arc::new(DirMount::new(path))
+        } else {
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase());
+
+            match extension.as_deref() {
+                Some("zip") => Arc::new(ZipMount::open(path)?),
+                Some("pak") => Arc::new(PakMount::open(path)?),
+                _ => return Err(LoadingError::InvalidMount(path.display().to_string())),
+            }
+        };
+
+        self.mount(mount, priority);
+
+        Ok(())
+    }
+
+    pub fn unmount(&mut self, name: &str) {
+        self.mounts.retain(|entry| entry.mount.name() != name);
+    }
+
+    pub fn clear(&mut self) {
+        self.mounts.clear();
+    }
+
+    /// Mount with the highest priority containing the file
+    pub fn find(&self, path: &str) -> Option<&Arc<dyn Mount>> {
+        self.mounts
+            .iter()
+            .map(|entry| &entry.mount)
+            .find(|mount| mount.contains(path))
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, LoadingError> {
+        let mount = self
+            .find(path)
+            .ok_or_else(|| LoadingError::AssetNotFound(path.to_string()))?;
+
+        let resolved = mount.resolve(path);
+        tracing::debug!(target: logger::RESOURCES, "Read {}", resolved);
+
+        mount.read(path).map_err(|source| LoadingError::ReadError {
+            path: resolved,
+            source,
+        })
+    }
+
+    pub fn read_string(&self, path: &str) -> Result<String, LoadingError> {
+        let data = self.read(path)?;
+
+        String::from_utf8(data).map_err(|e| LoadingError::ReadError {
+            path: self.resolve(path),
+            source: io::Error::new(io::ErrorKind::InvalidData, e),
+        })
+    }
+
+    /// Location of the file, for logs and error messages
+    pub fn resolve(&self, path: &str) -> String {
+        match self.find(path) {
+            Some(mount) => mount.resolve(path),
+            None => path.to_string(),
+        }
+    }
+
+    /// Location of the file on disk, if it is provided by a directory mount
+    pub fn real_path(&self, path: &str) -> Option<PathBuf> {
+        self.find(path).and_then(|mount| mount.real_path(path))
+    }
+
+    /// Directories mounted on disk, by decreasing priority
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        self.mounts
+            .iter()
+            .filter_map(|entry| entry.mount.real_path(""))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        env, fs,
+        path::PathBuf,
+        sync::Arc,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::load::{
+        LoadingError,
+        vfs::{MemoryMount, Vfs},
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("gobs-{}-{}", name, nanos));
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn test_overlay() {
+        setup();
+
+        let mut vfs = Vfs::new();
+
+        let base = MemoryMount::new("base")
+            .with_file("resources/graph.ron", b"base".as_slice())
+            .with_file("resources/pipelines.ron", b"base".as_slice());
+        let patch = MemoryMount::new("patch").with_file("resources/graph.ron", b"patch".as_slice());
+
+        vfs.mount(Arc::new(patch), 10);
+        vfs.mount(Arc::new(base), 0);
+
+        assert_eq!(vfs.read_string("resources/graph.ron").unwrap(), "patch");
+        assert_eq!(vfs.read_string("resources/pipelines.ron").unwrap(), "base");
+        assert_eq!(
+            vfs.resolve("resources/graph.ron"),
+            "patch:resources/graph.ron"
+        );
+
+        // same priority: last mounted wins
+        let other = MemoryMount::new("other").with_file("resources/graph.ron", b"other".as_slice());
+        vfs.mount(Arc::new(other), 10);
+        assert_eq!(vfs.read_string("resources/graph.ron").unwrap(), "other");
+
+        vfs.unmount("other");
+        vfs.unmount("patch");
+        assert_eq!(vfs.read_string("resources/graph.ron").unwrap(), "base");
+
+        assert!(matches!(
+            vfs.read("resources/missing.ron"),
+            Err(LoadingError::AssetNotFound(path)) if path == "resources/missing.ron"
+        ));
+    }
+
+    #[test]
+    fn test_dir_mount() {
+        setup();
+
+        let dir = temp_dir("vfs");
+        fs::create_dir_all(dir.join("resources")).unwrap();
+        fs::write(dir.join("resources/graph.ron"), "dir").unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount_path(&dir, 0).unwrap();
+
+        assert_eq!(vfs.read_string("resources/graph.ron").unwrap(), "dir");
+        assert_eq!(
+            vfs.real_path("resources/graph.ron"),
+            Some(dir.join("resources/graph.ron"))
+        );
+        assert_eq!(vfs.dirs(), vec![dir.clone()]);
+
+        assert!(matches!(
+            vfs.mount_path(dir.join("resources/graph.ron"), 0),
+            Err(LoadingError::InvalidMount(_))
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
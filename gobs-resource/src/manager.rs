@@ -1,3 +1,12 @@
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{
+        Arc, Weak,
+        mpsc::{self, Receiver, Sender},
+    },
+};
+
 use gobs_core::{
     data::{
         anymap::AnyMap,
@@ -7,17 +16,25 @@ use gobs_core::{
     logger,
 };
 
-use crate::resource::{
-    Resource, ResourceError, ResourceHandle, ResourceLifetime, ResourceLoader, ResourceProperties,
-    ResourceState, ResourceType,
+use crate::{
+    reference::{RefTracker, ResourceKey, ResourceRef},
+    resource::{
+        Resource, ResourceError, ResourceHandle, ResourceLifetime, ResourceLoader,
+        ResourceProperties, ResourceState, ResourceType,
+    },
+    worker,
 };
 
 pub type ResourceId = Key;
 
-#[derive(Default)]
 pub struct ResourceRegistry {
     registry: ObjectArena,
     labels: ObjectMap<String, ResourceId>,
+    refs: HashMap<ResourceKey, Weak<RefTracker>>,
+    dependencies: HashMap<ResourceKey, Vec<ResourceRef>>,
+    released: Vec<ResourceKey>,
+    release_sender: Sender<ResourceKey>,
+    release_receiver: Receiver<ResourceKey>,
 }
 
 pub struct ResourceData<'res, R: ResourceType> {
@@ -30,7 +47,27 @@ pub struct ResourceDataMut<'res, R: ResourceType> {
     pub properties: &'res mut R::ResourceProperties,
 }
 
+impl Default for ResourceRegistry {
+    fn default() -> Self {
+        let (release_sender, release_receiver) = mpsc::channel();
+
+        Self {
+            registry: ObjectArena::default(),
+            labels: ObjectMap::default(),
+            refs: HashMap::new(),
+            dependencies: HashMap::new(),
+            released: Vec::new(),
+            release_sender,
+            release_receiver,
+        }
+    }
+}
+
 impl ResourceRegistry {
+    fn key<R: ResourceType + 'static>(handle: &ResourceHandle<R>) -> ResourceKey {
+        (TypeId::of::<R>(), handle.id)
+    }
+
     pub fn add<R: ResourceType + 'static>(
         &mut self,
         properties: R::ResourceProperties,
@@ -56,23 +93,30 @@ impl ResourceRegistry {
         &mut self,
         handle: &ResourceHandle<R>,
     ) -> Option<Resource<R>> {
+        let key = Self::key(handle);
+
+        self.refs.remove(&key);
+        // release references held on dependencies
+        self.dependencies.remove(&key);
+
         self.registry.remove::<Resource<R>>(handle.id)
     }
 
     pub fn schedule_removal<R: ResourceType + 'static>(&mut self, handle: &ResourceHandle<R>) {
-        let resource = self.get_mut(handle);
-        resource.life = 0;
-        resource.lifetime = ResourceLifetime::Transient;
+        if let Some(resource) = self.get_mut(handle) {
+            resource.life = 0;
+            resource.lifetime = ResourceLifetime::Transient;
+        }
     }
 
     /// Clone a resource and schedule old resource for deletion
     pub fn replace<R: ResourceType + 'static>(
         &mut self,
         handle: &ResourceHandle<R>,
-    ) -> ResourceHandle<R> {
+    ) -> Option<ResourceHandle<R>> {
         tracing::trace!(target: logger::RESOURCES, "Resource cloned: {:?}", handle);
 
-        let old_resource = self.get_mut::<R>(handle);
+        let old_resource = self.get_mut::<R>(handle)?;
         let properties = old_resource.properties.clone();
         let lifetime = old_resource.lifetime;
         old_resource.life = 0;
@@ -85,7 +129,50 @@ impl ResourceRegistry {
         let resource: &Resource<R> = self.registry.get(key).unwrap();
         tracing::debug!(target: logger::RESOURCES, "New resource: {} ({}): {:?}", &resource.properties.name(), std::any::type_name::<R>(), resource.handle.id);
 
-        resource.handle
+        let new_handle = resource.handle;
+
+        // the clone depends on the same resources
+        if let Some(dependencies) = self.dependencies.get(&Self::key(handle)) {
+            let dependencies = dependencies.clone();
+            self.dependencies
+                .insert(Self::key(&new_handle), dependencies);
+        }
+
+        Some(new_handle)
+    }
+
+    /// Move loaded data to a transient copy of the resource, so that it is unloaded
+    /// once frames in flight are done with it
+    fn retire<R: ResourceType + 'static>(
+        &mut self,
+        handle: &ResourceHandle<R>,
+        data: R::ResourceData,
+        size: usize,
+    ) {
+        let Some(resource) = self.get(handle) else {
+            return;
+        };
+        let properties = resource.properties.clone();
+
+        let key = self.registry.insert_with_key(|key| {
+            let mut resource = Resource::<R>::new(
+                ResourceHandle::new(key),
+                properties,
+                ResourceLifetime::Transient,
+            );
+            resource.data = ResourceState::Loaded(data);
+            resource.size = size;
+            resource
+        });
+
+        tracing::trace!(target: logger::RESOURCES, "Resource data retired: {:?} -> {:?}", handle.id, key);
+
+        // retired data may still use the resource dependencies
+        if let Some(dependencies) = self.dependencies.get(&Self::key(handle)) {
+            let dependencies = dependencies.clone();
+            self.dependencies
+                .insert(Self::key(&ResourceHandle::<R>::new(key)), dependencies);
+        }
     }
 
     pub fn get_by_name<R: ResourceType + 'static>(&self, name: &str) -> Option<ResourceHandle<R>> {
@@ -98,16 +185,18 @@ impl ResourceRegistry {
         id.map(|id| ResourceHandle::new(*id))
     }
 
-    // TODO: return option
-    pub fn get<R: ResourceType + 'static>(&self, handle: &ResourceHandle<R>) -> &Resource<R> {
-        self.registry.get::<Resource<R>>(handle.id).unwrap()
+    pub fn get<R: ResourceType + 'static>(
+        &self,
+        handle: &ResourceHandle<R>,
+    ) -> Option<&Resource<R>> {
+        self.registry.get::<Resource<R>>(handle.id)
     }
 
     pub fn get_mut<R: ResourceType + 'static>(
         &mut self,
         handle: &ResourceHandle<R>,
-    ) -> &mut Resource<R> {
-        self.registry.get_mut::<Resource<R>>(handle.id).unwrap()
+    ) -> Option<&mut Resource<R>> {
+        self.registry.get_mut::<Resource<R>>(handle.id)
     }
 
     pub fn values<R: ResourceType + 'static>(&self) -> impl Iterator<Item = &Resource<R>> {
@@ -119,23 +208,149 @@ impl ResourceRegistry {
     ) -> impl Iterator<Item = &mut Resource<R>> {
         self.registry.values_mut()
     }
+
+    /// Get a strong reference on a ref-counted resource. Returns `None` for other lifetimes.
+    pub fn acquire<R: ResourceType + 'static>(
+        &mut self,
+        handle: &ResourceHandle<R>,
+    ) -> Option<ResourceRef> {
+        let resource = self.get(handle)?;
+
+        if resource.lifetime != ResourceLifetime::RefCounted {
+            return None;
+        }
+
+        let key = Self::key(handle);
+
+        if let Some(tracker) = self.refs.get(&key).and_then(|weak| weak.upgrade()) {
+            return Some(ResourceRef { tracker });
+        }
+
+        let tracker = Arc::new(RefTracker::new(key, self.release_sender.clone()));
+        self.refs.insert(key, Arc::downgrade(&tracker));
+
+        Some(ResourceRef { tracker })
+    }
+
+    /// Keep `dependency` alive as long as `handle` is not removed
+    pub fn add_dependency<R: ResourceType + 'static, D: ResourceType + 'static>(
+        &mut self,
+        handle: &ResourceHandle<R>,
+        dependency: &ResourceHandle<D>,
+    ) {
+        if let Some(dependency) = self.acquire(dependency) {
+            self.dependencies
+                .entry(Self::key(handle))
+                .or_default()
+                .push(dependency);
+        }
+    }
+
+    pub fn ref_count<R: ResourceType + 'static>(&self, handle: &ResourceHandle<R>) -> usize {
+        self.refs
+            .get(&Self::key(handle))
+            .map(|weak| weak.strong_count())
+            .unwrap_or(0)
+    }
+
+    /// Ref-counted resources of type R whose last reference has been dropped
+    pub(crate) fn released<R: ResourceType + 'static>(&mut self) -> Vec<ResourceHandle<R>> {
+        self.released.extend(self.release_receiver.try_iter());
+
+        let ty = TypeId::of::<R>();
+        let mut released = vec![];
+
+        self.released.retain(|key| {
+            if key.0 == ty {
+                released.push(*key);
+                false
+            } else {
+                true
+            }
+        });
+
+        released
+            .into_iter()
+            .filter(|key| {
+                // resource may have been acquired again in the meantime
+                let alive = self
+                    .refs
+                    .get(key)
+                    .is_some_and(|weak| weak.strong_count() > 0);
+                if !alive {
+                    self.refs.remove(key);
+                }
+                !alive
+            })
+            .map(|key| ResourceHandle::new(key.1))
+            .collect()
+    }
+}
+
+/// Progress of background loading since the last time the loading queue was empty
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadProgress {
+    pub requested: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    pub fn pending(&self) -> usize {
+        self.requested - self.loaded - self.failed
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pending() == 0
+    }
+
+    pub fn ratio(&self) -> f32 {
+        if self.requested == 0 {
+            1.
+        } else {
+            (self.loaded + self.failed) as f32 / self.requested as f32
+        }
+    }
 }
 
 pub struct ResourceManager {
     frames_in_flight: usize,
+    frame: usize,
     registry: ResourceRegistry,
     loader: AnyMap,
+    progress: LoadProgress,
+    memory_used: usize,
+    memory_budget: Option<usize>,
 }
 
 impl ResourceManager {
     pub fn new(frames_in_flight: usize) -> Self {
         Self {
             frames_in_flight,
+            frame: 0,
             registry: ResourceRegistry::default(),
             loader: AnyMap::default(),
+            progress: LoadProgress::default(),
+            memory_used: 0,
+            memory_budget: None,
         }
     }
 
+    /// Advance the frame counter used to track least recently used resources
+    pub fn new_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Unused resources are evicted (least recently used first) when the loaded
+    /// data exceeds the budget. Evicted resources are reloaded on next access.
+    pub fn set_memory_budget(&mut self, budget: Option<usize>) {
+        self.memory_budget = budget;
+    }
+
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
+    }
+
     pub fn add<R: ResourceType + 'static>(
         &mut self,
         properties: R::ResourceProperties,
@@ -159,22 +374,47 @@ impl ResourceManager {
     pub fn replace<R: ResourceType + 'static>(
         &mut self,
         handle: &ResourceHandle<R>,
-    ) -> ResourceHandle<R> {
+    ) -> Option<ResourceHandle<R>> {
         self.registry.replace(handle)
     }
 
+    /// Reload a resource in place: the handle stays valid and the resource is loaded again
+    /// on next access. Current data is released once frames in flight are done with it.
+    pub fn reload<R: ResourceType + 'static>(&mut self, handle: &ResourceHandle<R>) {
+        let Some(resource) = self.registry.get_mut(handle) else {
+            return;
+        };
+
+        tracing::debug!(target: logger::RESOURCES, "Reload resource {} ({})", resource.properties.name(), std::any::type_name::<R>());
+
+        match std::mem::replace(&mut resource.data, ResourceState::Unloaded) {
+            ResourceState::Loaded(data) => {
+                let size = std::mem::take(&mut resource.size);
+                self.registry.retire(handle, data, size);
+            }
+            ResourceState::Loading(_) => {
+                // source data is outdated
+                self.progress.requested = self.progress.requested.saturating_sub(1);
+            }
+            ResourceState::Unloaded | ResourceState::Failed => {}
+        }
+    }
+
     pub fn get_by_name<R: ResourceType + 'static>(&self, name: &str) -> Option<ResourceHandle<R>> {
         self.registry.get_by_name(name)
     }
 
-    pub fn get<R: ResourceType + 'static>(&self, handle: &ResourceHandle<R>) -> &Resource<R> {
+    pub fn get<R: ResourceType + 'static>(
+        &self,
+        handle: &ResourceHandle<R>,
+    ) -> Option<&Resource<R>> {
         self.registry.get(handle)
     }
 
     pub fn get_mut<R: ResourceType + 'static>(
         &mut self,
         handle: &ResourceHandle<R>,
-    ) -> &mut Resource<R> {
+    ) -> Option<&mut Resource<R>> {
         self.registry.get_mut(handle)
     }
 
@@ -182,10 +422,66 @@ impl ResourceManager {
         self.registry.values()
     }
 
+    pub fn acquire<R: ResourceType + 'static>(
+        &mut self,
+        handle: &ResourceHandle<R>,
+    ) -> Option<ResourceRef> {
+        self.registry.acquire(handle)
+    }
+
+    pub fn add_dependency<R: ResourceType + 'static, D: ResourceType + 'static>(
+        &mut self,
+        handle: &ResourceHandle<R>,
+        dependency: &ResourceHandle<D>,
+    ) {
+        self.registry.add_dependency(handle, dependency);
+    }
+
+    pub fn ref_count<R: ResourceType + 'static>(&self, handle: &ResourceHandle<R>) -> usize {
+        self.registry.ref_count(handle)
+    }
+
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
     pub fn update<'a, R: ResourceType + 'static>(&mut self, backend: &mut R::ResourceBackend<'a>) {
         tracing::trace!(target: logger::RESOURCES, "Update registry for {:?}", std::any::type_name::<R>());
 
+        for handle in self.registry.released::<R>() {
+            if let Some(resource) = self.registry.get_mut(&handle)
+                && resource.lifetime == ResourceLifetime::RefCounted
+            {
+                tracing::trace!(target: logger::RESOURCES, "Resource released: {:?}", handle.id);
+                resource.life = 0;
+                resource.lifetime = ResourceLifetime::Transient;
+            }
+        }
+
+        // ref-counted resources that are never acquired are released after a frame
+        let unreferenced = self
+            .registry
+            .values::<R>()
+            .filter(|resource| resource.lifetime == ResourceLifetime::RefCounted)
+            .map(|resource| {
+                (
+                    resource.handle,
+                    self.registry.ref_count(&resource.handle) == 0,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (handle, unreferenced) in unreferenced {
+            if let Some(resource) = self.registry.get_mut(&handle) {
+                if !unreferenced {
+                    resource.life = 0;
+                } else if resource.life > 0 {
+                    tracing::trace!(target: logger::RESOURCES, "Resource not referenced: {:?}", handle.id);
+                    resource.life = 0;
+                    resource.lifetime = ResourceLifetime::Transient;
+                } else {
+                    resource.life += 1;
+                }
+            }
+        }
+
         let mut to_delete: Vec<ResourceHandle<R>> = vec![];
 
         for value in self.registry.values_mut() {
@@ -202,16 +498,72 @@ impl ResourceManager {
 
         let loader = self.loader.get_mut::<R::ResourceLoader>().unwrap();
         for handle in to_delete {
-            if let Some(resource) = self.registry.remove(&handle)
-                && let ResourceState::Loaded(data) = resource.data
+            if let Some(resource) = self.registry.remove(&handle) {
+                match resource.data {
+                    ResourceState::Loaded(data) => {
+                        self.memory_used -= resource.size;
+                        loader.unload(backend, data);
+                    }
+                    ResourceState::Loading(_) => {
+                        self.progress.requested = self.progress.requested.saturating_sub(1)
+                    }
+                    ResourceState::Unloaded | ResourceState::Failed => {}
+                }
+            }
+        }
+
+        self.evict::<R>(backend);
+    }
+
+    /// Unload least recently used resources until memory usage fits in the budget.
+    /// Resources used by frames in flight are never evicted.
+    fn evict<'a, R: ResourceType + 'static>(&mut self, backend: &mut R::ResourceBackend<'a>) {
+        let Some(budget) = self.memory_budget else {
+            return;
+        };
+
+        if self.memory_used <= budget {
+            return;
+        }
+
+        let mut candidates = self
+            .registry
+            .values::<R>()
+            .filter(|resource| {
+                resource.is_loaded()
+                    && resource.size > 0
+                    && resource.lifetime != ResourceLifetime::Transient
+                    && resource.last_used + self.frames_in_flight < self.frame
+            })
+            .map(|resource| (resource.last_used, resource.handle))
+            .collect::<Vec<_>>();
+
+        candidates.sort_by_key(|(last_used, _)| *last_used);
+
+        let loader = self.loader.get_mut::<R::ResourceLoader>().unwrap();
+        for (_, handle) in candidates {
+            if self.memory_used <= budget {
+                break;
+            }
+
+            let Some(resource) = self.registry.get_mut(&handle) else {
+                continue;
+            };
+
+            if let ResourceState::Loaded(data) =
+                std::mem::replace(&mut resource.data, ResourceState::Unloaded)
             {
+                tracing::debug!(target: logger::MEMORY, "Evict resource {} ({} bytes)", resource.properties.name(), resource.size);
+
+                self.memory_used -= resource.size;
+                resource.size = 0;
                 loader.unload(backend, data);
             }
         }
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    pub fn flush<R: ResourceType + 'static>(&mut self) {
+    pub fn flush<'a, R: ResourceType + 'static>(&mut self, backend: &mut R::ResourceBackend<'a>) {
         tracing::trace!(target: logger::RESOURCES, "Flush loader {:?}", std::any::type_name::<R>());
 
         let loader = self
@@ -219,37 +571,148 @@ impl ResourceManager {
             .get_mut::<R::ResourceLoader>()
             .unwrap_or_else(|| panic!("Loader not registered: {:?}", std::any::type_name::<R>()));
 
-        loader.flush();
+        loader.flush(backend);
     }
 
     pub fn register_resource<R: ResourceType + 'static>(&mut self, loader: R::ResourceLoader) {
         self.loader.insert(loader);
     }
 
+    /// Start loading the resource on a worker thread if its loader supports it.
+    /// Resources without a prefetch step are left unloaded and loaded on first access.
+    /// Resources that failed to load are not requested again until [ResourceManager::reload].
+    pub fn request<R: ResourceType + 'static>(&mut self, handle: &ResourceHandle<R>) {
+        let Some(resource) = self.registry.get_mut(handle) else {
+            return;
+        };
+
+        if !matches!(resource.data, ResourceState::Unloaded) {
+            return;
+        }
+
+        let loader = self
+            .loader
+            .get::<R::ResourceLoader>()
+            .unwrap_or_else(|| panic!("Loader not registered: {:?}", std::any::type_name::<R>()));
+
+        if let Some(job) = loader.prefetch(&resource.properties) {
+            tracing::trace!(target: logger::RESOURCES, "Request background loading {:?}", handle);
+
+            if self.progress.is_complete() {
+                self.progress = LoadProgress::default();
+            }
+            self.progress.requested += 1;
+
+            resource.data = ResourceState::Loading(worker::pool().spawn(job));
+        }
+    }
+
+    /// Finish loading of all resources whose background job has completed
+    #[tracing::instrument(target = "profile", skip_all, level = "trace")]
+    pub fn poll<'a, R: ResourceType + 'static>(&mut self, backend: &mut R::ResourceBackend<'a>) {
+        let loading = self
+            .registry
+            .values::<R>()
+            .filter(|resource| resource.is_loading())
+            .map(|resource| resource.handle)
+            .collect::<Vec<_>>();
+
+        for handle in loading {
+            if let Err(e) = self.load_data(backend, &handle, false) {
+                tracing::error!(target: logger::RESOURCES, "Failed to load resource {:?}: {}", handle, e);
+            }
+        }
+    }
+
+    pub fn is_ready<R: ResourceType + 'static>(&self, handle: &ResourceHandle<R>) -> bool {
+        self.get(handle)
+            .is_some_and(|resource| resource.is_loaded())
+    }
+
+    pub fn load_progress(&self) -> LoadProgress {
+        self.progress
+    }
+
+    /// Returns true if the resource is loaded. When `wait` is false, resources loading
+    /// in background are not waited for.
     fn load_data<'a, R: ResourceType + 'static>(
         &mut self,
         backend: &mut R::ResourceBackend<'a>,
         handle: &ResourceHandle<R>,
-    ) -> Result<(), ResourceError> {
-        let resource = self.get_mut(handle);
-
-        if !resource.is_loaded() {
-            let loader = self
-                .loader
-                .get_mut::<R::ResourceLoader>()
-                .unwrap_or_else(|| {
-                    panic!("Loader not registered: {:?}", std::any::type_name::<R>())
+        wait: bool,
+    ) -> Result<bool, ResourceError> {
+        let resource = self
+            .registry
+            .get_mut(handle)
+            .ok_or(ResourceError::InvalidHandle)?;
+
+        resource.last_used = self.frame;
+
+        let prefetched = match std::mem::replace(&mut resource.data, ResourceState::Unloaded) {
+            ResourceState::Loaded(data) => {
+                resource.data = ResourceState::Loaded(data);
+                return Ok(true);
+            }
+            ResourceState::Unloaded => None,
+            ResourceState::Failed => {
+                resource.data = ResourceState::Failed;
+                return Err(ResourceError::LoadFailed);
+            }
+            ResourceState::Loading(mut task) => {
+                let result = if wait {
+                    task.wait()
+                } else {
+                    match task.try_take() {
+                        Some(result) => result,
+                        None => {
+                            resource.data = ResourceState::Loading(task);
+                            return Ok(false);
+                        }
+                    }
+                };
+
+                Some(result.map_err(ResourceError::from).and_then(|data| data))
+            }
+        };
+
+        let loader = self
+            .loader
+            .get_mut::<R::ResourceLoader>()
+            .unwrap_or_else(|| panic!("Loader not registered: {:?}", std::any::type_name::<R>()));
+
+        let data = match prefetched {
+            None => {
+                tracing::trace!(target: logger::RESOURCES, "Loading resource {:?}", handle);
+                loader.load(backend, handle, &mut self.registry)?
+            }
+            Some(prefetched) => {
+                tracing::trace!(target: logger::RESOURCES, "Finish loading resource {:?}", handle);
+                let data = prefetched.and_then(|prefetched| {
+                    loader.load_prefetched(backend, handle, &mut self.registry, prefetched)
                 });
 
-            tracing::trace!(target: logger::RESOURCES, "Loading resource {:?}", handle);
-            let data = loader.load(backend, handle, &mut self.registry)?;
+                match data {
+                    Ok(data) => {
+                        self.progress.loaded += 1;
+                        data
+                    }
+                    Err(e) => {
+                        self.progress.failed += 1;
+                        self.registry.get_mut::<R>(handle).unwrap().data = ResourceState::Failed;
+                        return Err(e);
+                    }
+                }
+            }
+        };
 
-            let resource = self.get_mut::<R>(handle);
+        let size = loader.size(&self.registry.get(handle).unwrap().properties, &data);
+        self.memory_used += size;
 
-            resource.data = ResourceState::Loaded(data);
-        }
+        let resource = self.registry.get_mut::<R>(handle).unwrap();
+        resource.size = size;
+        resource.data = ResourceState::Loaded(data);
 
-        Ok(())
+        Ok(true)
     }
 
     pub fn get_data<'a, R: ResourceType + 'static>(
@@ -257,9 +720,9 @@ impl ResourceManager {
         backend: &mut R::ResourceBackend<'a>,
         handle: &ResourceHandle<R>,
     ) -> Result<ResourceData<'_, R>, ResourceError> {
-        self.load_data::<R>(backend, handle)?;
+        self.load_data::<R>(backend, handle, true)?;
 
-        let resource = self.get(handle);
+        let resource = self.get(handle).unwrap();
 
         let data = match &resource.data {
             ResourceState::Loaded(data) => data,
@@ -272,14 +735,40 @@ impl ResourceManager {
         })
     }
 
+    /// Non-blocking version of [ResourceManager::get_data]: background loading is started
+    /// if needed and `None` is returned until the resource is ready.
+    pub fn try_get_data<'a, R: ResourceType + 'static>(
+        &'_ mut self,
+        backend: &mut R::ResourceBackend<'a>,
+        handle: &ResourceHandle<R>,
+    ) -> Result<Option<ResourceData<'_, R>>, ResourceError> {
+        self.request(handle);
+
+        if !self.load_data::<R>(backend, handle, false)? {
+            return Ok(None);
+        }
+
+        let resource = self.get(handle).unwrap();
+
+        let data = match &resource.data {
+            ResourceState::Loaded(data) => data,
+            _ => unreachable!(),
+        };
+
+        Ok(Some(ResourceData {
+            data,
+            properties: &resource.properties,
+        }))
+    }
+
     pub fn get_data_mut<'a, R: ResourceType + 'static>(
         &'_ mut self,
         backend: &mut R::ResourceBackend<'a>,
         handle: &ResourceHandle<R>,
     ) -> Result<ResourceDataMut<'_, R>, ResourceError> {
-        self.load_data::<R>(backend, handle)?;
+        self.load_data::<R>(backend, handle, true)?;
 
-        let resource = self.get_mut(handle);
+        let resource = self.get_mut(handle).unwrap();
 
         let data = match &mut resource.data {
             ResourceState::Loaded(data) => data,
@@ -295,7 +784,7 @@ impl ResourceManager {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, thread, time::Duration};
 
     use slotmap::SlotMap;
     use tracing::Level;
@@ -306,8 +795,8 @@ mod tests {
     use crate::{
         manager::ResourceManager,
         resource::{
-            ResourceError, ResourceHandle, ResourceLifetime, ResourceLoader, ResourceProperties,
-            ResourceType,
+            PrefetchData, PrefetchJob, ResourceError, ResourceHandle, ResourceLifetime,
+            ResourceLoader, ResourceProperties, ResourceType,
         },
     };
 
@@ -349,9 +838,68 @@ mod tests {
             Ok(DummyData {})
         }
 
+        fn size(&self, _properties: &DummyProperties, _data: &DummyData) -> usize {
+            100
+        }
+
         fn unload(&mut self, _backend: &mut Backend, _data: DummyData) {}
 
-        fn flush(&mut self) {}
+        fn flush(&mut self, _backend: &mut Backend) {}
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Prefetched;
+
+    impl ResourceType for Prefetched {
+        type ResourceData = PrefetchedData;
+        type ResourceBackend<'a> = Backend;
+        type ResourceProperties = DummyProperties;
+        type ResourceLoader = PrefetchedLoader;
+    }
+
+    pub struct PrefetchedData {
+        pub value: String,
+    }
+
+    pub struct PrefetchedLoader {}
+
+    impl ResourceLoader<Prefetched> for PrefetchedLoader {
+        fn load(
+            &mut self,
+            _backend: &mut Backend,
+            _handle: &ResourceHandle<Prefetched>,
+            _resource_registry: &mut super::ResourceRegistry,
+        ) -> Result<PrefetchedData, ResourceError> {
+            unreachable!()
+        }
+
+        fn prefetch(&self, properties: &DummyProperties) -> Option<PrefetchJob> {
+            let name = properties.name.clone();
+
+            Some(Box::new(move || {
+                if name.is_empty() {
+                    Err(ResourceError::InvalidData)
+                } else {
+                    Ok(Box::new(name.to_uppercase()) as PrefetchData)
+                }
+            }))
+        }
+
+        fn load_prefetched(
+            &mut self,
+            _backend: &mut Backend,
+            _handle: &ResourceHandle<Prefetched>,
+            _resource_registry: &mut super::ResourceRegistry,
+            data: PrefetchData,
+        ) -> Result<PrefetchedData, ResourceError> {
+            let value = *data.downcast::<String>().unwrap();
+
+            Ok(PrefetchedData { value })
+        }
+
+        fn unload(&mut self, _backend: &mut Backend, _data: PrefetchedData) {}
+
+        fn flush(&mut self, _backend: &mut Backend) {}
     }
 
     fn setup() {
@@ -427,4 +975,228 @@ mod tests {
         }
         tracing::debug!(target: logger::PROFILE, "get slotmap: {}", 1000. * timer.delta());
     }
+
+    #[test]
+    fn test_background_loading() {
+        setup();
+
+        let mut resource_manager = ResourceManager::new(2);
+        resource_manager.register_resource::<Prefetched>(PrefetchedLoader {});
+
+        let mut backend = Backend;
+
+        let handle = resource_manager.add::<Prefetched>(
+            DummyProperties {
+                name: "dummy".to_string(),
+            },
+            ResourceLifetime::Static,
+            false,
+        );
+        let invalid = resource_manager.add::<Prefetched>(
+            DummyProperties {
+                name: String::new(),
+            },
+            ResourceLifetime::Static,
+            false,
+        );
+
+        assert!(!resource_manager.is_ready(&handle));
+
+        resource_manager.request(&handle);
+        resource_manager.request(&invalid);
+        assert_eq!(resource_manager.load_progress().requested, 2);
+
+        let mut value = None;
+        for _ in 0..100 {
+            if let Some(data) = resource_manager
+                .try_get_data(&mut backend, &handle)
+                .unwrap()
+            {
+                value = Some(data.data.value.clone());
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(value.as_deref(), Some("DUMMY"));
+
+        assert!(resource_manager.get_data(&mut backend, &invalid).is_err());
+
+        let progress = resource_manager.load_progress();
+        assert_eq!(progress.loaded, 1);
+        assert_eq!(progress.failed, 1);
+        assert!(progress.is_complete());
+        assert_eq!(progress.ratio(), 1.);
+
+        // failed resources are not requested again until reloaded
+        resource_manager.request(&invalid);
+        assert!(resource_manager.get(&invalid).unwrap().is_failed());
+        assert!(matches!(
+            resource_manager.try_get_data(&mut backend, &invalid),
+            Err(ResourceError::LoadFailed)
+        ));
+        assert_eq!(resource_manager.load_progress().requested, 2);
+
+        resource_manager.reload(&invalid);
+        resource_manager.request(&invalid);
+        assert!(resource_manager.get(&invalid).unwrap().is_loading());
+    }
+
+    fn dummy(
+        resource_manager: &mut ResourceManager,
+        lifetime: ResourceLifetime,
+    ) -> ResourceHandle<Dummy> {
+        resource_manager.add::<Dummy>(
+            DummyProperties {
+                name: "dummy".to_string(),
+            },
+            lifetime,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_ref_counted() {
+        setup();
+
+        let mut resource_manager = ResourceManager::new(2);
+        resource_manager.register_resource::<Dummy>(DummyLoader {});
+
+        let mut backend = Backend;
+
+        let parent = dummy(&mut resource_manager, ResourceLifetime::RefCounted);
+        let child = dummy(&mut resource_manager, ResourceLifetime::RefCounted);
+        let other = dummy(&mut resource_manager, ResourceLifetime::Static);
+
+        resource_manager.add_dependency(&parent, &child);
+        resource_manager.add_dependency(&parent, &other);
+        assert_eq!(resource_manager.ref_count(&child), 1);
+        assert!(resource_manager.acquire(&other).is_none());
+
+        let parent_ref = resource_manager.acquire(&parent).unwrap();
+        let parent_ref2 = parent_ref.clone();
+        assert_eq!(resource_manager.ref_count(&parent), 2);
+
+        resource_manager.get_data(&mut backend, &parent).unwrap();
+        resource_manager.get_data(&mut backend, &child).unwrap();
+        assert_eq!(resource_manager.memory_used(), 200);
+
+        drop(parent_ref);
+        resource_manager.update::<Dummy>(&mut backend);
+        assert!(resource_manager.get(&parent).is_some());
+
+        drop(parent_ref2);
+        for _ in 0..3 {
+            resource_manager.update::<Dummy>(&mut backend);
+        }
+        assert!(resource_manager.get(&parent).is_none());
+        assert!(resource_manager.get_data(&mut backend, &parent).is_err());
+
+        // dependency is released once the parent is removed
+        assert_eq!(resource_manager.ref_count(&child), 0);
+        for _ in 0..3 {
+            resource_manager.update::<Dummy>(&mut backend);
+        }
+        assert!(resource_manager.get(&child).is_none());
+        assert!(resource_manager.get(&other).is_some());
+        assert_eq!(resource_manager.memory_used(), 0);
+    }
+
+    #[test]
+    fn test_never_acquired() {
+        setup();
+
+        let mut resource_manager = ResourceManager::new(2);
+        resource_manager.register_resource::<Dummy>(DummyLoader {});
+
+        let mut backend = Backend;
+
+        let unused = dummy(&mut resource_manager, ResourceLifetime::RefCounted);
+        let used = dummy(&mut resource_manager, ResourceLifetime::RefCounted);
+        resource_manager.get_data(&mut backend, &unused).unwrap();
+
+        // acquired within a frame of its creation
+        resource_manager.update::<Dummy>(&mut backend);
+        let used_ref = resource_manager.acquire(&used).unwrap();
+
+        for _ in 0..4 {
+            resource_manager.update::<Dummy>(&mut backend);
+        }
+        assert!(resource_manager.get(&unused).is_none());
+        assert!(resource_manager.get(&used).is_some());
+        assert_eq!(resource_manager.memory_used(), 0);
+
+        drop(used_ref);
+        for _ in 0..4 {
+            resource_manager.update::<Dummy>(&mut backend);
+        }
+        assert!(resource_manager.get(&used).is_none());
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        setup();
+
+        let mut resource_manager = ResourceManager::new(2);
+        resource_manager.register_resource::<Dummy>(DummyLoader {});
+        resource_manager.set_memory_budget(Some(250));
+
+        let mut backend = Backend;
+
+        let old = dummy(&mut resource_manager, ResourceLifetime::Static);
+        let recent = dummy(&mut resource_manager, ResourceLifetime::Static);
+        let current = dummy(&mut resource_manager, ResourceLifetime::Static);
+
+        resource_manager.get_data(&mut backend, &old).unwrap();
+        resource_manager.new_frame();
+        resource_manager.get_data(&mut backend, &recent).unwrap();
+        resource_manager.new_frame();
+        resource_manager.new_frame();
+        resource_manager.new_frame();
+        resource_manager.get_data(&mut backend, &current).unwrap();
+        assert_eq!(resource_manager.memory_used(), 300);
+
+        resource_manager.update::<Dummy>(&mut backend);
+
+        assert_eq!(resource_manager.memory_used(), 200);
+        assert!(!resource_manager.is_ready(&old));
+        assert!(resource_manager.is_ready(&recent));
+        assert!(resource_manager.is_ready(&current));
+
+        // evicted resources are reloaded on demand
+        resource_manager.get_data(&mut backend, &old).unwrap();
+        assert!(resource_manager.is_ready(&old));
+    }
+
+    #[test]
+    fn test_reload() {
+        setup();
+
+        let mut resource_manager = ResourceManager::new(2);
+        resource_manager.register_resource::<Dummy>(DummyLoader {});
+
+        let mut backend = Backend;
+
+        let parent = dummy(&mut resource_manager, ResourceLifetime::Static);
+        let child = dummy(&mut resource_manager, ResourceLifetime::RefCounted);
+        resource_manager.add_dependency(&parent, &child);
+
+        resource_manager.get_data(&mut backend, &parent).unwrap();
+        assert_eq!(resource_manager.memory_used(), 100);
+
+        resource_manager.reload(&parent);
+        assert!(!resource_manager.is_ready(&parent));
+
+        // old data is kept until frames in flight are done
+        resource_manager.get_data(&mut backend, &parent).unwrap();
+        assert_eq!(resource_manager.memory_used(), 200);
+        assert_eq!(resource_manager.values::<Dummy>().count(), 3);
+
+        for _ in 0..3 {
+            resource_manager.update::<Dummy>(&mut backend);
+        }
+        assert_eq!(resource_manager.memory_used(), 100);
+        assert_eq!(resource_manager.values::<Dummy>().count(), 2);
+        assert!(resource_manager.is_ready(&parent));
+        assert_eq!(resource_manager.ref_count(&child), 1);
+    }
 }
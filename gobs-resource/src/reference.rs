@@ -0,0 +1,48 @@
+use std::{
+    any::TypeId,
+    fmt::Debug,
+    sync::{Arc, mpsc::Sender},
+};
+
+use crate::manager::ResourceId;
+
+pub(crate) type ResourceKey = (TypeId, ResourceId);
+
+pub(crate) struct RefTracker {
+    key: ResourceKey,
+    released: Sender<ResourceKey>,
+}
+
+impl RefTracker {
+    pub(crate) fn new(key: ResourceKey, released: Sender<ResourceKey>) -> Self {
+        Self { key, released }
+    }
+}
+
+impl Drop for RefTracker {
+    fn drop(&mut self) {
+        // registry may be gone already
+        let _ = self.released.send(self.key);
+    }
+}
+
+/// Strong reference keeping a [ResourceLifetime::RefCounted](crate::ResourceLifetime::RefCounted)
+/// resource alive. The resource is scheduled for removal once the last reference is dropped.
+#[derive(Clone)]
+pub struct ResourceRef {
+    pub(crate) tracker: Arc<RefTracker>,
+}
+
+impl ResourceRef {
+    pub fn id(&self) -> ResourceId {
+        self.tracker.key.1
+    }
+}
+
+impl Debug for ResourceRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceRef")
+            .field("id", &self.tracker.key.1)
+            .finish()
+    }
+}
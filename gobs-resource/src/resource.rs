@@ -1,4 +1,4 @@
-use std::{fmt::Debug, marker::PhantomData};
+use std::{any::Any, fmt::Debug, marker::PhantomData};
 
 use serde::Serialize;
 use thiserror::Error;
@@ -8,19 +8,28 @@ use gobs_core::memory::allocator::AllocationError;
 use crate::{
     load::LoadingError,
     manager::{ResourceId, ResourceRegistry},
+    worker::LoadTask,
 };
 
-#[allow(unused)]
+/// Source data decoded off-thread by [ResourceLoader::prefetch]
+pub type PrefetchData = Box<dyn Any + Send>;
+pub type PrefetchJob = Box<dyn FnOnce() -> Result<PrefetchData, ResourceError> + Send>;
+
 pub enum ResourceState<R: ResourceType> {
     Unloaded,
-    Loading,
+    Loading(LoadTask<Result<PrefetchData, ResourceError>>),
     Loaded(R::ResourceData),
+    /// Background loading failed, the resource is not requested again until reloaded
+    Failed,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ResourceLifetime {
     Static,
     Transient,
+    /// Kept alive while a [ResourceRef](crate::ResourceRef) exists. Resources that are
+    /// not acquired within a frame of their creation are released
+    RefCounted,
 }
 
 #[derive(Hash, Eq, PartialEq, Serialize)]
@@ -67,6 +76,8 @@ pub struct Resource<R: ResourceType> {
     pub(crate) data: ResourceState<R>,
     pub lifetime: ResourceLifetime,
     pub life: usize,
+    pub(crate) last_used: usize,
+    pub(crate) size: usize,
 }
 
 impl<R: ResourceType> Resource<R> {
@@ -81,12 +92,27 @@ impl<R: ResourceType> Resource<R> {
             data: ResourceState::Unloaded,
             lifetime,
             life: 0,
+            last_used: 0,
+            size: 0,
         }
     }
 
-    pub(crate) fn is_loaded(&self) -> bool {
+    pub fn is_loaded(&self) -> bool {
         matches!(self.data, ResourceState::Loaded(_))
     }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self.data, ResourceState::Loading(_))
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self.data, ResourceState::Failed)
+    }
+
+    /// Memory used by the loaded resource data, as reported by [ResourceLoader::size]
+    pub fn size(&self) -> usize {
+        self.size
+    }
 }
 
 #[derive(Error, Debug)]
@@ -97,6 +123,10 @@ pub enum ResourceError {
     AllocationError(#[from] AllocationError),
     #[error("invalid data")]
     InvalidData,
+    #[error("invalid resource handle")]
+    InvalidHandle,
+    #[error("resource failed to load")]
+    LoadFailed,
 }
 
 pub trait ResourceProperties {
@@ -111,7 +141,30 @@ pub trait ResourceLoader<R: ResourceType> {
         resource_registry: &mut ResourceRegistry,
     ) -> Result<R::ResourceData, ResourceError>;
 
+    /// Returns a job decoding the resource source data (files, images...) on a worker thread.
+    /// Resources without a prefetch step are loaded synchronously.
+    fn prefetch(&self, _properties: &R::ResourceProperties) -> Option<PrefetchJob> {
+        None
+    }
+
+    /// Finish loading a resource from the data produced by [ResourceLoader::prefetch]
+    fn load_prefetched<'a>(
+        &mut self,
+        backend: &mut R::ResourceBackend<'a>,
+        handle: &ResourceHandle<R>,
+        resource_registry: &mut ResourceRegistry,
+        _data: PrefetchData,
+    ) -> Result<R::ResourceData, ResourceError> {
+        self.load(backend, handle, resource_registry)
+    }
+
+    /// Memory used by the resource data, used to enforce the memory budget
+    fn size(&self, _properties: &R::ResourceProperties, _data: &R::ResourceData) -> usize {
+        0
+    }
+
     fn unload<'a>(&mut self, backend: &mut R::ResourceBackend<'a>, data: R::ResourceData);
 
-    fn flush(&mut self);
+    /// Submit the pending loading work
+    fn flush<'a>(&mut self, backend: &mut R::ResourceBackend<'a>);
 }
@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+use notify::{Config, Event, EventKind, PollWatcher, RecursiveMode, Watcher, event::ModifyKind};
+
+use gobs_core::logger;
+
+use crate::load::{self, AssetType, LoadingError};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Editors often write a file in several steps: wait for the file to settle before reporting it
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A file modified in one of the watched asset directories
+#[derive(Clone, Debug)]
+pub struct AssetChange {
+    pub ty: AssetType,
+    /// Path relative to the asset directory, as passed to the [load] functions
+    pub file_name: String,
+    pub path: PathBuf,
+}
+
+/// Watch asset directories for modified files. Native file system notifications are used
+/// when available, with a fallback on polling.
+pub struct AssetWatcher {
+    _watcher: Box<dyn Watcher + Send>,
+    receiver: Receiver<notify::Result<Event>>,
+    dirs: Vec<(PathBuf, AssetType)>,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl AssetWatcher {
+    /// Watch the `resources`, `shaders` and `assets` directories of all asset roots mounted
+    /// from disk
+    pub fn new() -> Result<Self, LoadingError> {
+        let dirs = [
+            AssetType::RESOURCES,
+            AssetType::SHADER,
+            AssetType::IMAGE,
+            AssetType::MODEL,
+            AssetType::DATA,
+        ]
+        .into_iter()
+        .flat_map(|ty| load::asset_dirs(ty).into_iter().map(move |dir| (dir, ty)))
+        .collect();
+
+        Self::with_dirs(dirs, None)
+    }
+
+    /// Watch a custom set of directories. Polling is forced when `poll_interval` is set.
+    pub fn with_dirs(
+        dirs: Vec<(PathBuf, AssetType)>,
+        poll_interval: Option<Duration>,
+    ) -> Result<Self, LoadingError> {
+        let dirs = dirs
+            .into_iter()
+            .filter(|(dir, _)| {
+                let exists = dir.is_dir();
+                if !exists {
+                    tracing::debug!(target: logger::RESOURCES, "Skip missing asset directory: {:?}", dir);
+                }
+                exists
+            })
+            .collect::<Vec<_>>();
+
+        let (sender, receiver) = mpsc::channel();
+
+        let watcher: Box<dyn Watcher + Send> = match poll_interval {
+            Some(interval) => Box::new(Self::poll_watcher(sender, &dirs, interval)?),
+            None => match Self::native_watcher(sender.clone(), &dirs) {
+                Ok(watcher) => Box::new(watcher),
+                Err(e) => {
+                    tracing::warn!(target: logger::RESOURCES, "File notifications not available ({}), fall back to polling", e);
+                    Box::new(Self::poll_watcher(sender, &dirs, POLL_INTERVAL)?)
+                }
+            },
+        };
+
+        tracing::info!(target: logger::RESOURCES, "Watching asset directories: {:?}", dirs);
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+            dirs,
+            pending: HashMap::new(),
+        })
+    }
+
+    fn native_watcher(
+        sender: mpsc::Sender<notify::Result<Event>>,
+        dirs: &[(PathBuf, AssetType)],
+    ) -> notify::Result<notify::RecommendedWatcher> {
+        let mut watcher = notify::recommended_watcher(sender)?;
+
+        for (dir, _) in dirs {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+
+        Ok(watcher)
+    }
+
+    fn poll_watcher(
+        sender: mpsc::Sender<notify::Result<Event>>,
+        dirs: &[(PathBuf, AssetType)],
+        interval: Duration,
+    ) -> notify::Result<PollWatcher> {
+        let mut watcher = PollWatcher::new(sender, Config::default().with_poll_interval(interval))?;
+
+        for (dir, _) in dirs {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+
+        Ok(watcher)
+    }
+
+    /// Files modified since the last call. Each file is reported once, after it stopped changing.
+    pub fn poll_changes(&mut self) -> Vec<AssetChange> {
+        let now = Instant::now();
+
+        for event in self.receiver.try_iter() {
+            match event {
+                Ok(event) => {
+                    if matches!(
+                        event.kind,
+                        EventKind::Create(_)
+                            | EventKind::Modify(ModifyKind::Data(_))
+                            | EventKind::Modify(ModifyKind::Name(_))
+                            | EventKind::Modify(ModifyKind::Any)
+                    ) {
+                        for path in event.paths {
+                            self.pending.insert(path, now);
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(target: logger::RESOURCES, "File watcher error: {}", e);
+                }
+            }
+        }
+
+        let mut ready = self
+            .pending
+            .iter()
+            .filter(|(_, time)| now.duration_since(**time) >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        ready.sort();
+
+        ready
+            .into_iter()
+            .filter_map(|path| {
+                self.pending.remove(&path);
+
+                // removed after a rename, or directory
+                if !path.is_file() {
+                    return None;
+                }
+
+                let change = self.asset_change(&path);
+                if change.is_none() {
+                    tracing::trace!(target: logger::RESOURCES, "Ignore change outside of asset directories: {:?}", path);
+                }
+                change
+            })
+            .collect()
+    }
+
+    fn asset_change(&self, path: &Path) -> Option<AssetChange> {
+        self.dirs.iter().find_map(|(dir, ty)| {
+            let relative = path.strip_prefix(dir).ok()?;
+
+            let file_name = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            tracing::debug!(target: logger::RESOURCES, "Asset modified: {:?}", path);
+
+            Some(AssetChange {
+                ty: *ty,
+                file_name,
+                path: path.to_path_buf(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+        thread,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::{
+        load::AssetType,
+        watcher::{AssetChange, AssetWatcher},
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("gobs-{}-{}", name, nanos));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+
+        dir
+    }
+
+    fn wait_change(watcher: &mut AssetWatcher) -> Vec<AssetChange> {
+        for _ in 0..100 {
+            let changes = watcher.poll_changes();
+            if !changes.is_empty() {
+                return changes;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        vec![]
+    }
+
+    fn check_watcher(dir: &Path, mut watcher: AssetWatcher) {
+        fs::write(dir.join("sub/material.ron"), "()").unwrap();
+
+        let changes = wait_change(&mut watcher);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_name, "sub/material.ron");
+        assert_eq!(changes[0].ty, AssetType::RESOURCES);
+
+        assert!(watcher.poll_changes().is_empty());
+    }
+
+    #[test]
+    fn test_watch() {
+        setup();
+
+        let dir = temp_dir("watch");
+        let watcher =
+            AssetWatcher::with_dirs(vec![(dir.clone(), AssetType::RESOURCES)], None).unwrap();
+
+        check_watcher(&dir, watcher);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_watch_polling() {
+        setup();
+
+        let dir = temp_dir("poll");
+        let watcher = AssetWatcher::with_dirs(
+            vec![(dir.clone(), AssetType::RESOURCES)],
+            Some(Duration::from_millis(50)),
+        )
+        .unwrap();
+
+        check_watcher(&dir, watcher);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, Mutex, OnceLock,
+        mpsc::{self, Receiver, Sender},
+    },
+    task::{Context, Poll},
+    thread::{self, JoinHandle},
+};
+
+use futures::{FutureExt as _, channel::oneshot, executor::block_on};
+
+use gobs_core::logger;
+
+use crate::load::LoadingError;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+static POOL: OnceLock<WorkerPool> = OnceLock::new();
+
+/// Shared worker pool used for background asset decoding
+pub fn pool() -> &'static WorkerPool {
+    POOL.get_or_init(|| {
+        let size = thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1))
+            .unwrap_or(1)
+            .max(1);
+
+        WorkerPool::new(size)
+    })
+}
+
+pub struct WorkerPool {
+    sender: Mutex<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    pub fn new(size: usize) -> Self {
+        tracing::debug!(target: logger::RESOURCES, "Start loader pool with {} workers", size);
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..size)
+            .map(|i| {
+                let receiver = receiver.clone();
+                thread::Builder::new()
+                    .name(format!("gobs-loader-{}", i))
+                    .spawn(move || Self::run(receiver))
+                    .expect("Failed to spawn loader thread")
+            })
+            .collect();
+
+        Self {
+            sender: Mutex::new(sender),
+            workers,
+        }
+    }
+
+    fn run(receiver: Arc<Mutex<Receiver<Job>>>) {
+        loop {
+            let job = receiver.lock().unwrap().recv();
+
+            match job {
+                Ok(job) => job(),
+                Err(_) => break,
+            }
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run `f` on a worker thread. The result is retrieved through the returned [LoadTask]
+    pub fn spawn<T, F>(&self, f: F) -> LoadTask<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        let job: Job = Box::new(move || {
+            // receiver may have been dropped if the resource was released meanwhile
+            let _ = tx.send(f());
+        });
+
+        if self.sender.lock().unwrap().send(job).is_err() {
+            tracing::error!(target: logger::RESOURCES, "Loader pool is stopped");
+        }
+
+        LoadTask { receiver: rx }
+    }
+}
+
+/// Result of a job running on the [WorkerPool]
+pub struct LoadTask<T> {
+    receiver: oneshot::Receiver<T>,
+}
+
+impl<T> LoadTask<T> {
+    /// Non-blocking check for completion. Returns `None` while the job is still running
+    pub fn try_take(&mut self) -> Option<Result<T, LoadingError>> {
+        match self.receiver.try_recv() {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => None,
+            Err(_) => Some(Err(LoadingError::LoadCancelled)),
+        }
+    }
+
+    /// Block until the job completes
+    pub fn wait(self) -> Result<T, LoadingError> {
+        block_on(self)
+    }
+}
+
+impl<T> Future for LoadTask<T> {
+    type Output = Result<T, LoadingError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.receiver
+            .poll_unpin(cx)
+            .map(|res| res.map_err(|_| LoadingError::LoadCancelled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, thread, time::Duration};
+
+    use crate::worker::WorkerPool;
+
+    #[test]
+    fn test_spawn() {
+        let pool = WorkerPool::new(2);
+
+        let tasks = (0..8)
+            .map(|i| pool.spawn(move || i * 2))
+            .collect::<Vec<_>>();
+
+        let results = tasks
+            .into_iter()
+            .map(|t| t.wait().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(results, (0..8).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_try_take() {
+        let pool = WorkerPool::new(1);
+
+        let (tx, rx) = mpsc::channel::<()>();
+
+        let mut task = pool.spawn(move || {
+            rx.recv().unwrap();
+            42
+        });
+
+        assert!(task.try_take().is_none());
+
+        tx.send(()).unwrap();
+
+        let mut result = None;
+        for _ in 0..100 {
+            result = task.try_take();
+            if result.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(result.unwrap().unwrap(), 42);
+    }
+}
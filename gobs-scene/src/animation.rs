@@ -0,0 +1,366 @@
+use std::ops::{Add, Mul};
+
+use glam::{Quat, Vec3};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    Step,
+    #[default]
+    Linear,
+    /// Hermite spline, each keyframe stores an in-tangent, a value and an out-tangent
+    CubicSpline,
+}
+
+#[derive(Clone, Debug)]
+pub enum Keyframes {
+    Translation(Vec<Vec3>),
+    Rotation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+    /// Morph target weights, the weights of all targets are stored together
+    /// for each keyframe
+    Weights(Vec<f32>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackValue {
+    Translation(Vec3),
+    Rotation(Quat),
+    Scale(Vec3),
+    Weights(Vec<f32>),
+}
+
+/// Keyframes of one transform component of a node
+#[derive(Clone, Debug)]
+pub struct Track {
+    /// Index of the target node in the targets of the animation component
+    pub target: usize,
+    pub interpolation: Interpolation,
+    /// Keyframe times in seconds, in increasing order
+    pub times: Vec<f32>,
+    pub keyframes: Keyframes,
+}
+
+impl Track {
+    pub fn new(
+        target: usize,
+        interpolation: Interpolation,
+        times: Vec<f32>,
+        keyframes: Keyframes,
+    ) -> Self {
+        Self {
+            target,
+            interpolation,
+            times,
+            keyframes,
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.times.last().copied().unwrap_or_default()
+    }
+
+    /// Value at the given time, clamped to the first and last keyframes. Returns
+    /// `None` if the track has no keyframe or not enough values
+    pub fn sample(&self, time: f32) -> Option<TrackValue> {
+        match &self.keyframes {
+            Keyframes::Translation(values) => {
+                sample(&self.times, values, self.interpolation, time).map(TrackValue::Translation)
+            }
+            Keyframes::Rotation(values) => {
+                sample(&self.times, values, self.interpolation, time).map(TrackValue::Rotation)
+            }
+            Keyframes::Scale(values) => {
+                sample(&self.times, values, self.interpolation, time).map(TrackValue::Scale)
+            }
+            Keyframes::Weights(values) => {
+                let stride = match self.interpolation {
+                    Interpolation::CubicSpline => 3,
+                    _ => 1,
+                };
+                let count = values
+                    .len()
+                    .checked_div(stride * self.times.len())
+                    .filter(|&count| count > 0)?;
+
+                (0..count)
+                    .map(|target| {
+                        let values = values
+                            .iter()
+                            .skip(target)
+                            .step_by(count)
+                            .copied()
+                            .collect::<Vec<_>>();
+
+                        sample(&self.times, &values, self.interpolation, time)
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(TrackValue::Weights)
+            }
+        }
+    }
+}
+
+/// Named set of tracks played together
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub name: String,
+    pub tracks: Vec<Track>,
+    /// End of the longest track
+    pub duration: f32,
+}
+
+impl AnimationClip {
+    pub fn new(name: &str, tracks: Vec<Track>) -> Self {
+        let duration = tracks.iter().map(Track::duration).fold(0., f32::max);
+
+        Self {
+            name: name.to_string(),
+            tracks,
+            duration,
+        }
+    }
+}
+
+trait Interpolate: Copy + Add<Output = Self> + Mul<f32, Output = Self> {
+    fn interpolate(self, other: Self, t: f32) -> Self;
+
+    fn normalized(self) -> Self {
+        self
+    }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec3 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Quat {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+
+    fn normalized(self) -> Self {
+        self.normalize()
+    }
+}
+
+fn sample<T: Interpolate>(
+    times: &[f32],
+    values: &[T],
+    interpolation: Interpolation,
+    time: f32,
+) -> Option<T> {
+    let (stride, offset) = match interpolation {
+        Interpolation::CubicSpline => (3, 1),
+        _ => (1, 0),
+    };
+
+    if times.is_empty() || values.len() < stride * times.len() {
+        return None;
+    }
+
+    let value = |i: usize| values[stride * i + offset];
+
+    let last = times.len() - 1;
+    if time <= times[0] {
+        return Some(value(0));
+    }
+    if time >= times[last] {
+        return Some(value(last));
+    }
+
+    let next = times.partition_point(|&t| t <= time);
+    let prev = next - 1;
+    let dt = times[next] - times[prev];
+    let s = if dt > 0. {
+        (time - times[prev]) / dt
+    } else {
+        0.
+    };
+
+    match interpolation {
+        Interpolation::Step => Some(value(prev)),
+        Interpolation::Linear => Some(value(prev).interpolate(value(next), s)),
+        Interpolation::CubicSpline => {
+            let out_tangent = values[3 * prev + 2] * dt;
+            let in_tangent = values[3 * next] * dt;
+            let (s2, s3) = (s * s, s * s * s);
+
+            let value = value(prev) * (2. * s3 - 3. * s2 + 1.)
+                + out_tangent * (s3 - 2. * s2 + s)
+                + value(next) * (3. * s2 - 2. * s3)
+                + in_tangent * (s3 - s2);
+
+            Some(value.normalized())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::animation::{AnimationClip, Interpolation, Keyframes, Track, TrackValue};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn translation(interpolation: Interpolation, values: Vec<Vec3>) -> Track {
+        Track::new(
+            0,
+            interpolation,
+            vec![1., 3.],
+            Keyframes::Translation(values),
+        )
+    }
+
+    #[test]
+    fn test_step() {
+        setup();
+
+        let track = translation(Interpolation::Step, vec![Vec3::ZERO, Vec3::X]);
+
+        assert_eq!(track.sample(0.), Some(TrackValue::Translation(Vec3::ZERO)));
+        assert_eq!(track.sample(2.9), Some(TrackValue::Translation(Vec3::ZERO)));
+        assert_eq!(track.sample(3.), Some(TrackValue::Translation(Vec3::X)));
+        assert_eq!(track.sample(5.), Some(TrackValue::Translation(Vec3::X)));
+    }
+
+    #[test]
+    fn test_linear() {
+        setup();
+
+        let track = translation(Interpolation::Linear, vec![Vec3::ZERO, Vec3::X]);
+        assert_eq!(track.duration(), 3.);
+        assert_eq!(
+            track.sample(2.),
+            Some(TrackValue::Translation(0.5 * Vec3::X))
+        );
+
+        let track = Track::new(
+            0,
+            Interpolation::Linear,
+            vec![0., 1.],
+            Keyframes::Rotation(vec![Quat::IDENTITY, Quat::from_rotation_z(1.)]),
+        );
+        let Some(TrackValue::Rotation(rotation)) = track.sample(0.5) else {
+            panic!("invalid sample");
+        };
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_z(0.5), 1e-5));
+
+        // missing values
+        let track = translation(Interpolation::Linear, vec![Vec3::ZERO]);
+        assert_eq!(track.sample(2.), None);
+    }
+
+    #[test]
+    fn test_cubic_spline() {
+        setup();
+
+        // flat tangents
+        let track = translation(
+            Interpolation::CubicSpline,
+            vec![
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::X,
+                Vec3::ZERO,
+            ],
+        );
+        assert_eq!(track.sample(1.), Some(TrackValue::Translation(Vec3::ZERO)));
+        assert_eq!(
+            track.sample(2.),
+            Some(TrackValue::Translation(0.5 * Vec3::X))
+        );
+        assert_eq!(track.sample(3.), Some(TrackValue::Translation(Vec3::X)));
+
+        // tangents of a straight line give a linear motion
+        let slope = 0.5 * Vec3::X;
+        let track = translation(
+            Interpolation::CubicSpline,
+            vec![slope, Vec3::ZERO, slope, slope, Vec3::X, slope],
+        );
+        let Some(TrackValue::Translation(value)) = track.sample(1.5) else {
+            panic!("invalid sample");
+        };
+        assert!(value.abs_diff_eq(0.25 * Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn test_weights() {
+        setup();
+
+        // 2 targets
+        let track = Track::new(
+            0,
+            Interpolation::Linear,
+            vec![0., 1.],
+            Keyframes::Weights(vec![0., 1., 1., 0.]),
+        );
+        assert_eq!(
+            track.sample(0.25),
+            Some(TrackValue::Weights(vec![0.25, 0.75]))
+        );
+
+        let track = Track::new(
+            0,
+            Interpolation::Step,
+            vec![0., 1.],
+            Keyframes::Weights(vec![0., 1., 1., 0.]),
+        );
+        assert_eq!(track.sample(0.5), Some(TrackValue::Weights(vec![0., 1.])));
+
+        // flat tangents
+        let track = Track::new(
+            0,
+            Interpolation::CubicSpline,
+            vec![0., 1.],
+            Keyframes::Weights(vec![0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0.]),
+        );
+        assert_eq!(track.sample(0.5), Some(TrackValue::Weights(vec![0.5, 0.5])));
+
+        let track = Track::new(
+            0,
+            Interpolation::Linear,
+            vec![0., 1.],
+            Keyframes::Weights(vec![]),
+        );
+        assert_eq!(track.sample(0.5), None);
+    }
+
+    #[test]
+    fn test_clip() {
+        setup();
+
+        let clip = AnimationClip::new(
+            "clip",
+            vec![
+                translation(Interpolation::Linear, vec![Vec3::ZERO, Vec3::X]),
+                Track::new(
+                    1,
+                    Interpolation::Step,
+                    vec![0., 5.],
+                    Keyframes::Scale(vec![Vec3::ONE, Vec3::ONE]),
+                ),
+            ],
+        );
+
+        assert_eq!(clip.duration, 5.);
+    }
+}
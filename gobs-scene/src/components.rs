@@ -1,5 +1,11 @@
+mod animation;
 mod base;
 mod bounding;
+mod morph;
+mod skin;
 
+pub use animation::{AnimationComponent, AnimationLayer, AnimationPose};
 pub use base::{BaseComponent, NodeId, NodeValue};
 pub use bounding::BoundingComponent;
+pub use morph::MorphComponent;
+pub use skin::SkinComponent;
@@ -0,0 +1,415 @@
+use std::{collections::HashMap, sync::Arc};
+
+use glam::{Quat, Vec3};
+
+use gobs_core::Transform;
+
+use crate::{
+    animation::{AnimationClip, TrackValue},
+    components::NodeId,
+};
+
+/// Clip being played with its own time, speed and blend weight
+#[derive(Clone, Debug)]
+pub struct AnimationLayer {
+    pub clip: Arc<AnimationClip>,
+    /// Position in the clip, in seconds
+    pub time: f32,
+    pub speed: f32,
+    pub looping: bool,
+    pub weight: f32,
+    target_weight: f32,
+    /// Weight change per second, 0 when not fading
+    fade_rate: f32,
+}
+
+impl AnimationLayer {
+    fn new(clip: Arc<AnimationClip>, looping: bool, weight: f32) -> Self {
+        Self {
+            clip,
+            time: 0.,
+            speed: 1.,
+            looping,
+            weight,
+            target_weight: weight,
+            fade_rate: 0.,
+        }
+    }
+
+    fn fade_to(&mut self, weight: f32, duration: f32) {
+        self.target_weight = weight;
+        self.fade_rate = (weight - self.weight).abs() / duration;
+    }
+
+    /// Non looping clip that reached its end
+    pub fn is_finished(&self) -> bool {
+        !self.looping
+            && ((self.speed >= 0. && self.time >= self.clip.duration)
+                || (self.speed < 0. && self.time <= 0.))
+    }
+
+    fn update(&mut self, delta: f32) {
+        let duration = self.clip.duration;
+
+        self.time += delta * self.speed;
+        if self.looping && duration > 0. {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            self.time = self.time.clamp(0., duration);
+        }
+
+        if self.fade_rate > 0. {
+            let step = self.fade_rate * delta;
+            if (self.target_weight - self.weight).abs() <= step {
+                self.weight = self.target_weight;
+                self.fade_rate = 0.;
+            } else {
+                self.weight += step.copysign(self.target_weight - self.weight);
+            }
+        }
+    }
+
+    fn is_faded_out(&self) -> bool {
+        self.weight <= 0. && self.target_weight <= 0.
+    }
+}
+
+/// Blended transform components and morph weights of an animated node.
+/// Components that are not animated are left unchanged
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnimationPose {
+    pub translation: Option<Vec3>,
+    pub rotation: Option<Quat>,
+    pub scale: Option<Vec3>,
+    pub morph_weights: Option<Vec<f32>>,
+    totals: [f32; 4],
+}
+
+impl AnimationPose {
+    /// Running weighted average, weights are relative to each other
+    fn blend(&mut self, value: TrackValue, weight: f32) {
+        fn mix<T: Copy>(
+            current: &mut Option<T>,
+            total: &mut f32,
+            value: T,
+            weight: f32,
+            f: fn(T, T, f32) -> T,
+        ) {
+            *total += weight;
+            *current = Some(match *current {
+                Some(current) => f(current, value, weight / *total),
+                None => value,
+            });
+        }
+
+        if weight <= 0. {
+            return;
+        }
+
+        match value {
+            TrackValue::Translation(value) => mix(
+                &mut self.translation,
+                &mut self.totals[0],
+                value,
+                weight,
+                Vec3::lerp,
+            ),
+            TrackValue::Rotation(value) => mix(
+                &mut self.rotation,
+                &mut self.totals[1],
+                value,
+                weight,
+                Quat::slerp,
+            ),
+            TrackValue::Scale(value) => mix(
+                &mut self.scale,
+                &mut self.totals[2],
+                value,
+                weight,
+                Vec3::lerp,
+            ),
+            TrackValue::Weights(value) => {
+                self.totals[3] += weight;
+                let t = weight / self.totals[3];
+
+                self.morph_weights = Some(match self.morph_weights.take() {
+                    Some(current) => current
+                        .iter()
+                        .zip(&value)
+                        .map(|(current, value)| current + (value - current) * t)
+                        .collect(),
+                    None => value,
+                });
+            }
+        }
+    }
+
+    pub fn apply(&self, transform: &mut Transform) -> bool {
+        if self.translation.is_none() && self.rotation.is_none() && self.scale.is_none() {
+            return false;
+        }
+
+        *transform = Transform::new(
+            self.translation.unwrap_or(transform.translation()),
+            self.rotation.unwrap_or(transform.rotation()),
+            self.scale.unwrap_or(transform.scaling()),
+        );
+
+        true
+    }
+}
+
+/// Plays animation clips on a node and its descendants. Several clips can be
+/// blended together, each with its own weight.
+#[derive(Clone, Debug)]
+pub struct AnimationComponent {
+    pub clips: Vec<Arc<AnimationClip>>,
+    /// Nodes animated by the tracks of the clips, indexed by the target of the tracks
+    pub targets: Vec<NodeId>,
+    pub layers: Vec<AnimationLayer>,
+    pub paused: bool,
+    /// Applied on top of the speed of each layer
+    pub speed: f32,
+}
+
+impl Default for AnimationComponent {
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new())
+    }
+}
+
+impl AnimationComponent {
+    pub fn new(clips: Vec<Arc<AnimationClip>>, targets: Vec<NodeId>) -> Self {
+        Self {
+            clips,
+            targets,
+            layers: Vec::new(),
+            paused: false,
+            speed: 1.,
+        }
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&Arc<AnimationClip>> {
+        self.clips.iter().find(|clip| clip.name == name)
+    }
+
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut AnimationLayer> {
+        self.layers.iter_mut().find(|layer| layer.clip.name == name)
+    }
+
+    /// Stop all layers and play the clip from the start
+    pub fn play(&mut self, name: &str, looping: bool) -> bool {
+        let Some(clip) = self.clip(name).cloned() else {
+            return false;
+        };
+
+        self.layers = vec![AnimationLayer::new(clip, looping, 1.)];
+        self.paused = false;
+
+        true
+    }
+
+    /// Play the clip on top of the current layers, or change its weight if
+    /// it is already playing
+    pub fn blend(&mut self, name: &str, weight: f32, looping: bool) -> bool {
+        if let Some(layer) = self.layer_mut(name) {
+            layer.weight = weight;
+            layer.target_weight = weight;
+            layer.fade_rate = 0.;
+            layer.looping = looping;
+
+            return true;
+        }
+
+        let Some(clip) = self.clip(name).cloned() else {
+            return false;
+        };
+
+        self.layers.push(AnimationLayer::new(clip, looping, weight));
+
+        true
+    }
+
+    /// Fade the clip in and the other layers out over `duration` seconds
+    pub fn cross_fade(&mut self, name: &str, duration: f32, looping: bool) -> bool {
+        if duration <= 0. {
+            return self.play(name, looping);
+        }
+
+        if self.layer_mut(name).is_none() {
+            let Some(clip) = self.clip(name).cloned() else {
+                return false;
+            };
+            self.layers.push(AnimationLayer::new(clip, looping, 0.));
+        }
+
+        for layer in &mut self.layers {
+            if layer.clip.name == name {
+                layer.looping = looping;
+                layer.fade_to(1., duration);
+            } else {
+                layer.fade_to(0., duration);
+            }
+        }
+        self.paused = false;
+
+        true
+    }
+
+    pub fn stop(&mut self) {
+        self.layers.clear();
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.paused && self.layers.iter().any(|layer| !layer.is_finished())
+    }
+
+    /// Advance all layers. Returns true if the pose must be applied
+    pub fn update(&mut self, delta: f32) -> bool {
+        if self.paused || self.layers.is_empty() {
+            return false;
+        }
+
+        for layer in &mut self.layers {
+            layer.update(delta * self.speed);
+        }
+        self.layers.retain(|layer| !layer.is_faded_out());
+
+        !self.layers.is_empty()
+    }
+
+    /// Blended pose of each animated target node. Tracks without target node
+    /// are ignored
+    pub fn sample(&self) -> Vec<(NodeId, AnimationPose)> {
+        let mut poses: HashMap<NodeId, AnimationPose> = HashMap::new();
+
+        for layer in &self.layers {
+            for track in &layer.clip.tracks {
+                if let (Some(&target), Some(value)) =
+                    (self.targets.get(track.target), track.sample(layer.time))
+                {
+                    poses.entry(target).or_default().blend(value, layer.weight);
+                }
+            }
+        }
+
+        poses.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use glam::Vec3;
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::{
+        animation::{AnimationClip, Interpolation, Keyframes, Track},
+        components::{AnimationComponent, NodeId},
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn clip(name: &str, end: Vec3) -> Arc<AnimationClip> {
+        Arc::new(AnimationClip::new(
+            name,
+            vec![Track::new(
+                0,
+                Interpolation::Linear,
+                vec![0., 2.],
+                Keyframes::Translation(vec![Vec3::ZERO, end]),
+            )],
+        ))
+    }
+
+    fn translation(animation: &AnimationComponent) -> Vec3 {
+        animation.sample()[0].1.translation.unwrap()
+    }
+
+    #[test]
+    fn test_play() {
+        setup();
+
+        let mut animation =
+            AnimationComponent::new(vec![clip("walk", Vec3::X)], vec![NodeId::default()]);
+        assert!(!animation.play("run", true));
+        assert!(!animation.update(1.));
+
+        assert!(animation.play("walk", false));
+        assert!(animation.update(1.));
+        assert_eq!(translation(&animation), 0.5 * Vec3::X);
+
+        animation.pause();
+        assert!(!animation.update(1.));
+        assert!(!animation.is_playing());
+
+        animation.resume();
+        animation.speed = 2.;
+        assert!(animation.update(1.));
+        assert_eq!(translation(&animation), Vec3::X);
+        assert!(!animation.is_playing());
+
+        animation.speed = 1.;
+        animation.play("walk", true);
+        animation.update(2.5);
+        assert_eq!(translation(&animation), 0.25 * Vec3::X);
+        assert!(animation.is_playing());
+
+        animation.stop();
+        assert!(animation.sample().is_empty());
+    }
+
+    #[test]
+    fn test_blend() {
+        setup();
+
+        let mut animation = AnimationComponent::new(
+            vec![clip("walk", Vec3::X), clip("wave", Vec3::Y)],
+            vec![NodeId::default()],
+        );
+
+        animation.play("walk", true);
+        animation.blend("wave", 3., true);
+        animation.update(1.);
+        assert_eq!(translation(&animation), Vec3::new(0.125, 0.375, 0.));
+
+        animation.blend("wave", 0., true);
+        assert_eq!(translation(&animation), 0.5 * Vec3::X);
+    }
+
+    #[test]
+    fn test_cross_fade() {
+        setup();
+
+        let mut animation = AnimationComponent::new(
+            vec![clip("walk", Vec3::X), clip("wave", Vec3::Y)],
+            vec![NodeId::default()],
+        );
+
+        animation.play("walk", true);
+        assert!(animation.cross_fade("wave", 1., true));
+        animation.update(0.5);
+        assert_eq!(animation.layers.len(), 2);
+        assert_eq!(translation(&animation), Vec3::new(0.125, 0.125, 0.));
+
+        animation.update(0.5);
+        assert_eq!(animation.layers.len(), 1);
+        assert_eq!(animation.layers[0].clip.name, "wave");
+    }
+}
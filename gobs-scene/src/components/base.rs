@@ -24,11 +24,25 @@ impl Bounded for NodeValue {
     }
 }
 
+impl NodeValue {
+    /// Name given to new nodes
+    pub fn default_name(&self) -> &str {
+        match self {
+            NodeValue::None => "",
+            NodeValue::Model(model) => model.name(),
+            NodeValue::Camera(_) => "camera",
+            NodeValue::Light(_) => "light",
+        }
+    }
+}
+
 pub type NodeId = DefaultKey;
 
 #[derive(Clone)]
 pub struct BaseComponent {
     pub id: NodeId,
+    /// Used to find nodes by name or path. Names are not unique
+    pub name: String,
     pub value: NodeValue,
     pub enabled: bool,
     pub selected: bool,
@@ -47,6 +61,7 @@ impl BaseComponent {
     pub fn new(value: NodeValue, parent: Option<NodeId>) -> Self {
         Self {
             id: NodeId::null(),
+            name: value.default_name().to_string(),
             value,
             enabled: true,
             selected: false,
@@ -0,0 +1,11 @@
+/// Morph target weights of the model of a node, one per target
+#[derive(Clone, Debug, Default)]
+pub struct MorphComponent {
+    pub weights: Vec<f32>,
+}
+
+impl MorphComponent {
+    pub fn new(weights: Vec<f32>) -> Self {
+        Self { weights }
+    }
+}
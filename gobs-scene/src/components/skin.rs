@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use glam::Mat4;
+
+use gobs_core::Transform;
+use gobs_render::Skeleton;
+
+use crate::{components::NodeId, graph::scenegraph::SceneGraph};
+
+/// Deforms the model of a node with the transforms of the joint nodes
+#[derive(Clone, Debug)]
+pub struct SkinComponent {
+    pub skeleton: Arc<Skeleton>,
+    /// Joint nodes, in the order of the skeleton
+    pub joints: Vec<NodeId>,
+}
+
+impl SkinComponent {
+    pub fn new(skeleton: Arc<Skeleton>, joints: Vec<NodeId>) -> Self {
+        Self { skeleton, joints }
+    }
+
+    /// Joint palette of the skinned node. Removed joints keep the bind pose
+    pub fn joint_matrices(&self, graph: &SceneGraph, mesh: &Transform) -> Vec<Mat4> {
+        let joints = self
+            .joints
+            .iter()
+            .map(|&joint| graph.get(joint).map(|node| *node.global_transform()))
+            .collect::<Vec<_>>();
+
+        self.skeleton.joint_matrices(mesh, &joints)
+    }
+}
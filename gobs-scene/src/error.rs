@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+use gobs_resource::load::LoadingError;
+
+#[derive(Debug, Error)]
+pub enum SceneError {
+    #[error("cannot read scene")]
+    Load(#[from] LoadingError),
+    #[error("cannot write scene")]
+    Write(#[from] std::io::Error),
+    #[error("invalid scene data: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("cannot serialize scene: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("unsupported scene version: {0}")]
+    Version(u32),
+    #[error("invalid node: {0}")]
+    InvalidNode(usize),
+    #[error("model not found: {0}")]
+    ModelNotFound(String),
+}
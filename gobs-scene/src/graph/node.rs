@@ -1,12 +1,20 @@
+use slotmap::Key;
+
 use gobs_core::Transform;
 use gobs_render::{BoundingBox, RenderFlags, Renderable};
 
-use crate::components::{BaseComponent, BoundingComponent, NodeId, NodeValue};
+use crate::components::{
+    AnimationComponent, BaseComponent, BoundingComponent, MorphComponent, NodeId, NodeValue,
+    SkinComponent,
+};
 
 #[derive(Clone)]
 pub struct Node {
     pub base: BaseComponent,
     pub bounding: BoundingComponent,
+    pub animation: Option<AnimationComponent>,
+    pub skin: Option<SkinComponent>,
+    pub morph: Option<MorphComponent>,
     pub(crate) transform: Transform,
     pub(crate) global_transform: Transform,
 }
@@ -19,6 +27,9 @@ impl Default for Node {
         Self {
             base,
             bounding,
+            animation: None,
+            skin: None,
+            morph: None,
             transform: Transform::IDENTITY,
             global_transform: Transform::IDENTITY,
         }
@@ -38,6 +49,9 @@ impl Node {
         Self {
             base,
             bounding,
+            animation: None,
+            skin: None,
+            morph: None,
             transform,
             global_transform: parent_transform * transform,
         }
@@ -51,6 +65,18 @@ impl Node {
         &self.global_transform
     }
 
+    /// Id written to the picking attachment, with the arena slot in the low 20 bits
+    /// and the slot version in the high 12 bits so that a node reusing the slot of a
+    /// removed node gets another id. Slot 0 is never used by the arena so 0 means
+    /// no node
+    pub fn pick_id(&self) -> u32 {
+        let key = self.base.id.data().as_ffi();
+        let (slot, version) = (key as u32, (key >> 32) as u32);
+
+        // versions are odd for used slots and increase by 2 on reuse
+        (slot & 0xf_ffff) | ((version >> 1) << 20)
+    }
+
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
     pub fn update_transform<F>(&mut self, mut f: F)
     where
@@ -19,11 +19,14 @@ pub struct SceneGraph {
 }
 
 impl SceneGraph {
+    pub const ROOT_NAME: &'static str = "root";
+
     pub fn new() -> Self {
         let mut arena = SlotMap::new();
         let root_id = arena.insert(Node::default());
         if let Some(root) = arena.get_mut(root_id) {
             root.base.id = root_id;
+            root.base.name = Self::ROOT_NAME.to_string();
         }
 
         SceneGraph {
@@ -48,6 +51,18 @@ impl SceneGraph {
         self.arena.get_mut(key)
     }
 
+    /// Node drawn with the picking id
+    pub fn find_by_pick_id(&self, pick_id: u32) -> Option<NodeId> {
+        if pick_id == 0 {
+            return None;
+        }
+
+        self.arena
+            .iter()
+            .find(|(_, node)| node.pick_id() == pick_id)
+            .map(|(key, _)| key)
+    }
+
     pub fn toggle(&mut self, key: NodeId) {
         if let Some(node) = self.get_mut(key) {
             node.base.enabled = !node.base.enabled;
@@ -66,12 +81,71 @@ impl SceneGraph {
         }
     }
 
+    pub fn set_name(&mut self, key: NodeId, name: &str) {
+        if let Some(node) = self.get_mut(key) {
+            node.base.name = name.to_string();
+        }
+    }
+
     pub fn parent(&self, key: NodeId) -> Option<&Node> {
         self.get(key)
             .and_then(|node| node.base.parent)
             .and_then(|parent| self.get(parent))
     }
 
+    pub fn children(&self, key: NodeId) -> impl DoubleEndedIterator<Item = NodeId> + '_ {
+        self.get(key)
+            .into_iter()
+            .flat_map(|node| node.base.children.iter().copied())
+    }
+
+    /// All the nodes below the node, depth first
+    pub fn descendants(&self, key: NodeId) -> Descendants<'_> {
+        Descendants {
+            graph: self,
+            stack: self.children(key).rev().collect(),
+        }
+    }
+
+    /// Parents of the node, up to the root
+    pub fn ancestors(&self, key: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.get(key).and_then(|node| node.base.parent), |&parent| {
+            self.get(parent).and_then(|node| node.base.parent)
+        })
+    }
+
+    /// First node with this name, depth first
+    pub fn find_by_name(&self, name: &str) -> Option<NodeId> {
+        std::iter::once(self.root)
+            .chain(self.descendants(self.root))
+            .find(|&key| self.get(key).is_some_and(|node| node.base.name == name))
+    }
+
+    /// Node from the names of the nodes leading to it, starting with the root
+    /// ("root/car/wheel_fl")
+    pub fn find_by_path(&self, path: &str) -> Option<NodeId> {
+        let mut names = path.split('/');
+
+        if names.next()? != self.get(self.root)?.base.name {
+            return None;
+        }
+
+        names.try_fold(self.root, |key, name| {
+            self.children(key)
+                .find(|&child| self.get(child).is_some_and(|node| node.base.name == name))
+        })
+    }
+
+    /// Path of the node, as used by [SceneGraph::find_by_path]
+    pub fn path(&self, key: NodeId) -> Option<String> {
+        let names = std::iter::once(key)
+            .chain(self.ancestors(key))
+            .map(|key| self.get(key).map(|node| node.base.name.as_str()))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(names.into_iter().rev().collect::<Vec<_>>().join("/"))
+    }
+
     pub fn update<F>(&mut self, key: NodeId, mut f: F)
     where
         F: FnMut(&mut Node) -> bool,
@@ -81,6 +155,37 @@ impl SceneGraph {
         }
     }
 
+    /// Advance the animation players and apply their pose to their target nodes.
+    /// Players on disabled nodes are not updated, removed targets are skipped
+    pub(crate) fn update_animations(&mut self, delta: f32) {
+        let keys: Vec<NodeId> = self
+            .arena
+            .iter()
+            .filter(|(_, node)| node.base.enabled && node.animation.is_some())
+            .map(|(key, _)| key)
+            .collect();
+
+        for key in keys {
+            let Some(poses) = self
+                .get_mut(key)
+                .and_then(|node| node.animation.as_mut())
+                .and_then(|animation| animation.update(delta).then(|| animation.sample()))
+            else {
+                continue;
+            };
+
+            for (target, pose) in poses {
+                self.update(target, |node| {
+                    if let (Some(morph), Some(weights)) = (&mut node.morph, &pose.morph_weights) {
+                        morph.weights.clone_from(weights);
+                    }
+
+                    pose.apply(&mut node.transform)
+                });
+            }
+        }
+    }
+
     pub(crate) fn update_nodes(&mut self) {
         self.update_node(self.root, Transform::IDENTITY, false);
     }
@@ -121,8 +226,70 @@ impl SceneGraph {
         }
     }
 
+    /// Remove the node and its descendants
     pub fn remove(&mut self, key: NodeId) -> Option<Node> {
-        self.arena.remove(key)
+        self.remove_subtree(key).into_iter().next()
+    }
+
+    /// Remove the node and its descendants, parents first. The root cannot be
+    /// removed
+    pub fn remove_subtree(&mut self, key: NodeId) -> Vec<Node> {
+        if key == self.root || !self.arena.contains_key(key) {
+            return Vec::new();
+        }
+
+        if let Some(parent) = self.get(key).and_then(|node| node.base.parent)
+            && let Some(parent) = self.get_mut(parent)
+        {
+            parent.base.children.retain(|&child| child != key);
+            parent.base.updated = true;
+        }
+
+        let keys: Vec<NodeId> = std::iter::once(key).chain(self.descendants(key)).collect();
+
+        keys.into_iter()
+            .filter_map(|key| self.arena.remove(key))
+            .collect()
+    }
+
+    /// Move the node to a new parent. If `keep_world` is set, the local transform
+    /// is updated so that the node does not move. A node cannot be moved below
+    /// itself
+    pub fn reparent(&mut self, key: NodeId, parent: NodeId, keep_world: bool) -> bool {
+        if key == self.root
+            || key == parent
+            || !self.arena.contains_key(key)
+            || self.ancestors(parent).any(|ancestor| ancestor == key)
+        {
+            return false;
+        }
+
+        let Some(parent_transform) = self.get(parent).map(|node| node.global_transform) else {
+            return false;
+        };
+
+        if let Some(old_parent) = self.get(key).and_then(|node| node.base.parent)
+            && let Some(old_parent) = self.get_mut(old_parent)
+        {
+            old_parent.base.children.retain(|&child| child != key);
+            old_parent.base.updated = true;
+        }
+
+        if let Some(parent_node) = self.get_mut(parent) {
+            parent_node.base.children.push(key);
+            parent_node.base.updated = true;
+        }
+
+        if let Some(node) = self.get_mut(key) {
+            node.base.parent = Some(parent);
+            if keep_world {
+                node.transform = parent_transform.inverse() * node.global_transform;
+            }
+            node.global_transform = parent_transform * node.transform;
+            node.base.updated = true;
+        }
+
+        true
     }
 
     pub fn set_root(&mut self, value: NodeValue, transform: Transform) -> NodeId {
@@ -133,6 +300,7 @@ impl SceneGraph {
 
         if let Some(root) = self.get_mut(root_id) {
             root.base.id = root_id;
+            root.base.name = Self::ROOT_NAME.to_string();
         }
 
         self.root
@@ -170,6 +338,37 @@ impl SceneGraph {
         local_root: NodeId,
         target_root: NodeId,
         subgraph: &SceneGraph,
+    ) -> Option<NodeId> {
+        let mut copies = HashMap::new();
+        let node = self.copy_subgraph(local_root, target_root, subgraph, &mut copies)?;
+
+        // skins and animations point to the copied joints and targets
+        for &copy in copies.values() {
+            let Some(node) = self.get_mut(copy) else {
+                continue;
+            };
+
+            let joints = node.skin.iter_mut().flat_map(|skin| skin.joints.iter_mut());
+            let targets = node
+                .animation
+                .iter_mut()
+                .flat_map(|animation| animation.targets.iter_mut());
+            for key in joints.chain(targets) {
+                if let Some(&copied) = copies.get(key) {
+                    *key = copied;
+                }
+            }
+        }
+
+        Some(node)
+    }
+
+    fn copy_subgraph(
+        &mut self,
+        local_root: NodeId,
+        target_root: NodeId,
+        subgraph: &SceneGraph,
+        copies: &mut HashMap<NodeId, NodeId>,
     ) -> Option<NodeId> {
         if let Some(target_node) = subgraph.get(target_root) {
             let node = self.insert(
@@ -178,8 +377,16 @@ impl SceneGraph {
                 target_node.transform,
             );
             if let Some(node) = node {
+                copies.insert(target_root, node);
+                self.set_name(node, &target_node.base.name);
+                if let Some(node) = self.get_mut(node) {
+                    node.animation.clone_from(&target_node.animation);
+                    node.skin.clone_from(&target_node.skin);
+                    node.morph.clone_from(&target_node.morph);
+                }
+
                 for &child in &target_node.base.children {
-                    self.insert_subgraph(node, child, subgraph);
+                    self.copy_subgraph(node, child, subgraph, copies);
                 }
 
                 return Some(node);
@@ -265,3 +472,388 @@ impl Default for SceneGraph {
         Self::new()
     }
 }
+
+/// Depth first iterator over the descendants of a node
+pub struct Descendants<'a> {
+    graph: &'a SceneGraph,
+    stack: Vec<NodeId>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.stack.pop()?;
+
+        self.stack.extend(self.graph.children(key).rev());
+
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use glam::{Mat4, Quat, Vec3};
+    use slotmap::Key;
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_core::Transform;
+    use gobs_render::Skeleton;
+
+    use crate::{
+        animation::{AnimationClip, Interpolation, Keyframes, Track},
+        components::{AnimationComponent, MorphComponent, NodeId, NodeValue, SkinComponent},
+        graph::scenegraph::SceneGraph,
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn insert(graph: &mut SceneGraph, parent: NodeId, name: &str, transform: Transform) -> NodeId {
+        let key = graph.insert(parent, NodeValue::None, transform).unwrap();
+        graph.set_name(key, name);
+
+        key
+    }
+
+    /// root
+    ///   car
+    ///     body
+    ///     wheel_fl
+    ///   tree
+    fn build_graph() -> (SceneGraph, [NodeId; 4]) {
+        let mut graph = SceneGraph::new();
+        let root = graph.root;
+
+        let car = insert(
+            &mut graph,
+            root,
+            "car",
+            Transform::from_translation(Vec3::new(10., 0., 0.)),
+        );
+        let body = insert(&mut graph, car, "body", Transform::IDENTITY);
+        let wheel = insert(
+            &mut graph,
+            car,
+            "wheel_fl",
+            Transform::from_translation(Vec3::new(1., 0., 1.)),
+        );
+        let tree = insert(
+            &mut graph,
+            root,
+            "tree",
+            Transform::new(
+                Vec3::new(0., 0., -5.),
+                Quat::from_rotation_y(1.),
+                Vec3::splat(2.),
+            ),
+        );
+
+        graph.update_nodes();
+
+        (graph, [car, body, wheel, tree])
+    }
+
+    #[test]
+    fn test_iterators() {
+        setup();
+
+        let (graph, [car, body, wheel, tree]) = build_graph();
+
+        assert_eq!(graph.children(graph.root).collect::<Vec<_>>(), [car, tree]);
+        assert_eq!(
+            graph.descendants(graph.root).collect::<Vec<_>>(),
+            [car, body, wheel, tree]
+        );
+        assert_eq!(graph.descendants(wheel).count(), 0);
+        assert_eq!(
+            graph.ancestors(wheel).collect::<Vec<_>>(),
+            [car, graph.root]
+        );
+        assert_eq!(graph.ancestors(graph.root).count(), 0);
+    }
+
+    #[test]
+    fn test_names() {
+        setup();
+
+        let (mut graph, [car, _, wheel, tree]) = build_graph();
+
+        assert_eq!(graph.find_by_name("root"), Some(graph.root));
+        assert_eq!(graph.find_by_name("tree"), Some(tree));
+        assert_eq!(graph.find_by_name("rock"), None);
+
+        assert_eq!(graph.find_by_path("root"), Some(graph.root));
+        assert_eq!(graph.find_by_path("root/car/wheel_fl"), Some(wheel));
+        assert_eq!(graph.find_by_path("root/wheel_fl"), None);
+        assert_eq!(graph.find_by_path("car/wheel_fl"), None);
+        assert_eq!(graph.path(wheel).as_deref(), Some("root/car/wheel_fl"));
+
+        graph.set_name(car, "truck");
+        assert_eq!(graph.find_by_path("root/truck/wheel_fl"), Some(wheel));
+    }
+
+    #[test]
+    fn test_remove_subtree() {
+        setup();
+
+        let (mut graph, [car, body, wheel, tree]) = build_graph();
+
+        assert!(graph.remove_subtree(graph.root).is_empty());
+
+        let removed = graph.remove_subtree(car);
+        assert_eq!(
+            removed.iter().map(|node| node.base.id).collect::<Vec<_>>(),
+            [car, body, wheel]
+        );
+
+        assert_eq!(graph.len(), 2);
+        assert!(graph.get(wheel).is_none());
+        assert_eq!(graph.children(graph.root).collect::<Vec<_>>(), [tree]);
+        assert!(graph.remove_subtree(car).is_empty());
+
+        assert_eq!(graph.remove(tree).map(|node| node.base.id), Some(tree));
+        assert_eq!(graph.len(), 1);
+    }
+
+    #[test]
+    fn test_pick_id() {
+        setup();
+
+        let (mut graph, [car, _, _, tree]) = build_graph();
+
+        let pick_id = graph.get(tree).unwrap().pick_id();
+        assert_ne!(pick_id, 0);
+        assert_eq!(graph.find_by_pick_id(pick_id), Some(tree));
+        assert_eq!(graph.find_by_pick_id(0), None);
+
+        // the new node reuses the slot of the removed one
+        graph.remove(tree);
+        let rock = insert(&mut graph, car, "rock", Transform::IDENTITY);
+        assert_eq!(rock.data().as_ffi() as u32, tree.data().as_ffi() as u32);
+        assert_ne!(graph.get(rock).unwrap().pick_id(), pick_id);
+        assert_eq!(graph.find_by_pick_id(pick_id), None);
+    }
+
+    #[test]
+    fn test_reparent() {
+        setup();
+
+        let (mut graph, [car, body, wheel, tree]) = build_graph();
+
+        // cycles
+        assert!(!graph.reparent(car, wheel, false));
+        assert!(!graph.reparent(car, car, false));
+        assert!(!graph.reparent(graph.root, tree, false));
+
+        let world = *graph.get(wheel).unwrap().global_transform();
+
+        assert!(graph.reparent(wheel, tree, true));
+        graph.update_nodes();
+
+        assert_eq!(graph.children(car).collect::<Vec<_>>(), [body]);
+        assert_eq!(graph.children(tree).collect::<Vec<_>>(), [wheel]);
+        assert_eq!(graph.path(wheel).as_deref(), Some("root/tree/wheel_fl"));
+        assert!(
+            graph
+                .get(wheel)
+                .unwrap()
+                .global_transform()
+                .matrix()
+                .abs_diff_eq(*world.matrix(), 1e-5)
+        );
+
+        let local = *graph.get(body).unwrap().transform();
+
+        assert!(graph.reparent(body, tree, false));
+        graph.update_nodes();
+
+        let body = graph.get(body).unwrap();
+        assert_eq!(body.transform().matrix(), local.matrix());
+        assert!(
+            body.global_transform()
+                .matrix()
+                .abs_diff_eq(*graph.get(tree).unwrap().global_transform().matrix(), 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_animation() {
+        setup();
+
+        let (mut graph, [car, body, wheel, tree]) = build_graph();
+
+        let clip = AnimationClip::new(
+            "spin",
+            vec![Track::new(
+                0,
+                Interpolation::Linear,
+                vec![0., 1.],
+                Keyframes::Rotation(vec![Quat::IDENTITY, Quat::from_rotation_x(1.)]),
+            )],
+        );
+        let mut animation = AnimationComponent::new(vec![Arc::new(clip)], vec![wheel]);
+        animation.play("spin", true);
+
+        graph.update(car, |node| {
+            node.animation = Some(animation.clone());
+            false
+        });
+
+        graph.update_animations(0.5);
+        graph.update_nodes();
+
+        let transform = graph.get(wheel).unwrap().transform();
+        assert!(
+            transform
+                .rotation()
+                .abs_diff_eq(Quat::from_rotation_x(0.5), 1e-5)
+        );
+        assert_eq!(transform.translation(), Vec3::new(1., 0., 1.));
+
+        // the target is kept when the tree is edited
+        graph.remove(body);
+        insert(&mut graph, car, "wheel_fr", Transform::IDENTITY);
+        assert!(graph.reparent(tree, car, false));
+        graph.update_animations(0.25);
+        assert!(
+            graph
+                .get(wheel)
+                .unwrap()
+                .transform()
+                .rotation()
+                .abs_diff_eq(Quat::from_rotation_x(0.75), 1e-5)
+        );
+
+        // copied with the subgraph
+        let mut copy = SceneGraph::new();
+        let root = copy.root;
+        let car = copy.insert_subgraph(root, car, &graph).unwrap();
+        assert!(copy.get(car).unwrap().animation.is_some());
+
+        copy.set_enabled(car, false);
+        copy.update_animations(0.25);
+        let copied_wheel = copy.children(car).next().unwrap();
+        assert!(
+            copy.get(copied_wheel)
+                .unwrap()
+                .transform()
+                .rotation()
+                .abs_diff_eq(Quat::from_rotation_x(0.75), 1e-5)
+        );
+
+        // the copy animates its own target
+        copy.set_enabled(car, true);
+        copy.update_animations(0.125);
+        assert!(
+            copy.get(copied_wheel)
+                .unwrap()
+                .transform()
+                .rotation()
+                .abs_diff_eq(Quat::from_rotation_x(0.875), 1e-5)
+        );
+        assert!(
+            graph
+                .get(wheel)
+                .unwrap()
+                .transform()
+                .rotation()
+                .abs_diff_eq(Quat::from_rotation_x(0.75), 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_morph() {
+        setup();
+
+        let (mut graph, [car, body, _, _]) = build_graph();
+
+        let clip = AnimationClip::new(
+            "smile",
+            vec![Track::new(
+                0,
+                Interpolation::Linear,
+                vec![0., 1.],
+                Keyframes::Weights(vec![0., 0., 1., 0.5]),
+            )],
+        );
+        let mut animation = AnimationComponent::new(vec![Arc::new(clip)], vec![body]);
+        animation.play("smile", false);
+
+        graph.update(car, |node| {
+            node.animation = Some(animation.clone());
+            false
+        });
+        graph.update(body, |node| {
+            node.morph = Some(MorphComponent::new(vec![0., 0.]));
+            false
+        });
+
+        graph.update_animations(0.5);
+        assert_eq!(
+            graph.get(body).unwrap().morph.as_ref().unwrap().weights,
+            vec![0.5, 0.25]
+        );
+
+        // copied with the subgraph
+        let mut copy = SceneGraph::new();
+        let root = copy.root;
+        let car = copy.insert_subgraph(root, car, &graph).unwrap();
+        let body = copy.children(car).next().unwrap();
+        assert_eq!(
+            copy.get(body).unwrap().morph.as_ref().unwrap().weights,
+            vec![0.5, 0.25]
+        );
+    }
+
+    #[test]
+    fn test_skin() {
+        setup();
+
+        let (mut graph, [car, body, wheel, _]) = build_graph();
+        graph.update_nodes();
+
+        // bind pose of the joint relative to the skinned mesh
+        let mesh = graph.get(body).unwrap().global_transform().matrix();
+        let joint = graph.get(wheel).unwrap().global_transform().matrix();
+        let skeleton = Skeleton::new("car", vec![(mesh.inverse() * joint).inverse()]);
+        graph.update(body, |node| {
+            node.skin = Some(SkinComponent::new(skeleton.clone(), vec![wheel]));
+            false
+        });
+
+        let node = graph.get(body).unwrap();
+        let skin = node.skin.as_ref().unwrap();
+        let matrices = skin.joint_matrices(&graph, node.global_transform());
+        assert!(matrices[0].abs_diff_eq(Mat4::IDENTITY, 1e-5));
+
+        // joints are remapped to the copied nodes
+        let mut copy = SceneGraph::new();
+        let root = copy.root;
+        let car = copy.insert_subgraph(root, car, &graph).unwrap();
+        let body = copy.children(car).next().unwrap();
+        let wheel = copy.children(car).nth(1).unwrap();
+        assert_eq!(
+            copy.get(body).unwrap().skin.as_ref().unwrap().joints,
+            vec![wheel]
+        );
+
+        // removed joints keep the bind pose
+        copy.update_nodes();
+        copy.remove(wheel);
+        let node = copy.get(body).unwrap();
+        let skin = node.skin.as_ref().unwrap();
+        assert_eq!(
+            skin.joint_matrices(&copy, node.global_transform()),
+            vec![Mat4::IDENTITY]
+        );
+    }
+}
@@ -1,8 +1,13 @@
+pub mod animation;
 mod builder;
 pub mod components;
+mod error;
 pub mod graph;
+pub mod pick;
 pub mod scene;
+pub mod serialize;
 pub mod voxel;
 
 pub use builder::SceneBuilder;
+pub use error::SceneError;
 pub use scene::Scene;
@@ -0,0 +1,280 @@
+use glam::{Vec3, Vec4};
+
+use gobs_core::Transform;
+use gobs_render::{BoundingBox, MeshGeometry};
+
+use crate::components::NodeId;
+
+/// Nearest node under the cursor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickHit {
+    pub node: NodeId,
+    /// Distance from the ray origin
+    pub distance: f32,
+    /// World space hit position
+    pub position: Vec3,
+    /// World space normal of the hit surface, facing the ray
+    pub normal: Vec3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Normalized direction
+    pub dir: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, dir: Vec3) -> Self {
+        Self {
+            origin,
+            dir: dir.normalize(),
+        }
+    }
+
+    /// Ray from the near plane to the far plane through the unprojected points
+    pub fn from_points(near: Vec4, far: Vec4) -> Self {
+        let near = near.truncate() / near.w;
+        let far = far.truncate() / far.w;
+
+        Self::new(near, far - near)
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + distance * self.dir
+    }
+
+    /// Distance to the box and normal of the entered face. The distance is 0
+    /// if the origin is inside the box
+    pub fn intersect_box(&self, bounding_box: &BoundingBox) -> Option<(f32, Vec3)> {
+        let min = bounding_box.bottom_left();
+        let max = bounding_box.top_right();
+
+        if min.x > max.x || min.y > max.y || min.z > max.z {
+            return None;
+        }
+
+        let mut t_near = f32::MIN;
+        let mut t_far = f32::MAX;
+        let mut normal = Vec3::ZERO;
+
+        for axis in 0..3 {
+            let (origin, dir) = (self.origin[axis], self.dir[axis]);
+
+            if dir == 0. {
+                if origin < min[axis] || origin > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (min[axis] - origin) / dir;
+            let t2 = (max[axis] - origin) / dir;
+            let (t1, t2) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+            if t1 > t_near {
+                t_near = t1;
+                normal = Vec3::ZERO;
+                normal[axis] = -dir.signum();
+            }
+            t_far = t_far.min(t2);
+
+            if t_near > t_far {
+                return None;
+            }
+        }
+
+        if t_far < 0. {
+            return None;
+        }
+
+        if t_near < 0. {
+            Some((0., -self.dir))
+        } else {
+            Some((t_near, normal))
+        }
+    }
+
+    /// Möller–Trumbore intersection. Both faces are tested
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<(f32, Vec3)> {
+        const EPSILON: f32 = 1e-7;
+
+        let edge1 = b - a;
+        let edge2 = c - a;
+
+        let p = self.dir.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < EPSILON {
+            return None;
+        }
+
+        let inv_det = 1. / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = self.dir.dot(q) * inv_det;
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inv_det;
+        if t < 0. {
+            return None;
+        }
+
+        let normal = edge1.cross(edge2).normalize();
+        let normal = if normal.dot(self.dir) > 0. {
+            -normal
+        } else {
+            normal
+        };
+
+        Some((t, normal))
+    }
+
+    /// Nearest triangle of the geometry placed with the transform
+    pub fn intersect_geometry(
+        &self,
+        geometry: &MeshGeometry,
+        transform: Transform,
+    ) -> Option<(f32, Vec3)> {
+        let positions: Vec<Vec3> = geometry
+            .vertices
+            .iter()
+            .map(|vertex| (transform * vertex.position().extend(1.)).truncate())
+            .collect();
+
+        geometry
+            .indices
+            .chunks_exact(3)
+            .filter_map(|triangle| {
+                self.intersect_triangle(
+                    *positions.get(triangle[0] as usize)?,
+                    *positions.get(triangle[1] as usize)?,
+                    *positions.get(triangle[2] as usize)?,
+                )
+            })
+            .min_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec3, Vec4};
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_core::Transform;
+    use gobs_render::{BoundingBox, MeshGeometry, VertexData};
+
+    use crate::pick::Ray;
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::from_corners(&[Vec3::splat(-1.), Vec3::splat(1.)])
+    }
+
+    #[test]
+    fn test_ray() {
+        setup();
+
+        let ray = Ray::from_points(Vec4::new(0., 0., 2., 2.), Vec4::new(0., 0., -10., 1.));
+        assert_eq!(ray.origin, Vec3::new(0., 0., 1.));
+        assert_eq!(ray.dir, Vec3::NEG_Z);
+        assert_eq!(ray.at(3.), Vec3::new(0., 0., -2.));
+    }
+
+    #[test]
+    fn test_intersect_box() {
+        setup();
+
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::NEG_Z);
+        assert_eq!(ray.intersect_box(&unit_box()), Some((4., Vec3::Z)));
+
+        let ray = Ray::new(Vec3::new(-5., 0.5, 0.), Vec3::X);
+        assert_eq!(ray.intersect_box(&unit_box()), Some((4., Vec3::NEG_X)));
+
+        // behind the origin
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::Z);
+        assert_eq!(ray.intersect_box(&unit_box()), None);
+
+        // parallel and outside
+        let ray = Ray::new(Vec3::new(0., 2., 5.), Vec3::NEG_Z);
+        assert_eq!(ray.intersect_box(&unit_box()), None);
+
+        // inside
+        let ray = Ray::new(Vec3::ZERO, Vec3::Y);
+        assert_eq!(ray.intersect_box(&unit_box()), Some((0., Vec3::NEG_Y)));
+
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::NEG_Z);
+        assert_eq!(ray.intersect_box(&BoundingBox::default()), None);
+    }
+
+    #[test]
+    fn test_intersect_triangle() {
+        setup();
+
+        let (a, b, c) = (
+            Vec3::new(-1., -1., 0.),
+            Vec3::new(1., -1., 0.),
+            Vec3::new(0., 1., 0.),
+        );
+
+        let ray = Ray::new(Vec3::new(0., 0., 2.), Vec3::NEG_Z);
+        assert_eq!(ray.intersect_triangle(a, b, c), Some((2., Vec3::Z)));
+
+        // back face
+        let ray = Ray::new(Vec3::new(0., 0., -2.), Vec3::Z);
+        assert_eq!(ray.intersect_triangle(a, b, c), Some((2., Vec3::NEG_Z)));
+
+        let ray = Ray::new(Vec3::new(1., 1., 2.), Vec3::NEG_Z);
+        assert_eq!(ray.intersect_triangle(a, b, c), None);
+
+        let ray = Ray::new(Vec3::new(0., 0., 2.), Vec3::X);
+        assert_eq!(ray.intersect_triangle(a, b, c), None);
+    }
+
+    #[test]
+    fn test_intersect_geometry() {
+        setup();
+
+        let vertex = |x, y, z| VertexData::builder().position(Vec3::new(x, y, z)).build();
+
+        let mut builder = MeshGeometry::builder("quad");
+        builder
+            .vertices(&[
+                vertex(-1., -1., 0.),
+                vertex(1., -1., 0.),
+                vertex(1., 1., 0.),
+                vertex(-1., 1., 0.),
+            ])
+            .indices(&[0, 1, 2, 0, 2, 3], false);
+        let geometry = builder.build();
+
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 5.), Vec3::NEG_Z);
+        assert_eq!(
+            ray.intersect_geometry(&geometry, Transform::IDENTITY),
+            Some((5., Vec3::Z))
+        );
+
+        let transform = Transform::from_translation(Vec3::new(0., 0., -2.));
+        assert_eq!(
+            ray.intersect_geometry(&geometry, transform),
+            Some((7., Vec3::Z))
+        );
+
+        let transform = Transform::from_translation(Vec3::new(3., 0., 0.));
+        assert_eq!(ray.intersect_geometry(&geometry, transform), None);
+    }
+}
@@ -1,14 +1,23 @@
+use std::sync::Arc;
+
 use glam::{Vec3, Vec4};
 
-use gobs_core::Transform;
-use gobs_render::{BoundingBox, GfxContext, RenderBatch, RenderFlags, Renderable};
+use gobs_core::{Transform, logger};
+use gobs_render::{
+    Bounded, BoundingBox, GfxContext, MeshPath, Model, RenderBatch, RenderFlags, Renderable,
+    Renderer,
+};
 use gobs_resource::{
     ResourceError, ResourceManager,
+    load::{self, AssetType},
     {camera::Camera, light::Light},
 };
 
+use crate::SceneError;
 use crate::components::{NodeId, NodeValue};
 use crate::graph::scenegraph::SceneGraph;
+use crate::pick::{PickHit, Ray};
+use crate::serialize::{ModelRef, SceneData};
 
 pub struct Scene {
     pub graph: SceneGraph,
@@ -55,8 +64,60 @@ impl Scene {
         }
     }
 
+    /// Build the scene from saved data. Models are looked up by the resolver
+    pub fn from_data<F>(ctx: &GfxContext, data: &SceneData, resolver: F) -> Result<Self, SceneError>
+    where
+        F: FnMut(&ModelRef) -> Option<Arc<Model>>,
+    {
+        let (graph, camera, light) = data.build_graph(resolver)?;
+
+        let (width, height) = ctx.extent().into();
+
+        let mut scene = Scene {
+            graph,
+            camera,
+            light,
+            width: 0.,
+            height: 0.,
+        };
+        scene.resize(width, height);
+
+        Ok(scene)
+    }
+
+    /// Load a scene saved in the data directory
+    pub fn load<F>(ctx: &GfxContext, filename: &str, resolver: F) -> Result<Self, SceneError>
+    where
+        F: FnMut(&ModelRef) -> Option<Arc<Model>>,
+    {
+        tracing::debug!(target: logger::RESOURCES, "Load scene: {}", filename);
+
+        let data = load::load_string_sync(filename, AssetType::DATA)?;
+
+        Self::from_data(ctx, &SceneData::from_ron(&data)?, resolver)
+    }
+
+    pub fn to_data(&self) -> SceneData {
+        SceneData::new(&self.graph, self.camera, self.light)
+    }
+
+    /// Save the scene in the data directory
+    pub fn save(&self, filename: &str) -> Result<(), SceneError> {
+        let path = load::get_asset_dir(filename, AssetType::DATA)?;
+
+        tracing::debug!(target: logger::RESOURCES, "Save scene: {:?}", path);
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_data().to_ron()?)?;
+
+        Ok(())
+    }
+
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    pub fn update(&mut self, _delta: f32) {
+    pub fn update(&mut self, delta: f32) {
+        self.graph.update_animations(delta);
         self.graph.update_nodes();
     }
 
@@ -125,8 +186,127 @@ impl Scene {
         camera.screen_to_world(pos, camera_transform, self.width, self.height)
     }
 
-    pub fn select_node(&self, _x: f32, _y: f32) -> Option<NodeId> {
-        None
+    /// Ray going through the screen position, away from the camera
+    pub fn pick_ray(&self, x: f32, y: f32) -> Ray {
+        let (camera_transform, camera) = self.camera();
+
+        let near = camera.screen_to_world(
+            Vec3::new(x, y, 0.),
+            camera_transform,
+            self.width,
+            self.height,
+        );
+        let far = camera.screen_to_world(
+            Vec3::new(x, y, 1.),
+            camera_transform,
+            self.width,
+            self.height,
+        );
+
+        let ray = Ray::from_points(near, far);
+        if ray.dir.dot(camera.dir()) < 0. {
+            Ray::from_points(far, near)
+        } else {
+            ray
+        }
+    }
+
+    /// Nearest model under the screen position. Bounding boxes are tested first,
+    /// then the mesh triangles when the geometry is available on the CPU
+    pub fn pick(&self, resource_manager: &ResourceManager, x: f32, y: f32) -> Option<PickHit> {
+        let ray = self.pick_ray(x, y);
+
+        let mut hit = None;
+        self.pick_node(self.graph.root, &ray, resource_manager, &mut hit);
+
+        hit
+    }
+
+    fn pick_node(
+        &self,
+        key: NodeId,
+        ray: &Ray,
+        resource_manager: &ResourceManager,
+        hit: &mut Option<PickHit>,
+    ) {
+        let Some(node) = self.graph.get(key) else {
+            return;
+        };
+
+        if !node.base.enabled {
+            return;
+        }
+
+        let closer = |distance: f32, hit: &Option<PickHit>| {
+            hit.is_none_or(|hit: PickHit| distance < hit.distance)
+        };
+
+        // the node box includes all the children
+        match ray.intersect_box(&node.bounding.bounding_box) {
+            Some((distance, _)) if closer(distance, hit) => {}
+            _ => return,
+        }
+
+        if let NodeValue::Model(model) = &node.base.value {
+            let transform = *node.global_transform();
+
+            if let Some(box_hit) = ray.intersect_box(&model.boundings().transform(transform))
+                && closer(box_hit.0, hit)
+            {
+                let mut has_geometry = false;
+                let mut mesh_hit: Option<(f32, Vec3)> = None;
+
+                for (mesh, _) in &model.meshes {
+                    if let Some(mesh) = resource_manager.get(mesh)
+                        && let MeshPath::Mesh(geometry) = &mesh.properties.path
+                    {
+                        has_geometry = true;
+
+                        if let Some(triangle_hit) = ray.intersect_geometry(geometry, transform)
+                            && mesh_hit.is_none_or(|mesh_hit| triangle_hit.0 < mesh_hit.0)
+                        {
+                            mesh_hit = Some(triangle_hit);
+                        }
+                    }
+                }
+
+                let node_hit = if has_geometry {
+                    mesh_hit
+                } else {
+                    Some(box_hit)
+                };
+
+                if let Some((distance, normal)) = node_hit
+                    && closer(distance, hit)
+                {
+                    *hit = Some(PickHit {
+                        node: key,
+                        distance,
+                        position: ray.at(distance),
+                        normal,
+                    });
+                }
+            }
+        }
+
+        for &child in &node.base.children {
+            self.pick_node(child, ray, resource_manager, hit);
+        }
+    }
+
+    /// Nearest node under the screen position. The id buffer is used if the
+    /// frame graph has a picking pass, otherwise a ray is cast through the scene
+    pub fn select_node(
+        &self,
+        renderer: &mut Renderer,
+        resource_manager: &ResourceManager,
+        x: f32,
+        y: f32,
+    ) -> Option<NodeId> {
+        match renderer.pick(x, y) {
+            Some(pick_id) => self.graph.find_by_pick_id(pick_id),
+            None => self.pick(resource_manager, x, y).map(|hit| hit.node),
+        }
     }
 }
 
@@ -142,6 +322,13 @@ impl Renderable for Scene {
         render_flags: RenderFlags,
     ) -> Result<(), ResourceError> {
         self.graph.visit(self.graph.root, &mut |node| {
+            batch.set_pick_id(node.pick_id());
+            if let Some(skin) = &node.skin {
+                batch.set_joints(&skin.joint_matrices(&self.graph, node.global_transform()));
+            }
+            if let Some(morph) = &node.morph {
+                batch.set_morph_weights(&morph.weights);
+            }
             node.draw(
                 ctx,
                 resource_manager,
@@ -149,8 +336,17 @@ impl Renderable for Scene {
                 transform,
                 bounding_box,
                 render_flags,
-            )
+            )?;
+            if node.skin.is_some() {
+                batch.set_joints(&[]);
+            }
+            if node.morph.is_some() {
+                batch.set_morph_weights(&[]);
+            }
+
+            Ok(())
         })?;
+        batch.set_pick_id(0);
 
         let (light_transform, light) = self.light();
         let (camera_transform, camera) = self.camera();
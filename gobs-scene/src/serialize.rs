@@ -0,0 +1,421 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use gobs_core::Transform;
+use gobs_render::Model;
+use gobs_resource::{camera::Camera, light::Light};
+
+use crate::{
+    SceneError,
+    components::{NodeId, NodeValue},
+    graph::scenegraph::SceneGraph,
+};
+
+/// Model referenced by a saved scene. Models are resolved by the application
+/// when the scene is loaded
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ModelRef {
+    pub name: String,
+    /// Asset the model was loaded from
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+impl ModelRef {
+    pub fn new(model: &Model) -> Self {
+        Self {
+            name: model.name().to_string(),
+            path: model.path.as_ref().map(|path| path.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum NodeValueData {
+    None,
+    Model(ModelRef),
+    Camera(Camera),
+    Light(Light),
+}
+
+impl NodeValueData {
+    fn new(value: &NodeValue) -> Self {
+        match value {
+            NodeValue::None => NodeValueData::None,
+            NodeValue::Model(model) => NodeValueData::Model(ModelRef::new(model)),
+            NodeValue::Camera(camera) => NodeValueData::Camera(camera.clone()),
+            NodeValue::Light(light) => NodeValueData::Light(light.clone()),
+        }
+    }
+
+    fn resolve<F>(&self, resolver: &mut F) -> Result<NodeValue, SceneError>
+    where
+        F: FnMut(&ModelRef) -> Option<Arc<Model>>,
+    {
+        let value = match self {
+            NodeValueData::None => NodeValue::None,
+            NodeValueData::Model(model) => NodeValue::Model(
+                resolver(model).ok_or_else(|| SceneError::ModelNotFound(model.name.clone()))?,
+            ),
+            NodeValueData::Camera(camera) => NodeValue::Camera(camera.clone()),
+            NodeValueData::Light(light) => NodeValue::Light(light.clone()),
+        };
+
+        Ok(value)
+    }
+}
+
+/// Saved node. Parents are stored before their children. Skin, morph and animation
+/// components are not saved, skinned models of a loaded scene are drawn in their bind pose
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NodeData {
+    /// Index of the parent node, None for the root
+    pub parent: Option<usize>,
+    #[serde(default)]
+    pub name: String,
+    pub value: NodeValueData,
+    #[serde(default)]
+    pub transform: Transform,
+    #[serde(default = "NodeData::default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub selected: bool,
+}
+
+impl NodeData {
+    fn default_enabled() -> bool {
+        true
+    }
+}
+
+/// Versioned scene file
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SceneData {
+    pub version: u32,
+    /// Index of the active camera node
+    pub camera: usize,
+    /// Index of the active light node
+    pub light: usize,
+    pub nodes: Vec<NodeData>,
+}
+
+impl SceneData {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(graph: &SceneGraph, camera: NodeId, light: NodeId) -> Self {
+        let mut data = SceneData {
+            version: Self::VERSION,
+            camera: 0,
+            light: 0,
+            nodes: Vec::with_capacity(graph.len()),
+        };
+
+        data.add_node(graph, graph.root, None, camera, light);
+
+        data
+    }
+
+    fn add_node(
+        &mut self,
+        graph: &SceneGraph,
+        key: NodeId,
+        parent: Option<usize>,
+        camera: NodeId,
+        light: NodeId,
+    ) {
+        if let Some(node) = graph.get(key) {
+            let index = self.nodes.len();
+
+            if key == camera {
+                self.camera = index;
+            }
+            if key == light {
+                self.light = index;
+            }
+
+            self.nodes.push(NodeData {
+                parent,
+                name: node.base.name.clone(),
+                value: NodeValueData::new(&node.base.value),
+                transform: *node.transform(),
+                enabled: node.base.enabled,
+                selected: node.base.selected,
+            });
+
+            for &child in &node.base.children {
+                self.add_node(graph, child, Some(index), camera, light);
+            }
+        }
+    }
+
+    /// Rebuild the graph, models are looked up with the resolver. Returns the
+    /// graph with the camera and light nodes
+    pub fn build_graph<F>(
+        &self,
+        mut resolver: F,
+    ) -> Result<(SceneGraph, NodeId, NodeId), SceneError>
+    where
+        F: FnMut(&ModelRef) -> Option<Arc<Model>>,
+    {
+        if self.version > Self::VERSION {
+            return Err(SceneError::Version(self.version));
+        }
+
+        if self.nodes.is_empty() {
+            return Err(SceneError::InvalidNode(0));
+        }
+
+        let mut graph = SceneGraph::new();
+        let mut ids: Vec<NodeId> = Vec::with_capacity(self.nodes.len());
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let value = node.value.resolve(&mut resolver)?;
+
+            let id = match node.parent {
+                None if i == 0 => graph.set_root(value, node.transform),
+                Some(parent) if parent < i => graph
+                    .insert(ids[parent], value, node.transform)
+                    .ok_or(SceneError::InvalidNode(i))?,
+                _ => return Err(SceneError::InvalidNode(i)),
+            };
+
+            if !node.name.is_empty() {
+                graph.set_name(id, &node.name);
+            }
+            graph.set_enabled(id, node.enabled);
+            graph.set_selected(id, node.selected);
+
+            ids.push(id);
+        }
+
+        let camera = match self.nodes.get(self.camera) {
+            Some(NodeData {
+                value: NodeValueData::Camera(_),
+                ..
+            }) => ids[self.camera],
+            _ => return Err(SceneError::InvalidNode(self.camera)),
+        };
+
+        let light = match self.nodes.get(self.light) {
+            Some(NodeData {
+                value: NodeValueData::Light(_),
+                ..
+            }) => ids[self.light],
+            _ => return Err(SceneError::InvalidNode(self.light)),
+        };
+
+        graph.update_nodes();
+
+        Ok((graph, camera, light))
+    }
+
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+
+        Ok(data)
+    }
+
+    pub fn from_ron(data: &str) -> Result<Self, SceneError> {
+        let options = ron::options::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
+
+        let scene: SceneData = options.from_str(data)?;
+
+        if scene.version > Self::VERSION {
+            return Err(SceneError::Version(scene.version));
+        }
+
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use gobs_core::{Color, Transform};
+    use gobs_resource::{
+        camera::Camera,
+        light::{Light, LightType},
+    };
+
+    use crate::{
+        SceneError,
+        components::NodeValue,
+        graph::scenegraph::SceneGraph,
+        serialize::{ModelRef, NodeData, NodeValueData, SceneData},
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_round_trip() {
+        setup();
+
+        let mut graph = SceneGraph::new();
+
+        let group_transform = Transform::new(
+            Vec3::new(1., 2., 3.),
+            Quat::from_rotation_z(0.3),
+            Vec3::splat(2.),
+        );
+        let group = graph
+            .insert(graph.root, NodeValue::None, group_transform)
+            .unwrap();
+        graph.set_name(group, "group");
+        let camera = graph
+            .insert(
+                group,
+                NodeValue::Camera(Camera::perspective(1.5, 0.8, 0.1, 100., 0.2, -0.4)),
+                Transform::from_translation(Vec3::new(0., 0., 5.)),
+            )
+            .unwrap();
+        let light = graph
+            .insert(
+                graph.root,
+                NodeValue::Light(Light::with_type(
+                    Color::RED,
+                    2.,
+                    LightType::Point { range: Some(10.) },
+                )),
+                Transform::from_translation(Vec3::new(-2., 4., 0.)),
+            )
+            .unwrap();
+        let hidden = graph
+            .insert(group, NodeValue::None, Transform::IDENTITY)
+            .unwrap();
+        graph.set_enabled(hidden, false);
+        graph.set_selected(group, true);
+        graph.update_nodes();
+
+        let data = SceneData::new(&graph, camera, light).to_ron().unwrap();
+        tracing::info!("{}", data);
+
+        let scene = SceneData::from_ron(&data).unwrap();
+        assert_eq!(scene.version, SceneData::VERSION);
+        assert_eq!(scene.nodes.len(), graph.len());
+
+        let (loaded, loaded_camera, loaded_light) = scene.build_graph(|_| None).unwrap();
+        assert_eq!(loaded.len(), graph.len());
+
+        let root = loaded.get(loaded.root).unwrap();
+        assert_eq!(root.base.children.len(), 2);
+
+        let loaded_group = loaded.get(root.base.children[0]).unwrap();
+        assert_eq!(loaded_group.base.name, "group");
+        assert_eq!(
+            loaded.find_by_path("root/group/camera"),
+            Some(loaded_camera)
+        );
+        assert!(loaded_group.base.selected);
+        assert_eq!(loaded_group.transform().matrix(), group_transform.matrix());
+        assert_eq!(loaded_group.base.children.len(), 2);
+        assert!(
+            !loaded
+                .get(loaded_group.base.children[1])
+                .unwrap()
+                .base
+                .enabled
+        );
+
+        let node = loaded.get(loaded_camera).unwrap();
+        assert_eq!(node.base.parent, Some(loaded_group.base.id));
+        assert_eq!(
+            node.global_transform().matrix(),
+            graph.get(camera).unwrap().global_transform().matrix()
+        );
+        match &node.base.value {
+            NodeValue::Camera(loaded) => {
+                let NodeValue::Camera(camera) = &graph.get(camera).unwrap().base.value else {
+                    unreachable!()
+                };
+                assert_eq!(loaded.id, camera.id);
+                assert_eq!(loaded.yaw, camera.yaw);
+                assert_eq!(loaded.pitch, camera.pitch);
+                assert_eq!(loaded.mode.far(), 100.);
+            }
+            _ => panic!("camera expected"),
+        }
+
+        match &loaded.get(loaded_light).unwrap().base.value {
+            NodeValue::Light(light) => {
+                assert_eq!(light.colour, Color::RED);
+                assert_eq!(light.intensity, 2.);
+                assert_eq!(light.ty, LightType::Point { range: Some(10.) });
+            }
+            _ => panic!("light expected"),
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        setup();
+
+        let data = "(version: 2, camera: 0, light: 0, nodes: [])";
+        assert!(matches!(
+            SceneData::from_ron(data),
+            Err(SceneError::Version(2))
+        ));
+
+        let node = |parent, value| NodeData {
+            parent,
+            name: String::new(),
+            value,
+            transform: Transform::IDENTITY,
+            enabled: true,
+            selected: false,
+        };
+
+        let mut scene = SceneData {
+            version: SceneData::VERSION,
+            camera: 1,
+            light: 2,
+            nodes: vec![
+                node(None, NodeValueData::None),
+                node(Some(0), NodeValueData::Camera(Camera::default())),
+                node(Some(0), NodeValueData::Light(Light::default())),
+            ],
+        };
+        assert!(scene.build_graph(|_| None).is_ok());
+
+        // light is not a camera
+        scene.camera = 2;
+        assert!(matches!(
+            scene.build_graph(|_| None),
+            Err(SceneError::InvalidNode(2))
+        ));
+        scene.camera = 1;
+
+        // children must follow their parent
+        scene.nodes[1].parent = Some(2);
+        assert!(matches!(
+            scene.build_graph(|_| None),
+            Err(SceneError::InvalidNode(1))
+        ));
+        scene.nodes[1].parent = Some(0);
+
+        scene.nodes.push(node(
+            Some(0),
+            NodeValueData::Model(ModelRef {
+                name: "ship".to_string(),
+                path: Some("ship.glb".to_string()),
+            }),
+        ));
+        assert!(matches!(
+            scene.build_graph(|model| {
+                assert_eq!(model.path.as_deref(), Some("ship.glb"));
+                None
+            }),
+            Err(SceneError::ModelNotFound(name)) if name == "ship"
+        ));
+    }
+}
@@ -7,6 +7,19 @@ use gobs_core::logger;
 
 use crate::{Wrap, buffers::BufferUsage, device::Device, memory::Memory};
 
+/// Statistics of the memory blocks managed by the allocator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocatorStats {
+    pub allocation_count: usize,
+    pub block_count: usize,
+    /// Memory used by allocations
+    pub allocated: u64,
+    /// Memory of all blocks, including free regions
+    pub reserved: u64,
+    /// Largest contiguous free region of a block
+    pub largest_free: u64,
+}
+
 pub struct Allocator {
     pub allocator: Mutex<vulkan::Allocator>,
     pub device: Arc<Device>,
@@ -36,6 +49,40 @@ impl Allocator {
         })
     }
 
+    pub fn stats(&self) -> AllocatorStats {
+        let report = self.allocator.lock().unwrap().generate_report();
+
+        let largest_free = report
+            .blocks
+            .iter()
+            .map(|block| {
+                let mut allocations: Vec<_> = report.allocations[block.allocations.clone()]
+                    .iter()
+                    .map(|allocation| (allocation.offset, allocation.size))
+                    .collect();
+                allocations.sort_unstable();
+
+                let mut largest_free = 0;
+                let mut end = 0;
+                for (offset, size) in allocations {
+                    largest_free = largest_free.max(offset.saturating_sub(end));
+                    end = end.max(offset + size);
+                }
+
+                largest_free.max(block.size.saturating_sub(end))
+            })
+            .max()
+            .unwrap_or_default();
+
+        AllocatorStats {
+            allocation_count: report.allocations.len(),
+            block_count: report.blocks.len(),
+            allocated: report.total_allocated_bytes,
+            reserved: report.total_capacity_bytes,
+            largest_free,
+        }
+    }
+
     pub fn allocate_buffer(
         self: Arc<Self>,
         usage: BufferUsage,
@@ -0,0 +1,192 @@
+use std::fmt::Display;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{
+    alloc::Allocator,
+    buffers::{Buffer, BufferUsage},
+    command::CommandBuffer,
+    device::Device,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreadcrumbStatus {
+    NotStarted,
+    InFlight,
+    Completed,
+}
+
+impl Display for BreadcrumbStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BreadcrumbStatus::NotStarted => write!(f, "not started"),
+            BreadcrumbStatus::InFlight => write!(f, "in flight"),
+            BreadcrumbStatus::Completed => write!(f, "completed"),
+        }
+    }
+}
+
+/// Command label with the progress of the GPU
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Breadcrumb {
+    pub marker: u32,
+    pub label: String,
+    pub status: BreadcrumbStatus,
+}
+
+impl Display for Breadcrumb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} [{}] {}", self.marker, self.status, self.label)
+    }
+}
+
+struct BreadcrumbRing {
+    labels: Vec<(u32, String)>,
+    next: u32,
+}
+
+/// Ring of markers written by the GPU at the start and end of command labels.
+/// Markers are written by transfer commands and are only an approximation of
+/// the progress of the GPU
+pub struct Breadcrumbs {
+    buffer: Buffer,
+    ring: Mutex<BreadcrumbRing>,
+}
+
+impl Breadcrumbs {
+    /// Begin and end markers
+    const SLOT_SIZE: u64 = 8;
+
+    pub fn new(device: Arc<Device>, allocator: Arc<Allocator>, capacity: usize) -> Arc<Self> {
+        let buffer = Buffer::new(
+            "breadcrumbs",
+            capacity * Self::SLOT_SIZE as usize,
+            BufferUsage::Readback,
+            device,
+            allocator,
+        );
+        buffer.copy(&vec![0_u32; 2 * capacity], 0);
+
+        Arc::new(Self {
+            buffer,
+            ring: Mutex::new(BreadcrumbRing {
+                labels: vec![(0, String::new()); capacity],
+                next: 1,
+            }),
+        })
+    }
+
+    /// Record the begin marker of a label. Returns the marker to end
+    pub fn begin(&self, cmd: &mut CommandBuffer, label: &str) -> u32 {
+        let (marker, slot) = {
+            let mut ring = self.ring.lock();
+
+            let marker = ring.next;
+            ring.next = ring.next.wrapping_add(1).max(1);
+
+            let slot = marker as usize % ring.labels.len();
+            ring.labels[slot] = (marker, label.to_string());
+
+            (marker, slot as u64)
+        };
+
+        cmd.fill_buffer(&self.buffer, slot * Self::SLOT_SIZE, 4, marker);
+
+        marker
+    }
+
+    pub fn end(&self, cmd: &mut CommandBuffer, marker: u32) {
+        let slot = (marker as usize % self.ring.lock().labels.len()) as u64;
+
+        cmd.fill_buffer(&self.buffer, slot * Self::SLOT_SIZE + 4, 4, marker);
+    }
+
+    /// Labels of the ring ordered by marker, with the status read from the GPU
+    pub fn collect(&self) -> Vec<Breadcrumb> {
+        let mut markers = vec![];
+        self.buffer.get_bytes(&mut markers);
+
+        Self::resolve(&self.ring.lock().labels, &markers)
+    }
+
+    fn resolve(labels: &[(u32, String)], markers: &[u32]) -> Vec<Breadcrumb> {
+        let mut breadcrumbs: Vec<_> = labels
+            .iter()
+            .enumerate()
+            .filter(|(_, (marker, _))| *marker != 0)
+            .map(|(slot, (marker, label))| {
+                let begin = markers.get(2 * slot).copied().unwrap_or_default();
+                let end = markers.get(2 * slot + 1).copied().unwrap_or_default();
+
+                let status = if end == *marker {
+                    BreadcrumbStatus::Completed
+                } else if begin == *marker {
+                    BreadcrumbStatus::InFlight
+                } else {
+                    BreadcrumbStatus::NotStarted
+                };
+
+                Breadcrumb {
+                    marker: *marker,
+                    label: label.clone(),
+                    status,
+                }
+            })
+            .collect();
+
+        breadcrumbs.sort_by_key(|breadcrumb| breadcrumb.marker);
+
+        breadcrumbs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::breadcrumbs::{BreadcrumbStatus, Breadcrumbs};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_resolve() {
+        setup();
+
+        // ring of 4 slots after 5 labels: marker 5 replaced marker 1 in slot 1
+        let labels = vec![
+            (4, "shadow".to_string()),
+            (5, "ui".to_string()),
+            (2, "frame".to_string()),
+            (3, "forward".to_string()),
+        ];
+        let markers = [4, 0, 1, 1, 2, 0, 3, 3];
+
+        let breadcrumbs = Breadcrumbs::resolve(&labels, &markers);
+
+        let status: Vec<_> = breadcrumbs
+            .iter()
+            .map(|breadcrumb| (breadcrumb.marker, breadcrumb.status))
+            .collect();
+
+        assert_eq!(
+            status,
+            vec![
+                (2, BreadcrumbStatus::InFlight),
+                (3, BreadcrumbStatus::Completed),
+                (4, BreadcrumbStatus::InFlight),
+                (5, BreadcrumbStatus::NotStarted),
+            ]
+        );
+        assert_eq!(breadcrumbs[1].to_string(), "#3 [completed] forward");
+
+        assert!(Breadcrumbs::resolve(&[(0, String::new())], &[0, 0]).is_empty());
+    }
+}
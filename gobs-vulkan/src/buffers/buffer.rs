@@ -21,6 +21,27 @@ pub enum BufferUsage {
     Instance,
     Index,
     Uniform,
+    /// Shader storage, written by compute passes
+    Storage,
+    /// Shader storage updated by the host every frame
+    HostStorage,
+    /// Indirect draw commands, written by compute passes
+    Indirect,
+    /// Written by commands of any queue and read back by the host
+    Readback,
+}
+
+impl BufferUsage {
+    /// Buffers accessed by the async compute queue
+    pub fn is_shared(self) -> bool {
+        matches!(
+            self,
+            BufferUsage::Storage
+                | BufferUsage::HostStorage
+                | BufferUsage::Indirect
+                | BufferUsage::Readback
+        )
+    }
 }
 
 impl From<BufferUsage> for vk::MemoryPropertyFlags {
@@ -40,6 +61,14 @@ impl From<BufferUsage> for vk::MemoryPropertyFlags {
             BufferUsage::Uniform => {
                 vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT
             }
+            BufferUsage::Storage => vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            BufferUsage::HostStorage => {
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT
+            }
+            BufferUsage::Indirect => vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            BufferUsage::Readback => {
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT
+            }
         }
     }
 }
@@ -53,6 +82,10 @@ impl From<BufferUsage> for MemoryLocation {
             BufferUsage::Instance => MemoryLocation::CpuToGpu,
             BufferUsage::Index => MemoryLocation::GpuOnly,
             BufferUsage::Uniform => MemoryLocation::CpuToGpu,
+            BufferUsage::Storage => MemoryLocation::GpuOnly,
+            BufferUsage::HostStorage => MemoryLocation::CpuToGpu,
+            BufferUsage::Indirect => MemoryLocation::GpuOnly,
+            BufferUsage::Readback => MemoryLocation::GpuToCpu,
         }
     }
 }
@@ -72,6 +105,23 @@ impl From<BufferUsage> for vk::BufferUsageFlags {
                 vk::BufferUsageFlags::TRANSFER_DST | vk::BufferUsageFlags::INDEX_BUFFER
             }
             BufferUsage::Uniform => vk::BufferUsageFlags::UNIFORM_BUFFER,
+            BufferUsage::Storage => {
+                vk::BufferUsageFlags::TRANSFER_DST
+                    | vk::BufferUsageFlags::STORAGE_BUFFER
+                    | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS
+            }
+            BufferUsage::HostStorage => {
+                vk::BufferUsageFlags::STORAGE_BUFFER
+                    | vk::BufferUsageFlags::INDIRECT_BUFFER
+                    | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS
+            }
+            BufferUsage::Indirect => {
+                vk::BufferUsageFlags::TRANSFER_DST
+                    | vk::BufferUsageFlags::STORAGE_BUFFER
+                    | vk::BufferUsageFlags::INDIRECT_BUFFER
+                    | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS
+            }
+            BufferUsage::Readback => vk::BufferUsageFlags::TRANSFER_DST,
         }
     }
 }
@@ -97,12 +147,26 @@ impl Buffer {
         allocator: Arc<Allocator>,
     ) -> Self {
         let usage_flags = usage.into();
+        let families = match usage {
+            BufferUsage::Readback => device.queue_families(),
+            _ => device.concurrent_families(),
+        };
 
         let buffer_info = vk::BufferCreateInfo::default()
             .size(size as u64)
             .usage(usage_flags)
             .sharing_mode(vk::SharingMode::EXCLUSIVE);
 
+        // storage buffers are shared with the async compute queue, readback
+        // buffers with all queues
+        let buffer_info = if usage.is_shared() && families.len() > 1 {
+            buffer_info
+                .sharing_mode(vk::SharingMode::CONCURRENT)
+                .queue_family_indices(&families)
+        } else {
+            buffer_info
+        };
+
         let buffer = unsafe { device.raw().create_buffer(&buffer_info, None).unwrap() };
 
         let buffer_label = format!("[Buffer] {label}");
@@ -144,6 +208,11 @@ impl Buffer {
     pub fn get_bytes<T: Pod>(&self, vec: &mut Vec<T>) {
         self.memory.read().download(vec);
     }
+
+    /// Allocated memory size, may be larger than the buffer size
+    pub fn memory_size(&self) -> u64 {
+        self.memory.read().size()
+    }
 }
 
 impl Debug for Buffer {
@@ -10,12 +10,13 @@ use crate::buffers::Buffer;
 use crate::command::CommandPool;
 use crate::descriptor::DescriptorSet;
 use crate::device::Device;
+use crate::error::VulkanError;
 use crate::framebuffer::Framebuffer;
 use crate::images::{Image, ImageLayout};
-use crate::pipelines::{Pipeline, PipelineLayout, PipelineStage};
+use crate::pipelines::{PUSH_CONSTANT_STAGES, Pipeline, PipelineLayout, PipelineStage};
 use crate::query::QueryPool;
 use crate::queue::Queue;
-use crate::sync::{Fence, Semaphore};
+use crate::sync::{Fence, Semaphore, SemaphoreSubmit};
 use crate::{Wrap, debug};
 
 pub trait IndexType: Copy {
@@ -323,6 +324,20 @@ impl CommandBuffer {
         }
     }
 
+    /// Fill `size` bytes of the buffer with a repeated u32 value. Must be
+    /// called outside of rendering
+    pub fn fill_buffer(&mut self, buffer: &Buffer, offset: u64, size: u64, data: u32) {
+        unsafe {
+            self.device.raw().cmd_fill_buffer(
+                self.command_buffer,
+                buffer.raw(),
+                offset,
+                size,
+                data,
+            );
+        }
+    }
+
     pub fn dispatch(&mut self, x: u32, y: u32, z: u32) {
         unsafe { self.device.raw().cmd_dispatch(self.command_buffer, x, y, z) }
     }
@@ -348,12 +363,53 @@ impl CommandBuffer {
         }
     }
 
+    pub fn draw_indexed_indirect(
+        &mut self,
+        buffer: &Buffer,
+        offset: u64,
+        draw_count: u32,
+        stride: u32,
+    ) {
+        unsafe {
+            self.device.raw().cmd_draw_indexed_indirect(
+                self.command_buffer,
+                buffer.raw(),
+                offset,
+                draw_count,
+                stride,
+            );
+        }
+    }
+
+    /// Draw count is read from `count_buffer` and clamped to `max_draw_count`
+    pub fn draw_indexed_indirect_count(
+        &mut self,
+        buffer: &Buffer,
+        offset: u64,
+        count_buffer: &Buffer,
+        count_offset: u64,
+        max_draw_count: u32,
+        stride: u32,
+    ) {
+        unsafe {
+            self.device.raw().cmd_draw_indexed_indirect_count(
+                self.command_buffer,
+                buffer.raw(),
+                offset,
+                count_buffer.raw(),
+                count_offset,
+                max_draw_count,
+                stride,
+            );
+        }
+    }
+
     pub fn push_constants<T: Pod>(&mut self, layout: Arc<PipelineLayout>, constants: &[T]) {
         unsafe {
             self.device.raw().cmd_push_constants(
                 self.command_buffer,
                 layout.layout,
-                vk::ShaderStageFlags::VERTEX,
+                PUSH_CONSTANT_STAGES,
                 0,
                 bytemuck::cast_slice(constants),
             );
@@ -431,14 +487,31 @@ impl CommandBuffer {
     }
 
     pub fn copy_image_to_buffer(&mut self, src: &Image, dst: &Buffer, offset: u64) {
+        self.copy_image_region_to_buffer(src, dst, offset, 0, 0, src.extent);
+    }
+
+    /// Copy the region of the image at (x, y) with tightly packed rows
+    pub fn copy_image_region_to_buffer(
+        &mut self,
+        src: &Image,
+        dst: &Buffer,
+        offset: u64,
+        x: u32,
+        y: u32,
+        extent: ImageExtent2D,
+    ) {
         let copy_info = vk::BufferImageCopy::default()
             .buffer_offset(offset)
-            .buffer_image_height(src.extent.height)
-            .buffer_row_length(src.extent.width)
-            .image_offset(Offset3D { x: 0, y: 0, z: 0 })
+            .buffer_image_height(extent.height)
+            .buffer_row_length(extent.width)
+            .image_offset(Offset3D {
+                x: x as i32,
+                y: y as i32,
+                z: 0,
+            })
             .image_extent(Extent3D {
-                width: src.extent.width,
-                height: src.extent.height,
+                width: extent.width,
+                height: extent.height,
                 depth: 1,
             })
             .image_subresource(
@@ -556,10 +629,22 @@ impl CommandBuffer {
     }
 
     pub fn copy_buffer_to_image(&mut self, src: &Buffer, dst: &Image, offset: u64) {
-        let (width, height) = (dst.extent.width, dst.extent.height);
+        self.copy_buffer_to_image_mip(src, dst, offset, 0);
+    }
+
+    pub fn copy_buffer_to_image_mip(
+        &mut self,
+        src: &Buffer,
+        dst: &Image,
+        offset: u64,
+        mip_level: u32,
+    ) {
+        let width = (dst.extent.width >> mip_level).max(1);
+        let height = (dst.extent.height >> mip_level).max(1);
 
         let image_subresource = vk::ImageSubresourceLayers::default()
             .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .mip_level(mip_level)
             .layer_count(1);
 
         let copy_info = vk::BufferImageCopy::default()
@@ -619,6 +704,50 @@ impl CommandBuffer {
         image.layout = dst_layout
     }
 
+    /// Make compute shader writes visible to the next dispatches
+    pub fn compute_barrier(&mut self) {
+        tracing::trace!(target: logger::SYNC, "Compute barrier");
+
+        let barrier_info = vk::MemoryBarrier2::default()
+            .src_access_mask(vk::AccessFlags2::SHADER_WRITE)
+            .dst_access_mask(vk::AccessFlags2::SHADER_READ | vk::AccessFlags2::SHADER_WRITE)
+            .src_stage_mask(vk::PipelineStageFlags2::COMPUTE_SHADER)
+            .dst_stage_mask(vk::PipelineStageFlags2::COMPUTE_SHADER);
+
+        let dep_info =
+            vk::DependencyInfo::default().memory_barriers(std::slice::from_ref(&barrier_info));
+
+        unsafe {
+            self.device
+                .raw()
+                .cmd_pipeline_barrier2(self.command_buffer, &dep_info);
+        }
+    }
+
+    /// Make draw commands written by compute shaders visible to indirect draws
+    pub fn indirect_barrier(&mut self) {
+        tracing::trace!(target: logger::SYNC, "Indirect barrier");
+
+        let barrier_info = vk::MemoryBarrier2::default()
+            .src_access_mask(vk::AccessFlags2::SHADER_WRITE)
+            .dst_access_mask(
+                vk::AccessFlags2::INDIRECT_COMMAND_READ | vk::AccessFlags2::SHADER_STORAGE_READ,
+            )
+            .src_stage_mask(vk::PipelineStageFlags2::COMPUTE_SHADER)
+            .dst_stage_mask(
+                vk::PipelineStageFlags2::DRAW_INDIRECT | vk::PipelineStageFlags2::VERTEX_SHADER,
+            );
+
+        let dep_info =
+            vk::DependencyInfo::default().memory_barriers(std::slice::from_ref(&barrier_info));
+
+        unsafe {
+            self.device
+                .raw()
+                .cmd_pipeline_barrier2(self.command_buffer, &dep_info);
+        }
+    }
+
     pub fn end_render_pass(&mut self) {
         unsafe {
             self.device.raw().cmd_end_render_pass(self.command_buffer);
@@ -634,39 +763,42 @@ impl CommandBuffer {
         }
     }
 
-    pub fn submit2(&self, wait: Option<&Semaphore>, signal: Option<&Semaphore>, fence: &Fence) {
+    pub fn submit2(
+        &self,
+        wait: Option<&Semaphore>,
+        signal: Option<&Semaphore>,
+        fence: &Fence,
+    ) -> Result<(), VulkanError> {
+        let wait: Vec<SemaphoreSubmit> = wait
+            .map(|wait| SemaphoreSubmit::binary(wait, PipelineStage::ColorAttachmentOutput))
+            .into_iter()
+            .collect();
+        let signal: Vec<SemaphoreSubmit> = signal
+            .map(|signal| SemaphoreSubmit::binary(signal, PipelineStage::AllGraphics))
+            .into_iter()
+            .collect();
+
+        self.submit(&wait, &signal, fence)
+    }
+
+    /// Submit to the queue with binary or timeline semaphores dependencies
+    pub fn submit(
+        &self,
+        wait: &[SemaphoreSubmit],
+        signal: &[SemaphoreSubmit],
+        fence: &Fence,
+    ) -> Result<(), VulkanError> {
         let command_info = vk::CommandBufferSubmitInfo::default()
             .command_buffer(self.command_buffer)
             .device_mask(0);
 
-        let mut submit_info =
-            vk::SubmitInfo2::default().command_buffer_infos(std::slice::from_ref(&command_info));
-
-        let mut wait_info = Vec::new();
-        if let Some(wait) = wait {
-            wait_info.push(
-                vk::SemaphoreSubmitInfo::default()
-                    .stage_mask(vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT_KHR)
-                    .semaphore(wait.raw())
-                    .device_index(0)
-                    .value(1),
-            );
+        let wait_info: Vec<_> = wait.iter().map(SemaphoreSubmit::info).collect();
+        let signal_info: Vec<_> = signal.iter().map(SemaphoreSubmit::info).collect();
 
-            submit_info = submit_info.wait_semaphore_infos(&wait_info);
-        };
-
-        let mut signal_info = Vec::new();
-        if let Some(signal) = signal {
-            signal_info.push(
-                vk::SemaphoreSubmitInfo::default()
-                    .stage_mask(vk::PipelineStageFlags2::ALL_GRAPHICS)
-                    .semaphore(signal.raw())
-                    .device_index(0)
-                    .value(1),
-            );
-
-            submit_info = submit_info.signal_semaphore_infos(&signal_info);
-        };
+        let submit_info = vk::SubmitInfo2::default()
+            .command_buffer_infos(std::slice::from_ref(&command_info))
+            .wait_semaphore_infos(&wait_info)
+            .signal_semaphore_infos(&signal_info);
 
         unsafe {
             self.device
@@ -676,7 +808,127 @@ impl CommandBuffer {
                     std::slice::from_ref(&submit_info),
                     fence.raw(),
                 )
-                .unwrap();
+                .map_err(|err| self.device.map_error(err))
+        }
+    }
+
+    pub fn queue_family(&self) -> u32 {
+        self.queue.family.index
+    }
+
+    /// Release buffer ownership to another queue family. Must be matched by `acquire_buffer`
+    pub fn release_buffer(&mut self, buffer: &Buffer, dst_family: u32) {
+        tracing::trace!(target: logger::SYNC, "Release [{}] to family {}", buffer.label(), dst_family);
+
+        let barrier_info = vk::BufferMemoryBarrier2::default()
+            .src_queue_family_index(self.queue.family.index)
+            .dst_queue_family_index(dst_family)
+            .src_access_mask(vk::AccessFlags2::MEMORY_WRITE)
+            .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .buffer(buffer.raw())
+            .offset(0)
+            .size(vk::WHOLE_SIZE);
+
+        self.buffer_barrier(&barrier_info);
+    }
+
+    pub fn acquire_buffer(&mut self, buffer: &Buffer, src_family: u32) {
+        tracing::trace!(target: logger::SYNC, "Acquire [{}] from family {}", buffer.label(), src_family);
+
+        let barrier_info = vk::BufferMemoryBarrier2::default()
+            .src_queue_family_index(src_family)
+            .dst_queue_family_index(self.queue.family.index)
+            .dst_access_mask(vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .buffer(buffer.raw())
+            .offset(0)
+            .size(vk::WHOLE_SIZE);
+
+        self.buffer_barrier(&barrier_info);
+    }
+
+    fn buffer_barrier(&mut self, barrier_info: &vk::BufferMemoryBarrier2) {
+        let dep_info = vk::DependencyInfo::default()
+            .buffer_memory_barriers(std::slice::from_ref(barrier_info));
+
+        unsafe {
+            self.device
+                .raw()
+                .cmd_pipeline_barrier2(self.command_buffer, &dep_info);
+        }
+    }
+
+    /// Transition image layout and release ownership to another queue family.
+    /// Returns the previous layout to be used by `acquire_image`
+    pub fn release_image(
+        &mut self,
+        image: &mut Image,
+        dst_layout: ImageLayout,
+        dst_family: u32,
+    ) -> ImageLayout {
+        tracing::trace!(target: logger::SYNC, "Release [{}] to family {}", &image.label, dst_family);
+
+        let src_layout = image.layout;
+
+        let barrier_info = Self::image_barrier_info(image, src_layout, dst_layout)
+            .src_queue_family_index(self.queue.family.index)
+            .dst_queue_family_index(dst_family)
+            .src_access_mask(vk::AccessFlags2::MEMORY_WRITE)
+            .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS);
+
+        self.image_barrier(&barrier_info);
+
+        image.layout = dst_layout;
+
+        src_layout
+    }
+
+    /// Acquire an image released by `release_image` with the same layout transition
+    pub fn acquire_image(
+        &mut self,
+        image: &Image,
+        src_layout: ImageLayout,
+        dst_layout: ImageLayout,
+        src_family: u32,
+    ) {
+        tracing::trace!(target: logger::SYNC, "Acquire [{}] from family {}", &image.label, src_family);
+
+        let barrier_info = Self::image_barrier_info(image, src_layout, dst_layout)
+            .src_queue_family_index(src_family)
+            .dst_queue_family_index(self.queue.family.index)
+            .dst_access_mask(vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS);
+
+        self.image_barrier(&barrier_info);
+    }
+
+    fn image_barrier_info(
+        image: &Image,
+        src_layout: ImageLayout,
+        dst_layout: ImageLayout,
+    ) -> vk::ImageMemoryBarrier2<'static> {
+        vk::ImageMemoryBarrier2::default()
+            .old_layout(src_layout.into())
+            .new_layout(dst_layout.into())
+            .image(image.raw())
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(image.usage.into())
+                    .base_mip_level(0)
+                    .level_count(vk::REMAINING_MIP_LEVELS)
+                    .base_array_layer(0)
+                    .layer_count(vk::REMAINING_ARRAY_LAYERS),
+            )
+    }
+
+    fn image_barrier(&mut self, barrier_info: &vk::ImageMemoryBarrier2) {
+        let dep_info =
+            vk::DependencyInfo::default().image_memory_barriers(std::slice::from_ref(barrier_info));
+
+        unsafe {
+            self.device
+                .raw()
+                .cmd_pipeline_barrier2(self.command_buffer, &dep_info);
         }
     }
 }
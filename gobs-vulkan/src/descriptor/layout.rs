@@ -13,6 +13,7 @@ use crate::device::Device;
 pub enum DescriptorType {
     Uniform,
     UniformDynamic,
+    StorageBuffer,
     ImageSampler,
     StorageImage,
     Sampler,
@@ -24,6 +25,7 @@ impl From<DescriptorType> for vk::DescriptorType {
         match val {
             DescriptorType::Uniform => vk::DescriptorType::UNIFORM_BUFFER,
             DescriptorType::UniformDynamic => vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC,
+            DescriptorType::StorageBuffer => vk::DescriptorType::STORAGE_BUFFER,
             DescriptorType::ImageSampler => vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
             DescriptorType::StorageImage => vk::DescriptorType::STORAGE_IMAGE,
             DescriptorType::Sampler => vk::DescriptorType::SAMPLER,
@@ -106,21 +108,16 @@ impl DescriptorSetLayout {
         set: u32,
         push: bool,
     ) -> Arc<Self> {
-        let mut idx = 0;
-
         let vk_bindings: Vec<vk::DescriptorSetLayoutBinding> = bindings
             .iter()
-            .map(|binding| {
-                let b = vk::DescriptorSetLayoutBinding {
-                    binding: idx,
-                    descriptor_type: binding.ty.into(),
-                    descriptor_count: binding.count,
-                    p_immutable_samplers: ptr::null(),
-                    stage_flags: binding.stage.into(),
-                    _marker: std::marker::PhantomData,
-                };
-                idx += binding.count;
-                b
+            .enumerate()
+            .map(|(idx, binding)| vk::DescriptorSetLayoutBinding {
+                binding: idx as u32,
+                descriptor_type: binding.ty.into(),
+                descriptor_count: binding.count,
+                p_immutable_samplers: ptr::null(),
+                stage_flags: binding.stage.into(),
+                _marker: std::marker::PhantomData,
             })
             .collect();
 
@@ -23,6 +23,7 @@ struct ResourceInfo {
 enum ResourceInfoType {
     Buffer(vk::DescriptorBufferInfo),
     DynamicBuffer(vk::DescriptorBufferInfo),
+    StorageBuffer(vk::DescriptorBufferInfo),
     Image(vk::DescriptorImageInfo),
     SampledImage(vk::DescriptorImageInfo),
     ImageCombined(vk::DescriptorImageInfo),
@@ -98,6 +99,28 @@ impl DescriptorSetUpdates {
         self
     }
 
+    pub fn bind_storage_buffer(
+        mut self,
+        binding: u32,
+        index: u32,
+        buffer: &Buffer,
+        start: u64,
+        len: usize,
+    ) -> Self {
+        let buffer_info = vk::DescriptorBufferInfo::default()
+            .buffer(buffer.raw())
+            .offset(start)
+            .range(len as u64);
+
+        self.updates.push(ResourceInfo {
+            binding,
+            index,
+            ty: ResourceInfoType::StorageBuffer(buffer_info),
+        });
+
+        self
+    }
+
     pub fn bind_dynamic_buffer(
         mut self,
         binding: u32,
@@ -209,6 +232,7 @@ impl DescriptorSetUpdates {
                         ResourceInfoType::DynamicBuffer(_) => {
                             vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC
                         }
+                        ResourceInfoType::StorageBuffer(_) => vk::DescriptorType::STORAGE_BUFFER,
                         ResourceInfoType::Image(_) => vk::DescriptorType::STORAGE_IMAGE,
                         ResourceInfoType::SampledImage(_) => vk::DescriptorType::SAMPLED_IMAGE,
                         ResourceInfoType::ImageCombined(_) => {
@@ -228,6 +252,9 @@ impl DescriptorSetUpdates {
                     ResourceInfoType::DynamicBuffer(buffer_info) => {
                         DescriptorInfo::BufferInfo(*buffer_info)
                     }
+                    ResourceInfoType::StorageBuffer(buffer_info) => {
+                        DescriptorInfo::BufferInfo(*buffer_info)
+                    }
                     ResourceInfoType::ImageCombined(image_info) => {
                         DescriptorInfo::ImageInfo(*image_info)
                     }
@@ -1,8 +1,9 @@
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use ash::{
-    ext::debug_utils,
+    ext::{debug_utils, device_fault, memory_budget},
     khr::{push_descriptor, swapchain},
     vk::{self, FormatFeatureFlags},
 };
@@ -12,10 +13,13 @@ use gobs_core::{ImageFormat, logger};
 use crate::{
     Wrap,
     error::VulkanError,
+    fault::DeviceFault,
     feature::Features,
     images::{ImageUsage, VkFormat},
     instance::Instance,
+    memory::MemoryHeap,
     physical::PhysicalDevice,
+    pipelines::{PipelineCache, PipelineCacheHeader},
     queue::{Queue, QueueFamily},
     surface::Surface,
 };
@@ -27,9 +31,16 @@ pub struct Device {
     pub p_device: PhysicalDevice,
     pub(crate) debug_utils_device: debug_utils::Device,
     pub(crate) push_descriptor_device: push_descriptor::Device,
+    device_fault_device: Option<device_fault::Device>,
+    lost: AtomicBool,
+    memory_budget: bool,
     pub features: Features,
     pub graphics_family: QueueFamily,
+    pub compute_family: QueueFamily,
     pub transfer_family: QueueFamily,
+    compute_queue_index: u32,
+    transfer_queue_index: u32,
+    pub pipeline_cache: PipelineCache,
 }
 
 impl Device {
@@ -39,45 +50,68 @@ impl Device {
         surface: Option<&Surface>,
     ) -> Result<Arc<Self>, VulkanError> {
         let (graphics_family, transfer_family) = p_device.find_family(surface);
-        tracing::debug!(target: logger::INIT, "Using queue families Graphics={:?}, Transfer={:?}", &graphics_family, &transfer_family);
+        let compute_family = p_device.find_compute_family().unwrap_or(graphics_family);
+        tracing::debug!(target: logger::INIT, "Using queue families Graphics={:?}, Compute={:?}, Transfer={:?}", &graphics_family, &compute_family, &transfer_family);
 
-        let priorities = if transfer_family.index != graphics_family.index {
-            vec![1.0]
-        } else {
-            vec![1.0, 1.0]
-        };
+        let families = [graphics_family, compute_family, transfer_family];
+        let slots = Self::queue_slots(&families);
+
+        // one create info per family with enough queues for the highest slot
+        let mut queue_counts: Vec<(u32, u32)> = vec![];
+        for (family, slot) in families.iter().zip(&slots) {
+            match queue_counts
+                .iter_mut()
+                .find(|(index, _)| *index == family.index)
+            {
+                Some((_, count)) => *count = (*count).max(slot + 1),
+                None => queue_counts.push((family.index, slot + 1)),
+            }
+        }
 
-        let mut queues = vec![];
+        let priorities = vec![1.0; families.len()];
 
-        let queue_info = vk::DeviceQueueCreateInfo::default()
-            .queue_family_index(graphics_family.index)
-            .queue_priorities(&priorities);
-        queues.push(queue_info);
+        let queues: Vec<_> = queue_counts
+            .iter()
+            .map(|(index, count)| {
+                vk::DeviceQueueCreateInfo::default()
+                    .queue_family_index(*index)
+                    .queue_priorities(&priorities[..*count as usize])
+            })
+            .collect();
 
-        if transfer_family.index != graphics_family.index {
-            let queue_info = vk::DeviceQueueCreateInfo::default()
-                .queue_family_index(transfer_family.index)
-                .queue_priorities(&priorities);
-            queues.push(queue_info);
+        let features = Features::from_device(&instance, &p_device);
+
+        // fault info can only be queried with the extension enabled
+        let device_fault = features.supports_device_fault()
+            && p_device.supports_extension(&instance, device_fault::NAME);
+
+        let mut extensions = vec![swapchain::NAME.as_ptr(), push_descriptor::NAME.as_ptr()];
+        if device_fault {
+            extensions.push(device_fault::NAME.as_ptr());
         }
 
-        let extensions = [swapchain::NAME.as_ptr(), push_descriptor::NAME.as_ptr()];
+        let memory_budget = p_device.supports_extension(&instance, memory_budget::NAME);
+        if memory_budget {
+            extensions.push(memory_budget::NAME.as_ptr());
+        }
 
-        let features = Features::from_device(&instance, &p_device);
         let features10 = features.features10();
         let mut features11 = features.features11();
         let mut features12 = features.features12();
         let mut features13 = features.features13();
         let mut fault_features = features.fault_features();
 
-        let device_info = vk::DeviceCreateInfo::default()
+        let mut device_info = vk::DeviceCreateInfo::default()
             .queue_create_infos(&queues)
             .enabled_extension_names(&extensions)
             .enabled_features(&features10)
             .push_next(&mut features11)
             .push_next(&mut features12)
-            .push_next(&mut features13)
-            .push_next(&mut fault_features);
+            .push_next(&mut features13);
+
+        if device_fault {
+            device_info = device_info.push_next(&mut fault_features);
+        }
 
         let device: ash::Device = unsafe {
             tracing::debug!(target: logger::INIT, "Create device");
@@ -91,30 +125,85 @@ impl Device {
 
         let push_descriptor_device = push_descriptor::Device::new(&instance.instance, &device);
 
+        let device_fault_device =
+            device_fault.then(|| device_fault::Device::new(&instance.instance, &device));
+
+        let pipeline_cache = PipelineCache::new(&device);
+
         Ok(Arc::new(Device {
             instance,
             device,
             p_device,
             debug_utils_device,
             push_descriptor_device,
+            device_fault_device,
+            lost: AtomicBool::new(false),
+            memory_budget,
             features,
             graphics_family,
+            compute_family,
             transfer_family,
+            compute_queue_index: slots[1],
+            transfer_queue_index: slots[2],
+            pipeline_cache,
         }))
     }
 
+    /// Queue index of each family in its queue family. Families sharing an index
+    /// get distinct queues when the family has enough of them.
+    fn queue_slots(families: &[QueueFamily]) -> Vec<u32> {
+        families
+            .iter()
+            .enumerate()
+            .map(|(i, family)| {
+                let previous = families[..i]
+                    .iter()
+                    .filter(|f| f.index == family.index)
+                    .count() as u32;
+
+                previous.min(family.size.saturating_sub(1))
+            })
+            .collect()
+    }
+
     pub fn graphics_queue(self: Arc<Self>) -> Arc<Queue> {
         Queue::new(self.clone(), self.graphics_family, 0)
     }
 
+    pub fn compute_queue(self: Arc<Self>) -> Arc<Queue> {
+        Queue::new(self.clone(), self.compute_family, self.compute_queue_index)
+    }
+
     pub fn transfer_queue(self: Arc<Self>) -> Arc<Queue> {
-        let transfer_queue_index = if self.transfer_family.index != self.graphics_family.index {
-            0
+        Queue::new(
+            self.clone(),
+            self.transfer_family,
+            self.transfer_queue_index,
+        )
+    }
+
+    /// Compute work can run concurrently with the graphics queue
+    pub fn async_compute(&self) -> bool {
+        self.compute_family.index != self.graphics_family.index || self.compute_queue_index != 0
+    }
+
+    /// Families sharing storage resources without ownership transfers
+    pub fn concurrent_families(&self) -> Vec<u32> {
+        if self.compute_family.index != self.graphics_family.index {
+            vec![self.graphics_family.index, self.compute_family.index]
         } else {
-            1
-        };
+            vec![self.graphics_family.index]
+        }
+    }
+
+    /// Distinct families of the graphics, compute and transfer queues
+    pub fn queue_families(&self) -> Vec<u32> {
+        let mut families = self.concurrent_families();
+        if !families.contains(&self.transfer_family.index) {
+            families.push(self.transfer_family.index);
+        }
 
-        Queue::new(self.clone(), self.transfer_family, transfer_queue_index)
+        families
     }
 
     pub(crate) fn instance(&self) -> Arc<Instance> {
@@ -122,11 +211,68 @@ impl Device {
     }
 
     pub fn wait(&self) {
-        if let Err(vk::Result::ERROR_DEVICE_LOST) = unsafe { self.device.device_wait_idle() } {
-            tracing::error!("Wait idle: device lost");
+        if let Err(err) = unsafe { self.device.device_wait_idle() } {
+            tracing::error!(target: logger::SYNC, "Wait idle: {}", self.map_error(err));
+        }
+    }
+
+    /// Convert a failed call result. `ERROR_DEVICE_LOST` flags the device as lost
+    pub fn map_error(&self, result: vk::Result) -> VulkanError {
+        if result == vk::Result::ERROR_DEVICE_LOST {
+            if !self.lost.swap(true, Ordering::Relaxed) {
+                tracing::error!(target: logger::RENDER, "Device lost");
+            }
+
+            VulkanError::DeviceLost
+        } else {
+            VulkanError::VkResult(result)
         }
     }
 
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Fault info reported by the driver once the device is lost
+    pub fn fault_info(&self) -> Option<DeviceFault> {
+        if !self.is_lost() {
+            return None;
+        }
+
+        self.device_fault_device
+            .as_ref()
+            .and_then(DeviceFault::query)
+    }
+
+    /// Memory heaps with their current usage and budget if VK_EXT_memory_budget is available
+    pub fn memory_heaps(&self) -> Vec<MemoryHeap> {
+        let mut budget_props = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let mut props2 = vk::PhysicalDeviceMemoryProperties2::default();
+        if self.memory_budget {
+            props2 = props2.push_next(&mut budget_props);
+        }
+
+        unsafe {
+            self.instance
+                .instance
+                .get_physical_device_memory_properties2(self.p_device.raw(), &mut props2);
+        }
+
+        let memory_budget = self.memory_budget;
+        let heaps = props2.memory_properties.memory_heaps_as_slice().to_vec();
+
+        heaps
+            .iter()
+            .enumerate()
+            .map(|(i, heap)| MemoryHeap {
+                size: heap.size,
+                device_local: heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL),
+                usage: memory_budget.then_some(budget_props.heap_usage[i]),
+                budget: memory_budget.then_some(budget_props.heap_budget[i]),
+            })
+            .collect()
+    }
+
     pub fn raw(&self) -> &ash::Device {
         &self.device
     }
@@ -153,6 +299,18 @@ impl Device {
         }
     }
 
+    /// Load pipeline cache data saved by `save_pipeline_cache`
+    pub fn load_pipeline_cache(&self, bytes: &[u8]) -> bool {
+        match PipelineCacheHeader::new(&self.p_device).decode(bytes) {
+            Some(data) => self.pipeline_cache.load(&self.device, data),
+            None => false,
+        }
+    }
+
+    pub fn save_pipeline_cache(&self) -> Vec<u8> {
+        PipelineCacheHeader::new(&self.p_device).encode(&self.pipeline_cache.data(&self.device))
+    }
+
     pub fn cloned(&self) -> ash::Device {
         self.device.clone()
     }
@@ -169,7 +327,61 @@ impl Drop for Device {
         tracing::debug!(target: logger::MEMORY, "Drop device");
         unsafe {
             let _ = self.device.device_wait_idle();
+            self.pipeline_cache.destroy(&self.device);
             self.device.destroy_device(None);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::{device::Device, queue::QueueFamily};
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn family(index: u32, size: u32, graphics: bool) -> QueueFamily {
+        QueueFamily {
+            index,
+            size,
+            graphics_bit: graphics,
+            compute_bits: true,
+            transfer_bits: true,
+            present_bits: graphics,
+        }
+    }
+
+    #[test]
+    fn test_queue_slots() {
+        setup();
+
+        let graphics = family(0, 16, true);
+        let single = family(0, 1, true);
+        let compute = family(1, 2, false);
+
+        assert_eq!(
+            Device::queue_slots(&[graphics, graphics, graphics]),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            Device::queue_slots(&[single, single, single]),
+            vec![0, 0, 0]
+        );
+        assert_eq!(
+            Device::queue_slots(&[graphics, compute, compute]),
+            vec![0, 0, 1]
+        );
+        assert_eq!(
+            Device::queue_slots(&[single, compute, single]),
+            vec![0, 0, 0]
+        );
+    }
+}
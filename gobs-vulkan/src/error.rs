@@ -12,6 +12,10 @@ pub enum VulkanError {
     IOError(#[from] io::Error),
     #[error("null error")]
     NULError(#[from] NulError),
+    #[error("device lost")]
+    DeviceLost,
+    #[error("timeout")]
+    Timeout,
     #[error("vk result")]
     VkResult(#[from] ash::vk::Result),
 }
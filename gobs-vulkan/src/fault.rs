@@ -0,0 +1,120 @@
+use std::fmt::Display;
+
+use ash::{ext::device_fault, vk};
+
+use gobs_core::logger;
+
+/// Faulting address reported by the driver
+#[derive(Clone, Debug)]
+pub struct FaultAddress {
+    pub ty: String,
+    pub address: u64,
+    /// Power of two range around the address
+    pub precision: u64,
+}
+
+/// Vendor specific fault
+#[derive(Clone, Debug)]
+pub struct FaultVendorInfo {
+    pub description: String,
+    pub code: u64,
+    pub data: u64,
+}
+
+/// Fault info of a lost device (VK_EXT_device_fault)
+#[derive(Clone, Debug, Default)]
+pub struct DeviceFault {
+    pub description: String,
+    pub addresses: Vec<FaultAddress>,
+    pub vendor_infos: Vec<FaultVendorInfo>,
+    /// Size of the vendor binary crash dump, not collected
+    pub vendor_binary_size: u64,
+}
+
+impl DeviceFault {
+    pub(crate) fn query(loader: &device_fault::Device) -> Option<Self> {
+        let get_fault_info = loader.fp().get_device_fault_info_ext;
+
+        let mut counts = vk::DeviceFaultCountsEXT::default();
+        if let Err(err) =
+            unsafe { get_fault_info(loader.device(), &mut counts, std::ptr::null_mut()) }.result()
+        {
+            tracing::warn!(target: logger::RENDER, "Cannot query device fault: {:?}", err);
+            return None;
+        }
+
+        let vendor_binary_size = counts.vendor_binary_size;
+        counts.vendor_binary_size = 0;
+
+        let mut addresses =
+            vec![vk::DeviceFaultAddressInfoEXT::default(); counts.address_info_count as usize];
+        let mut vendor_infos =
+            vec![vk::DeviceFaultVendorInfoEXT::default(); counts.vendor_info_count as usize];
+
+        let mut info = vk::DeviceFaultInfoEXT {
+            p_address_infos: addresses.as_mut_ptr(),
+            p_vendor_infos: vendor_infos.as_mut_ptr(),
+            ..Default::default()
+        };
+
+        let result = unsafe { get_fault_info(loader.device(), &mut counts, &mut info) };
+        if result != vk::Result::SUCCESS && result != vk::Result::INCOMPLETE {
+            tracing::warn!(target: logger::RENDER, "Cannot query device fault: {:?}", result);
+            return None;
+        }
+
+        addresses.truncate(counts.address_info_count as usize);
+        vendor_infos.truncate(counts.vendor_info_count as usize);
+
+        Some(Self {
+            description: c_str(info.description_as_c_str()),
+            addresses: addresses
+                .iter()
+                .map(|address| FaultAddress {
+                    ty: format!("{:?}", address.address_type),
+                    address: address.reported_address,
+                    precision: address.address_precision,
+                })
+                .collect(),
+            vendor_infos: vendor_infos
+                .iter()
+                .map(|vendor| FaultVendorInfo {
+                    description: c_str(vendor.description_as_c_str()),
+                    code: vendor.vendor_fault_code,
+                    data: vendor.vendor_fault_data,
+                })
+                .collect(),
+            vendor_binary_size,
+        })
+    }
+}
+
+fn c_str(s: Result<&std::ffi::CStr, std::ffi::FromBytesUntilNulError>) -> String {
+    s.map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+impl Display for DeviceFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Device fault: {}", self.description)?;
+        for address in &self.addresses {
+            writeln!(
+                f,
+                "  Address {}: {:#x} (precision {:#x})",
+                address.ty, address.address, address.precision
+            )?;
+        }
+        for vendor in &self.vendor_infos {
+            writeln!(
+                f,
+                "  Vendor fault {}: code={:#x}, data={:#x}",
+                vendor.description, vendor.code, vendor.data
+            )?;
+        }
+        if self.vendor_binary_size > 0 {
+            writeln!(f, "  Vendor binary: {} bytes", self.vendor_binary_size)?;
+        }
+
+        Ok(())
+    }
+}
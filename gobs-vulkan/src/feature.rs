@@ -16,10 +16,11 @@ bitflags! {
         const ShaderDrawParameters = 1 << 5;
         const DeviceFault = 1 << 6;
         const ScalarBlockLayout = 1 << 7;
+        const DrawIndirectCount = 1 << 8;
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Features {
     enabled_features: Feature,
 }
@@ -82,6 +83,10 @@ impl Features {
             Feature::ScalarBlockLayout,
             features12.scalar_block_layout == 1,
         );
+        enabled_features.set(
+            Feature::DrawIndirectCount,
+            features10.multi_draw_indirect == 1 && features12.draw_indirect_count == 1,
+        );
         enabled_features.set(Feature::DynamicRendering, features13.dynamic_rendering == 1);
         enabled_features.set(Feature::Synchronization2, features13.synchronization2 == 1);
         enabled_features.set(Feature::DeviceFault, fault_features.device_fault == 1);
@@ -93,9 +98,39 @@ impl Features {
         self.enabled_features.contains(expected.enabled_features)
     }
 
+    /// Expected features that are not in this set
+    pub fn missing(&self, expected: &Self) -> Self {
+        Self {
+            enabled_features: expected.enabled_features.difference(self.enabled_features),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.enabled_features
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Required for wireframe (line) polygon mode
+    pub fn supports_fill_mode_non_solid(&self) -> bool {
+        self.enabled_features.contains(Feature::FillModeNonSolid)
+    }
+
+    /// Required for GPU-driven rendering with indirect draws
+    pub fn supports_draw_indirect_count(&self) -> bool {
+        self.enabled_features.contains(Feature::DrawIndirectCount)
+    }
+
+    /// Required to query fault info when the device is lost
+    pub fn supports_device_fault(&self) -> bool {
+        self.enabled_features.contains(Feature::DeviceFault)
+    }
+
     pub fn features10(&self) -> vk::PhysicalDeviceFeatures {
         vk::PhysicalDeviceFeatures::default()
             .fill_mode_non_solid(self.enabled_features.contains(Feature::FillModeNonSolid))
+            .multi_draw_indirect(self.enabled_features.contains(Feature::DrawIndirectCount))
     }
 
     pub fn features11(&'_ self) -> vk::PhysicalDeviceVulkan11Features<'_> {
@@ -116,6 +151,7 @@ impl Features {
                 self.enabled_features.contains(Feature::DescriptorIndexing),
             )
             .scalar_block_layout(self.enabled_features.contains(Feature::ScalarBlockLayout))
+            .draw_indirect_count(self.enabled_features.contains(Feature::DrawIndirectCount))
     }
 
     pub fn features13(&'_ self) -> vk::PhysicalDeviceVulkan13Features<'_> {
@@ -178,4 +214,10 @@ impl Features {
 
         self
     }
+
+    pub fn draw_indirect_count(mut self) -> Self {
+        self.enabled_features.set(Feature::DrawIndirectCount, true);
+
+        self
+    }
 }
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use gobs_core::logger;
 
-use crate::{device::Device, feature::Features, instance::Instance};
+use crate::{device::Device, feature::Features, instance::Instance, physical::AdapterSelector};
 
 pub struct Context {
     pub device: Arc<Device>,
@@ -13,15 +13,16 @@ impl Context {
         let instance = Instance::new(name, 1, None, false).expect("Failed to init Instance");
 
         let expected_features = Features::default()
-            .fill_mode_non_solid()
             .shader_draw_parameters()
             .buffer_device_address()
             .descriptor_indexing()
             .dynamic_rendering()
             .synchronization2();
 
-        let physical_device = instance
-            .find_adapter(&expected_features, None)
+        let selector = AdapterSelector::from_env().unwrap_or_default();
+
+        let (_, physical_device) = instance
+            .select_adapter(&selector, &expected_features, None)
             .expect("Find suitable adapter");
 
         tracing::info!(target: logger::RENDER, "Using adapter {}", physical_device.name);
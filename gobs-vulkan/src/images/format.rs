@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use ash::vk;
 
 use gobs_core::ImageFormat;
@@ -42,6 +44,9 @@ impl From<ImageFormat> for VkFormat {
             ImageFormat::R32g32b32Sfloat => VkFormat(vk::Format::R32G32B32_SFLOAT),
             ImageFormat::R32g32b32a32Sfloat => VkFormat(vk::Format::R32G32B32A32_SFLOAT),
             ImageFormat::D32Sfloat => VkFormat(vk::Format::D32_SFLOAT),
+            ImageFormat::A2b10g10r10UnormPack32 => VkFormat(vk::Format::A2B10G10R10_UNORM_PACK32),
+            ImageFormat::A2r10g10b10UnormPack32 => VkFormat(vk::Format::A2R10G10B10_UNORM_PACK32),
+            ImageFormat::R5g6b5UnormPack16 => VkFormat(vk::Format::R5G6B5_UNORM_PACK16),
             _ => panic!("Format not implemented: {value:?}"),
         }
     }
@@ -59,19 +64,60 @@ impl From<vk::Format> for VkFormat {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ColorSpace {
     SrgbNonlinear,
+    /// scRGB, linear extended range (requires VK_EXT_swapchain_colorspace)
+    ExtendedSrgbLinear,
+    /// HDR10 with the ST2084 (PQ) transfer function (requires VK_EXT_swapchain_colorspace)
+    Hdr10St2084,
+    DisplayP3Nonlinear,
+    Unsupported,
+}
+
+impl ColorSpace {
+    pub fn is_hdr(&self) -> bool {
+        matches!(
+            self,
+            ColorSpace::ExtendedSrgbLinear | ColorSpace::Hdr10St2084
+        )
+    }
+}
+
+impl FromStr for ColorSpace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "srgb" => Ok(ColorSpace::SrgbNonlinear),
+            "scrgb" => Ok(ColorSpace::ExtendedSrgbLinear),
+            "hdr10" => Ok(ColorSpace::Hdr10St2084),
+            "display_p3" => Ok(ColorSpace::DisplayP3Nonlinear),
+            _ => Err(format!("invalid color space: {s}")),
+        }
+    }
 }
 
 impl From<vk::ColorSpaceKHR> for ColorSpace {
-    fn from(_color_space: vk::ColorSpaceKHR) -> ColorSpace {
-        ColorSpace::SrgbNonlinear
+    fn from(color_space: vk::ColorSpaceKHR) -> ColorSpace {
+        match color_space {
+            vk::ColorSpaceKHR::SRGB_NONLINEAR => ColorSpace::SrgbNonlinear,
+            vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => ColorSpace::ExtendedSrgbLinear,
+            vk::ColorSpaceKHR::HDR10_ST2084_EXT => ColorSpace::Hdr10St2084,
+            vk::ColorSpaceKHR::DISPLAY_P3_NONLINEAR_EXT => ColorSpace::DisplayP3Nonlinear,
+            _ => ColorSpace::Unsupported,
+        }
     }
 }
 
 impl From<ColorSpace> for vk::ColorSpaceKHR {
-    fn from(_val: ColorSpace) -> Self {
-        vk::ColorSpaceKHR::SRGB_NONLINEAR
+    fn from(val: ColorSpace) -> Self {
+        match val {
+            ColorSpace::SrgbNonlinear => vk::ColorSpaceKHR::SRGB_NONLINEAR,
+            ColorSpace::ExtendedSrgbLinear => vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+            ColorSpace::Hdr10St2084 => vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+            ColorSpace::DisplayP3Nonlinear => vk::ColorSpaceKHR::DISPLAY_P3_NONLINEAR_EXT,
+            ColorSpace::Unsupported => panic!("Invalid color space: {val:?}"),
+        }
     }
 }
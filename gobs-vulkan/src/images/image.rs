@@ -107,6 +107,7 @@ pub struct Image {
     pub usage: ImageUsage,
     pub layout: ImageLayout,
     pub extent: ImageExtent2D,
+    pub mip_levels: u32,
     memory: Option<Memory>,
 }
 
@@ -118,16 +119,28 @@ impl Image {
         usage: ImageUsage,
         extent: ImageExtent2D,
         allocator: Arc<Allocator>,
+    ) -> Self {
+        Self::with_mip_levels(label, device, format, usage, extent, 1, allocator)
+    }
+
+    pub fn with_mip_levels(
+        label: &str,
+        device: Arc<Device>,
+        format: ImageFormat,
+        usage: ImageUsage,
+        extent: ImageExtent2D,
+        mip_levels: u32,
+        allocator: Arc<Allocator>,
     ) -> Self {
         let image_label = format!("[Image] {label}");
 
-        let image = Self::create_image(&device, extent, format, usage);
+        let image = Self::create_image(&device, extent, format, usage, mip_levels);
 
         debug::add_label(device.clone(), &image_label, image);
 
         let memory = allocator.allocate_image(image, &image_label);
 
-        let image_view = Self::create_image_view(device.clone(), image, format, usage);
+        let image_view = Self::create_image_view(device.clone(), image, format, usage, mip_levels);
 
         let view_label = format!("[Image View] {label}");
 
@@ -144,6 +157,7 @@ impl Image {
             usage,
             layout,
             extent,
+            mip_levels,
             memory: Some(memory), // swapchain images don't need manual memory allocation
         }
     }
@@ -160,7 +174,7 @@ impl Image {
 
         debug::add_label(device.clone(), &image_label, image);
 
-        let image_view = Self::create_image_view(device.clone(), image, format, usage);
+        let image_view = Self::create_image_view(device.clone(), image, format, usage, 1);
 
         let view_label = format!("[Image View] {label}");
         debug::add_label(device.clone(), &view_label, image_view);
@@ -176,6 +190,7 @@ impl Image {
             usage,
             layout,
             extent,
+            mip_levels: 1,
             memory: None,
         }
     }
@@ -184,12 +199,21 @@ impl Image {
         self.layout = ImageLayout::Undefined;
     }
 
+    /// Allocated memory size. Swapchain images are not allocated
+    pub fn memory_size(&self) -> u64 {
+        self.memory.as_ref().map(Memory::size).unwrap_or_default()
+    }
+
     fn create_image(
         device: &Arc<Device>,
         extent: ImageExtent2D,
         format: ImageFormat,
         usage: ImageUsage,
+        mip_levels: u32,
     ) -> vk::Image {
+        let usage_flags: vk::ImageUsageFlags = usage.into();
+        let families = device.concurrent_families();
+
         let image_info = vk::ImageCreateInfo::default()
             .image_type(vk::ImageType::TYPE_2D)
             .extent(
@@ -198,15 +222,25 @@ impl Image {
                     .height(extent.height)
                     .depth(1),
             )
-            .mip_levels(1)
+            .mip_levels(mip_levels)
             .array_layers(1)
             .format(VkFormat::from(format).into())
             .tiling(usage.into())
             .initial_layout(vk::ImageLayout::UNDEFINED)
-            .usage(usage.into())
+            .usage(usage_flags)
             .sharing_mode(vk::SharingMode::EXCLUSIVE)
             .samples(vk::SampleCountFlags::TYPE_1);
 
+        // storage images are shared with the async compute queue
+        let image_info = if usage_flags.contains(vk::ImageUsageFlags::STORAGE) && families.len() > 1
+        {
+            image_info
+                .sharing_mode(vk::SharingMode::CONCURRENT)
+                .queue_family_indices(&families)
+        } else {
+            image_info
+        };
+
         unsafe { device.raw().create_image(&image_info, None).unwrap() }
     }
 
@@ -215,6 +249,7 @@ impl Image {
         image: vk::Image,
         format: ImageFormat,
         usage: ImageUsage,
+        mip_levels: u32,
     ) -> vk::ImageView {
         let view_info = vk::ImageViewCreateInfo::default()
             .image(image)
@@ -224,7 +259,7 @@ impl Image {
                 vk::ImageSubresourceRange::default()
                     .aspect_mask(usage.into())
                     .base_mip_level(0)
-                    .level_count(1)
+                    .level_count(mip_levels)
                     .base_array_layer(0)
                     .layer_count(1),
             );
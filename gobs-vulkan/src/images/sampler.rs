@@ -33,7 +33,9 @@ impl Sampler {
     pub fn new(device: Arc<Device>, mag_filter: SamplerFilter, min_filter: SamplerFilter) -> Self {
         let sampler_info = vk::SamplerCreateInfo::default()
             .mag_filter(VkFilter::from(mag_filter).into())
-            .min_filter(VkFilter::from(min_filter).into());
+            .min_filter(VkFilter::from(min_filter).into())
+            .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+            .max_lod(vk::LOD_CLAMP_NONE);
 
         let sampler = unsafe { device.raw().create_sampler(&sampler_info, None).unwrap() };
 
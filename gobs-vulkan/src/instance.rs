@@ -1,9 +1,12 @@
 use std::borrow::Cow;
-use std::cmp::Ordering;
 use std::ffi::{CStr, CString};
 use std::sync::Arc;
 
-use ash::{ext::debug_utils, khr::surface, vk};
+use ash::{
+    ext::{debug_utils, swapchain_colorspace},
+    khr::surface,
+    vk,
+};
 use gobs_core::logger;
 use raw_window_handle::HasDisplayHandle;
 use winit::window::Window;
@@ -11,7 +14,7 @@ use winit::window::Window;
 use crate::error::VulkanError;
 use crate::{
     feature::Features,
-    physical::{PhysicalDevice, PhysicalDeviceType},
+    physical::{AdapterSelector, PhysicalDevice},
     surface::Surface,
 };
 
@@ -148,6 +151,15 @@ impl Instance {
             tracing::debug!(target: logger::INIT, "Available extensions: {:?}",
                     available_extensions.iter().map(|ext| ext.extension_name_as_c_str().unwrap()).collect::<Vec<&CStr>>());
 
+            // optional, exposes HDR color spaces on the surface
+            if window.is_some()
+                && available_extensions.iter().any(|avail_ext| {
+                    avail_ext.extension_name_as_c_str().unwrap() == swapchain_colorspace::NAME
+                })
+            {
+                extensions.push(swapchain_colorspace::NAME.as_ptr());
+            }
+
             extensions
                 .iter()
                 .filter(|ext| {
@@ -216,24 +228,52 @@ impl Instance {
         expected_features: &Features,
         surface: Option<&Surface>,
     ) -> Option<PhysicalDevice> {
-        let mut p_devices = PhysicalDevice::enumerate(self, surface);
-        let mut candidates = vec![];
+        self.select_adapter(&AdapterSelector::Auto, expected_features, surface)
+            .map(|(_, p_device)| p_device)
+    }
+
+    pub fn enumerate_adapters(&self, surface: Option<&Surface>) -> Vec<PhysicalDevice> {
+        PhysicalDevice::enumerate(self, surface)
+    }
+
+    /// Select a suitable adapter matching the selector and return it with its index.
+    /// Falls back to automatic selection if no suitable adapter matches
+    pub fn select_adapter(
+        &self,
+        selector: &AdapterSelector,
+        expected_features: &Features,
+        surface: Option<&Surface>,
+    ) -> Option<(usize, PhysicalDevice)> {
+        let p_devices = self.enumerate_adapters(surface);
 
         tracing::debug!(target: logger::INIT, "{} physical devices found", p_devices.len());
 
-        for p_device in p_devices.drain(..) {
-            if p_device.check_features(self, expected_features) {
-                candidates.push(p_device);
+        let mut candidates = p_devices
+            .into_iter()
+            .enumerate()
+            .filter(|(_, p_device)| {
+                p_device.supports_surface(surface)
+                    && p_device.check_features(self, expected_features)
+            })
+            .collect::<Vec<_>>();
+
+        if *selector != AdapterSelector::Auto {
+            match candidates
+                .iter()
+                .position(|(index, p_device)| selector.matches(*index, p_device))
+            {
+                Some(pos) => return Some(candidates.swap_remove(pos)),
+                None => {
+                    tracing::warn!(target: logger::INIT, "No suitable adapter matching {}, using default", selector)
+                }
             }
         }
 
-        candidates.into_iter().max_by(|_, device2| {
-            if device2.gpu_type == PhysicalDeviceType::DiscreteGpu {
-                Ordering::Less
-            } else {
-                Ordering::Greater
-            }
-        })
+        // first adapter of the preferred type
+        candidates
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, p_device)| p_device.gpu_type.rank())
     }
 
     pub fn raw(&self) -> &ash::Instance {
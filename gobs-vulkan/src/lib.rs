@@ -1,10 +1,12 @@
 pub mod alloc;
+pub mod breadcrumbs;
 pub mod buffers;
 pub mod command;
 pub(crate) mod debug;
 pub mod descriptor;
 pub mod device;
 pub mod error;
+pub mod fault;
 pub mod feature;
 pub mod framebuffer;
 pub mod images;
@@ -10,6 +10,17 @@ use gobs_core::logger;
 use crate::alloc::Allocator;
 use crate::device::Device;
 
+/// Usage of a memory heap. Usage is the memory allocated by this process and budget how much
+/// it can allocate, given the usage of other processes. Both are only reported with
+/// VK_EXT_memory_budget
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryHeap {
+    pub size: u64,
+    pub device_local: bool,
+    pub usage: Option<u64>,
+    pub budget: Option<u64>,
+}
+
 #[allow(unused)]
 pub struct Memory {
     pub device: Arc<Device>,
@@ -18,6 +29,14 @@ pub struct Memory {
 }
 
 impl Memory {
+    /// Size of the allocation, including alignment requirements
+    pub fn size(&self) -> u64 {
+        self.allocation
+            .as_ref()
+            .map(|allocation| allocation.size())
+            .unwrap_or_default()
+    }
+
     pub fn upload<T: Copy>(&mut self, entries: &[T], offset: usize) {
         let size = std::mem::size_of_val(entries) as u64;
 
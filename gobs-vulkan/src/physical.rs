@@ -1,4 +1,6 @@
 use std::ffi::CStr;
+use std::fmt::Display;
+use std::str::FromStr;
 
 use ash::vk;
 
@@ -10,7 +12,7 @@ use crate::instance::Instance;
 use crate::queue::QueueFamily;
 use crate::surface::Surface;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PhysicalDeviceType {
     Other,
     IntegratedGpu,
@@ -19,14 +21,143 @@ pub enum PhysicalDeviceType {
     Cpu,
 }
 
+impl PhysicalDeviceType {
+    /// Higher is preferred
+    pub(crate) fn rank(&self) -> u32 {
+        match self {
+            PhysicalDeviceType::DiscreteGpu => 4,
+            PhysicalDeviceType::IntegratedGpu => 3,
+            PhysicalDeviceType::VirtualGpu => 2,
+            PhysicalDeviceType::Cpu => 1,
+            PhysicalDeviceType::Other => 0,
+        }
+    }
+}
+
+impl FromStr for PhysicalDeviceType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "discrete" => Ok(PhysicalDeviceType::DiscreteGpu),
+            "integrated" => Ok(PhysicalDeviceType::IntegratedGpu),
+            "virtual" => Ok(PhysicalDeviceType::VirtualGpu),
+            "cpu" => Ok(PhysicalDeviceType::Cpu),
+            "other" => Ok(PhysicalDeviceType::Other),
+            _ => Err(format!("invalid device type: {s}")),
+        }
+    }
+}
+
+/// Adapter selection criteria
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AdapterSelector {
+    /// Prefer discrete GPUs, then integrated, virtual and software
+    #[default]
+    Auto,
+    /// Position in the list of enumerated adapters
+    Index(usize),
+    /// Case insensitive substring of the adapter name
+    Name(String),
+    Type(PhysicalDeviceType),
+}
+
+impl AdapterSelector {
+    /// Environment variable overriding the adapter selection (e.g. GOBS_ADAPTER=cpu)
+    pub const ENV: &'static str = "GOBS_ADAPTER";
+
+    pub fn from_env() -> Option<Self> {
+        std::env::var(Self::ENV)
+            .ok()
+            .and_then(|value| value.parse().ok())
+    }
+
+    pub fn matches(&self, index: usize, p_device: &PhysicalDevice) -> bool {
+        match self {
+            AdapterSelector::Auto => true,
+            AdapterSelector::Index(idx) => *idx == index,
+            AdapterSelector::Name(name) => {
+                p_device.name.to_lowercase().contains(&name.to_lowercase())
+            }
+            AdapterSelector::Type(ty) => p_device.gpu_type == *ty,
+        }
+    }
+}
+
+/// Parse "auto" (or empty), an index, a device type (discrete, integrated, virtual, cpu)
+/// or a name
+impl FromStr for AdapterSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.is_empty() || s.eq_ignore_ascii_case("auto") {
+            Ok(AdapterSelector::Auto)
+        } else if let Ok(index) = s.parse() {
+            Ok(AdapterSelector::Index(index))
+        } else if let Ok(ty) = s.parse() {
+            Ok(AdapterSelector::Type(ty))
+        } else {
+            Ok(AdapterSelector::Name(s.to_string()))
+        }
+    }
+}
+
+impl Display for AdapterSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdapterSelector::Auto => write!(f, "auto"),
+            AdapterSelector::Index(index) => write!(f, "#{index}"),
+            AdapterSelector::Name(name) => write!(f, "\"{name}\""),
+            AdapterSelector::Type(ty) => write!(f, "{ty:?}"),
+        }
+    }
+}
+
+/// Subset of the device limits
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    pub max_image_dimension_2d: u32,
+    pub max_push_constants_size: u32,
+    pub max_bound_descriptor_sets: u32,
+    pub max_per_stage_descriptor_sampled_images: u32,
+    pub max_memory_allocation_count: u32,
+    pub max_sampler_anisotropy: f32,
+    pub max_compute_work_group_count: [u32; 3],
+    pub max_compute_work_group_size: [u32; 3],
+    pub max_compute_work_group_invocations: u32,
+    pub timestamp_period: f32,
+}
+
+impl From<vk::PhysicalDeviceLimits> for Limits {
+    fn from(limits: vk::PhysicalDeviceLimits) -> Self {
+        Self {
+            max_image_dimension_2d: limits.max_image_dimension2_d,
+            max_push_constants_size: limits.max_push_constants_size,
+            max_bound_descriptor_sets: limits.max_bound_descriptor_sets,
+            max_per_stage_descriptor_sampled_images: limits.max_per_stage_descriptor_sampled_images,
+            max_memory_allocation_count: limits.max_memory_allocation_count,
+            max_sampler_anisotropy: limits.max_sampler_anisotropy,
+            max_compute_work_group_count: limits.max_compute_work_group_count,
+            max_compute_work_group_size: limits.max_compute_work_group_size,
+            max_compute_work_group_invocations: limits.max_compute_work_group_invocations,
+            timestamp_period: limits.timestamp_period,
+        }
+    }
+}
+
 /// A physical graphic card
 pub struct PhysicalDevice {
     p_device: vk::PhysicalDevice,
     pub name: String,
     pub gpu_type: PhysicalDeviceType,
+    pub driver: String,
+    pub api_version: String,
+    pub vram: u64,
+    pub limits: Limits,
     pub queue_families: Vec<QueueFamily>,
     pub(crate) props: vk::PhysicalDeviceProperties,
-    pub(crate) mem_props: vk::PhysicalDeviceMemoryProperties,
 }
 
 impl PhysicalDevice {
@@ -53,13 +184,25 @@ impl PhysicalDevice {
             _ => panic!("Invalid device type"),
         };
 
+        let driver = Self::get_driver(instance, p_device, &props);
+
+        let api_version = format!(
+            "{}.{}.{}",
+            vk::api_version_major(props.api_version),
+            vk::api_version_minor(props.api_version),
+            vk::api_version_patch(props.api_version)
+        );
+
         PhysicalDevice {
             name: String::from(name),
             gpu_type,
+            driver,
+            api_version,
+            vram: Self::get_vram(&mem_props),
+            limits: props.limits.into(),
             queue_families: Self::get_queue_families(&p_device, name, instance, surface),
             p_device,
             props,
-            mem_props,
         }
     }
 
@@ -92,26 +235,48 @@ impl PhysicalDevice {
         (graphics_family, transfer_family)
     }
 
+    /// Queue family dedicated to compute, used for async compute
+    pub fn find_compute_family(&self) -> Option<QueueFamily> {
+        self.queue_families
+            .iter()
+            .find(|family| family.compute_bits && !family.graphics_bit)
+            .copied()
+    }
+
+    pub fn features(&self, instance: &Instance) -> Features {
+        Features::from_device(instance, self)
+    }
+
+    pub fn supports_extension(&self, instance: &Instance, name: &CStr) -> bool {
+        let extensions = unsafe {
+            instance
+                .instance
+                .enumerate_device_extension_properties(self.p_device)
+                .unwrap_or_default()
+        };
+
+        extensions
+            .iter()
+            .any(|ext| ext.extension_name_as_c_str() == Ok(name))
+    }
+
+    /// A graphics queue must be able to present on the surface
+    pub fn supports_surface(&self, surface: Option<&Surface>) -> bool {
+        match surface {
+            Some(_) => self
+                .queue_families
+                .iter()
+                .any(|family| family.graphics_bit && family.present_bits),
+            None => self.queue_families.iter().any(|family| family.graphics_bit),
+        }
+    }
+
     pub fn check_features(&self, instance: &Instance, expected_features: &Features) -> bool {
         tracing::debug!(target: logger::INIT, "Checking device: {:?}", self.name);
 
         tracing::debug!(target: logger::INIT, "Device type: {:?}", self.props.device_type);
 
-        let vram = self
-            .mem_props
-            .memory_heaps_as_slice()
-            .iter()
-            .map(|heap| {
-                if heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL) {
-                    heap.size
-                } else {
-                    0
-                }
-            })
-            .max()
-            .unwrap_or(0);
-
-        tracing::debug!(target: logger::INIT, "VRAM size: {}", vram);
+        tracing::debug!(target: logger::INIT, "VRAM size: {}", self.vram);
 
         if self.props.api_version < vk::make_api_version(0, 1, 3, 0) {
             tracing::debug!(target: logger::INIT, "Reject: wrong version");
@@ -121,7 +286,7 @@ impl PhysicalDevice {
         let features = Features::from_device(instance, self);
 
         if !features.check_features(expected_features) {
-            tracing::debug!(target: logger::INIT, "Reject: missing features");
+            tracing::debug!(target: logger::INIT, "Reject: missing features {:?}", features.missing(expected_features).names());
             return false;
         }
 
@@ -130,6 +295,47 @@ impl PhysicalDevice {
         true
     }
 
+    fn get_vram(mem_props: &vk::PhysicalDeviceMemoryProperties) -> u64 {
+        mem_props
+            .memory_heaps_as_slice()
+            .iter()
+            .filter(|heap| heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+            .map(|heap| heap.size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn get_driver(
+        instance: &Instance,
+        p_device: vk::PhysicalDevice,
+        props: &vk::PhysicalDeviceProperties,
+    ) -> String {
+        // core in 1.2
+        if props.api_version < vk::make_api_version(0, 1, 2, 0) {
+            return format!("{:#x}", props.driver_version);
+        }
+
+        let mut driver_props = vk::PhysicalDeviceDriverProperties::default();
+        let mut props2 = vk::PhysicalDeviceProperties2::default().push_next(&mut driver_props);
+
+        unsafe {
+            instance
+                .instance
+                .get_physical_device_properties2(p_device, &mut props2);
+        }
+
+        let name = driver_props
+            .driver_name_as_c_str()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let info = driver_props
+            .driver_info_as_c_str()
+            .map(|info| info.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        format!("{name} {info}").trim().to_string()
+    }
+
     fn get_queue_families(
         p_device: &vk::PhysicalDevice,
         p_device_name: &str,
@@ -181,3 +387,27 @@ impl Wrap<vk::PhysicalDevice> for PhysicalDevice {
         self.p_device
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::physical::{AdapterSelector, PhysicalDeviceType};
+
+    #[test]
+    fn test_adapter_selector() {
+        assert_eq!("".parse(), Ok(AdapterSelector::Auto));
+        assert_eq!("Auto".parse(), Ok(AdapterSelector::Auto));
+        assert_eq!("1".parse(), Ok(AdapterSelector::Index(1)));
+        assert_eq!(
+            "cpu".parse(),
+            Ok(AdapterSelector::Type(PhysicalDeviceType::Cpu))
+        );
+        assert_eq!(
+            " Discrete ".parse(),
+            Ok(AdapterSelector::Type(PhysicalDeviceType::DiscreteGpu))
+        );
+        assert_eq!(
+            "llvmpipe".parse(),
+            Ok(AdapterSelector::Name("llvmpipe".to_string()))
+        );
+    }
+}
@@ -1,14 +1,22 @@
+mod cache;
 mod compute;
 mod graphics;
 mod layout;
 mod pipeline;
+mod reflect;
 mod shader;
 mod vertex_layout;
 
+pub use self::cache::{PipelineCache, PipelineCacheHeader};
 pub use self::compute::*;
 pub use self::graphics::*;
+pub(crate) use self::layout::PUSH_CONSTANT_STAGES;
 pub use self::layout::PipelineLayout;
 pub use self::pipeline::{Pipeline, PipelineStage, Rect2D, ShaderStage};
+pub use self::reflect::{
+    BlockLayout, BlockMember, EntryPoint, MemberType, ReflectedBinding, ReflectedDescriptorType,
+    ReflectionError, ShaderReflection, VertexInput,
+};
 pub use self::shader::{Shader, ShaderType};
 pub use self::vertex_layout::{
     VertexAttribute, VertexAttributeFormat, VertexLayout, VertexLayoutBinding,
@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use ash::vk;
+
+use gobs_core::logger;
+
+use crate::physical::PhysicalDevice;
+
+const CACHE_MAGIC: &[u8; 4] = b"GPLC";
+const CACHE_VERSION: u32 = 1;
+const HEADER_SIZE: usize = 44;
+
+/// Header of the serialized cache. Data is discarded if the device or driver changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PipelineCacheHeader {
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub driver_version: u32,
+    pub uuid: [u8; vk::UUID_SIZE],
+}
+
+impl PipelineCacheHeader {
+    pub fn new(p_device: &PhysicalDevice) -> Self {
+        Self {
+            vendor_id: p_device.props.vendor_id,
+            device_id: p_device.props.device_id,
+            driver_version: p_device.props.driver_version,
+            uuid: p_device.props.pipeline_cache_uuid,
+        }
+    }
+
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + data.len());
+
+        bytes.extend_from_slice(CACHE_MAGIC);
+        bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.vendor_id.to_le_bytes());
+        bytes.extend_from_slice(&self.device_id.to_le_bytes());
+        bytes.extend_from_slice(&self.driver_version.to_le_bytes());
+        bytes.extend_from_slice(&self.uuid);
+        bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(data);
+
+        bytes
+    }
+
+    /// Returns the cache data if the header matches
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Option<&'a [u8]> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != CACHE_MAGIC {
+            tracing::warn!(target: logger::INIT, "Invalid pipeline cache");
+            return None;
+        }
+
+        let read_u32 =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+        let version = read_u32(4);
+        let header = Self {
+            vendor_id: read_u32(8),
+            device_id: read_u32(12),
+            driver_version: read_u32(16),
+            uuid: bytes[20..36].try_into().unwrap(),
+        };
+        let len = u64::from_le_bytes(bytes[36..44].try_into().unwrap()) as usize;
+
+        if version != CACHE_VERSION || header != *self {
+            tracing::info!(target: logger::INIT, "Pipeline cache created by another device or driver version, discarded");
+            return None;
+        }
+
+        if bytes.len() - HEADER_SIZE != len {
+            tracing::warn!(target: logger::INIT, "Truncated pipeline cache");
+            return None;
+        }
+
+        Some(&bytes[HEADER_SIZE..])
+    }
+}
+
+/// Pipeline cache of a device, records cache hits using pipeline creation feedback
+pub struct PipelineCache {
+    cache: vk::PipelineCache,
+    hits: AtomicU32,
+    misses: AtomicU32,
+}
+
+impl PipelineCache {
+    pub(crate) fn new(device: &ash::Device) -> Self {
+        let cache_info = vk::PipelineCacheCreateInfo::default();
+
+        let cache = unsafe { device.create_pipeline_cache(&cache_info, None).unwrap() };
+
+        Self {
+            cache,
+            hits: AtomicU32::new(0),
+            misses: AtomicU32::new(0),
+        }
+    }
+
+    pub(crate) fn raw(&self) -> vk::PipelineCache {
+        self.cache
+    }
+
+    /// Merge previously saved data into the cache
+    pub(crate) fn load(&self, device: &ash::Device, data: &[u8]) -> bool {
+        let cache_info = vk::PipelineCacheCreateInfo::default().initial_data(data);
+
+        unsafe {
+            let Ok(src_cache) = device.create_pipeline_cache(&cache_info, None) else {
+                return false;
+            };
+
+            let result = device.merge_pipeline_caches(self.cache, &[src_cache]);
+            device.destroy_pipeline_cache(src_cache, None);
+
+            result.is_ok()
+        }
+    }
+
+    pub(crate) fn data(&self, device: &ash::Device) -> Vec<u8> {
+        unsafe {
+            device
+                .get_pipeline_cache_data(self.cache)
+                .unwrap_or_default()
+        }
+    }
+
+    pub(crate) fn record(&self, label: &str, feedback: &vk::PipelineCreationFeedback) {
+        if !feedback
+            .flags
+            .contains(vk::PipelineCreationFeedbackFlags::VALID)
+        {
+            return;
+        }
+
+        let hit = feedback
+            .flags
+            .contains(vk::PipelineCreationFeedbackFlags::APPLICATION_PIPELINE_CACHE_HIT);
+
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        tracing::debug!(target: logger::INIT, "Pipeline {} created in {}µs (cache hit: {})", label, feedback.duration / 1000, hit);
+    }
+
+    /// Number of pipelines found in the cache and compiled
+    pub fn stats(&self) -> (u32, u32) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    pub(crate) fn destroy(&self, device: &ash::Device) {
+        unsafe {
+            device.destroy_pipeline_cache(self.cache, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipelines::cache::PipelineCacheHeader;
+
+    #[test]
+    fn test_cache_header() {
+        let header = PipelineCacheHeader {
+            vendor_id: 0x10de,
+            device_id: 0x2684,
+            driver_version: 1,
+            uuid: [7; 16],
+        };
+
+        let data = [1, 2, 3, 4];
+        let bytes = header.encode(&data);
+        assert_eq!(header.decode(&bytes), Some(data.as_slice()));
+        assert!(header.decode(&bytes[..bytes.len() - 1]).is_none());
+        assert!(header.decode(&bytes[..10]).is_none());
+
+        let updated = PipelineCacheHeader {
+            driver_version: 2,
+            ..header
+        };
+        assert!(updated.decode(&bytes).is_none());
+
+        let other = PipelineCacheHeader {
+            uuid: [8; 16],
+            ..header
+        };
+        assert!(other.decode(&bytes).is_none());
+    }
+}
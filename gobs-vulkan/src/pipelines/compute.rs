@@ -41,20 +41,27 @@ impl ComputePipelineBuilder {
         let compute_stage = self.compute_stage.unwrap();
         let compute_stage_info = compute_stage.info();
 
+        let mut feedback = vk::PipelineCreationFeedback::default();
+        let mut feedback_info = vk::PipelineCreationFeedbackCreateInfo::default()
+            .pipeline_creation_feedback(&mut feedback);
+
         let pipeline_info = vk::ComputePipelineCreateInfo::default()
+            .push_next(&mut feedback_info)
             .stage(compute_stage_info)
             .layout(pipeline_layout.raw());
         let pipeline = unsafe {
             device
                 .raw()
                 .create_compute_pipelines(
-                    vk::PipelineCache::null(),
+                    device.pipeline_cache.raw(),
                     std::slice::from_ref(&pipeline_info),
                     None,
                 )
                 .unwrap()[0]
         };
 
+        device.pipeline_cache.record(&self.label, &feedback);
+
         let bind_point = vk::PipelineBindPoint::COMPUTE;
 
         Pipeline {
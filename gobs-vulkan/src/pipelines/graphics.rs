@@ -3,7 +3,7 @@ use std::sync::Arc;
 use ash::vk;
 use serde::{Deserialize, Serialize};
 
-use gobs_core::ImageFormat;
+use gobs_core::{ImageFormat, logger};
 
 use crate::images::VkFormat;
 use crate::pipelines::{Pipeline, PipelineLayout, Rect2D, Shader, ShaderStage, VertexLayout};
@@ -137,7 +137,7 @@ impl InputAssemblyState {
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub enum PolygonMode {
     #[default]
     Fill,
@@ -597,8 +597,17 @@ impl GraphicsPipelineBuilder {
         let input_assembly_state = InputAssemblyState::new(self.primitive_topology);
         let input_assembly_state_info = input_assembly_state.info();
 
+        let polygon_mode = if self.polygon_mode != PolygonMode::Fill
+            && !device.features.supports_fill_mode_non_solid()
+        {
+            tracing::warn!(target: logger::INIT, "{}: {:?} polygon mode not supported, using Fill", self.label, self.polygon_mode);
+            PolygonMode::Fill
+        } else {
+            self.polygon_mode
+        };
+
         let rasterization_state =
-            RasterizationState::new(self.polygon_mode, self.front_face, self.cull_mode);
+            RasterizationState::new(polygon_mode, self.front_face, self.cull_mode);
         let rasterization_state_info = rasterization_state.info();
 
         let multisample_state = MultisampleState::new();
@@ -616,8 +625,13 @@ impl GraphicsPipelineBuilder {
 
         let pipeline_layout = self.pipeline_layout.unwrap();
 
+        let mut feedback = vk::PipelineCreationFeedback::default();
+        let mut feedback_info = vk::PipelineCreationFeedbackCreateInfo::default()
+            .pipeline_creation_feedback(&mut feedback);
+
         let pipeline_info = vk::GraphicsPipelineCreateInfo::default()
             .push_next(&mut rendering_state_info)
+            .push_next(&mut feedback_info)
             .stages(&shader_stages_info)
             .vertex_input_state(&vertex_input_state_info)
             .viewport_state(&viewport_state_info)
@@ -635,13 +649,15 @@ impl GraphicsPipelineBuilder {
             device
                 .raw()
                 .create_graphics_pipelines(
-                    vk::PipelineCache::null(),
+                    device.pipeline_cache.raw(),
                     std::slice::from_ref(&pipeline_info),
                     None,
                 )
                 .unwrap()[0]
         };
 
+        device.pipeline_cache.record(&self.label, &feedback);
+
         Pipeline {
             label: self.label,
             device,
@@ -8,6 +8,11 @@ use crate::Wrap;
 use crate::descriptor::DescriptorSetLayout;
 use crate::device::Device;
 
+/// Push constants are shared by the vertex stage and compute shaders.
+pub(crate) const PUSH_CONSTANT_STAGES: vk::ShaderStageFlags = vk::ShaderStageFlags::from_raw(
+    vk::ShaderStageFlags::VERTEX.as_raw() | vk::ShaderStageFlags::COMPUTE.as_raw(),
+);
+
 #[derive(Debug)]
 pub struct PipelineLayout {
     device: Arc<Device>,
@@ -66,7 +71,7 @@ impl PipelineLayout {
                 vk::PushConstantRange::default()
                     .offset(0)
                     .size(push_constant_size as u32)
-                    .stage_flags(vk::ShaderStageFlags::VERTEX),
+                    .stage_flags(PUSH_CONSTANT_STAGES),
             );
             layout_info = layout_info.push_constant_ranges(&push_constant_range);
         }
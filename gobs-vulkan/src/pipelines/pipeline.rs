@@ -43,6 +43,7 @@ impl Rect2D {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub enum PipelineStage {
     AllCommands,
     AllGraphics,
@@ -50,6 +51,8 @@ pub enum PipelineStage {
     Compute,
     Vertex,
     Fragment,
+    ColorAttachmentOutput,
+    Transfer,
     BottomOfPipe,
 }
 
@@ -62,11 +65,31 @@ impl From<PipelineStage> for vk::PipelineStageFlags {
             PipelineStage::Compute => vk::PipelineStageFlags::COMPUTE_SHADER,
             PipelineStage::Vertex => vk::PipelineStageFlags::VERTEX_SHADER,
             PipelineStage::Fragment => vk::PipelineStageFlags::FRAGMENT_SHADER,
+            PipelineStage::ColorAttachmentOutput => vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            PipelineStage::Transfer => vk::PipelineStageFlags::TRANSFER,
             PipelineStage::BottomOfPipe => vk::PipelineStageFlags::BOTTOM_OF_PIPE,
         }
     }
 }
 
+impl From<PipelineStage> for vk::PipelineStageFlags2 {
+    fn from(val: PipelineStage) -> Self {
+        match val {
+            PipelineStage::AllCommands => vk::PipelineStageFlags2::ALL_COMMANDS,
+            PipelineStage::AllGraphics => vk::PipelineStageFlags2::ALL_GRAPHICS,
+            PipelineStage::TopOfPipe => vk::PipelineStageFlags2::TOP_OF_PIPE,
+            PipelineStage::Compute => vk::PipelineStageFlags2::COMPUTE_SHADER,
+            PipelineStage::Vertex => vk::PipelineStageFlags2::VERTEX_SHADER,
+            PipelineStage::Fragment => vk::PipelineStageFlags2::FRAGMENT_SHADER,
+            PipelineStage::ColorAttachmentOutput => {
+                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT
+            }
+            PipelineStage::Transfer => vk::PipelineStageFlags2::TRANSFER,
+            PipelineStage::BottomOfPipe => vk::PipelineStageFlags2::BOTTOM_OF_PIPE,
+        }
+    }
+}
+
 pub struct ShaderStage {
     entry: CString,
     shader: Shader,
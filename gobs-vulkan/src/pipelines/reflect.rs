@@ -0,0 +1,870 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{descriptor::DescriptorType, pipelines::ShaderType};
+
+const MAGIC: u32 = 0x0723_0203;
+const HEADER_SIZE: usize = 5;
+
+const OP_NAME: u32 = 5;
+const OP_MEMBER_NAME: u32 = 6;
+const OP_ENTRY_POINT: u32 = 15;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLER: u32 = 26;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+const DECORATION_BUFFER_BLOCK: u32 = 3;
+const DECORATION_ARRAY_STRIDE: u32 = 6;
+const DECORATION_MATRIX_STRIDE: u32 = 7;
+const DECORATION_BUILTIN: u32 = 11;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const STORAGE_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_INPUT: u32 = 1;
+const STORAGE_UNIFORM: u32 = 2;
+const STORAGE_PUSH_CONSTANT: u32 = 9;
+const STORAGE_STORAGE_BUFFER: u32 = 12;
+
+const MODEL_VERTEX: u32 = 0;
+const MODEL_FRAGMENT: u32 = 4;
+const MODEL_GL_COMPUTE: u32 = 5;
+
+/// Entry points list all the global variables they use starting with SPIR-V 1.4
+const VERSION_FULL_INTERFACE: u32 = 0x0001_0400;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReflectionError {
+    #[error("SPIR-V code size is not a multiple of 4")]
+    InvalidSize,
+    #[error("invalid SPIR-V magic number")]
+    InvalidMagic,
+    #[error("truncated SPIR-V instruction at word {0}")]
+    Truncated(usize),
+}
+
+/// Type of a descriptor as declared in the shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReflectedDescriptorType {
+    UniformBuffer,
+    StorageBuffer,
+    SampledImage,
+    StorageImage,
+    Sampler,
+    CombinedImageSampler,
+}
+
+impl ReflectedDescriptorType {
+    pub fn is_compatible(self, ty: DescriptorType) -> bool {
+        matches!(
+            (self, ty),
+            (Self::UniformBuffer, DescriptorType::Uniform)
+                | (Self::UniformBuffer, DescriptorType::UniformDynamic)
+                | (Self::StorageBuffer, DescriptorType::StorageBuffer)
+                | (Self::SampledImage, DescriptorType::SampledImage)
+                | (Self::StorageImage, DescriptorType::StorageImage)
+                | (Self::Sampler, DescriptorType::Sampler)
+                | (Self::CombinedImageSampler, DescriptorType::ImageSampler)
+        )
+    }
+
+    /// Descriptor type used to declare this binding in a pipeline layout
+    pub fn descriptor_type(self) -> DescriptorType {
+        match self {
+            Self::UniformBuffer => DescriptorType::Uniform,
+            Self::StorageBuffer => DescriptorType::StorageBuffer,
+            Self::SampledImage => DescriptorType::SampledImage,
+            Self::StorageImage => DescriptorType::StorageImage,
+            Self::Sampler => DescriptorType::Sampler,
+            Self::CombinedImageSampler => DescriptorType::ImageSampler,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemberType {
+    Scalar,
+    Vector(u32),
+    Matrix {
+        columns: u32,
+        rows: u32,
+    },
+    /// Buffer device address
+    Pointer,
+    Array,
+    Struct,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockMember {
+    pub name: String,
+    pub offset: u32,
+    pub size: u32,
+    pub ty: MemberType,
+}
+
+/// Layout of a uniform or push constant block
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockLayout {
+    pub name: String,
+    pub size: u32,
+    pub members: Vec<BlockMember>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflectedBinding {
+    id: u32,
+    pub name: String,
+    pub set: u32,
+    pub binding: u32,
+    pub ty: ReflectedDescriptorType,
+    /// 0 for runtime sized arrays
+    pub count: u32,
+    pub block: Option<BlockLayout>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexInput {
+    id: u32,
+    pub name: String,
+    pub location: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub name: String,
+    pub stage: ShaderType,
+    interface: Vec<u32>,
+}
+
+/// Resources declared in a SPIR-V module: descriptor sets, push constants and vertex inputs
+#[derive(Clone, Debug, Default)]
+pub struct ShaderReflection {
+    version: u32,
+    pub entry_points: Vec<EntryPoint>,
+    bindings: Vec<ReflectedBinding>,
+    push_constants: Vec<(u32, BlockLayout)>,
+    inputs: Vec<VertexInput>,
+}
+
+impl ShaderReflection {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ReflectionError> {
+        if !data.len().is_multiple_of(4) {
+            return Err(ReflectionError::InvalidSize);
+        }
+
+        let code = data
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect::<Vec<_>>();
+
+        Self::new(&code)
+    }
+
+    pub fn new(code: &[u32]) -> Result<Self, ReflectionError> {
+        let module = Module::parse(code)?;
+
+        Ok(module.reflect())
+    }
+
+    pub fn entry_point(&self, entry: &str) -> Option<&EntryPoint> {
+        self.entry_points.iter().find(|e| e.name == entry)
+    }
+
+    /// Descriptor bindings used by the entry point
+    pub fn bindings<'a>(&'a self, entry: &'a str) -> impl Iterator<Item = &'a ReflectedBinding> {
+        self.bindings
+            .iter()
+            .filter(move |binding| self.uses(entry, binding.id, false))
+    }
+
+    /// Push constants used by the entry point. Modules listing several blocks without telling
+    /// which entry point uses them (before SPIR-V 1.4) get their blocks merged into one range
+    pub fn push_constants(&self, entry: &str) -> Option<BlockLayout> {
+        self.push_constants
+            .iter()
+            .filter(|(id, _)| self.uses(entry, *id, false))
+            .map(|(_, block)| block.clone())
+            .reduce(|mut merged, block| {
+                for member in block.members {
+                    if !merged.members.iter().any(|m| m.offset == member.offset) {
+                        merged.members.push(member);
+                    }
+                }
+                merged.members.sort_by_key(|member| member.offset);
+                merged.size = merged.size.max(block.size);
+
+                merged
+            })
+    }
+
+    /// Vertex attributes of the entry point, ordered by location
+    pub fn inputs<'a>(&'a self, entry: &'a str) -> impl Iterator<Item = &'a VertexInput> {
+        self.inputs
+            .iter()
+            .filter(move |input| self.uses(entry, input.id, true))
+    }
+
+    fn uses(&self, entry: &str, id: u32, interface: bool) -> bool {
+        match self.entry_point(entry) {
+            Some(entry_point) if interface || self.version >= VERSION_FULL_INTERFACE => {
+                entry_point.interface.contains(&id)
+            }
+            _ => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Type {
+    Scalar { width: u32 },
+    Vector { component: u32, count: u32 },
+    Matrix { column: u32, columns: u32 },
+    Image { sampled: u32 },
+    Sampler,
+    SampledImage,
+    Array { element: u32, length: u32 },
+    RuntimeArray { element: u32 },
+    Struct { members: Vec<u32> },
+    Pointer { pointee: u32 },
+}
+
+#[derive(Debug, Default)]
+struct Decorations {
+    set: Option<u32>,
+    binding: Option<u32>,
+    location: Option<u32>,
+    array_stride: Option<u32>,
+    builtin: bool,
+    buffer_block: bool,
+}
+
+#[derive(Debug, Default)]
+struct MemberDecorations {
+    offset: Option<u32>,
+    matrix_stride: Option<u32>,
+}
+
+#[derive(Default)]
+struct Module {
+    version: u32,
+    names: HashMap<u32, String>,
+    member_names: HashMap<(u32, u32), String>,
+    decorations: HashMap<u32, Decorations>,
+    member_decorations: HashMap<(u32, u32), MemberDecorations>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    variables: Vec<(u32, u32, u32)>,
+    entry_points: Vec<EntryPoint>,
+}
+
+impl Module {
+    fn parse(code: &[u32]) -> Result<Self, ReflectionError> {
+        if code.len() < HEADER_SIZE {
+            return Err(ReflectionError::InvalidMagic);
+        }
+
+        let swapped;
+        let code = match code[0] {
+            MAGIC => code,
+            magic if magic.swap_bytes() == MAGIC => {
+                swapped = code.iter().map(|w| w.swap_bytes()).collect::<Vec<_>>();
+                &swapped
+            }
+            _ => return Err(ReflectionError::InvalidMagic),
+        };
+
+        let mut module = Module {
+            version: code[1],
+            ..Default::default()
+        };
+
+        let mut pos = HEADER_SIZE;
+        while pos < code.len() {
+            let count = (code[pos] >> 16) as usize;
+            let opcode = code[pos] & 0xffff;
+
+            if count == 0 || pos + count > code.len() {
+                return Err(ReflectionError::Truncated(pos));
+            }
+
+            module.instruction(opcode, &code[pos + 1..pos + count]);
+            pos += count;
+        }
+
+        Ok(module)
+    }
+
+    fn instruction(&mut self, opcode: u32, args: &[u32]) {
+        let arg = |i: usize| args.get(i).copied().unwrap_or_default();
+
+        match opcode {
+            OP_NAME => {
+                self.names
+                    .insert(arg(0), read_string(&args[1.min(args.len())..]).0);
+            }
+            OP_MEMBER_NAME => {
+                self.member_names
+                    .insert((arg(0), arg(1)), read_string(&args[2.min(args.len())..]).0);
+            }
+            OP_ENTRY_POINT => {
+                let (name, len) = read_string(&args[2.min(args.len())..]);
+                let stage = match arg(0) {
+                    MODEL_VERTEX => ShaderType::Vertex,
+                    MODEL_FRAGMENT => ShaderType::Fragment,
+                    MODEL_GL_COMPUTE => ShaderType::Compute,
+                    _ => return,
+                };
+                self.entry_points.push(EntryPoint {
+                    name,
+                    stage,
+                    interface: args[(2 + len).min(args.len())..].to_vec(),
+                });
+            }
+            OP_DECORATE => {
+                let decorations = self.decorations.entry(arg(0)).or_default();
+                match arg(1) {
+                    DECORATION_BUFFER_BLOCK => decorations.buffer_block = true,
+                    DECORATION_ARRAY_STRIDE => decorations.array_stride = Some(arg(2)),
+                    DECORATION_BUILTIN => decorations.builtin = true,
+                    DECORATION_LOCATION => decorations.location = Some(arg(2)),
+                    DECORATION_BINDING => decorations.binding = Some(arg(2)),
+                    DECORATION_DESCRIPTOR_SET => decorations.set = Some(arg(2)),
+                    _ => {}
+                }
+            }
+            OP_MEMBER_DECORATE => {
+                let decorations = self.member_decorations.entry((arg(0), arg(1))).or_default();
+                match arg(2) {
+                    DECORATION_OFFSET => decorations.offset = Some(arg(3)),
+                    DECORATION_MATRIX_STRIDE => decorations.matrix_stride = Some(arg(3)),
+                    _ => {}
+                }
+            }
+            OP_TYPE_BOOL => {
+                self.types.insert(arg(0), Type::Scalar { width: 32 });
+            }
+            OP_TYPE_INT | OP_TYPE_FLOAT => {
+                self.types.insert(arg(0), Type::Scalar { width: arg(1) });
+            }
+            OP_TYPE_VECTOR => {
+                self.types.insert(
+                    arg(0),
+                    Type::Vector {
+                        component: arg(1),
+                        count: arg(2),
+                    },
+                );
+            }
+            OP_TYPE_MATRIX => {
+                self.types.insert(
+                    arg(0),
+                    Type::Matrix {
+                        column: arg(1),
+                        columns: arg(2),
+                    },
+                );
+            }
+            OP_TYPE_IMAGE => {
+                self.types.insert(arg(0), Type::Image { sampled: arg(6) });
+            }
+            OP_TYPE_SAMPLER => {
+                self.types.insert(arg(0), Type::Sampler);
+            }
+            OP_TYPE_SAMPLED_IMAGE => {
+                self.types.insert(arg(0), Type::SampledImage);
+            }
+            OP_TYPE_ARRAY => {
+                self.types.insert(
+                    arg(0),
+                    Type::Array {
+                        element: arg(1),
+                        length: arg(2),
+                    },
+                );
+            }
+            OP_TYPE_RUNTIME_ARRAY => {
+                self.types
+                    .insert(arg(0), Type::RuntimeArray { element: arg(1) });
+            }
+            OP_TYPE_STRUCT => {
+                self.types.insert(
+                    arg(0),
+                    Type::Struct {
+                        members: args[1.min(args.len())..].to_vec(),
+                    },
+                );
+            }
+            OP_TYPE_POINTER => {
+                self.types.insert(arg(0), Type::Pointer { pointee: arg(2) });
+            }
+            OP_CONSTANT => {
+                self.constants.insert(arg(1), arg(2));
+            }
+            OP_VARIABLE => {
+                self.variables.push((arg(1), arg(0), arg(2)));
+            }
+            _ => {}
+        }
+    }
+
+    fn reflect(self) -> ShaderReflection {
+        let mut reflection = ShaderReflection {
+            version: self.version,
+            ..Default::default()
+        };
+
+        for &(id, ty, storage) in &self.variables {
+            let Some(Type::Pointer { pointee, .. }) = self.types.get(&ty) else {
+                continue;
+            };
+            let decorations = self.decorations.get(&id);
+
+            match storage {
+                STORAGE_PUSH_CONSTANT => {
+                    reflection.push_constants.push((id, self.block(*pointee)));
+                }
+                STORAGE_INPUT => {
+                    if let Some(decorations) = decorations
+                        && !decorations.builtin
+                        && let Some(location) = decorations.location
+                    {
+                        reflection.inputs.push(VertexInput {
+                            id,
+                            name: self.name(id),
+                            location,
+                        });
+                    }
+                }
+                STORAGE_UNIFORM_CONSTANT | STORAGE_UNIFORM | STORAGE_STORAGE_BUFFER => {
+                    let Some(decorations) = decorations else {
+                        continue;
+                    };
+                    let (Some(set), Some(binding)) = (decorations.set, decorations.binding) else {
+                        continue;
+                    };
+
+                    let (element, count) = match self.types.get(pointee) {
+                        Some(Type::Array { element, length }) => {
+                            (*element, self.constants.get(length).copied().unwrap_or(1))
+                        }
+                        Some(Type::RuntimeArray { element }) => (*element, 0),
+                        _ => (*pointee, 1),
+                    };
+
+                    let Some(ty) = self.descriptor_type(element, storage) else {
+                        continue;
+                    };
+
+                    let block = match ty {
+                        ReflectedDescriptorType::UniformBuffer
+                        | ReflectedDescriptorType::StorageBuffer => Some(self.block(element)),
+                        _ => None,
+                    };
+
+                    reflection.bindings.push(ReflectedBinding {
+                        id,
+                        name: self.name(id),
+                        set,
+                        binding,
+                        ty,
+                        count,
+                        block,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        reflection
+            .bindings
+            .sort_by_key(|binding| (binding.set, binding.binding));
+        reflection.inputs.sort_by_key(|input| input.location);
+        reflection.entry_points = self.entry_points;
+
+        reflection
+    }
+
+    fn descriptor_type(&self, ty: u32, storage: u32) -> Option<ReflectedDescriptorType> {
+        let block = self.decorations.get(&ty);
+
+        match (storage, self.types.get(&ty)?) {
+            (STORAGE_UNIFORM, Type::Struct { .. }) if block.is_some_and(|d| d.buffer_block) => {
+                Some(ReflectedDescriptorType::StorageBuffer)
+            }
+            (STORAGE_UNIFORM, Type::Struct { .. }) => Some(ReflectedDescriptorType::UniformBuffer),
+            (STORAGE_STORAGE_BUFFER, Type::Struct { .. }) => {
+                Some(ReflectedDescriptorType::StorageBuffer)
+            }
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { sampled: 2 }) => {
+                Some(ReflectedDescriptorType::StorageImage)
+            }
+            (STORAGE_UNIFORM_CONSTANT, Type::Image { .. }) => {
+                Some(ReflectedDescriptorType::SampledImage)
+            }
+            (STORAGE_UNIFORM_CONSTANT, Type::Sampler) => Some(ReflectedDescriptorType::Sampler),
+            (STORAGE_UNIFORM_CONSTANT, Type::SampledImage) => {
+                Some(ReflectedDescriptorType::CombinedImageSampler)
+            }
+            _ => None,
+        }
+    }
+
+    fn name(&self, id: u32) -> String {
+        self.names.get(&id).cloned().unwrap_or_default()
+    }
+
+    fn block(&self, ty: u32) -> BlockLayout {
+        let Some(Type::Struct { members }) = self.types.get(&ty) else {
+            return BlockLayout::default();
+        };
+
+        let mut offset = 0;
+        let members = members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| {
+                let decorations = self.member_decorations.get(&(ty, index as u32));
+                let size = self.size(*member, decorations.and_then(|d| d.matrix_stride));
+                let member_offset = decorations.and_then(|d| d.offset).unwrap_or(offset);
+                offset = member_offset + size;
+
+                BlockMember {
+                    name: self
+                        .member_names
+                        .get(&(ty, index as u32))
+                        .cloned()
+                        .unwrap_or_default(),
+                    offset: member_offset,
+                    size,
+                    ty: self.member_type(*member),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        BlockLayout {
+            name: self.name(ty),
+            size: members.iter().map(|m| m.offset + m.size).max().unwrap_or(0),
+            members,
+        }
+    }
+
+    fn member_type(&self, ty: u32) -> MemberType {
+        match self.types.get(&ty) {
+            Some(Type::Vector { count, .. }) => MemberType::Vector(*count),
+            Some(Type::Matrix { column, columns }) => MemberType::Matrix {
+                columns: *columns,
+                rows: match self.types.get(column) {
+                    Some(Type::Vector { count, .. }) => *count,
+                    _ => 1,
+                },
+            },
+            Some(Type::Pointer { .. }) => MemberType::Pointer,
+            Some(Type::Array { .. }) | Some(Type::RuntimeArray { .. }) => MemberType::Array,
+            Some(Type::Struct { .. }) => MemberType::Struct,
+            _ => MemberType::Scalar,
+        }
+    }
+
+    fn size(&self, ty: u32, matrix_stride: Option<u32>) -> u32 {
+        match self.types.get(&ty) {
+            Some(Type::Scalar { width }) => width / 8,
+            Some(Type::Vector { component, count }) => count * self.size(*component, None),
+            Some(Type::Matrix { column, columns }) => {
+                columns * matrix_stride.unwrap_or_else(|| self.size(*column, None))
+            }
+            Some(Type::Array { element, length }) => {
+                let length = self.constants.get(length).copied().unwrap_or(1);
+                let stride = self
+                    .decorations
+                    .get(&ty)
+                    .and_then(|d| d.array_stride)
+                    .unwrap_or_else(|| self.size(*element, matrix_stride));
+                length * stride
+            }
+            Some(Type::Struct { .. }) => self.block(ty).size,
+            Some(Type::Pointer { .. }) => 8,
+            _ => 0,
+        }
+    }
+}
+
+/// Decode a nul terminated string, returns the string and the number of words read
+fn read_string(words: &[u32]) -> (String, usize) {
+    let mut bytes = Vec::new();
+
+    for (i, word) in words.iter().enumerate() {
+        for byte in word.to_le_bytes() {
+            if byte == 0 {
+                return (String::from_utf8_lossy(&bytes).into_owned(), i + 1);
+            }
+            bytes.push(byte);
+        }
+    }
+
+    (String::from_utf8_lossy(&bytes).into_owned(), words.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::Level;
+    use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+
+    use crate::{
+        descriptor::DescriptorType,
+        pipelines::{
+            ShaderType,
+            reflect::{
+                MAGIC, MemberType, ReflectedDescriptorType, ReflectionError, ShaderReflection,
+            },
+        },
+    };
+
+    fn setup() {
+        let sub = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+        tracing::subscriber::set_global_default(sub).unwrap_or_default();
+    }
+
+    fn string(s: &str) -> Vec<u32> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.resize(s.len() / 4 * 4 + 4, 0);
+
+        bytes
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect()
+    }
+
+    fn inst(code: &mut Vec<u32>, opcode: u32, args: &[u32]) {
+        code.push(((args.len() as u32 + 1) << 16) | opcode);
+        code.extend_from_slice(args);
+    }
+
+    fn named(code: &mut Vec<u32>, opcode: u32, prefix: &[u32], name: &str, suffix: &[u32]) {
+        let mut args = prefix.to_vec();
+        args.extend(string(name));
+        args.extend_from_slice(suffix);
+        inst(code, opcode, &args);
+    }
+
+    /// Equivalent of:
+    ///   ConstantBuffer<SceneData> scene_data; // (0, 0)
+    ///   PushConstants push_constants;
+    ///   Sampler2D textures[4]; // (2, 0)
+    ///   float3 position: POSITION; // location 0
+    fn module() -> Vec<u32> {
+        let (vs, fs, f32_, v3, v4, m4, u32_) = (1, 2, 3, 4, 5, 6, 7);
+        let (scene_t, scene_ptr, scene) = (10, 11, 12);
+        let (push_t, bda, push_ptr, push) = (20, 21, 22, 23);
+        let (img, sampled, four, arr, tex_ptr, tex) = (30, 31, 32, 33, 34, 35);
+        let (in_ptr, position) = (40, 41);
+
+        let mut code = vec![MAGIC, 0x0001_0500, 0, 50, 0];
+
+        named(
+            &mut code,
+            15,
+            &[0, vs],
+            "vertex_main",
+            &[scene, push, position],
+        );
+        named(&mut code, 15, &[4, fs], "fragment_main", &[tex]);
+        named(&mut code, 5, &[scene], "scene_data", &[]);
+        named(&mut code, 5, &[scene_t], "SceneData", &[]);
+        named(&mut code, 6, &[scene_t, 0], "view_proj", &[]);
+        named(&mut code, 6, &[scene_t, 1], "camera_position", &[]);
+        named(&mut code, 6, &[push_t, 0], "world_matrix", &[]);
+        named(&mut code, 6, &[push_t, 1], "vertex_reference", &[]);
+        named(&mut code, 5, &[tex], "textures", &[]);
+        named(&mut code, 5, &[position], "position", &[]);
+
+        inst(&mut code, 71, &[scene, 34, 0]);
+        inst(&mut code, 71, &[scene, 33, 0]);
+        inst(&mut code, 71, &[scene_t, 2]);
+        inst(&mut code, 72, &[scene_t, 0, 35, 0]);
+        inst(&mut code, 72, &[scene_t, 0, 7, 16]);
+        inst(&mut code, 72, &[scene_t, 1, 35, 64]);
+        inst(&mut code, 71, &[push_t, 2]);
+        inst(&mut code, 72, &[push_t, 0, 35, 0]);
+        inst(&mut code, 72, &[push_t, 0, 7, 16]);
+        inst(&mut code, 72, &[push_t, 1, 35, 64]);
+        inst(&mut code, 71, &[tex, 34, 2]);
+        inst(&mut code, 71, &[tex, 33, 0]);
+        inst(&mut code, 71, &[position, 30, 0]);
+
+        inst(&mut code, 22, &[f32_, 32]);
+        inst(&mut code, 21, &[u32_, 32, 0]);
+        inst(&mut code, 23, &[v3, f32_, 3]);
+        inst(&mut code, 23, &[v4, f32_, 4]);
+        inst(&mut code, 24, &[m4, v4, 4]);
+        inst(&mut code, 30, &[scene_t, m4, v3]);
+        inst(&mut code, 32, &[scene_ptr, 2, scene_t]);
+        inst(&mut code, 59, &[scene_ptr, scene, 2]);
+        inst(&mut code, 32, &[bda, 5349, v4]);
+        inst(&mut code, 30, &[push_t, m4, bda]);
+        inst(&mut code, 32, &[push_ptr, 9, push_t]);
+        inst(&mut code, 59, &[push_ptr, push, 9]);
+        inst(&mut code, 25, &[img, f32_, 1, 0, 0, 0, 1, 0]);
+        inst(&mut code, 27, &[sampled, img]);
+        inst(&mut code, 43, &[u32_, four, 4]);
+        inst(&mut code, 28, &[arr, sampled, four]);
+        inst(&mut code, 32, &[tex_ptr, 0, arr]);
+        inst(&mut code, 59, &[tex_ptr, tex, 0]);
+        inst(&mut code, 32, &[in_ptr, 1, v3]);
+        inst(&mut code, 59, &[in_ptr, position, 1]);
+
+        code
+    }
+
+    #[test]
+    fn test_reflect() {
+        setup();
+
+        let reflection = ShaderReflection::new(&module()).unwrap();
+
+        assert_eq!(reflection.entry_points.len(), 2);
+        assert_eq!(
+            reflection.entry_point("fragment_main").unwrap().stage,
+            ShaderType::Fragment
+        );
+
+        let bindings = reflection.bindings("vertex_main").collect::<Vec<_>>();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!((bindings[0].set, bindings[0].binding), (0, 0));
+        assert_eq!(bindings[0].ty, ReflectedDescriptorType::UniformBuffer);
+        assert!(bindings[0].ty.is_compatible(DescriptorType::UniformDynamic));
+        let block = bindings[0].block.as_ref().unwrap();
+        assert_eq!(block.name, "SceneData");
+        assert_eq!(block.size, 76);
+        assert_eq!(block.members[1].name, "camera_position");
+        assert_eq!(block.members[1].offset, 64);
+
+        let push = reflection.push_constants("vertex_main").unwrap();
+        assert_eq!(push.size, 72);
+        assert_eq!(
+            push.members[0].ty,
+            MemberType::Matrix {
+                columns: 4,
+                rows: 4
+            }
+        );
+        assert_eq!(push.members[1].ty, MemberType::Pointer);
+        assert!(reflection.push_constants("fragment_main").is_none());
+
+        let inputs = reflection.inputs("vertex_main").collect::<Vec<_>>();
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].name, "position");
+        assert_eq!(reflection.inputs("fragment_main").count(), 0);
+
+        let bindings = reflection.bindings("fragment_main").collect::<Vec<_>>();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!((bindings[0].set, bindings[0].count), (2, 4));
+        assert_eq!(
+            bindings[0].ty,
+            ReflectedDescriptorType::CombinedImageSampler
+        );
+
+        let bytes = module()
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ShaderReflection::from_bytes(&bytes)
+                .unwrap()
+                .bindings("fragment_main")
+                .count(),
+            1
+        );
+
+        assert_eq!(
+            ShaderReflection::new(&[0; 5]).unwrap_err(),
+            ReflectionError::InvalidMagic
+        );
+        assert_eq!(
+            ShaderReflection::from_bytes(&[0; 6]).unwrap_err(),
+            ReflectionError::InvalidSize
+        );
+        let mut truncated = module();
+        truncated.push(10 << 16);
+        assert!(matches!(
+            ShaderReflection::new(&truncated),
+            Err(ReflectionError::Truncated(_))
+        ));
+    }
+
+    /// Two push constant blocks, one for each entry point
+    fn push_module(version: u32) -> Vec<u32> {
+        let (vs, fs, f32_, u32_, v4, m4) = (1, 2, 3, 4, 5, 6);
+        let (vs_t, vs_ptr, vs_push) = (10, 11, 12);
+        let (fs_t, fs_ptr, fs_push) = (20, 21, 22);
+
+        let mut code = vec![MAGIC, version, 0, 30, 0];
+
+        named(&mut code, 15, &[0, vs], "vertex_main", &[vs_push]);
+        named(&mut code, 15, &[4, fs], "fragment_main", &[fs_push]);
+        named(&mut code, 6, &[vs_t, 0], "world_matrix", &[]);
+        named(&mut code, 6, &[fs_t, 0], "material_index", &[]);
+
+        inst(&mut code, 71, &[vs_t, 2]);
+        inst(&mut code, 72, &[vs_t, 0, 35, 0]);
+        inst(&mut code, 72, &[vs_t, 0, 7, 16]);
+        inst(&mut code, 71, &[fs_t, 2]);
+        inst(&mut code, 72, &[fs_t, 0, 35, 64]);
+
+        inst(&mut code, 22, &[f32_, 32]);
+        inst(&mut code, 21, &[u32_, 32, 0]);
+        inst(&mut code, 23, &[v4, f32_, 4]);
+        inst(&mut code, 24, &[m4, v4, 4]);
+        inst(&mut code, 30, &[vs_t, m4]);
+        inst(&mut code, 32, &[vs_ptr, 9, vs_t]);
+        inst(&mut code, 59, &[vs_ptr, vs_push, 9]);
+        inst(&mut code, 30, &[fs_t, u32_]);
+        inst(&mut code, 32, &[fs_ptr, 9, fs_t]);
+        inst(&mut code, 59, &[fs_ptr, fs_push, 9]);
+
+        code
+    }
+
+    #[test]
+    fn test_push_constants() {
+        setup();
+
+        let reflection = ShaderReflection::new(&push_module(0x0001_0000)).unwrap();
+
+        let push = reflection.push_constants("vertex_main").unwrap();
+        assert_eq!(push.size, 68);
+        assert_eq!(push.members.len(), 2);
+        assert_eq!(push.members[0].name, "world_matrix");
+        assert_eq!(push.members[1].name, "material_index");
+        assert_eq!(push.members[1].offset, 64);
+        assert_eq!(reflection.push_constants("fragment_main"), Some(push));
+
+        let reflection = ShaderReflection::new(&push_module(0x0001_0500)).unwrap();
+
+        let push = reflection.push_constants("vertex_main").unwrap();
+        assert_eq!(push.size, 64);
+        assert_eq!(push.members.len(), 1);
+        let push = reflection.push_constants("fragment_main").unwrap();
+        assert_eq!(push.size, 68);
+        assert_eq!(push.members[0].name, "material_index");
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Cursor, Read};
 use std::path::Path;
 use std::ptr;
 use std::sync::Arc;
@@ -13,6 +13,7 @@ use crate::Wrap;
 use crate::device::Device;
 use crate::error::VulkanError;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ShaderType {
     Compute,
     Vertex,
@@ -39,12 +40,24 @@ impl Shader {
 
         let data: Vec<u8> = reader.bytes().filter_map(|b| b.ok()).collect();
 
+        Self::from_bytes(&data, device, ty)
+    }
+
+    /// Create a shader module from SPIR-V bytecode
+    pub fn from_bytes(
+        data: &[u8],
+        device: Arc<Device>,
+        ty: ShaderType,
+    ) -> Result<Self, VulkanError> {
+        // bytecode must be aligned on u32
+        let code = ash::util::read_spv(&mut Cursor::new(data))?;
+
         let shader_info = vk::ShaderModuleCreateInfo {
             s_type: vk::StructureType::SHADER_MODULE_CREATE_INFO,
             p_next: ptr::null(),
             flags: Default::default(),
-            code_size: data.len(),
-            p_code: data.as_ptr() as *const u32,
+            code_size: code.len() * size_of::<u32>(),
+            p_code: code.as_ptr(),
             _marker: std::marker::PhantomData,
         };
 
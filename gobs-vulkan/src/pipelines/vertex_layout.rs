@@ -7,6 +7,7 @@ pub enum VertexAttributeFormat {
     Vec2,
     Vec3,
     Vec4,
+    UVec4,
     Mat4,
 }
 
@@ -16,6 +17,7 @@ impl VertexAttributeFormat {
             VertexAttributeFormat::Vec2 => 1,
             VertexAttributeFormat::Vec3 => 1,
             VertexAttributeFormat::Vec4 => 1,
+            VertexAttributeFormat::UVec4 => 1,
             VertexAttributeFormat::Mat4 => 4,
         }
     }
@@ -31,6 +33,7 @@ impl From<VertexAttributeFormat> for vk::Format {
             VertexAttributeFormat::Vec2 => vk::Format::R32G32_SFLOAT,
             VertexAttributeFormat::Vec3 => vk::Format::R32G32B32_SFLOAT,
             VertexAttributeFormat::Vec4 => vk::Format::R32G32B32A32_SFLOAT,
+            VertexAttributeFormat::UVec4 => vk::Format::R32G32B32A32_UINT,
             VertexAttributeFormat::Mat4 => vk::Format::R32G32B32A32_SFLOAT,
         }
     }
@@ -48,12 +48,13 @@ pub struct Queue {
     pub device: Arc<Device>,
     pub(crate) queue: vk::Queue,
     pub family: QueueFamily,
+    pub index: u32,
 }
 
 impl Queue {
     pub fn new(device: Arc<Device>, family: QueueFamily, index: u32) -> Arc<Self> {
         let queue = unsafe {
-            tracing::debug!(target: logger::INIT, "Create queue");
+            tracing::debug!(target: logger::INIT, "Create queue {} in family {}", index, family.index);
             device.raw().get_device_queue(family.index, index)
         };
 
@@ -61,6 +62,7 @@ impl Queue {
             device,
             queue,
             family,
+            index,
         })
     }
 
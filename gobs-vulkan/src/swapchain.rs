@@ -1,4 +1,5 @@
 use std;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use ash::khr::swapchain;
@@ -14,7 +15,7 @@ use crate::queue::Queue;
 use crate::surface::{Surface, SurfaceFormat};
 use crate::sync::Semaphore;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PresentationMode {
     Fifo,
     FifoRelaxed,
@@ -23,6 +24,27 @@ pub enum PresentationMode {
     Unsupported,
 }
 
+impl PresentationMode {
+    /// Presentation is synchronized with the vertical blank
+    pub fn is_vsync(&self) -> bool {
+        matches!(self, PresentationMode::Fifo | PresentationMode::FifoRelaxed)
+    }
+}
+
+impl FromStr for PresentationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fifo" => Ok(PresentationMode::Fifo),
+            "fifo_relaxed" => Ok(PresentationMode::FifoRelaxed),
+            "mailbox" => Ok(PresentationMode::Mailbox),
+            "immediate" => Ok(PresentationMode::Immediate),
+            _ => Err(format!("invalid present mode: {s}")),
+        }
+    }
+}
+
 impl From<vk::PresentModeKHR> for PresentationMode {
     fn from(present: vk::PresentModeKHR) -> PresentationMode {
         match present {
@@ -144,8 +166,9 @@ impl SwapChain {
                 u64::MAX,
                 signal.raw(),
                 vk::Fence::null(),
-            )?
-        };
+            )
+        }
+        .map_err(|err| self.device.map_error(err))?;
 
         Ok(idx as usize)
     }
@@ -167,7 +190,9 @@ impl SwapChain {
             .swapchains(std::slice::from_ref(&swapchains));
 
         unsafe {
-            self.loader.queue_present(queue.queue, &present_info)?;
+            self.loader
+                .queue_present(queue.queue, &present_info)
+                .map_err(|err| self.device.map_error(err))?;
         }
 
         Ok(())
@@ -1,7 +1,9 @@
 mod fence;
 mod semaphore;
+mod submit;
 mod timeline_semaphore;
 
 pub use self::fence::Fence;
 pub use self::semaphore::Semaphore;
+pub use self::submit::SemaphoreSubmit;
 pub use self::timeline_semaphore::TimeLineSemaphore;
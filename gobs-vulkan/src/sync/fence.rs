@@ -6,6 +6,7 @@ use ash::vk;
 use gobs_core::logger;
 
 use crate::device::Device;
+use crate::error::VulkanError;
 use crate::{Wrap, debug};
 
 pub struct Fence {
@@ -35,44 +36,46 @@ impl Fence {
     pub fn reset(&self) {
         let fences = [self.fence];
 
-        unsafe {
-            self.device
-                .raw()
-                .reset_fences(&fences)
-                .expect("Device lost")
+        if let Err(err) = unsafe { self.device.raw().reset_fences(&fences) } {
+            tracing::error!(target: logger::SYNC, "Fence reset: {}", self.device.map_error(err));
         }
     }
 
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    pub fn wait(&self) {
-        unsafe {
+    pub fn wait(&self) -> Result<(), VulkanError> {
+        let result = unsafe {
             self.device
                 .raw()
                 .wait_for_fences(&[self.fence], true, 5_000_000_000)
-                .expect("Fence timeout");
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(vk::Result::TIMEOUT) => {
+                tracing::error!(target: logger::SYNC, "Fence timeout");
+                Err(VulkanError::Timeout)
+            }
+            Err(err) => Err(self.device.map_error(err)),
         }
     }
 
+    /// The fence is not reset if the wait fails, the GPU may still use the resources
     #[tracing::instrument(target = "profile", skip_all, level = "trace")]
-    pub fn wait_and_reset(&self) {
-        unsafe {
-            self.device
-                .raw()
-                .wait_for_fences(&[self.fence], true, 5_000_000_000)
-                .expect("Fence timeout");
-            self.device
-                .raw()
-                .reset_fences(&[self.fence])
-                .expect("Device lost");
-        }
+    pub fn wait_and_reset(&self) -> Result<(), VulkanError> {
+        self.wait()?;
+        self.reset();
+
+        Ok(())
     }
 
     pub fn signaled(&self) -> bool {
-        unsafe {
-            self.device
-                .raw()
-                .get_fence_status(self.fence)
-                .expect("Device lost")
+        match unsafe { self.device.raw().get_fence_status(self.fence) } {
+            Ok(signaled) => signaled,
+            Err(err) => {
+                tracing::error!(target: logger::SYNC, "Fence status: {}", self.device.map_error(err));
+                // nothing will signal the fence anymore, don't wait on it
+                true
+            }
         }
     }
 }
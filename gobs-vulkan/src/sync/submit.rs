@@ -0,0 +1,39 @@
+use ash::vk;
+
+use crate::Wrap;
+use crate::pipelines::PipelineStage;
+use crate::sync::{Semaphore, TimeLineSemaphore};
+
+/// Semaphore waited or signaled by a queue submission
+#[derive(Clone, Copy, Debug)]
+pub struct SemaphoreSubmit {
+    semaphore: vk::Semaphore,
+    value: u64,
+    stage: PipelineStage,
+}
+
+impl SemaphoreSubmit {
+    pub fn binary(semaphore: &Semaphore, stage: PipelineStage) -> Self {
+        Self {
+            semaphore: semaphore.raw(),
+            value: 1,
+            stage,
+        }
+    }
+
+    pub fn timeline(semaphore: &TimeLineSemaphore, value: u64, stage: PipelineStage) -> Self {
+        Self {
+            semaphore: semaphore.raw(),
+            value,
+            stage,
+        }
+    }
+
+    pub(crate) fn info(&self) -> vk::SemaphoreSubmitInfo<'static> {
+        vk::SemaphoreSubmitInfo::default()
+            .stage_mask(self.stage.into())
+            .semaphore(self.semaphore)
+            .device_index(0)
+            .value(self.value)
+    }
+}
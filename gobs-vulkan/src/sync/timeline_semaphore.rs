@@ -35,25 +35,38 @@ impl TimeLineSemaphore {
     }
 
     pub fn value(&self) -> u64 {
-        unsafe {
+        match unsafe {
             self.device
                 .raw()
                 .get_semaphore_counter_value(self.semaphore)
-                .expect("Device lost")
+        } {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::error!(target: logger::SYNC, "Semaphore value: {}", self.device.map_error(err));
+                // nothing is pending on a lost device
+                u64::MAX
+            }
         }
     }
 
     pub fn wait(&self, value: u64) {
-        unsafe {
-            self.device
-                .raw()
-                .wait_semaphores(
-                    &vk::SemaphoreWaitInfo::default()
-                        .semaphores(&[self.semaphore])
-                        .values(&[value]),
-                    5_000_000_000,
-                )
-                .expect("Device lost")
+        let result = unsafe {
+            self.device.raw().wait_semaphores(
+                &vk::SemaphoreWaitInfo::default()
+                    .semaphores(&[self.semaphore])
+                    .values(&[value]),
+                5_000_000_000,
+            )
+        };
+
+        match result {
+            Ok(()) => {}
+            Err(vk::Result::TIMEOUT) => {
+                tracing::error!(target: logger::SYNC, "Semaphore timeout: {}", value)
+            }
+            Err(err) => {
+                tracing::error!(target: logger::SYNC, "Semaphore wait: {}", self.device.map_error(err))
+            }
         }
     }
 }